@@ -43,7 +43,7 @@ fn benchmark_http(c: &mut Criterion) {
                             "out",
                             &["in"],
                             sinks::http::HttpSinkConfig {
-                                uri: out_addr.to_string().parse::<http::Uri>().unwrap().into(),
+                                uri: out_addr.to_string(),
                                 compression: *compression,
                                 method: Default::default(),
                                 auth: Default::default(),
@@ -53,6 +53,9 @@ fn benchmark_http(c: &mut Criterion) {
                                     ..Default::default()
                                 },
                                 encoding: sinks::http::Encoding::Text.into(),
+                                payload_prefix: Default::default(),
+                                payload_suffix: Default::default(),
+                                payload_separator: Default::default(),
                                 request: Default::default(),
                                 tls: Default::default(),
                             },
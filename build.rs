@@ -122,6 +122,13 @@ fn main() {
             .unwrap();
     }
 
+    #[cfg(feature = "sinks-loki")]
+    {
+        println!("cargo:rerun-if-changed=proto/loki.proto");
+
+        prost_build::compile_protos(&["proto/loki.proto"], &["proto/"]).unwrap();
+    }
+
     // We keep track of which environment variables we slurp in, and then emit stanzas at the end to
     // inform Cargo when it needs to rerun this build script.  This allows us to avoid rerunning it
     // every single time unless something _actually_ changes.
@@ -191,6 +191,38 @@ cli: {
 	}
 
 	commands: {
+		"checkpoints": {
+			description: """
+				Inspect and edit the checkpoints of the `file` and `kubernetes_logs`
+				sources. The data directory of a source is locked while it runs, so
+				Vector must be stopped first.
+
+				Subcommands:
+
+				* `list <data_dir>` lists the checkpoints, as `text` or `json` (`--format`).
+				* `reset <data_dir> <file>` removes the checkpoint of a file, so that it's
+				  read again from where `read_from` points to.
+				* `set <data_dir> <file> <position>` sets the position a file is read from.
+
+				Files are fingerprinted like the source does, use the `--lines`,
+				`--ignored-header-bytes`, `--bytes` and `--device-and-inode` options to match
+				its `fingerprint` configuration, and `--max-line-bytes` to match its
+				`max_line_bytes` option (`32768` by default for the `kubernetes_logs` source).
+
+				Example:
+
+				```
+				vector checkpoints list /var/lib/vector/my_file_source
+				```
+				"""
+
+			args: {
+				data_dir: {
+					description: "The data directory of the source, that is `<data_dir>/<source id>`"
+					type:        "string"
+				}
+			}
+		}
 		"graph": {
 			description: """
 				Generate a visual representation of topologies. The output is in the [DOT format](\(urls.dot_format))
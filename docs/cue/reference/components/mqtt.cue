@@ -0,0 +1,137 @@
+package metadata
+
+components: _mqtt: {
+	features: {
+		collect: from: {
+			service: services.mqtt
+			interface: {
+				socket: {
+					api: {
+						title: "MQTT protocol"
+						url:   urls.mqtt
+					}
+					direction: "outgoing"
+					port:      1883
+					protocols: ["tcp"]
+					ssl: "optional"
+				}
+			}
+		}
+
+		send: to: {
+			service: services.mqtt
+			interface: {
+				socket: {
+					api: {
+						title: "MQTT protocol"
+						url:   urls.mqtt
+					}
+					direction: "outgoing"
+					protocols: ["tcp"]
+					ssl: "optional"
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		clean_session: {
+			common:      false
+			description: "Whether to start a clean session. When disabled, the broker keeps the subscriptions and undelivered QoS 1 messages of the `client_id` across reconnects."
+			required:    false
+			type: bool: default: true
+		}
+		client_id: {
+			common:      true
+			description: "The client identifier to connect with. It must be unique among the clients of the broker."
+			required:    false
+			type: string: {
+				default: "vector"
+				examples: ["vector-1"]
+				syntax: "literal"
+			}
+		}
+		host: {
+			description: "The host name of the MQTT broker."
+			required:    true
+			type: string: {
+				examples: ["127.0.0.1", "mqtt.example.com"]
+				syntax: "literal"
+			}
+		}
+		keep_alive_secs: {
+			common:      false
+			description: "The interval at which the connection is kept alive with pings when idle."
+			required:    false
+			type: uint: {
+				default: 60
+				unit:    "seconds"
+			}
+		}
+		password: {
+			common:      false
+			description: "The password to authenticate with. Must be set together with `user`."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["${MQTT_PASSWORD}"]
+				syntax: "literal"
+			}
+		}
+		port: {
+			common:      true
+			description: "The port of the MQTT broker."
+			required:    false
+			type: uint: {
+				default: 1883
+				unit:    null
+			}
+		}
+		qos: {
+			common:      true
+			description: "The MQTT quality of service level: `0` (at most once) or `1` (at least once). `2` (exactly once) isn't supported."
+			required:    false
+			type: uint: {
+				default: 0
+				examples: [1]
+				unit: null
+			}
+		}
+		user: {
+			common:      false
+			description: "The user name to authenticate with. Must be set together with `password`."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["vector"]
+				syntax: "literal"
+			}
+		}
+	}
+
+	how_it_works: {
+		rumqttc: {
+			title: "rumqttc"
+			body:  """
+				The `mqtt` source/sink uses [`rumqttc`](\(urls.rumqttc)) under the hood,
+				which supports MQTT 3.1.1. TLS connections require `tls.ca_file` to be set.
+				"""
+		}
+	}
+}
@@ -0,0 +1,74 @@
+package metadata
+
+components: _parquet: {
+	configuration: {
+		parquet: {
+			common:      false
+			description: "Options for the `parquet` encoding codec."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					compression: {
+						common:      false
+						description: "The compression applied to the pages of the Parquet files. The objects themselves are never compressed when using the `parquet` codec, so the `compression` option is ignored."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "snappy"
+							enum: {
+								none:   "The pages are not compressed."
+								snappy: "The pages are compressed with [Snappy](\(urls.snappy))."
+								zstd:   "The pages are compressed with [Zstandard](\(urls.zstd))."
+							}
+							syntax: "literal"
+						}
+					}
+					schema: {
+						common:      true
+						description: "The columns of the Parquet files, mapping field names to their type. If unset, the columns are inferred from the top level fields of the batches: columns keep the type they were first inferred with, and fields first seen in later batches add new columns."
+						required:    false
+						warnings: []
+						type: object: {
+							examples: [{"message": "string", "status": "integer", "timestamp": "timestamp"}]
+							options: {
+								"*": {
+									description: "The type of the column, one of `string`, `integer`, `float`, `boolean` or `timestamp`."
+									required:    true
+									warnings: []
+									type: string: {
+										enum: {
+											string:    "UTF-8 strings. Maps and arrays are written as JSON."
+											integer:   "64 bit signed integers."
+											float:     "64 bit floating point numbers."
+											boolean:   "Booleans."
+											timestamp: "Timestamps with millisecond precision."
+										}
+										syntax: "literal"
+									}
+								}
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+
+	how_it_works: {
+		parquet: {
+			title: "Parquet encoding"
+			body:  """
+				With the `parquet` codec, each batch is written as a Parquet file holding a
+				single row group, so the `batch` options size the row groups as well as the
+				objects. All the columns are optional: fields missing from an event are written
+				as nulls, and fields not in a declared `schema` are dropped. Values are coerced
+				to the type of their column: `string` columns hold any value, `float` columns
+				hold integers too, and strings are parsed for the other types. Values that
+				can't be coerced are written as nulls, and counted in
+				`processing_errors_total`. Batches that fail to be encoded are dropped.
+				"""
+		}
+	}
+}
@@ -0,0 +1,88 @@
+package metadata
+
+components: sinks: amqp: {
+	title: "AMQP"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: {
+					enabled: true
+					default: null
+					enum: ["json", "text"]
+				}
+			}
+			request: enabled: false
+			tls: enabled:     false
+			to: components._amqp.features.send.to
+		}
+	}
+
+	support: components._amqp.support
+
+	configuration: components._amqp.configuration & {
+		exchange: {
+			description: "The exchange to publish messages to. It must already exist."
+			required:    true
+			type: string: {
+				examples: ["audit", "logs-{{ application }}"]
+				syntax: "template"
+			}
+		}
+		routing_key: {
+			common:      true
+			description: "The routing key of the published messages."
+			required:    false
+			type: string: {
+				default: ""
+				examples: ["{{ kubernetes.pod_namespace }}", "auth.login"]
+				syntax: "template"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: components._amqp.how_it_works & {
+		publisher_confirms: {
+			title: "Publisher confirms"
+			body:  """
+				Messages are published as persistent and events are only acknowledged once
+				the broker confirms it has taken responsibility for their message. Events
+				whose message the broker rejected, or that failed to be published, are
+				marked as errored.
+				"""
+		}
+		reconnects: {
+			title: "Reconnects"
+			body:  """
+				When publishing fails, as it does once the connection to the broker is lost,
+				Vector reconnects with an exponential backoff, up to a minute between
+				attempts, before publishing the next event.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		connection_errors_total: components.sources.internal_metrics.output.metrics.connection_errors_total
+		connection_failed_total: components.sources.internal_metrics.output.metrics.connection_failed_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		send_errors_total:       components.sources.internal_metrics.output.metrics.send_errors_total
+	}
+}
@@ -33,7 +33,7 @@ components: sinks: aws_s3: components._aws & {
 				codec: {
 					enabled: true
 					default: null
-					enum: ["ndjson", "text"]
+					enum: ["ndjson", "text", "parquet"]
 				}
 			}
 			proxy: enabled: true
@@ -82,7 +82,7 @@ components: sinks: aws_s3: components._aws & {
 		notices: []
 	}
 
-	configuration: {
+	configuration: components._parquet.configuration & {
 		acl: {
 			category:    "ACL"
 			common:      false
@@ -290,7 +290,7 @@ components: sinks: aws_s3: components._aws & {
 		metrics: null
 	}
 
-	how_it_works: {
+	how_it_works: components._parquet.how_it_works & {
 		cross_account: {
 			title: "Cross account object writing"
 			body:  """
@@ -33,7 +33,7 @@ components: sinks: azure_blob: {
 				codec: {
 					enabled: true
 					default: null
-					enum: ["ndjson", "text"]
+					enum: ["ndjson", "text", "parquet"]
 				}
 			}
 			request: {
@@ -77,7 +77,7 @@ components: sinks: azure_blob: {
 		notices: []
 	}
 
-	configuration: {
+	configuration: components._parquet.configuration & {
 		connection_string: {
 			description: "The Azure Blob Storage Account connection string. Only authentication with access key supported."
 			required:    true
@@ -134,7 +134,7 @@ components: sinks: azure_blob: {
 		metrics: null
 	}
 
-	how_it_works: {
+	how_it_works: components._parquet.how_it_works & {
 		object_naming: {
 			title: "Object naming"
 			body:  """
@@ -30,7 +30,11 @@ components: sinks: clickhouse: {
 			}
 			encoding: {
 				enabled: true
-				codec: enabled: false
+				codec: {
+					enabled: true
+					default: "default"
+					enum: ["default", "row_binary"]
+				}
 			}
 			proxy: enabled: true
 			request: {
@@ -124,7 +128,7 @@ components: sinks: clickhouse: {
 		}
 		skip_unknown_fields: {
 			common:      true
-			description: "Sets `input_format_skip_unknown_fields`, allowing Clickhouse to discard fields not present in the table schema."
+			description: "Sets `input_format_skip_unknown_fields`, allowing Clickhouse to discard fields not present in the table schema. With the `row_binary` codec, the fields not present in the table schema are always discarded."
 			required:    false
 			type: bool: default: false
 		}
@@ -134,4 +138,35 @@ components: sinks: clickhouse: {
 		logs:    true
 		metrics: null
 	}
+
+	how_it_works: {
+		row_binary: {
+			title: "Typed inserts"
+			body:  """
+				By default, events are inserted in the `JSONEachRow` format, leaving the conversion
+				of their fields to Clickhouse, which fails the whole batch on a type mismatch. With
+				the `row_binary` codec, Vector fetches the schema of the table with the first request,
+				retrying until the table can be described, coerces the fields named after the columns
+				to their types, and inserts the rows in the `RowBinary` format, which is cheaper to
+				encode and to parse. When an insert fails because the rows don't match the table,
+				the schema is fetched again and the rows are encoded with it. The missing fields
+				get the default value of the type of their column, or `NULL` for the `Nullable`
+				columns, and nested fields are matched by the column names with dots, like
+				`input_format_import_nested_json`.
+
+				The supported types are the integers, `Float32`, `Float64`, `Bool`, `String`,
+				`FixedString`, `Date`, `DateTime`, `DateTime64`, `UUID`, and the `Nullable`,
+				`Array`, `Map` and `LowCardinality` of them. The columns with a `MATERIALIZED`
+				or `ALIAS` default are left to Clickhouse. An event with a field that can't be
+				coerced, like a string that isn't a number for an integer column, is dropped and
+				the failure is logged with the name of the field and counted by the
+				`processing_errors_total` metric.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
 }
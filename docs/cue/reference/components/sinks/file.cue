@@ -72,6 +72,64 @@ components: sinks: file: {
 				syntax: "template"
 			}
 		}
+		rotation: {
+			common:      false
+			description: "Rotates the files written to, keeping the previous ones under numbered suffixes. The current file is renamed to `<path>.1`, the previous `<path>.1` to `<path>.2`, and so on."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					compression: {
+						common:      true
+						description: "The compression applied to rotated files. Can't be used along with `compression`, which compresses files as they're written."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "none"
+							enum: {
+								none: "Rotated files are left uncompressed."
+								gzip: "Rotated files are compressed with gzip, and suffixed with `.gz`."
+							}
+							syntax: "literal"
+						}
+					}
+					max_age_secs: {
+						common:      true
+						description: "The age after which a file is rotated, counted from when it was created. Files that are idle or were written to before a restart are rotated once old enough too."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: null
+							examples: [86400]
+							unit: "seconds"
+						}
+					}
+					max_file_size: {
+						common:      true
+						description: "The size after which a file is rotated."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: null
+							examples: [104857600]
+							unit: "bytes"
+						}
+					}
+					max_files: {
+						common:      true
+						description: "The number of rotated files to keep per path. Older files are removed. If unset, rotated files are never removed."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: null
+							examples: [7]
+							unit: null
+						}
+					}
+				}
+			}
+		}
 	}
 
 	input: {
@@ -89,6 +147,18 @@ components: sinks: file: {
 				to create and write to files in the specified directories.
 				"""
 		}
+		rotation: {
+			title: "Rotation"
+			body: """
+				With `rotation` set, a file is rotated before an event is written to it once
+				it has reached `rotation.max_file_size`, and as soon as it was created
+				`rotation.max_age_secs` ago. The age of a file counts from its creation time
+				on disk, or, on filesystems that don't record it, from when Vector first
+				opened it. The file is closed and synced to disk first, so that
+				rotated files are complete. Rotation applies to each rendered `path`
+				separately, so it can be combined with a templated path.
+				"""
+		}
 	}
 
 	telemetry: metrics: {
@@ -33,7 +33,7 @@ components: sinks: gcp_cloud_storage: {
 				codec: {
 					enabled: true
 					default: null
-					enum: ["ndjson", "text"]
+					enum: ["ndjson", "text", "parquet"]
 				}
 			}
 			proxy: enabled: true
@@ -88,7 +88,7 @@ components: sinks: gcp_cloud_storage: {
 		notices: []
 	}
 
-	configuration: {
+	configuration: components._parquet.configuration & {
 		acl: {
 			category:    "ACL"
 			common:      false
@@ -206,7 +206,7 @@ components: sinks: gcp_cloud_storage: {
 		metrics: null
 	}
 
-	how_it_works: {
+	how_it_works: components._parquet.how_it_works & {
 		object_access_control_list: {
 			title: "Object access control list (ACL)"
 			body:  """
@@ -0,0 +1,117 @@
+package metadata
+
+components: sinks: gelf: {
+	title: "GELF"
+
+	classes: sinks.socket.classes
+
+	features: {
+		buffer:      sinks.socket.features.buffer
+		healthcheck: sinks.socket.features.healthcheck
+		send: {
+			compression: sinks.socket.features.send.compression
+			encoding: {
+				enabled: true
+				codec: enabled: false
+			}
+			request: sinks.socket.features.send.request
+			send_buffer_bytes: {
+				enabled:       true
+				relevant_when: "mode = `tcp` or mode = `udp`"
+			}
+			tls: sinks.socket.features.send.tls
+			to: {
+				service: services.gelf
+
+				interface: {
+					socket: {
+						api: {
+							title: "GELF"
+							url:   urls.gelf
+						}
+						direction: "outgoing"
+						protocols: ["tcp", "udp"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+	}
+
+	support: sinks.socket.support
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	configuration: {
+		address: {
+			description: "The address to connect to. The address _must_ include a port."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["127.0.0.1:12201"]
+				syntax: "literal"
+			}
+		}
+		compression: {
+			common:        true
+			description:   "The compression of the messages sent over UDP."
+			relevant_when: "mode = `udp`"
+			required:      false
+			warnings: []
+			type: string: {
+				default: "gzip"
+				enum: {
+					none: "No compression."
+					gzip: "[Gzip](\(urls.gzip)) compression."
+					zlib: "[Zlib](\(urls.zlib)) compression."
+				}
+				syntax: "literal"
+			}
+		}
+		max_chunk_size: {
+			common:        false
+			description:   "The maximum size of the datagrams sent over UDP, including the 12 bytes header of the chunks. Larger messages are split into chunks, and dropped if they need more than 128 chunks."
+			relevant_when: "mode = `udp`"
+			required:      false
+			warnings: []
+			type: uint: {
+				default: 1420
+				unit:    "bytes"
+			}
+		}
+		mode: {
+			description: "The type of socket to use."
+			required:    true
+			warnings: []
+			type: string: {
+				enum: {
+					tcp: "TCP socket, with messages terminated by a null byte."
+					udp: "UDP socket, with messages compressed and chunked."
+				}
+				syntax: "literal"
+			}
+		}
+	}
+
+	how_it_works: {
+		fields: {
+			title: "Fields"
+			body:  """
+				The message, host and timestamp fields of the log schema become the `short_message`,
+				`host` and `timestamp` of the GELF messages, the events missing a message or a host
+				being dropped. A `level` field holding a syslog severity, either as a number or a
+				name like `error`, becomes the `level` of the message, and the `full_message`,
+				`facility`, `line` and `file` fields are sent as they are. The other fields are sent
+				as additional fields, prefixed with an underscore, with nested fields flattened and
+				the characters GELF doesn't allow in their names replaced by `_`.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
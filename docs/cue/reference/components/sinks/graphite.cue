@@ -0,0 +1,125 @@
+package metadata
+
+components: sinks: graphite: {
+	title: "Graphite"
+
+	classes: sinks.socket.classes
+
+	features: {
+		buffer:      sinks.socket.features.buffer
+		healthcheck: sinks.socket.features.healthcheck
+		send: {
+			compression: sinks.socket.features.send.compression
+			encoding: {
+				enabled: true
+				codec: enabled: false
+			}
+			request: sinks.socket.features.send.request
+			send_buffer_bytes: {
+				enabled:       true
+				relevant_when: "mode = `tcp` or mode = `udp`"
+			}
+			tls: sinks.socket.features.send.tls
+			to: {
+				service: services.graphite
+
+				interface: {
+					socket: {
+						api: {
+							title: "Carbon plaintext protocol"
+							url:   urls.graphite_plaintext_protocol
+						}
+						direction: "outgoing"
+						protocols: ["tcp", "udp"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+	}
+
+	support: sinks.socket.support
+
+	input: {
+		logs: false
+		metrics: {
+			counter:      true
+			distribution: false
+			gauge:        true
+			histogram:    true
+			set:          true
+			summary:      true
+		}
+	}
+
+	configuration: {
+		address: {
+			description: "The address to connect to. The address _must_ include a port."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["127.0.0.1:2003"]
+				syntax: "literal"
+			}
+		}
+		default_namespace: {
+			common: true
+			description: """
+				Used as a namespace for metrics that don't have it.
+				A namespace will be prefixed to a metric's name.
+				"""
+			required: false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["service"]
+				syntax: "literal"
+			}
+		}
+		mode: {
+			description: "The type of socket to use."
+			required:    true
+			warnings: []
+			type: string: {
+				enum: {
+					tcp: "TCP socket"
+					udp: "UDP socket"
+				}
+				syntax: "literal"
+			}
+		}
+		path: {
+			common:      true
+			description: "The path of the metrics. Defaults to their namespace and name joined by dots."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["servers.{{ tags.host }}.{{ name }}"]
+				syntax: "template"
+			}
+		}
+		tagged_series: {
+			common:      false
+			description: "Appends the tags of the metrics to their path as [tagged series](\(urls.graphite_tags)), which Graphite supports since version 1.1."
+			required:    false
+			warnings: []
+			type: bool: default: true
+		}
+	}
+
+	how_it_works: {
+		metric_types: {
+			title: "Metric types"
+			body:  """
+				Counters and gauges are sent as their value, sets as their number of values, and
+				aggregated histograms and summaries as their count and sum, under the `count` and
+				`sum` children of their path. Distributions can't be sent, and are dropped.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
@@ -97,12 +97,46 @@ components: sinks: http: {
 		uri: {
 			description: """
 				The full URI to make HTTP requests to. This should include the protocol and host,
-				but can also include the port, path, and any other valid part of a URI.
+				but can also include the port, path, and any other valid part of a URI. Events are
+				batched per rendered URI.
 				"""
 			required: true
 			warnings: []
 			type: string: {
-				examples: ["https://10.22.212.22:9000/endpoint"]
+				examples: ["https://10.22.212.22:9000/endpoint", "https://10.22.212.22:9000/{{ tenant }}/endpoint"]
+				syntax: "template"
+			}
+		}
+		payload_prefix: {
+			common:      false
+			description: "Text written at the start of the body of each request, before the first event. Defaults to `[` for the `json` codec, and nothing otherwise."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["{\"events\":["]
+				syntax: "literal"
+			}
+		}
+		payload_separator: {
+			common:      false
+			description: "Text written between two events in the body of each request. Defaults to `,` for the `json` codec, and a newline otherwise."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: [",", "\n"]
+				syntax: "literal"
+			}
+		}
+		payload_suffix: {
+			common:      false
+			description: "Text written at the end of the body of each request, after the last event. Defaults to `]` for the `json` codec, and a newline otherwise."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["]}"]
 				syntax: "literal"
 			}
 		}
@@ -127,6 +161,29 @@ components: sinks: http: {
 		metrics: null
 	}
 
+	how_it_works: {
+		partitioning: {
+			title: "Partitioning"
+			body: """
+				Both the `uri` and the values of `request.headers` are templates. Events are
+				batched by the rendered URI and header values, so each request only contains
+				events sharing them. Events for which a template can't be rendered are dropped.
+				Only `{{ field }}` placeholders are rendered: `%` is kept as is, so
+				percent-encoded values don't need escaping.
+				"""
+		}
+
+		payload_framing: {
+			title: "Payload Framing"
+			body: """
+				The body of each request is made of `payload_prefix`, the encoded events
+				separated by `payload_separator`, and `payload_suffix`. Overriding them allows
+				talking to bulk APIs expecting a different envelope, like
+				`{"events":[...]}`.
+				"""
+		}
+	}
+
 	telemetry: metrics: {
 		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
 		http_bad_requests_total: components.sources.internal_metrics.output.metrics.http_bad_requests_total
@@ -34,7 +34,7 @@ components: sinks: kafka: {
 				codec: {
 					enabled: true
 					default: null
-					enum: ["json", "text"]
+					enum: ["avro", "json", "text"]
 				}
 			}
 			request: enabled: false
@@ -53,6 +53,17 @@ components: sinks: kafka: {
 
 	configuration: {
 		bootstrap_servers: components._kafka.configuration.bootstrap_servers
+		headers_key: {
+			common:      false
+			description: "The log field name to read the message headers from. The field must be a map, each key and value of which is sent as a header. If unspecified, no headers are sent."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["headers"]
+				syntax: "literal"
+			}
+		}
 		key_field: {
 			common:      true
 			description: "The log field name or tags key to use for the topic key. If the field does not exist in the log or in tags, a blank value will be used. If unspecified, the key is not sent. Kafka uses a hash of the key to choose the partition or uses round-robin if the record has no key."
@@ -127,6 +138,30 @@ components: sinks: kafka: {
 				}
 			}
 		}
+		schema_registry: {
+			common:      false
+			description: "The schema registry used to register and look up the schemas of `avro` encoded messages. Required if `encoding.codec` is `avro`."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					auth: configuration._http_auth & {_args: {
+						password_example: "${SCHEMA_REGISTRY_PASSWORD}"
+						username_example: "${SCHEMA_REGISTRY_USERNAME}"
+					}}
+					url: {
+						description: "The URL of the Confluent compatible schema registry."
+						required:    true
+						warnings: []
+						type: string: {
+							examples: ["http://localhost:8081"]
+							syntax: "literal"
+						}
+					}
+				}
+			}
+		}
 		socket_timeout_ms: components._kafka.configuration.socket_timeout_ms
 		topic: {
 			description: "The Kafka topic name to write events to."
@@ -134,7 +169,7 @@ components: sinks: kafka: {
 			warnings: []
 			type: string: {
 				examples: ["topic-1234", "logs-{{unit}}-%Y-%m-%d"]
-				syntax: "literal"
+				syntax: "template"
 			}
 		}
 	}
@@ -151,7 +186,22 @@ components: sinks: kafka: {
 		}
 	}
 
-	how_it_works: components._kafka.how_it_works
+	how_it_works: components._kafka.how_it_works & {
+		avro: {
+			title: "Avro Encoding"
+			body:  """
+				With `encoding.codec` set to `avro`, log events are encoded with Avro and written
+				in the Confluent wire format, which prefixes each message with the id of its
+				schema in the `schema_registry`. The schema is looked up under the
+				`<topic>-value` subject. If `encoding.schema` is set, it's registered under that
+				subject first, otherwise the latest version registered is used. The latest version
+				is looked up again every five minutes, and whenever an event doesn't match it, so
+				schemas evolved in the registry are picked up without a restart. Fields that
+				aren't part of the schema are dropped, and events that don't match it are
+				rejected.
+				"""
+		}
+	}
 
 	telemetry: metrics: {
 		events_discarded_total:              components.sources.internal_metrics.output.metrics.events_discarded_total
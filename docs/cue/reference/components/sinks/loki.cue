@@ -35,7 +35,7 @@ components: sinks: loki: {
 			proxy: enabled: true
 			request: {
 				enabled:                    true
-				concurrency:                1
+				concurrency:                5
 				rate_limit_duration_secs:   1
 				rate_limit_num:             5
 				retry_initial_backoff_secs: 1
@@ -93,10 +93,26 @@ components: sinks: loki: {
 			password_example: "${LOKI_PASSWORD}"
 			username_example: "${LOKI_USERNAME}"
 		}}
+		compression: {
+			common:      false
+			description: "The compression and format of the requests."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "none"
+				enum: {
+					none:   "The streams are sent in JSON, uncompressed."
+					snappy: "The streams are sent in protobuf, compressed with [Snappy](\(urls.snappy)). This is the format of Promtail, much smaller than JSON."
+				}
+				syntax: "literal"
+			}
+		}
 		labels: {
 			description: """
 				A set of labels that are attached to each batch of events. Both keys and values are templatable, which
-				enables you to attach dynamic labels to events. Note: If the set of labels has high cardinality, this
+				enables you to attach dynamic labels to events. A key ending with `*` expands an object field into one
+				label per key of the object, named after the key with the `*` replaced by the object key, like
+				`"pod_labels_*" = "{{ kubernetes.pod_labels }}"`. Note: If the set of labels has high cardinality, this
 				can cause drastic performance issues with Loki. To prevent this from happening, reduce the number of
 				unique label keys and values.
 				"""
@@ -109,6 +125,7 @@ components: sinks: loki: {
 						"event":                 "{{ event_field }}"
 						"key":                   "value"
 						"\"{{ event_field }}\"": "{{ another_event_field }}"
+						"pod_labels_*":          "{{ kubernetes.pod_labels }}"
 					},
 				]
 				options: {
@@ -198,9 +215,26 @@ components: sinks: loki: {
 		concurrency: {
 			title: "Concurrency"
 			body: """
-				To make sure logs arrive at Loki in a correct order,
-				the `loki` sink only sends one request at a time.
-				Setting `request.concurrency` will not have any effects.
+				The events are batched per tenant and stream, the set of
+				labels of the events. To make sure logs arrive at Loki in
+				a correct order, the requests of a stream are sent one at
+				a time, while the requests of different streams are sent
+				concurrently, following `request.concurrency`. At most 1000
+				streams are batched at once, the batch of a stream being
+				sent early when the events of another stream come in.
+				"""
+		}
+
+		wildcard_labels: {
+			title: "Wildcard Labels"
+			body: """
+				A label key ending with `*` must have a single field as
+				value, like `{{ kubernetes.pod_labels }}`. When this field
+				is an object, each of its keys becomes a label prefixed
+				by the label key without the `*`, with the characters not
+				allowed in label names, like `.` or `/`, replaced by `_`.
+				The labels with an explicit key take precedence over the
+				expanded ones.
 				"""
 		}
 
@@ -0,0 +1,81 @@
+package metadata
+
+components: sinks: mqtt: {
+	title: "MQTT"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: {
+					enabled: true
+					default: null
+					enum: ["json", "text"]
+				}
+			}
+			request: enabled: false
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: false
+				can_verify_hostname:    false
+				enabled_default:        false
+			}
+			to: components._mqtt.features.send.to
+		}
+	}
+
+	support: components._mqtt.support
+
+	configuration: components._mqtt.configuration & {
+		retain: {
+			common:      false
+			description: "Whether the broker retains the last message of each topic for new subscribers."
+			required:    false
+			type: bool: default: false
+		}
+		topic: {
+			description: "The topic to publish messages to."
+			required:    true
+			type: string: {
+				examples: ["vector", "sensors/{{ host }}"]
+				syntax: "template"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: components._mqtt.how_it_works & {
+		delivery: {
+			title: "Delivery"
+			body:  """
+				With `qos` set to `1`, events are only acknowledged once the broker has
+				acknowledged the message, and messages that weren't are sent again after
+				reconnecting. With `qos` set to `0`, events are acknowledged as soon as they
+				are written to the connection.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		connection_errors_total: components.sources.internal_metrics.output.metrics.connection_errors_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		send_errors_total:       components.sources.internal_metrics.output.metrics.send_errors_total
+	}
+}
@@ -91,6 +91,16 @@ components: sinks: prometheus_exporter: {
 				items: type: float: examples: [0.005, 0.01]
 			}
 		}
+		distributions_as_summaries: {
+			common:      false
+			description: """
+				Exposes [distribution](\(urls.vector_data_model)/metric#distribution) metrics as summaries,
+				with the `quantiles` computed from their samples, instead of aggregating them into histograms.
+				"""
+			required:    false
+			warnings: []
+			type: bool: default: false
+		}
 		flush_period_secs: {
 			common:      false
 			description: "Time interval between [set](\(urls.vector_data_model)/metric#set) values are reset."
@@ -101,6 +111,16 @@ components: sinks: prometheus_exporter: {
 				unit:    "seconds"
 			}
 		}
+		ttl_secs: {
+			common:      false
+			description: "The time after which a series that wasn't updated is removed. By default, series are never removed."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: null
+				unit:    "seconds"
+			}
+		}
 		default_namespace: {
 			common:      true
 			description: """
@@ -341,6 +361,17 @@ components: sinks: prometheus_exporter: {
 	]
 
 	how_it_works: {
+		openmetrics: {
+			title: "OpenMetrics"
+			body:  """
+				Scrapers accepting the [OpenMetrics](\(urls.openmetrics)) format, like Prometheus 2.5 and
+				later, receive the metrics in that format instead of the Prometheus text format. The
+				counters, histograms and summaries accumulated from incremental metrics then include a
+				`_created` sample with the time Vector started accumulating them. Exemplars can't be
+				exposed, as Vector's metrics don't carry them.
+				"""
+		}
+
 		histogram_buckets: {
 			title: "Histogram Buckets"
 			body:  """
@@ -364,6 +395,16 @@ components: sinks: prometheus_exporter: {
 			]
 		}
 
+		expiration: {
+			title: "Expiration"
+			body:  """
+				With `ttl_secs` set, the series which weren't updated for that long are removed, so
+				that the series of sources which went away, like terminated pods, don't stay exposed
+				and accumulate in memory. The `ttl_secs` should therefore be longer than the interval
+				the metrics are sent at.
+				"""
+		}
+
 		memory_usage: {
 			title: "Memory Usage"
 			body: """
@@ -122,6 +122,44 @@ components: sinks: splunk_hec: {
 				syntax: "template"
 			}
 		}
+		indexer_acknowledgements: {
+			common:      false
+			description: "Options for Splunk's [indexer acknowledgements](\(urls.splunk_hec_indexer_acknowledgements))."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					enabled: {
+						common:      false
+						description: "Only finalize events once Splunk confirms it has indexed them, rather than once it accepted the request. The HEC token must have indexer acknowledgements enabled, otherwise events are finalized as usual."
+						required:    false
+						warnings: []
+						type: bool: default: false
+					}
+					query_interval_secs: {
+						common:      false
+						description: "How often to query Splunk for the status of the pending acknowledgements."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 10
+							unit:    "seconds"
+						}
+					}
+					timeout_secs: {
+						common:      false
+						description: "The time after which a request that Splunk hasn't acknowledged is given up on, and its events are reported as errored."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 300
+							unit:    "seconds"
+						}
+					}
+				}
+			}
+		}
 		indexed_fields: {
 			common:      true
 			description: "Fields to be [added to Splunk index](\(urls.splunk_hec_indexed_fields))."
@@ -169,8 +207,43 @@ components: sinks: splunk_hec: {
 	}
 
 	input: {
-		logs:    true
-		metrics: null
+		logs: true
+		metrics: {
+			counter:      true
+			distribution: true
+			gauge:        true
+			histogram:    true
+			set:          true
+			summary:      true
+		}
+	}
+
+	how_it_works: {
+		metrics: {
+			title: "Metrics"
+			body: """
+				Metrics are sent in Splunk's multi-metric [metrics format](\(urls.splunk_hec_metrics)),
+				to be stored in a metrics index. The tags of a metric become its dimensions, except for
+				the one named by `host_key`, which is sent as the host. Counters and gauges are sent as
+				a single `metric_name:<namespace>.<name>` measurement, and sets as their number of
+				values. Other metrics are flattened into several measurements: `.count` and `.sum` for
+				histograms and summaries, along with `.bucket.<upper limit>` for each bucket of a
+				histogram and `.quantile.<quantile>` for each quantile of a summary. Distributions are
+				sent as their `.count`, `.sum`, `.min`, `.max`, `.median` and `.avg` statistics.
+				"""
+		}
+
+		indexer_acknowledgements: {
+			title: "Indexer acknowledgements"
+			body: """
+				With `indexer_acknowledgements.enabled` the sink sends its requests on a channel of its
+				own and keeps the `ackId` Splunk returns for each. It then polls the
+				`/services/collector/ack` endpoint every `query_interval_secs`, and only reports the events
+				of a request as delivered once Splunk confirms they were indexed. Requests that are still
+				unconfirmed after `timeout_secs` are reported as errored. The events of a request are only
+				acknowledged to a disk buffer once its status is known.
+				"""
+		}
 	}
 
 	telemetry: metrics: {
@@ -180,5 +253,6 @@ components: sinks: splunk_hec: {
 		processed_bytes_total:     components.sources.internal_metrics.output.metrics.processed_bytes_total
 		processed_events_total:    components.sources.internal_metrics.output.metrics.processed_events_total
 		requests_received_total:   components.sources.internal_metrics.output.metrics.requests_received_total
+		send_errors_total:         components.sources.internal_metrics.output.metrics.send_errors_total
 	}
 }
@@ -76,19 +76,120 @@ components: sinks: vector: {
 
 	configuration: {
 		address: {
-			description: "The downstream Vector address to connect to. The address _must_ include a port."
-			required:    true
+			description: "The downstream Vector address to connect to. The address _must_ include a port. Either this or `addresses` must be set."
+			required:    false
+			common:      true
 			warnings: []
 			type: string: {
+				default: null
 				examples: ["92.12.333.224:5000"]
 				syntax: "literal"
 			}
 		}
+		addresses: {
+			description: "The downstream Vector addresses to load balance events over. Each address _must_ include a port, and host names resolving to several addresses add one peer per address."
+			required:    false
+			common:      true
+			warnings: []
+			type: array: {
+				default: null
+				items: type: string: {
+					examples: ["92.12.333.224:5000", "http://aggregator.svc:6000"]
+					syntax: "literal"
+				}
+			}
+		}
+		load_balancing: {
+			common:      false
+			description: "Options for spreading events over the peers, see [load balancing](#load-balancing)."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					strategy: {
+						common:      false
+						description: "How the peer receiving an event is picked."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "round_robin"
+							enum: {
+								round_robin:     "Batches are sent to the healthy peers in turn."
+								consistent_hash: "Events sharing the value of `hash_field` are sent to the same peer, for stateful aggregation downstream."
+							}
+							syntax: "literal"
+						}
+					}
+					hash_field: {
+						common:      false
+						description: "The field, or tag for metrics, whose value picks the peer of an event with the `consistent_hash` strategy. Events without it are sent round robin."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["host"]
+							syntax: "field_path"
+						}
+					}
+					dns_refresh_secs: {
+						common:      false
+						description: "How often host names are resolved again to discover peers."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 30
+							unit:    "seconds"
+						}
+					}
+					unhealthy_threshold: {
+						common:      false
+						description: "The number of consecutive failed requests after which a peer is ejected."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 3
+							unit:    null
+						}
+					}
+					ejection_secs: {
+						common:      false
+						description: "How long an ejected peer doesn't receive requests."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 30
+							unit:    "seconds"
+						}
+					}
+				}
+			}
+		}
 	}
 
-	how_it_works: components.sources.vector.how_it_works
+	how_it_works: components.sources.vector.how_it_works & {
+		load_balancing: {
+			title: "Load Balancing"
+			body: """
+				The sink keeps a gRPC connection to each peer: every address in `address` and
+				`addresses`, or every IP address their host names resolve to. Host names are
+				resolved again every `load_balancing.dns_refresh_secs`, adding and removing peers
+				as the DNS records change.
+
+				With the `round_robin` strategy batches go to the peers in turn. With
+				`consistent_hash` events are routed by the value of `hash_field` using rendezvous
+				hashing, so that only the events of a peer which goes away move to other peers.
+
+				A peer whose requests fail `unhealthy_threshold` times in a row is ejected for
+				`ejection_secs`, during which its requests fail over to the remaining peers. When
+				every peer is ejected, events are spread over all of them.
+				"""
+		}
+	}
 
 	telemetry: metrics: {
+		dns_lookup_errors_total:      components.sources.internal_metrics.output.metrics.dns_lookup_errors_total
+		peer_ejections_total:         components.sources.internal_metrics.output.metrics.peer_ejections_total
 		processed_bytes_total:        components.sources.internal_metrics.output.metrics.processed_bytes_total
 		processed_events_total:       components.sources.internal_metrics.output.metrics.processed_events_total
 		protobuf_decode_errors_total: components.sources.internal_metrics.output.metrics.protobuf_decode_errors_total
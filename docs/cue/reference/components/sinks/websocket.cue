@@ -0,0 +1,128 @@
+package metadata
+
+components: sinks: websocket: {
+	title: "WebSocket"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: {
+					enabled: true
+					default: null
+					enum: ["json", "text"]
+				}
+			}
+			request: enabled: false
+			tls: {
+				enabled:                true
+				can_enable:             false
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        false
+			}
+			to: {
+				service: services.websocket
+
+				interface: {
+					socket: {
+						api: {
+							title: "WebSocket"
+							url:   urls.websocket
+						}
+						direction: "outgoing"
+						protocols: ["tcp"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+	}
+
+	support: sinks.socket.support
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	configuration: {
+		frame_type: {
+			common:      false
+			description: "The type of the frames the events are sent in."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "text"
+				enum: {
+					text:   "Text frames. Encoded events which aren't valid UTF-8 have their invalid bytes replaced."
+					binary: "Binary frames."
+				}
+				syntax: "literal"
+			}
+		}
+		ping_interval_secs: {
+			common:      false
+			description: "How often the server is pinged to keep the connection alive."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: 30
+				unit:    "seconds"
+			}
+		}
+		ping_timeout_secs: {
+			common:      false
+			description: "The time to wait for the server to answer a ping. The connection is reestablished when the server doesn't answer in time."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: 10
+				unit:    "seconds"
+			}
+		}
+		uri: {
+			description: "The URI of the WebSocket server to connect to. `wss://` URIs connect over TLS, using the `tls` options."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["ws://127.0.0.1:8080/events", "wss://dashboards.example.com/events"]
+				syntax: "literal"
+			}
+		}
+	}
+
+	how_it_works: {
+		connection: {
+			title: "Connection"
+			body:  """
+				Each event is sent as a single WebSocket message. The sink keeps a single connection
+				to the server, reconnecting with an exponential backoff when the connection fails or
+				is closed by the server. The server is pinged every `ping_interval_secs`, and the
+				connection is reestablished if it doesn't answer within `ping_timeout_secs`. An event
+				whose sending failed is sent again once reconnected. The messages sent by the server
+				are ignored.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		connection_errors_total:      components.sources.internal_metrics.output.metrics.connection_errors_total
+		connection_established_total: components.sources.internal_metrics.output.metrics.connection_established_total
+		connection_failed_total:      components.sources.internal_metrics.output.metrics.connection_failed_total
+		connection_shutdown_total:    components.sources.internal_metrics.output.metrics.connection_shutdown_total
+		processed_bytes_total:        components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+}
@@ -0,0 +1,142 @@
+package metadata
+
+components: sources: amqp: {
+	title: "AMQP"
+
+	features: {
+		collect: {
+			checkpoint: enabled: false
+			from: components._amqp.features.collect.from
+		}
+		multiline: enabled: false
+	}
+
+	classes: {
+		commonly_used: false
+		deployment_roles: ["aggregator"]
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	support: components._amqp.support
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: components._amqp.configuration & {
+		acknowledgements: configuration._acknowledgements
+		consumer_tag: {
+			common:      false
+			description: "The identifier of the consumer within the channel."
+			required:    false
+			type: string: {
+				default: "vector"
+				examples: ["vector-audit"]
+				syntax: "literal"
+			}
+		}
+		exchange_key: {
+			common:      false
+			description: "The log field name to use for the exchange the message was published to."
+			required:    false
+			type: string: {
+				default: "exchange"
+				examples: ["exchange"]
+				syntax: "literal"
+			}
+		}
+		prefetch_count: {
+			common:      false
+			description: "The maximum number of messages the broker hands out without them being acknowledged."
+			required:    false
+			type: uint: {
+				default: 100
+				unit:    null
+			}
+		}
+		queue: {
+			description: "The queue to consume from. It must already exist."
+			required:    true
+			type: string: {
+				examples: ["audit-logs"]
+				syntax: "literal"
+			}
+		}
+		routing_key_field: {
+			common:      false
+			description: "The log field name to use for the routing key of the message."
+			required:    false
+			type: string: {
+				default: "routing_key"
+				examples: ["routing_key"]
+				syntax: "literal"
+			}
+		}
+	}
+
+	output: logs: record: {
+		description: "An individual AMQP message"
+		fields: {
+			exchange: {
+				description: "The exchange the message was published to."
+				required:    true
+				type: string: {
+					examples: ["audit"]
+					syntax: "literal"
+				}
+			}
+			message: {
+				description: "The body of the AMQP message."
+				required:    true
+				type: string: {
+					examples: ["{\"user\": \"alice\", \"action\": \"login\"}"]
+					syntax: "literal"
+				}
+			}
+			routing_key: {
+				description: "The routing key of the message."
+				required:    true
+				type: string: {
+					examples: ["auth.login"]
+					syntax: "literal"
+				}
+			}
+			timestamp: fields._current_timestamp & {
+				description: "The `timestamp` property of the message, falling back to the time it was received."
+			}
+		}
+	}
+
+	telemetry: metrics: {
+		connection_errors_total:          components.sources.internal_metrics.output.metrics.connection_errors_total
+		connection_failed_total:          components.sources.internal_metrics.output.metrics.connection_failed_total
+		connection_send_ack_errors_total: components.sources.internal_metrics.output.metrics.connection_send_ack_errors_total
+		events_failed_total:              components.sources.internal_metrics.output.metrics.events_failed_total
+		events_in_total:                  components.sources.internal_metrics.output.metrics.events_in_total
+		processed_bytes_total:            components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+
+	how_it_works: components._amqp.how_it_works & {
+		acknowledgements: {
+			title: "Acknowledgements"
+			body:  """
+				Messages are acknowledged once they're sent to the connected transforms and
+				sinks or, with `acknowledgements` enabled, once they're delivered by the
+				sinks. Messages that failed to be delivered with a transient error are
+				requeued, while those that were rejected are dropped. Unacknowledged messages
+				are redelivered by the broker if Vector disconnects.
+				"""
+		}
+		reconnects: {
+			title: "Reconnects"
+			body:  """
+				When the connection to the broker is lost, Vector reconnects with an
+				exponential backoff, up to a minute between attempts, and consumes from the
+				queue again.
+				"""
+		}
+	}
+}
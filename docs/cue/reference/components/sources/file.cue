@@ -125,6 +125,16 @@ components: sources: file: {
 				}
 			}
 		}
+		checkpoint_interval_ms: {
+			common:      false
+			description: "Delay between writes of the checkpoints to disk. Defaults to `glob_minimum_cooldown_ms`. Lower values reduce the amount of data read again after a crash, at the cost of more disk IO."
+			required:    false
+			type: uint: {
+				default: null
+				examples: [5_000]
+				unit: "milliseconds"
+			}
+		}
 		glob_minimum_cooldown_ms: {
 			common: false
 			description: """
@@ -205,6 +215,28 @@ components: sources: file: {
 				unit: "bytes"
 			}
 		}
+		max_read_bytes_per_sec: {
+			category:    "Reading"
+			common:      false
+			description: "The maximum rate at which data is read across all watched files. When the limit is reached, the next reads resume with the file that was skipped, so every file gets its turn."
+			required:    false
+			type: uint: {
+				default: null
+				examples: [10485760]
+				unit: "bytes"
+			}
+		}
+		max_read_bytes_per_sec_per_file: {
+			category:    "Reading"
+			common:      false
+			description: "The maximum rate at which data is read from a single file. This keeps a busy file from using up the read capacity shared with quieter files."
+			required:    false
+			type: uint: {
+				default: null
+				examples: [1048576]
+				unit: "bytes"
+			}
+		}
 		oldest_first: {
 			category:    "Reading"
 			common:      false
@@ -223,6 +255,25 @@ components: sources: file: {
 				unit: "seconds"
 			}
 		}
+		read_weights: {
+			category:    "Reading"
+			common:      false
+			description: "Weights of the files matching the given glob patterns. A file reads up to `max_read_bytes` times its weight before switching over to the next file. The first matching pattern applies, files matching none have a weight of `1`."
+			required:    false
+			type: object: {
+				examples: [{"/var/log/important/*": 4}]
+				options: {
+					"*": {
+						description: "The weight of the files matching the pattern."
+						required:    true
+						type: uint: {
+							examples: [4]
+							unit: null
+						}
+					}
+				}
+			}
+		}
 		read_from: {
 			common:      true
 			description: "In the absence of a checkpoint, this setting tells Vector where to start reading files that are present at startup."
@@ -309,19 +360,17 @@ components: sources: file: {
 			title: "Compressed Files"
 			body: """
 				Vector will transparently detect files which have been compressed
-				using Gzip and decompress them for reading. This detection process
-				looks for the unique sequence of bytes in the Gzip header and does
-				not rely on the compressed files adhering to any kind of naming
-				convention.
-
-				One caveat with reading compressed files is that Vector is not able
-				to efficiently seek into them. Rather than implement a
-				potentially-expensive full scan as a seek mechanism, Vector
-				currently will not attempt to make further reads from a file for
-				which it has already stored a checkpoint in a previous run. For
-				this reason, users should take care to allow Vector to fully
-				process any compressed files before shutting the process down or moving the
-				files to another location on disk.
+				using Gzip or Zstandard and decompress them for reading. This
+				detection process looks for the unique sequence of bytes in the
+				compression headers and does not rely on the compressed files
+				adhering to any kind of naming convention.
+
+				Compressed files are fingerprinted and checkpointed based on their
+				decompressed content. A file that is compressed after rotation is
+				therefore recognized as the file Vector was already reading, and
+				reading resumes from the last checkpoint instead of ingesting the
+				file again. Since compressed files can't be seeked into, resuming
+				requires decompressing the file up to the checkpoint.
 				"""
 		}
 
@@ -592,6 +641,28 @@ components: sources: file: {
 				"""
 		}
 
+		checkpoint_storage: {
+			title: "Checkpoint Storage"
+			body: """
+				Checkpoints are written to a temporary file which is flushed to disk
+				and then atomically renamed over the previous checkpoints, so that a
+				crash in the middle of a write never leaves them corrupted.
+
+				While running, the source holds an exclusive lock on its data
+				directory. A second Vector process configured with the same data
+				directory fails to start the source instead of reading the same files
+				again and overwriting the checkpoints.
+
+				The checkpoints can be inspected and edited with the
+				`vector checkpoints` command while Vector is stopped, for example to
+				read a single file again from the beginning:
+
+				```bash
+				vector checkpoints reset /var/lib/vector/my_file_source /var/log/app.log
+				```
+				"""
+		}
+
 		read_position: {
 			title: "Read Position"
 			body: """
@@ -613,6 +684,7 @@ components: sources: file: {
 		checkpoint_write_errors_total: components.sources.internal_metrics.output.metrics.checkpoint_write_errors_total
 		checkpoints_total:             components.sources.internal_metrics.output.metrics.checkpoints_total
 		checksum_errors_total:         components.sources.internal_metrics.output.metrics.checksum_errors_total
+		file_backlog_bytes:            components.sources.internal_metrics.output.metrics.file_backlog_bytes
 		file_delete_errors_total:      components.sources.internal_metrics.output.metrics.file_delete_errors_total
 		file_watch_errors_total:       components.sources.internal_metrics.output.metrics.file_watch_errors_total
 		files_added_total:             components.sources.internal_metrics.output.metrics.files_added_total
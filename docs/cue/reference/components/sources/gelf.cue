@@ -0,0 +1,225 @@
+package metadata
+
+components: sources: gelf: {
+	_port: 12201
+
+	title: "GELF"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		deployment_roles: ["aggregator"]
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	features: {
+		multiline: enabled: false
+		receive: {
+			from: {
+				service: services.gelf
+				interface: socket: {
+					api: {
+						title: "GELF"
+						url:   urls.gelf
+					}
+					direction: "incoming"
+					port:      _port
+					protocols: ["tcp", "udp"]
+					ssl: "optional"
+				}
+			}
+			receive_buffer_bytes: {
+				enabled:       true
+				relevant_when: "mode = `tcp` or mode = `udp`"
+			}
+			keepalive: enabled: true
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				enabled_default:        false
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		address: {
+			description:   "The address to listen for messages on. If `mode` is `tcp`, `systemd#N` can be used instead to use the Nth socket passed by systemd socket activation. If an address is used it _must_ include a port."
+			required:      true
+			warnings: []
+			type: string: {
+				examples: ["0.0.0.0:\(_port)", "systemd", "systemd#3"]
+				syntax: "literal"
+			}
+		}
+		chunk_timeout_secs: {
+			common:        false
+			description:   "The time to wait for all the chunks of a message. Messages whose chunks didn't all arrive in time are dropped."
+			relevant_when: "mode = `udp`"
+			required:      false
+			warnings: []
+			type: uint: {
+				default: 5
+				unit:    "seconds"
+			}
+		}
+		max_chunked_messages: {
+			common:        false
+			description:   "The maximum number of messages being reassembled at once. The chunks of new messages are dropped while this many are."
+			relevant_when: "mode = `udp`"
+			required:      false
+			warnings: []
+			type: uint: {
+				default: 1000
+				unit:    null
+			}
+		}
+		max_length: {
+			common:        false
+			description:   "The maximum bytes size of the messages, once decompressed over UDP. Larger messages are discarded."
+			required:      false
+			warnings: []
+			type: uint: {
+				default: 1048576
+				unit:    "bytes"
+			}
+		}
+		mode: {
+			description: "The type of socket to use."
+			required:    true
+			warnings: []
+			type: string: {
+				enum: {
+					tcp: "TCP socket, with messages terminated by a null byte."
+					udp: "UDP socket, with messages optionally compressed and chunked."
+				}
+				syntax: "literal"
+			}
+		}
+		shutdown_timeout_secs: {
+			common:        false
+			description:   "The timeout before a connection is forcefully closed during shutdown."
+			relevant_when: "mode = `tcp`"
+			required:      false
+			warnings: []
+			type: uint: {
+				default: 30
+				unit:    "seconds"
+			}
+		}
+	}
+
+	output: logs: line: {
+		description: "A GELF message"
+		fields: {
+			host: {
+				description: "The `host` of the GELF message."
+				required:    true
+				type: string: {
+					examples: ["web-1"]
+					syntax: "literal"
+				}
+			}
+			message: {
+				description: "The `short_message` of the GELF message."
+				required:    true
+				type: string: {
+					examples: ["Connection refused"]
+					syntax: "literal"
+				}
+			}
+			source_type: {
+				description: "The name of the source type."
+				required:    true
+				type: string: {
+					examples: ["gelf"]
+					syntax: "literal"
+				}
+			}
+			timestamp: {
+				description: "The `timestamp` of the GELF message, or the time it was received at if it has none."
+				required:    true
+				type: timestamp: {}
+			}
+			"*": {
+				description: "The other fields of the GELF message, with the leading underscore of additional fields removed."
+				required:    false
+				type: "*": {}
+			}
+		}
+	}
+
+	examples: [
+		{
+			title: "GELF message"
+			configuration: {}
+			input: """
+				```json
+				{"version":"1.1","host":"web-1","short_message":"Connection refused","timestamp":1628000000.25,"level":3,"_user_id":42}
+				```
+				"""
+			output: log: {
+				host:        "web-1"
+				message:     "Connection refused"
+				timestamp:   "2021-08-03T14:13:20.250Z"
+				level:       3
+				user_id:     42
+				source_type: "gelf"
+			}
+		},
+	]
+
+	how_it_works: {
+		additional_fields: {
+			title: "Additional fields"
+			body:  """
+				The `short_message`, `host` and `timestamp` fields of the messages are mapped onto
+				the message, host and timestamp fields of the [log schema](\(urls.vector_configuration)/global-options#log_schema),
+				and the `version` field is dropped. Additional fields are inserted without their
+				leading underscore, unless a field of that name already exists, and the other fields,
+				like `full_message` and `level`, are inserted as they are.
+				"""
+		}
+
+		chunking: {
+			title: "Chunking and compression"
+			body:  """
+				Over UDP, messages can be compressed with gzip or zlib, and split into up to 128
+				chunks, which are reassembled before the message is decoded. Messages whose chunks
+				don't all arrive within `chunk_timeout_secs` are dropped, as are the chunks of new
+				messages while `max_chunked_messages` are being reassembled.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		events_in_total:            components.sources.internal_metrics.output.metrics.events_in_total
+		connection_errors_total:    components.sources.internal_metrics.output.metrics.connection_errors_total
+		invalid_record_total:       components.sources.internal_metrics.output.metrics.invalid_record_total
+		invalid_record_bytes_total: components.sources.internal_metrics.output.metrics.invalid_record_bytes_total
+		processed_bytes_total:      components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processing_errors_total:    components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
@@ -0,0 +1,191 @@
+package metadata
+
+components: sources: graphite: {
+	_port: 2003
+
+	title: "Graphite"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		deployment_roles: ["aggregator"]
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	features: {
+		multiline: enabled: false
+		receive: {
+			from: {
+				service: services.graphite
+				interface: socket: {
+					api: {
+						title: "Carbon plaintext protocol"
+						url:   urls.graphite_plaintext_protocol
+					}
+					direction: "incoming"
+					port:      _port
+					protocols: ["tcp", "udp"]
+					ssl: "optional"
+				}
+			}
+			receive_buffer_bytes: {
+				enabled:       true
+				relevant_when: "mode = `tcp` or mode = `udp`"
+			}
+			keepalive: enabled: true
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				enabled_default:        false
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		address: {
+			description: "The address to listen for connections on, or `systemd#N` to use the Nth socket passed by systemd socket activation. If an address is used it _must_ include a port."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["0.0.0.0:\(_port)", "systemd", "systemd#3"]
+				syntax: "literal"
+			}
+		}
+		mode: {
+			description: "The type of socket to use."
+			required:    true
+			warnings: []
+			type: string: {
+				enum: {
+					tcp: "TCP Socket."
+					udp: "UDP Socket, which only supports the plaintext protocol."
+				}
+				syntax: "literal"
+			}
+		}
+		protocol: {
+			common:        true
+			description:   "The protocol the metrics are sent with."
+			relevant_when: "mode = `tcp`"
+			required:      false
+			warnings: []
+			type: string: {
+				default: "plaintext"
+				enum: {
+					plaintext: "The [plaintext protocol](\(urls.graphite_plaintext_protocol)), with a `path value timestamp` line per metric."
+					pickle:    "The [pickle protocol](\(urls.graphite_pickle_protocol)), used by Carbon relays and aggregators, usually on port 2004."
+				}
+				syntax: "literal"
+			}
+		}
+		separator: {
+			common:      false
+			description: "Joins the segments of a path that are part of the same name, namespace or tag."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "."
+				examples: ["_"]
+				syntax: "literal"
+			}
+		}
+		shutdown_timeout_secs: {
+			common:        false
+			description:   "The timeout before a connection is forcefully closed during shutdown."
+			relevant_when: "mode = `tcp`"
+			required:      false
+			warnings: []
+			type: uint: {
+				default: 30
+				unit:    "seconds"
+			}
+		}
+		templates: {
+			common:      true
+			description: "Rules splitting the metric paths into a name, a namespace and tags, each made of an optional filter, a template and optional default tags. See [Templates](#templates)."
+			required:    false
+			warnings: []
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["servers.* .host.name*", "stats.* namespace..name* source=statsd", "host.name.name"]
+					syntax: "literal"
+				}
+			}
+		}
+	}
+
+	output: metrics: {
+		gauge: output._passthrough_gauge
+	}
+
+	how_it_works: {
+		templates: {
+			title: "Templates"
+			body:  """
+				Each template rule is made of up to three parts separated by spaces: a filter, a
+				template and default tags, like `servers.* .host.name* region=us-east`. The first
+				rule whose filter matches the path of a metric is applied, a rule without filter
+				matching any path. A filter matches the paths starting with its dot separated
+				segments, `*` matching any segment.
+
+				Each dot separated part of the template tells what the segment of the path at the
+				same position becomes: `name` and `namespace` make it part of the name or the
+				namespace of the metric, `name*` makes it and all the following segments part of
+				the name, an empty part skips it, and any other part makes it the value of the tag
+				of that name. When several segments make up the same name, namespace or tag, they
+				are joined with the `separator`. The whole path is the name of the metrics matching
+				no rule, or whose rule has no `name` part.
+				"""
+		}
+
+		tagged_series: {
+			title: "Tagged series"
+			body:  """
+				[Tagged series](\(urls.graphite_tags)) like `disk.used;datacenter=dc1;server=web-1`
+				are supported, their tags taking precedence over the ones from templates.
+				"""
+		}
+
+		timestamps: {
+			title: "Timestamps"
+			body:  """
+				Metrics are assigned the timestamp they are sent with. Negative timestamps, like the
+				`-1` used by some clients, and missing ones leave the timestamp of the metric empty,
+				which downstream sinks usually substitute with the current time.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		events_in_total:            components.sources.internal_metrics.output.metrics.events_in_total
+		connection_errors_total:    components.sources.internal_metrics.output.metrics.connection_errors_total
+		invalid_record_total:       components.sources.internal_metrics.output.metrics.invalid_record_total
+		invalid_record_bytes_total: components.sources.internal_metrics.output.metrics.invalid_record_bytes_total
+		processed_bytes_total:      components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processing_errors_total:    components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
@@ -130,6 +130,107 @@ components: sources: http: {
 			required: false
 			type: bool: default: true
 		}
+		paths: {
+			common: false
+			description: """
+				A list of URL paths on which log event POST requests are accepted. When set, this replaces
+				`path` and `strict_path`. Path segments written as `{name}` match any single segment and
+				their value is stored in the event under `name`.
+				"""
+			required: false
+			type: array: {
+				default: null
+				items: type: object: options: {
+					path: {
+						description: "The URL path pattern to accept requests on."
+						required:    true
+						type: string: {
+							examples: ["/webhooks/{service}", "/logs"]
+							syntax: "literal"
+						}
+					}
+					strict: {
+						common:      false
+						description: "If set to `false`, requests sent to any URL path starting with `path` are also accepted."
+						required:    false
+						type: bool: default: true
+					}
+					output: {
+						common:      false
+						description: "A name stored in the `output_key` field of events received on this path, for use with the `route` transform."
+						required:    false
+						type: string: {
+							default: null
+							examples: ["webhooks"]
+							syntax: "literal"
+						}
+					}
+					response_code: {
+						common:      false
+						description: "The HTTP status code returned once the events of a request have been accepted."
+						required:    false
+						type: uint: {
+							default: 200
+							unit:    null
+						}
+					}
+					response_body: {
+						common:      false
+						description: "The body returned once the events of a request have been accepted."
+						required:    false
+						type: string: {
+							default: ""
+							syntax:  "literal"
+						}
+					}
+				}
+			}
+		}
+		output_key: {
+			common:      false
+			description: "The event key in which the `output` name of the matching entry of `paths` will be stored."
+			required:    false
+			type: string: {
+				default: "output"
+				syntax:  "literal"
+			}
+		}
+		health_checks: {
+			common:      false
+			description: "A list of URL paths answering `GET` and `HEAD` requests with a fixed response, without producing events."
+			required:    false
+			type: array: {
+				default: null
+				items: type: object: options: {
+					path: {
+						description: "The URL path to answer on."
+						required:    true
+						type: string: {
+							examples: ["/healthz"]
+							syntax: "literal"
+						}
+					}
+					response_code: {
+						common:      false
+						description: "The HTTP status code to respond with."
+						required:    false
+						type: uint: {
+							default: 200
+							unit:    null
+						}
+					}
+					response_body: {
+						common:      false
+						description: "The body to respond with."
+						required:    false
+						type: string: {
+							default: ""
+							syntax:  "literal"
+						}
+					}
+				}
+			}
+		}
 		path_key: {
 			common:      false
 			description: "The event key in which the requested URL path used to send the request will be stored."
@@ -266,7 +367,7 @@ components: sources: http: {
 			title: "Decompression"
 			body: """
 				Received body is decompressed according to `Content-Encoding` header.
-				Supported algorithms are `gzip`, `deflate`, and `snappy`.
+				Supported algorithms are `gzip`, `deflate`, `snappy`, and `zstd`.
 				"""
 		}
 	}
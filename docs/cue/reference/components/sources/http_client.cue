@@ -0,0 +1,167 @@
+package metadata
+
+components: sources: http_client: {
+	title: "HTTP Client"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		deployment_roles: ["daemon", "sidecar", "aggregator"]
+		development:   "beta"
+		egress_method: "batch"
+		stateful:      false
+	}
+
+	features: {
+		collect: {
+			checkpoint: enabled: false
+			from: {
+				service: services.http
+
+				interface: socket: {
+					direction: "outgoing"
+					protocols: ["http"]
+					ssl: "optional"
+				}
+			}
+			proxy: enabled: true
+			tls: {
+				enabled:                true
+				can_enable:             false
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        false
+			}
+		}
+		multiline: enabled: false
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		endpoints: {
+			description: "Endpoints to poll for events."
+			required:    true
+			type: array: {
+				items: type: string: {
+					examples: ["http://localhost:8080/logs"]
+					syntax: "literal"
+				}
+			}
+		}
+		scrape_interval_secs: {
+			common:      true
+			description: "The interval between polls, in seconds."
+			required:    false
+			type: uint: {
+				default: 15
+				unit:    "seconds"
+			}
+		}
+		method: {
+			common:      false
+			description: "The HTTP method to use when polling."
+			required:    false
+			type: string: {
+				default: "get"
+				enum: {
+					get:  "GET"
+					post: "POST"
+					put:  "PUT"
+				}
+				syntax: "literal"
+			}
+		}
+		headers: {
+			common:      false
+			description: "Headers to add to each request."
+			required:    false
+			type: object: {
+				examples: [{"Accept": "application/json"}]
+				options: {}
+			}
+		}
+		query: {
+			common: false
+			description: """
+				Query parameters to add to each request. Values may use `strftime` specifiers,
+				which are rendered against the time of the poll.
+				"""
+			required: false
+			type: object: {
+				examples: [{"since": "%Y-%m-%dT%H:%M:%SZ"}]
+				options: {}
+			}
+		}
+		encoding: {
+			common:      true
+			description: "The expected encoding of response bodies. Note that for `json` and `ndjson` encodings, the fields of the JSON objects are output as separate fields."
+			required:    false
+			type: string: {
+				default: "text"
+				enum: {
+					text:   "Newline-delimited text, with each line forming a message."
+					ndjson: "Newline-delimited JSON objects, where each line must contain a JSON object."
+					json:   "A JSON object or an array of JSON objects."
+					binary: "Binary or text, the whole response body is considered as one message."
+				}
+				syntax: "literal"
+			}
+		}
+		auth: configuration._http_auth & {_args: {
+			password_example: "${HTTP_PASSWORD}"
+			username_example: "${HTTP_USERNAME}"
+		}}
+	}
+
+	output: logs: line: {
+		description: "An individual event from a polled response."
+		fields: {
+			message: {
+				description:   "The raw line from the response body."
+				relevant_when: "encoding == \"text\" or encoding == \"binary\""
+				required:      true
+				type: string: {
+					examples: ["Hello world"]
+					syntax: "literal"
+				}
+			}
+			"*": {
+				common:        false
+				description:   "Any field contained in the JSON response."
+				relevant_when: "encoding == \"json\" or encoding == \"ndjson\""
+				required:      false
+				type: "*": {}
+			}
+			timestamp: fields._current_timestamp
+		}
+	}
+
+	telemetry: metrics: {
+		events_in_total:           components.sources.internal_metrics.output.metrics.events_in_total
+		http_error_response_total: components.sources.internal_metrics.output.metrics.http_error_response_total
+		http_request_errors_total: components.sources.internal_metrics.output.metrics.http_request_errors_total
+		parse_errors_total:        components.sources.internal_metrics.output.metrics.parse_errors_total
+		processed_bytes_total:     components.sources.internal_metrics.output.metrics.processed_bytes_total
+		requests_completed_total:  components.sources.internal_metrics.output.metrics.requests_completed_total
+		request_duration_seconds:  components.sources.internal_metrics.output.metrics.request_duration_seconds
+	}
+}
@@ -345,6 +345,12 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		dns_lookup_errors_total: {
+			description:       "The total number of failed DNS lookups."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		k8s_format_picker_edge_cases_total: {
 			description:       "The total number of edge cases encountered while picking format of the Kubernetes log message."
 			type:              "counter"
@@ -552,6 +558,14 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		file_backlog_bytes: {
+			description:       "The number of bytes between the current read position and the end of a watched file. It is reset to zero once the file is no longer watched at that path."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _internal_metrics_tags & {
+				file: _file
+			}
+		}
 		file_delete_errors_total: {
 			description:       "The total number of failures to delete a file."
 			type:              "counter"
@@ -713,6 +727,12 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _internal_metrics_tags
 		}
+		peer_ejections_total: {
+			description:       "The total number of times a downstream peer was ejected after consecutive failures."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		processed_bytes_total: {
 			description:       "The number of bytes processed by the component."
 			type:              "counter"
@@ -35,6 +35,58 @@ components: sources: kafka: {
 
 	configuration: {
 		acknowledgements: configuration._acknowledgements
+		assignments: {
+			common:      false
+			description: "The partitions to consume, instead of subscribing to `topics` with the consumer group. Useful to replay a range of offsets. If every partition has an `end_offset`, the source stops once they are all reached."
+			required:    false
+			warnings: []
+			type: array: {
+				default: []
+				items: type: object: {
+					examples: [{topic: "topic-1", partition: 0, start_offset: 1000, end_offset: 2000}]
+					options: {
+						end_offset: {
+							common:      true
+							description: "The offset at which to stop consuming the partition, exclusive. If unset, the partition is consumed indefinitely."
+							required:    false
+							warnings: []
+							type: int: {
+								default: null
+								examples: [2000]
+							}
+						}
+						partition: {
+							description: "The partition to consume."
+							required:    true
+							warnings: []
+							type: uint: {
+								examples: [0]
+								unit: null
+							}
+						}
+						start_offset: {
+							common:      true
+							description: "The offset from which to start consuming the partition. If unset, the committed offset of the consumer group is used, falling back to `auto_offset_reset`."
+							required:    false
+							warnings: []
+							type: int: {
+								default: null
+								examples: [1000]
+							}
+						}
+						topic: {
+							description: "The topic of the partition."
+							required:    true
+							warnings: []
+							type: string: {
+								examples: ["topic-1"]
+								syntax: "literal"
+							}
+						}
+					}
+				}
+			}
+		}
 		auto_offset_reset: {
 			common:      false
 			description: """
@@ -201,13 +253,41 @@ components: sources: kafka: {
 			}
 		}
 		socket_timeout_ms: components._kafka.configuration.socket_timeout_ms
+		schema_registry: {
+			common:      false
+			description: "The schema registry used to decode messages encoded with Avro in the Confluent wire format. The fields of the decoded records replace the `message` field. Messages that can't be decoded are kept as is."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					auth: configuration._http_auth & {_args: {
+						password_example: "${SCHEMA_REGISTRY_PASSWORD}"
+						username_example: "${SCHEMA_REGISTRY_USERNAME}"
+					}}
+					url: {
+						description: "The URL of the Confluent compatible schema registry."
+						required:    true
+						warnings: []
+						type: string: {
+							examples: ["http://localhost:8081"]
+							syntax: "literal"
+						}
+					}
+				}
+			}
+		}
 		topics: {
-			description: "The Kafka topics names to read events from. Regex is supported if the topic begins with `^`."
-			required:    true
+			description: "The Kafka topics names to read events from. Regex is supported if the topic begins with `^`. Required unless `assignments` is set."
+			required:    false
+			common:      true
 			warnings: []
-			type: array: items: type: string: {
-				examples: ["^(prefix1|prefix2)-.+", "topic-1", "topic-2"]
-				syntax: "literal"
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["^(prefix1|prefix2)-.+", "topic-1", "topic-2"]
+					syntax: "literal"
+				}
 			}
 		}
 	}
@@ -215,6 +295,14 @@ components: sources: kafka: {
 	output: logs: record: {
 		description: "An individual Kafka record"
 		fields: {
+			headers: {
+				description: "The headers of the Kafka record."
+				required:    true
+				type: object: {
+					examples: [{"header-name": "header value"}]
+					options: {}
+				}
+			}
 			message: {
 				description: "The raw line from the Kafka record."
 				required:    true
@@ -271,5 +359,14 @@ components: sources: kafka: {
 		kafka_consumed_messages_bytes_total:  components.sources.internal_metrics.output.metrics.kafka_consumed_messages_bytes_total
 	}
 
-	how_it_works: components._kafka.how_it_works
+	how_it_works: components._kafka.how_it_works & {
+		avro: {
+			title: "Avro Decoding"
+			body:  """
+				With a `schema_registry` set, messages in the Confluent wire format are decoded
+				with the schema whose id they're prefixed with. Schemas are fetched from the
+				registry once and cached for the lifetime of the source.
+				"""
+		}
+	}
 }
@@ -190,6 +190,26 @@ components: sources: kubernetes_logs: {
 				syntax:  "literal"
 			}
 		}
+		max_read_bytes_per_sec: {
+			common:      false
+			description: "The maximum rate at which data is read across all the log files on the node."
+			required:    false
+			type: uint: {
+				default: null
+				examples: [10485760]
+				unit: "bytes"
+			}
+		}
+		max_read_bytes_per_sec_per_file: {
+			common:      false
+			description: "The maximum rate at which data is read from a single log file, keeping a chatty container from starving the others."
+			required:    false
+			type: uint: {
+				default: null
+				examples: [1048576]
+				unit: "bytes"
+			}
+		}
 		self_node_name: {
 			common:      false
 			description: "The name of the Kubernetes `Node` this Vector instance runs at. Configured to use an env var by default, to be evaluated to a value provided by Kubernetes at Pod deploy time."
@@ -647,6 +667,7 @@ components: sources: kubernetes_logs: {
 
 	telemetry: metrics: {
 		events_in_total:                        components.sources.internal_metrics.output.metrics.events_in_total
+		file_backlog_bytes:                     components.sources.internal_metrics.output.metrics.file_backlog_bytes
 		k8s_format_picker_edge_cases_total:     components.sources.internal_metrics.output.metrics.k8s_format_picker_edge_cases_total
 		k8s_docker_format_parse_failures_total: components.sources.internal_metrics.output.metrics.k8s_docker_format_parse_failures_total
 		k8s_event_annotation_failures_total:    components.sources.internal_metrics.output.metrics.k8s_event_annotation_failures_total
@@ -0,0 +1,100 @@
+package metadata
+
+components: sources: mqtt: {
+	title: "MQTT"
+
+	features: {
+		collect: {
+			checkpoint: enabled: false
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: false
+				can_verify_hostname:    false
+				enabled_default:        false
+			}
+			from: components._mqtt.features.collect.from
+		}
+		multiline: enabled: false
+	}
+
+	classes: {
+		commonly_used: false
+		deployment_roles: ["aggregator"]
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	support: components._mqtt.support
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: components._mqtt.configuration & {
+		acknowledgements: configuration._acknowledgements
+		topics: {
+			description: "The [topic filters](\(urls.mqtt_topic_filters)) to subscribe to. Wildcards `+` and `#` are supported."
+			required:    true
+			type: array: items: type: string: {
+				examples: ["sensors/+/temperature", "vector/#"]
+				syntax: "literal"
+			}
+		}
+		topic_key: {
+			common:      false
+			description: "The log field name to use for the topic the message was published to."
+			required:    false
+			type: string: {
+				default: "topic"
+				examples: ["topic"]
+				syntax: "literal"
+			}
+		}
+	}
+
+	output: logs: record: {
+		description: "An individual MQTT message"
+		fields: {
+			message: {
+				description: "The payload of the MQTT message."
+				required:    true
+				type: string: {
+					examples: ["{\"temperature\": 21.5}"]
+					syntax: "literal"
+				}
+			}
+			timestamp: fields._current_timestamp
+			topic: {
+				description: "The topic the message was published to."
+				required:    true
+				type: string: {
+					examples: ["sensors/kitchen/temperature"]
+					syntax: "literal"
+				}
+			}
+		}
+	}
+
+	telemetry: metrics: {
+		connection_errors_total:          components.sources.internal_metrics.output.metrics.connection_errors_total
+		connection_send_ack_errors_total: components.sources.internal_metrics.output.metrics.connection_send_ack_errors_total
+		events_in_total:                  components.sources.internal_metrics.output.metrics.events_in_total
+		processed_bytes_total:            components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+
+	how_it_works: components._mqtt.how_it_works & {
+		acknowledgements: {
+			title: "Acknowledgements"
+			body:  """
+				QoS 1 messages are acknowledged to the broker once they're sent to the
+				connected transforms and sinks or, with `acknowledgements` enabled, once
+				they're delivered by the sinks, so that the broker redelivers messages
+				that weren't. Set a fixed `client_id` and disable `clean_session` for the
+				broker to keep unacknowledged messages across restarts of Vector.
+				"""
+		}
+	}
+}
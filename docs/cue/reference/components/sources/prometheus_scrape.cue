@@ -63,16 +63,162 @@ components: sources: prometheus_scrape: {
 
 	configuration: {
 		endpoints: {
-			description: "Endpoints to scrape metrics from."
-			required:    true
+			common:      true
+			description: "Endpoints to scrape metrics from. Required unless `file_sd` is set."
+			required:    false
 			warnings: ["You must explicitly add the path to your endpoints. Vector will _not_ automatically add `/metics`."]
 			type: array: {
+				default: []
 				items: type: string: {
 					examples: ["http://localhost:9090/metrics"]
 					syntax: "literal"
 				}
 			}
 		}
+		file_sd: {
+			common:      false
+			description: "Discovers the targets to scrape from files, in the format of Prometheus' [`file_sd_configs`](\(urls.prometheus_file_sd)). See [File based service discovery](#file-based-service-discovery)."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					files: {
+						description: "The JSON or YAML files listing the targets. The last segment of the paths may be a glob pattern."
+						required:    true
+						warnings: []
+						type: array: items: type: string: {
+							examples: ["/etc/vector/targets/*.json", "/etc/vector/targets.yml"]
+							syntax: "literal"
+						}
+					}
+					metrics_path: {
+						common:      false
+						description: "The path the metrics of the discovered targets are exposed on, unless their `__metrics_path__` label sets it."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "/metrics"
+							syntax:  "literal"
+						}
+					}
+					scheme: {
+						common:      false
+						description: "The scheme of the discovered targets, unless their `__scheme__` label sets it."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "http"
+							enum: {
+								http:  "HTTP"
+								https: "HTTPS"
+							}
+							syntax: "literal"
+						}
+					}
+				}
+			}
+		}
+		honor_labels: {
+			common:      false
+			description: "Keeps the labels of the scraped metrics which conflict with the labels of their target. Otherwise the conflicting labels of the metrics are renamed to `exported_<name>`."
+			required:    false
+			warnings: []
+			type: bool: default: false
+		}
+		honor_timestamps: {
+			common:      false
+			description: "Keeps the timestamps of the scraped metrics. Otherwise the metrics are given the time of the scrape."
+			required:    false
+			warnings: []
+			type: bool: default: true
+		}
+		relabel_configs: {
+			common:      false
+			description: "Rules rewriting the labels of the targets, or dropping targets, before they are scraped, applied in order. See [Relabeling](#relabeling)."
+			required:    false
+			warnings: []
+			type: array: {
+				default: []
+				items: type: object: {
+					examples: [{source_labels: ["__address__"], regex: "([^:]+):\\d+", target_label: "instance"}]
+					options: {
+						action: {
+							common:      true
+							description: "What to do when the regex matches."
+							required:    false
+							warnings: []
+							type: string: {
+								default: "replace"
+								enum: {
+									replace: "Sets the `target_label` to the `replacement`, if the regex matches."
+									keep:    "Drops the targets for which the regex doesn't match."
+									drop:    "Drops the targets for which the regex matches."
+								}
+								syntax: "literal"
+							}
+						}
+						regex: {
+							common:      true
+							description: "The regex matched against the values of the `source_labels`, which must match the whole value."
+							required:    false
+							warnings: []
+							type: string: {
+								default: "(.*)"
+								examples: ["prod|staging"]
+								syntax: "regex"
+							}
+						}
+						replacement: {
+							common:      false
+							description: "The value the `target_label` is set to, which may refer to the capture groups of the regex. An empty replacement removes the label."
+							required:    false
+							warnings: []
+							type: string: {
+								default: "$1"
+								examples: ["${1}:9100"]
+								syntax: "literal"
+							}
+						}
+						separator: {
+							common:      false
+							description: "Joins the values of the `source_labels`."
+							required:    false
+							warnings: []
+							type: string: {
+								default: ";"
+								syntax:  "literal"
+							}
+						}
+						source_labels: {
+							common:      true
+							description: "The labels whose values are joined with the `separator` and matched against the regex. Missing labels have an empty value."
+							required:    false
+							warnings: []
+							type: array: {
+								default: []
+								items: type: string: {
+									examples: ["__address__", "env"]
+									syntax: "literal"
+								}
+							}
+						}
+						target_label: {
+							common:        true
+							description:   "The label set by the `replace` action, which may refer to the capture groups of the regex."
+							relevant_when: "action = `replace`"
+							required:      false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["instance"]
+								syntax: "literal"
+							}
+						}
+					}
+				}
+			}
+		}
 		scrape_interval_secs: {
 			common:      true
 			description: "The interval between scrapes, in seconds."
@@ -89,6 +235,32 @@ components: sources: prometheus_scrape: {
 		}}
 	}
 
+	how_it_works: {
+		file_sd: {
+			title: "File based service discovery"
+			body:  """
+				With `file_sd`, the targets are read from JSON or YAML files holding a list of target
+				groups, each made of `targets` addresses and optional `labels`, like
+				`[{"targets": ["web-1:9100"], "labels": {"env": "prod"}}]`. The files are checked
+				for changes before each scrape, and reread when they were modified. A file which can't
+				be read or parsed keeps the targets it previously listed.
+				"""
+		}
+
+		relabeling: {
+			title: "Relabeling"
+			body:  """
+				Each target has labels, which the `relabel_configs` rewrite before it is scraped. The
+				`__address__`, `__scheme__` and `__metrics_path__` labels make the URL of the target,
+				and come from the `endpoints`, or from the discovered targets and their file, whose
+				path is the `__meta_filepath` label. After relabeling, the labels starting with `__`
+				are removed, and the other ones are added to the scraped metrics, as specified by
+				`honor_labels`. For example, the `instance` label of Prometheus can be set from the
+				`__address__` label with a `replace` rule.
+				"""
+		}
+	}
+
 	output: metrics: {
 		counter:   output._passthrough_counter
 		gauge:     output._passthrough_gauge
@@ -103,6 +275,7 @@ components: sources: prometheus_scrape: {
 		parse_errors_total:        components.sources.internal_metrics.output.metrics.parse_errors_total
 		processed_bytes_total:     components.sources.internal_metrics.output.metrics.processed_bytes_total
 		processed_events_total:    components.sources.internal_metrics.output.metrics.processed_events_total
+		processing_errors_total:   components.sources.internal_metrics.output.metrics.processing_errors_total
 		requests_completed_total:  components.sources.internal_metrics.output.metrics.requests_completed_total
 		request_duration_seconds:  components.sources.internal_metrics.output.metrics.request_duration_seconds
 	}
@@ -83,6 +83,47 @@ components: sources: splunk_hec: {
 				syntax: "literal"
 			}
 		}
+		indexer_acknowledgements: {
+			common:      false
+			description: "Limits for [indexer acknowledgements](\(urls.splunk_hec_indexer_acknowledgements)), which are enabled along with the source's `acknowledgements` option."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					max_idle_time_secs: {
+						common:      false
+						description: "Channels that have no events in flight and haven't been used for this long are forgotten, along with the ack ids they haven't been queried for."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 300
+							unit:    "seconds"
+						}
+					}
+					max_number_of_ack_channels: {
+						common:      false
+						description: "The maximum number of channels tracked at once. Requests on new channels are refused with a `503` once reached."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 1000000
+							unit:    null
+						}
+					}
+					max_pending_acks_per_channel: {
+						common:      false
+						description: "The maximum number of ack ids per channel that haven't been queried yet. Requests on the channel are refused with a `503` once reached."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 1000000
+							unit:    null
+						}
+					}
+				}
+			}
+		}
 		valid_tokens: {
 			common:      true
 			description: "If supplied, incoming requests must supply one of these tokens in the `Authorization` header, just as a client would if it was communicating with the Splunk HEC endpoint directly. If _not_ supplied, the `Authorization` header will be ignored and requests will not be authenticated."
@@ -111,6 +152,36 @@ components: sources: splunk_hec: {
 		}
 	}
 
+	output: metrics: {
+		gauge: output._passthrough_gauge
+	}
+
+	how_it_works: {
+		metrics: {
+			title: "Metrics"
+			body: """
+				Events whose `event` field is `metric` are received as metrics rather than logs, following
+				Splunk's [metrics format](\(urls.splunk_hec_metrics)). Each `metric_name:<name>` field
+				of the multi-metric format, as well as the `metric_name` and `_value` fields of the single
+				metric format, becomes an absolute gauge. The remaining fields become tags, along with
+				the `host`, `splunk_channel`, `splunk_index`, `splunk_source` and `splunk_sourcetype`
+				values of the event.
+				"""
+		}
+
+		indexer_acknowledgements: {
+			title: "Indexer acknowledgements"
+			body: """
+				When `acknowledgements` is enabled, this source implements Splunk's
+				[indexer acknowledgements](\(urls.splunk_hec_indexer_acknowledgements)) for forwarders that
+				require them. Requests to the event and raw endpoints must then supply a channel, and the
+				response carries an `ackId`. Querying the `/services/collector/ack` endpoint of the same
+				channel with that id reports `true` once the sinks have delivered all events of the
+				request. Each delivered id is reported once.
+				"""
+		}
+	}
+
 	telemetry: metrics: {
 		events_in_total:           components.sources.internal_metrics.output.metrics.events_in_total
 		http_request_errors_total: components.sources.internal_metrics.output.metrics.http_request_errors_total
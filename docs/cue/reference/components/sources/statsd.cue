@@ -107,6 +107,142 @@ components: sources: statsd: {
 
 	}
 
+	output: logs: {
+		event: {
+			description: "A DogStatsD [event](\(urls.dogstatsd_datagram_format)), named after the fields of the Datadog events API."
+			fields: {
+				title: {
+					description: "The title of the event."
+					required:    true
+					type: string: {
+						examples: ["An exception occurred"]
+						syntax: "literal"
+					}
+				}
+				message: {
+					description: "The text of the event."
+					required:    true
+					type: string: {
+						examples: ["Cannot parse CSV file from 10.0.0.17"]
+						syntax: "literal"
+					}
+				}
+				host: {
+					description: "The host name of the event."
+					required:    false
+					common:      true
+					type: string: {
+						default: null
+						examples: ["web-1"]
+						syntax: "literal"
+					}
+				}
+				aggregation_key: {
+					description: "The key grouping the event with others."
+					required:    false
+					common:      false
+					type: string: {
+						default: null
+						examples: ["csv"]
+						syntax: "literal"
+					}
+				}
+				priority: {
+					description: "The priority of the event."
+					required:    false
+					common:      false
+					type: string: {
+						default: null
+						examples: ["normal", "low"]
+						syntax: "literal"
+					}
+				}
+				source_type_name: {
+					description: "The type of source the event comes from."
+					required:    false
+					common:      false
+					type: string: {
+						default: null
+						examples: ["nagios"]
+						syntax: "literal"
+					}
+				}
+				alert_type: {
+					description: "The alert type of the event."
+					required:    false
+					common:      false
+					type: string: {
+						default: null
+						examples: ["error", "warning", "info", "success"]
+						syntax: "literal"
+					}
+				}
+				container_id: {
+					description: "The id of the container which sent the event."
+					required:    false
+					common:      false
+					type: string: {
+						default: null
+						examples: ["83c0a99c0a54c0c187f461c7980e9b57f3f6a8b0c918c8d93df19a9de6f3fe1d"]
+						syntax: "literal"
+					}
+				}
+				tags: {
+					description: "The tags of the event."
+					required:    false
+					common:      true
+					type: array: {
+						default: null
+						items: type: string: {
+							examples: ["env:prod"]
+							syntax: "literal"
+						}
+					}
+				}
+				timestamp: {
+					description: "The time of the event, or the time it was received if it doesn't have one."
+					required:    true
+					type: timestamp: {}
+				}
+			}
+		}
+		service_check: {
+			description: "A DogStatsD [service check](\(urls.dogstatsd_datagram_format))."
+			fields: {
+				check: {
+					description: "The name of the service check."
+					required:    true
+					type: string: {
+						examples: ["Redis connection"]
+						syntax: "literal"
+					}
+				}
+				status: {
+					description: "The status of the service: `0` for OK, `1` for warning, `2` for critical and `3` for unknown."
+					required:    true
+					type: uint: {
+						examples: [0, 2]
+						unit: null
+					}
+				}
+				message: {
+					description: "The message describing the status."
+					required:    false
+					common:      true
+					type: string: {
+						default: null
+						examples: ["Redis connection timed out after 10s"]
+						syntax: "literal"
+					}
+				}
+				host:         output.logs.event.fields.host
+				container_id: output.logs.event.fields.container_id
+				tags:         output.logs.event.fields.tags
+				timestamp:    output.logs.event.fields.timestamp
+			}
+		}
+	}
+
 	output: metrics: {
 		counter:      output._passthrough_counter
 		distribution: output._passthrough_distribution
@@ -123,7 +259,19 @@ components: sources: statsd: {
 				value indicating a realtime metric (i.e. not a historical metric). Normally, such
 				`null` timestamps are substituted with the current time by downstream sinks or
 				third-party services during sending/ingestion. See the
-				[metric data model](\(urls.vector_metric)) page for more info.
+				[metric data model](\(urls.vector_metric)) page for more info. Metrics carrying a
+				DogStatsD `|T<unix timestamp>` part are assigned that timestamp instead.
+				"""
+		}
+
+		dogstatsd: {
+			title: "DogStatsD"
+			body:  """
+				The [DogStatsD](\(urls.dogstatsd)) extensions of the protocol are supported. Tags
+				(`|#tag:value`) and the container id (`|c:<id>`, added as the `container_id` tag) are
+				attached to metrics, and distributions (`|d`) are received as distributions. The
+				optional parts of a metric may come in any order. Events (`_e{...}`) and service checks
+				(`_sc|...`) are received as log events.
 				"""
 		}
 	}
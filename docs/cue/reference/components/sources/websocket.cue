@@ -0,0 +1,158 @@
+package metadata
+
+components: sources: websocket: {
+	_port: 8080
+
+	title: "WebSocket"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		deployment_roles: ["aggregator"]
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	features: {
+		multiline: enabled: false
+		receive: {
+			from: {
+				service: services.websocket
+				interface: socket: {
+					api: {
+						title: "WebSocket"
+						url:   urls.websocket
+					}
+					direction: "incoming"
+					port:      _port
+					protocols: ["tcp"]
+					ssl: "optional"
+				}
+			}
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				enabled_default:        false
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		address: {
+			description: "The address to accept WebSocket connections on. The address _must_ include a port."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["0.0.0.0:\(_port)"]
+				syntax: "literal"
+			}
+		}
+		encoding: {
+			common:      true
+			description: "How the messages are decoded into events."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "text"
+				enum: {
+					text: "Each message becomes the `message` field of an event."
+					json: "Each message is a JSON object, or an array of JSON objects, giving the fields of the events."
+				}
+				syntax: "literal"
+			}
+		}
+		max_length: {
+			common:      false
+			description: "The maximum bytes size of the messages. A client sending a larger message is disconnected."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: 1048576
+				unit:    "bytes"
+			}
+		}
+	}
+
+	output: logs: line: {
+		description: "A WebSocket message"
+		fields: {
+			host: {
+				description: "The IP address of the client, unless a JSON message sets it."
+				required:    true
+				type: string: {
+					examples: ["127.0.0.1"]
+					syntax: "literal"
+				}
+			}
+			message: {
+				description: "The message, with the `text` encoding."
+				required:    false
+				type: string: {
+					examples: ["Hello world"]
+					syntax: "literal"
+				}
+			}
+			source_type: {
+				description: "The name of the source type."
+				required:    true
+				type: string: {
+					examples: ["websocket"]
+					syntax: "literal"
+				}
+			}
+			timestamp: {
+				description: "The time the message was received at, unless a JSON message sets it."
+				required:    true
+				type: timestamp: {}
+			}
+			"*": {
+				description: "The fields of the JSON objects, with the `json` encoding."
+				required:    false
+				type: "*": {}
+			}
+		}
+	}
+
+	how_it_works: {
+		messages: {
+			title: "Messages"
+			body:  """
+				The source accepts any number of WebSocket connections, each text or binary message
+				being decoded into events according to the `encoding`. Pings are answered and close
+				frames acknowledged, and the connections are closed when Vector shuts down. Messages
+				that can't be decoded are discarded.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		events_in_total:              components.sources.internal_metrics.output.metrics.events_in_total
+		connection_errors_total:      components.sources.internal_metrics.output.metrics.connection_errors_total
+		connection_established_total: components.sources.internal_metrics.output.metrics.connection_established_total
+		invalid_record_total:         components.sources.internal_metrics.output.metrics.invalid_record_total
+		invalid_record_bytes_total:   components.sources.internal_metrics.output.metrics.invalid_record_bytes_total
+		processed_bytes_total:        components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+}
@@ -0,0 +1,10 @@
+package metadata
+
+services: gelf: {
+	name:     "GELF"
+	thing:    "a \(name) client or server, like Graylog"
+	url:      urls.gelf
+	versions: null
+
+	description: "The [Graylog Extended Log Format](\(urls.gelf)) (GELF) is a JSON log format sent over UDP, optionally compressed and chunked, or over TCP, understood by Graylog and many logging libraries."
+}
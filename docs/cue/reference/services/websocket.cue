@@ -0,0 +1,10 @@
+package metadata
+
+services: websocket: {
+	name:     "WebSocket"
+	thing:    "a \(name) client or server"
+	url:      urls.websocket
+	versions: null
+
+	description: "[WebSocket](\(urls.websocket)) is a protocol providing full-duplex communication over a single, long-lived TCP connection, commonly used to stream data to and from browsers."
+}
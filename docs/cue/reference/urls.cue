@@ -5,6 +5,8 @@ urls: {
 	affine_type_system:                                       "\(wikipedia)/wiki/Substructural_type_system#Affine_type_systems"
 	adaptive_request_concurrency_post:                        "/blog/adaptive-request-concurrency/"
 	amazon_linux:                                             "https://aws.amazon.com/amazon-linux-ami/"
+	amqp:                                                     "https://www.amqp.org/"
+	amqp_uri:                                                 "https://www.rabbitmq.com/uri-spec.html"
 	ansi_escape_codes:                                        "\(wikipedia)/wiki/ANSI_escape_code"
 	apache:                                                   "https://httpd.apache.org"
 	apache_common:                                            "\(apache)/docs/current/logs.html#common"
@@ -152,6 +154,7 @@ urls: {
 	docker_setup:                                             "\(docker_docs)/get-docker/"
 	dockerfile:                                               "\(vector_repo)/blob/master/Dockerfile"
 	dogstatsd:                                                "\(datadog_docs)/developers/dogstatsd/?tab=hostagent"
+	dogstatsd_datagram_format:                                "\(datadog_docs)/developers/dogstatsd/datagram_shell/"
 	dot_format:                                               "https://graphviz.org/doc/info/lang.html"
 	dpkg:                                                     "https://wiki.debian.org/dpkg"
 	dry_code:                                                 "\(wikipedia)/wiki/Don%27t_repeat_yourself"
@@ -163,6 +166,7 @@ urls: {
 	elasticsearch_id_field:                                   "https://www.elastic.co/guide/en/elasticsearch/reference/current/mapping-id-field.html"
 	elasticsearch_id_performance:                             "https://www.elastic.co/guide/en/elasticsearch/reference/master/tune-for-indexing-speed.html#_use_auto_generated_ids"
 	elasticsearch_ignore_malformed:                           "https://www.elastic.co/guide/en/elasticsearch/reference/current/ignore-malformed.html"
+	elasticsearch_nodes_info:                                 "https://www.elastic.co/guide/en/elasticsearch/reference/current/cluster-nodes-info.html"
 	encoding_charset_labels:                                  "https://encoding.spec.whatwg.org/#concept-encoding-get"
 	encoding_standard:                                        "https://encoding.spec.whatwg.org/"
 	endler_dev:                                               "https://endler.dev/"
@@ -202,12 +206,17 @@ urls: {
 	gcs_predefined_acl:                                       "\(gcp)/storage/docs/access-control/lists#predefined-acl"
 	gcs_storage_classes:                                      "\(gcp)/storage/docs/storage-classes"
 	gcs_custom_metadata:                                      "\(gcp)/storage/docs/metadata#custom-metadata"
+	gelf:                                                     "https://go2docs.graylog.org/current/getting_in_log_data/gelf.html"
 	git:                                                      "https://git-scm.com/"
 	github:                                                   "https://github.com"
 	github_protected_branches:                                "https://help.github.com/en/github/administering-a-repository/about-protected-branches"
 	github_sign_commits:                                      "https://help.github.com/en/github/authenticating-to-github/signing-commits"
 	globbing:                                                 "\(wikipedia)/wiki/Glob_(programming)"
 	glog:                                                     "\(github)/google/glog"
+	graphite:                                                 "https://graphiteapp.org/"
+	graphite_pickle_protocol:                                 "https://graphite.readthedocs.io/en/latest/feeding-carbon.html#the-pickle-protocol"
+	graphite_plaintext_protocol:                              "https://graphite.readthedocs.io/en/latest/feeding-carbon.html#the-plaintext-protocol"
+	graphite_tags:                                            "https://graphite.readthedocs.io/en/latest/tags.html"
 	graphql:                                                  "https://graphql.org"
 	graphql_playground:                                       "\(github)/graphql/graphql-playground"
 	graphviz:                                                 "https://graphviz.org/"
@@ -271,6 +280,7 @@ urls: {
 	kubernetes_rbac:                                          "\(kubernetes)/docs/reference/access-authn-authz/rbac/"
 	kubernetes_request_verbs:                                 "\(kubernetes)/docs/reference/access-authn-authz/authorization/#determine-the-request-verb"
 	kubernetes_watch_api:                                     "\(kubernetes)/docs/reference/generated/kubernetes-api/v1.10/#watch-30"
+	lapin:                                                    "\(github)/amqp-rs/lapin"
 	leveldb:                                                  "\(github)/google/leveldb"
 	leveldb_sys_2:                                            "https://crates.io/crates/leveldb-sys"
 	leveldb_sys_3:                                            "\(github)/timberio/leveldb-sys/tree/v3.0.0"
@@ -324,6 +334,9 @@ urls: {
 	mongodb:                                                  "https://www.mongodb.com"
 	mongodb_command_server_status:                            "https://docs.mongodb.com/manual/reference/command/serverStatus/"
 	mongodb_connection_string_uri_format:                     "https://docs.mongodb.com/manual/reference/connection-string/"
+	mosquitto:                                                "https://mosquitto.org/"
+	mqtt:                                                     "https://mqtt.org/"
+	mqtt_topic_filters:                                       "http://docs.oasis-open.org/mqtt/mqtt/v3.1.1/os/mqtt-v3.1.1-os.html#_Toc398718106"
 	musl_builder_docker_image:                                "\(vector_repo)/blob/master/scripts/ci-docker-images/builder-x86_64-unknown-linux-musl/Dockerfile"
 	nats:                                                     "https://nats.io/"
 	nats_rs:                                                  "\(github)/nats-io/nats.rs"
@@ -343,6 +356,7 @@ urls: {
 	nix:                                                      "https://nixos.org/nix/"
 	nixos:                                                    "https://nixos.org/"
 	nixpkgs_9682:                                             "\(github)/NixOS/nixpkgs/issues/9682"
+	openmetrics:                                              "https://openmetrics.io/"
 	openssl:                                                  "https://www.openssl.org/"
 	order_of_ops:                                             "\(wikipedia)/wiki/Order_of_operations"
 	papertrail:                                               "https://www.papertrail.com/"
@@ -365,6 +379,7 @@ urls: {
 	prometheus_client:                                        "https://prometheus.io/docs/instrumenting/clientlibs/"
 	prometheus_remote_integrations:                           "https://prometheus.io/docs/operating/integrations/#remote-endpoints-and-storage"
 	prometheus_counter:                                       "https://prometheus.io/docs/concepts/metric_types/#counter"
+	prometheus_file_sd:                                       "https://prometheus.io/docs/prometheus/latest/configuration/configuration/#file_sd_config"
 	prometheus_gauge:                                         "https://prometheus.io/docs/concepts/metric_types/#gauge"
 	prometheus_high_cardinality:                              "https://prometheus.io/docs/practices/naming/#labels"
 	prometheus_histogram:                                     "https://prometheus.io/docs/concepts/metric_types/#histogram"
@@ -378,6 +393,7 @@ urls: {
 	protobuf:                                                 "https://developers.google.com/protocol-buffers"
 	pulsar:                                                   "https://pulsar.apache.org/"
 	pulsar_protocol:                                          "https://pulsar.apache.org/docs/en/develop-binary-protocol/"
+	rabbitmq:                                                 "https://www.rabbitmq.com/"
 	raspbian:                                                 "https://www.raspbian.org/"
 	rdkafka:                                                  "\(github)/edenhill/librdkafka"
 	regex:                                                    "\(wikipedia)/wiki/Regular_expression"
@@ -397,6 +413,7 @@ urls: {
 	rfc_6891:                                                 "https://tools.ietf.org/html/rfc6891"
 	rhel:                                                     "https://www.redhat.com/en/technologies/linux-platforms/enterprise-linux"
 	rpm:                                                      "https://rpm.org/"
+	rumqttc:                                                  "\(github)/bytebeamio/rumqtt"
 	rust:                                                     "https://www.rust-lang.org/"
 	rust_date_time:                                           "https://docs.rs/chrono/latest/chrono/struct.DateTime.html"
 	rust_grok_library:                                        "\(github)/daschl/grok"
@@ -429,6 +446,8 @@ urls: {
 	splunk_hec:                                               "https://dev.splunk.com/enterprise/docs/dataapps/httpeventcollector/"
 	splunk_hec_event_endpoint:                                "https://docs.splunk.com/Documentation/Splunk/8.0.0/RESTREF/RESTinput#services.2Fcollector.2Fevent"
 	splunk_hec_indexed_fields:                                "https://docs.splunk.com/Documentation/Splunk/8.0.0/Data/IFXandHEC"
+	splunk_hec_indexer_acknowledgements:                      "https://docs.splunk.com/Documentation/Splunk/8.0.0/Data/AboutHECIDXAck"
+	splunk_hec_metrics:                                       "https://docs.splunk.com/Documentation/Splunk/8.0.0/Metrics/GetMetricsInOther#The_multiple-metric_JSON_format"
 	splunk_hec_protocol:                                      "https://docs.splunk.com/Documentation/Splunk/8.0.0/Data/HECRESTendpoints"
 	splunk_hec_raw_endpoint:                                  "https://docs.splunk.com/Documentation/Splunk/8.0.0/RESTREF/RESTinput#services.2Fcollector.2Fraw"
 	splunk_hec_setup:                                         "https://docs.splunk.com/Documentation/Splunk/latest/Data/UsetheHTTPEventCollector"
@@ -588,6 +607,7 @@ urls: {
 	vote_feature:                                             "\(vector_repo)/issues?q=is%3Aissue+is%3Aopen+sort%3Areactions-%2B1-desc+label%3A%22Type%3A+New+Feature%22"
 	wasm:                                                     "https://webassembly.org/"
 	wasm_languages:                                           "\(github)/appcypher/awesome-wasm-langs"
+	websocket:                                                "https://developer.mozilla.org/en-US/docs/Web/API/WebSockets_API"
 	wikipedia:                                                "https://en.wikipedia.org"
 	windows:                                                  "https://www.microsoft.com/en-us/windows"
 	windows_installer:                                        "\(wikipedia)/wiki/Windows_Installer"
@@ -4,6 +4,7 @@ use super::{
 };
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
+use fs2::FileExt;
 use glob::glob;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -17,6 +18,7 @@ use tracing::{error, info, warn};
 
 const TMP_FILE_NAME: &str = "checkpoints.new.json";
 const STABLE_FILE_NAME: &str = "checkpoints.json";
+const LOCK_FILE_NAME: &str = "checkpoints.lock";
 
 /// This enum represents the file format of checkpoints persisted to disk. Right
 /// now there is only one variant, but any incompatible changes will require and
@@ -33,10 +35,39 @@ enum State {
 /// fingerprints as objects cannot be keys in a plain JSON map.
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
 #[serde(rename_all = "snake_case")]
-struct Checkpoint {
-    fingerprint: FileFingerprint,
-    position: FilePosition,
-    modified: DateTime<Utc>,
+pub struct Checkpoint {
+    pub fingerprint: FileFingerprint,
+    pub position: FilePosition,
+    pub modified: DateTime<Utc>,
+}
+
+/// An exclusive lock on a data directory, held for as long as checkpoints are
+/// read from and written to it. This keeps two processes configured with the
+/// same data directory from both reading the same files and overwriting each
+/// other's checkpoints. The lock is released when dropped.
+#[derive(Debug)]
+pub struct DataDirLock {
+    _file: fs::File,
+}
+
+impl DataDirLock {
+    pub fn acquire(data_dir: &Path) -> Result<DataDirLock, io::Error> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(data_dir.join(LOCK_FILE_NAME))?;
+        file.try_lock_exclusive().map_err(|error| {
+            if error.raw_os_error() == fs2::lock_contended_error().raw_os_error() {
+                io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    format!("{:?} is locked by another process", data_dir),
+                )
+            } else {
+                error
+            }
+        })?;
+        Ok(DataDirLock { _file: file })
+    }
 }
 
 pub struct Checkpointer {
@@ -68,6 +99,19 @@ impl CheckpointsView {
         self.checkpoints.get(&fng).map(|r| *r.value())
     }
 
+    pub fn remove(&self, fng: FileFingerprint) -> Option<FilePosition> {
+        self.modified_times.remove(&fng);
+        self.removed_times.remove(&fng);
+        self.checkpoints.remove(&fng).map(|(_, pos)| pos)
+    }
+
+    /// Returns all of the checkpoints, ordered by fingerprint.
+    pub fn list(&self) -> Vec<Checkpoint> {
+        match self.get_state() {
+            State::V1 { checkpoints } => checkpoints.into_iter().collect(),
+        }
+    }
+
     pub fn set_dead(&self, fng: FileFingerprint) {
         self.removed_times.insert(fng, Utc::now());
     }
@@ -315,6 +359,14 @@ impl Checkpointer {
             // one full valid file to recover from.
             fs::rename(&self.tmp_file_path, &self.stable_file_path)?;
 
+            // The rename itself only becomes durable once the directory entry
+            // is flushed, otherwise a host crash may still leave us with the
+            // previous stable file.
+            #[cfg(unix)]
+            if let Some(dir) = self.stable_file_path.parent() {
+                fs::File::open(dir)?.sync_all()?;
+            }
+
             *last = Some(current);
         }
 
@@ -420,7 +472,8 @@ impl Checkpointer {
 mod test {
     use super::{
         super::{FingerprintStrategy, Fingerprinter},
-        Checkpoint, Checkpointer, FileFingerprint, FilePosition, STABLE_FILE_NAME, TMP_FILE_NAME,
+        Checkpoint, Checkpointer, DataDirLock, FileFingerprint, FilePosition, STABLE_FILE_NAME,
+        TMP_FILE_NAME,
     };
     use chrono::{Duration, Utc};
     use pretty_assertions::assert_eq;
@@ -835,4 +888,44 @@ mod test {
             assert_eq!(chkptr.get_checkpoint(fingerprint), Some(1234))
         }
     }
+
+    #[test]
+    fn test_checkpointer_remove() {
+        let data_dir = tempdir().unwrap();
+        let removed = FileFingerprint::FirstLinesChecksum(1234);
+        let kept = FileFingerprint::DevInode(1, 2);
+        {
+            let mut chkptr = Checkpointer::new(data_dir.path());
+            chkptr.update_checkpoint(removed, 10);
+            chkptr.update_checkpoint(kept, 20);
+            chkptr.write_checkpoints().unwrap();
+        }
+        {
+            let mut chkptr = Checkpointer::new(data_dir.path());
+            chkptr.read_checkpoints(None);
+            assert_eq!(chkptr.view().remove(removed), Some(10));
+            assert_eq!(chkptr.view().remove(removed), None);
+            chkptr.write_checkpoints().unwrap();
+        }
+        {
+            let mut chkptr = Checkpointer::new(data_dir.path());
+            chkptr.read_checkpoints(None);
+            let checkpoints = chkptr.view().list();
+            assert_eq!(checkpoints.len(), 1);
+            assert_eq!(checkpoints[0].fingerprint, kept);
+            assert_eq!(checkpoints[0].position, 20);
+        }
+    }
+
+    #[test]
+    fn test_data_dir_lock() {
+        let data_dir = tempdir().unwrap();
+
+        let lock = DataDirLock::acquire(data_dir.path()).unwrap();
+        let error = DataDirLock::acquire(data_dir.path()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::WouldBlock);
+
+        drop(lock);
+        DataDirLock::acquire(data_dir.path()).unwrap();
+    }
 }
@@ -0,0 +1,130 @@
+use flate2::bufread::MultiGzDecoder;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::Path,
+};
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression formats that files are transparently decompressed from.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Detects the compression of a file from the magic bytes at its start.
+    pub fn detect<R: BufRead>(reader: &mut R) -> io::Result<Option<Self>> {
+        // WARN: The paired `BufRead::consume` is not called intentionally. If we
+        // do we'll chop a decent part of the potential compressed stream off.
+        let header_bytes = reader.fill_buf()?;
+        Ok(if header_bytes.starts_with(GZIP_MAGIC) {
+            Some(Compression::Gzip)
+        } else if header_bytes.starts_with(ZSTD_MAGIC) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        })
+    }
+
+    /// Wraps `reader` in a decoder for this compression format.
+    pub fn decoder<R: BufRead + 'static>(self, reader: R) -> io::Result<Box<dyn BufRead>> {
+        Ok(match self {
+            Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+            Compression::Zstd => Box::new(BufReader::new(zstd::Decoder::with_buffer(reader)?)),
+        })
+    }
+}
+
+/// Opens the file at `path`, decompressing its content if it is compressed.
+pub fn open_decompressed(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    match Compression::detect(&mut reader)? {
+        Some(compression) => compression.decoder(reader),
+        None => Ok(Box::new(reader)),
+    }
+}
+
+/// Discards the first `count` bytes of `reader`.
+///
+/// Returns an `UnexpectedEof` error if `reader` holds fewer bytes.
+pub fn skip_bytes<R: Read + ?Sized>(reader: &mut R, count: u64) -> io::Result<()> {
+    let skipped = io::copy(&mut reader.take(count), &mut io::sink())?;
+    if skipped < count {
+        Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "EOF reached while skipping bytes",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{open_decompressed, skip_bytes, Compression};
+    use flate2::{write::GzEncoder, Compression as GzCompression};
+    use std::io::{BufReader, Cursor, Read, Write};
+    use tempfile::tempdir;
+
+    const CONTENT: &[u8] = b"first line\nsecond line\n";
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), GzCompression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn detects_compression() {
+        let detect = |data: Vec<u8>| Compression::detect(&mut BufReader::new(Cursor::new(data)));
+
+        assert_eq!(detect(gzip(CONTENT)).unwrap(), Some(Compression::Gzip));
+        assert_eq!(
+            detect(zstd::encode_all(CONTENT, 0).unwrap()).unwrap(),
+            Some(Compression::Zstd)
+        );
+        assert_eq!(detect(CONTENT.to_vec()).unwrap(), None);
+        assert_eq!(detect(Vec::new()).unwrap(), None);
+    }
+
+    #[test]
+    fn opens_compressed_files() {
+        let dir = tempdir().unwrap();
+        let files = vec![
+            ("plain.log", CONTENT.to_vec()),
+            ("gzipped.log.gz", gzip(CONTENT)),
+            ("zstd.log.zst", zstd::encode_all(CONTENT, 0).unwrap()),
+        ];
+
+        for (name, data) in files {
+            let path = dir.path().join(name);
+            std::fs::write(&path, data).unwrap();
+
+            let mut content = Vec::new();
+            open_decompressed(&path)
+                .unwrap()
+                .read_to_end(&mut content)
+                .unwrap();
+            assert_eq!(content, CONTENT, "{}", name);
+        }
+    }
+
+    #[test]
+    fn skips_decompressed_bytes() {
+        let mut reader = Compression::Gzip
+            .decoder(BufReader::new(Cursor::new(gzip(CONTENT))))
+            .unwrap();
+        skip_bytes(&mut reader, 11).unwrap();
+
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "second line\n");
+
+        let mut reader = Cursor::new(CONTENT);
+        assert!(skip_bytes(&mut reader, 100).is_err());
+    }
+}
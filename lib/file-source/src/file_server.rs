@@ -3,6 +3,7 @@ use crate::{
     checkpointer::{Checkpointer, CheckpointsView},
     file_watcher::FileWatcher,
     fingerprinter::{FileFingerprint, Fingerprinter},
+    rate_limiter::RateLimiter,
     FileSourceInternalEvents, ReadFrom,
 };
 use bytes::Bytes;
@@ -11,12 +12,13 @@ use futures::{
     future::{select, Either, FutureExt},
     stream, Future, Sink, SinkExt,
 };
+use glob::Pattern;
 use indexmap::IndexMap;
 use std::{
     cmp,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fs::{self, remove_file},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
     time::{self, Duration},
 };
@@ -45,9 +47,13 @@ where
     pub line_delimiter: Bytes,
     pub data_dir: PathBuf,
     pub glob_minimum_cooldown: Duration,
+    pub checkpoint_interval: Duration,
     pub fingerprinter: Fingerprinter,
     pub oldest_first: bool,
     pub remove_after: Option<Duration>,
+    pub max_read_bytes_per_sec: Option<usize>,
+    pub max_read_bytes_per_sec_per_file: Option<usize>,
+    pub read_weights: Vec<(Pattern, usize)>,
     pub emitter: E,
     pub handle: tokio::runtime::Handle,
 }
@@ -85,6 +91,9 @@ where
         let mut fingerprint_buffer = Vec::new();
 
         let mut fp_map: IndexMap<FileFingerprint, FileWatcher> = Default::default();
+        let mut schedules: HashMap<FileFingerprint, ReadSchedule> = Default::default();
+        let mut global_limiter = self.max_read_bytes_per_sec.map(RateLimiter::new);
+        let mut next_start: usize = 0;
 
         let mut backoff_cap: usize = 1;
         let mut lines = Vec::new();
@@ -137,7 +146,7 @@ where
         let mut shutdown2 = shutdown.clone();
         let emitter = self.emitter.clone();
         let checkpointer = Arc::new(checkpointer);
-        let sleep_duration = self.glob_minimum_cooldown;
+        let sleep_duration = self.checkpoint_interval;
         let checkpoint_task_handle = self.handle.spawn(async move {
             loop {
                 let sleep = sleep(sleep_duration);
@@ -222,6 +231,7 @@ where
                                         path = ?path,
                                         old_path = ?watcher.path
                                     );
+                                    self.emitter.emit_file_backlog(&watcher.path, 0);
                                     watcher.update_path(path).ok(); // ok if this fails: might fix next cycle
                                 } else {
                                     info!(
@@ -240,6 +250,7 @@ where
                                                 new_modified_time = ?new_modified_time,
                                                 old_modified_time = ?old_modified_time,
                                             );
+                                            self.emitter.emit_file_backlog(&watcher.path, 0);
                                             watcher.update_path(path).ok(); // ok if this fails: might fix next cycle
                                         }
                                     }
@@ -252,20 +263,62 @@ where
                         }
                     }
                 }
+
+                for (_file_id, watcher) in &fp_map {
+                    if let Some(bytes) = watcher.backlog() {
+                        self.emitter.emit_file_backlog(&watcher.path, bytes);
+                    }
+                }
                 stats.record("discovery", start.elapsed());
             }
 
             // Collect lines by polling files.
+            //
+            // Each file may read up to `max_read_bytes` times its weight per
+            // pass, further limited by the per-file and global rate limits.
+            // When the global rate limit cuts a pass short, the next pass
+            // resumes with the file that was skipped so that files at the end
+            // of the map aren't starved.
             let reading_span = trace_span!("reading").entered();
             let mut global_bytes_read: usize = 0;
             let mut maxed_out_reading_single_file = false;
-            for (&file_id, watcher) in &mut fp_map {
+            let mut rate_limited = false;
+            let start_index = if self.oldest_first { 0 } else { next_start };
+            next_start = 0;
+            for offset in 0..fp_map.len() {
+                let index = (start_index + offset) % fp_map.len();
+                let (&mut file_id, watcher) = fp_map.get_index_mut(index).expect("index in bounds");
                 let _span = trace_span!("reading", path = ?watcher.path).entered();
 
                 if !watcher.should_read() {
                     continue;
                 }
 
+                let schedule = schedules
+                    .entry(file_id)
+                    .or_insert_with(|| self.new_read_schedule(&watcher.path));
+                let mut budget = self.max_read_bytes.saturating_mul(schedule.weight);
+                if let Some(limiter) = schedule.limiter.as_mut() {
+                    budget = cmp::min(budget, limiter.available());
+                }
+                if let Some(limiter) = global_limiter.as_mut() {
+                    let available = limiter.available();
+                    if available == 0 {
+                        rate_limited = true;
+                        next_start = index;
+                        break;
+                    }
+                    budget = cmp::min(budget, available);
+                }
+                if budget == 0 {
+                    rate_limited = true;
+                    // Do not move on to newer files while an older file is limited
+                    if self.oldest_first {
+                        break;
+                    }
+                    continue;
+                }
+
                 let start = time::Instant::now();
                 let mut bytes_read: usize = 0;
                 while let Ok(Some(line)) = watcher.read_line() {
@@ -290,13 +343,20 @@ where
                         offset: watcher.get_file_position(),
                     });
 
-                    if bytes_read > self.max_read_bytes {
+                    if bytes_read > budget {
                         maxed_out_reading_single_file = true;
                         break;
                     }
                 }
                 stats.record("reading", start.elapsed());
 
+                if let Some(limiter) = schedule.limiter.as_mut() {
+                    limiter.consume(bytes_read);
+                }
+                if let Some(limiter) = global_limiter.as_mut() {
+                    limiter.consume(bytes_read);
+                }
+
                 if bytes_read > 0 {
                     global_bytes_read = global_bytes_read.saturating_add(bytes_read);
                 } else {
@@ -330,7 +390,11 @@ where
             fp_map.retain(|file_id, watcher| {
                 if watcher.dead() {
                     self.emitter.emit_file_unwatched(&watcher.path);
+                    // Zero the backlog of the path, so that its series doesn't
+                    // keep reporting the last backlog of a file gone.
+                    self.emitter.emit_file_backlog(&watcher.path, 0);
                     checkpoints.set_dead(*file_id);
+                    schedules.remove(file_id);
                     false
                 } else {
                     true
@@ -360,8 +424,11 @@ where
             // limited by the hard-coded cap. Else, we set the backup_cap to its
             // minimum on the assumption that next time through there will be
             // more lines to read promptly.
+            // While rate limited, the cap is kept low enough for the limits
+            // to be refilled, and not exceeded, by the time we wake up.
             backoff_cap = if global_bytes_read == 0 {
-                cmp::min(2_048, backoff_cap.saturating_mul(2))
+                let max_backoff = if rate_limited { 100 } else { 2_048 };
+                cmp::min(max_backoff, backoff_cap.saturating_mul(2))
             } else {
                 1
             };
@@ -395,6 +462,18 @@ where
         }
     }
 
+    fn new_read_schedule(&self, path: &Path) -> ReadSchedule {
+        let weight = self
+            .read_weights
+            .iter()
+            .find(|(pattern, _)| pattern.matches_path(path))
+            .map_or(1, |(_, weight)| cmp::max(1, *weight));
+        ReadSchedule {
+            weight,
+            limiter: self.max_read_bytes_per_sec_per_file.map(RateLimiter::new),
+        }
+    }
+
     fn watch_new_file(
         &self,
         path: PathBuf,
@@ -458,6 +537,12 @@ where
 #[derive(Debug)]
 pub struct Shutdown;
 
+/// Per-file state used to schedule reads fairly.
+struct ReadSchedule {
+    weight: usize,
+    limiter: Option<RateLimiter>,
+}
+
 struct TimingStats {
     started_at: time::Instant,
     segments: BTreeMap<&'static str, Duration>,
@@ -1,9 +1,9 @@
 use crate::buffer::read_until_with_max_size;
+use crate::compression::{skip_bytes, Compression};
 use crate::metadata_ext::PortableFileExt;
 use crate::{FilePosition, ReadFrom};
 use bytes::{Bytes, BytesMut};
 use chrono::{DateTime, Utc};
-use flate2::bufread::MultiGzDecoder;
 use std::{
     fs::{self, File},
     io::{self, BufRead, Seek},
@@ -26,6 +26,7 @@ pub struct FileWatcher {
     findable: bool,
     reader: Box<dyn BufRead>,
     file_position: FilePosition,
+    compressed: bool,
     devno: u64,
     inode: u64,
     is_dead: bool,
@@ -63,53 +64,52 @@ impl FileWatcher {
             false
         };
 
-        let gzipped = is_gzipped(&mut reader)?;
+        let compression = Compression::detect(&mut reader)?;
 
         // Determine the actual position at which we should start reading
         let (reader, file_position): (Box<dyn BufRead>, FilePosition) =
-            match (gzipped, too_old, read_from) {
-                (true, true, _) => {
+            match (compression, too_old, read_from) {
+                (Some(_), true, _) => {
                     debug!(
-                        message = "Not reading gzipped file older than `ignore_older`.",
+                        message = "Not reading compressed file older than `ignore_older`.",
                         ?path,
                     );
                     (Box::new(null_reader()), 0)
                 }
-                (true, _, ReadFrom::Checkpoint(file_position)) => {
-                    debug!(
-                        message = "Not re-reading gzipped file with existing stored offset.",
-                        ?path,
-                        %file_position
-                    );
-                    (Box::new(null_reader()), file_position)
+                (Some(compression), false, ReadFrom::Checkpoint(file_position)) => {
+                    // Checkpoints of compressed files are offsets into their decompressed
+                    // content, so we have to decompress everything up to them again.
+                    let mut reader = compression.decoder(reader)?;
+                    skip_bytes(&mut reader, file_position)?;
+                    (reader, file_position)
                 }
-                // TODO: This may become the default, leading us to stop reading gzipped files that
-                // we were reading before. Should we merge this and the next branch to read
+                // TODO: This may become the default, leading us to stop reading compressed files
+                // that we were reading before. Should we merge this and the next branch to read
                 // compressed file from the beginning even when `read_from = "end"` (implicitly via
                 // default or explicitly via config)?
-                (true, _, ReadFrom::End) => {
+                (Some(_), false, ReadFrom::End) => {
                     debug!(
                         message = "Can't read from the end of already-compressed file.",
                         ?path,
                     );
                     (Box::new(null_reader()), 0)
                 }
-                (true, false, ReadFrom::Beginning) => {
-                    (Box::new(io::BufReader::new(MultiGzDecoder::new(reader))), 0)
+                (Some(compression), false, ReadFrom::Beginning) => {
+                    (compression.decoder(reader)?, 0)
                 }
-                (false, true, _) => {
+                (None, true, _) => {
                     let pos = reader.seek(io::SeekFrom::End(0)).unwrap();
                     (Box::new(reader), pos)
                 }
-                (false, false, ReadFrom::Checkpoint(file_position)) => {
+                (None, false, ReadFrom::Checkpoint(file_position)) => {
                     let pos = reader.seek(io::SeekFrom::Start(file_position)).unwrap();
                     (Box::new(reader), pos)
                 }
-                (false, false, ReadFrom::Beginning) => {
+                (None, false, ReadFrom::Beginning) => {
                     let pos = reader.seek(io::SeekFrom::Start(0)).unwrap();
                     (Box::new(reader), pos)
                 }
-                (false, false, ReadFrom::End) => {
+                (None, false, ReadFrom::End) => {
                     let pos = reader.seek(io::SeekFrom::End(0)).unwrap();
                     (Box::new(reader), pos)
                 }
@@ -127,6 +127,7 @@ impl FileWatcher {
             findable: true,
             reader,
             file_position,
+            compressed: compression.is_some(),
             devno,
             inode: ino,
             is_dead: false,
@@ -142,18 +143,22 @@ impl FileWatcher {
         let file_handle = File::open(&path)?;
         if (file_handle.portable_dev()?, file_handle.portable_ino()?) != (self.devno, self.inode) {
             let mut reader = io::BufReader::new(fs::File::open(&path)?);
-            let gzipped = is_gzipped(&mut reader)?;
-            let new_reader: Box<dyn BufRead> = if gzipped {
-                if self.file_position != 0 {
-                    Box::new(null_reader())
-                } else {
-                    Box::new(io::BufReader::new(MultiGzDecoder::new(reader)))
+            // A file that was compressed after rotation holds the same content, so we
+            // continue reading from the same decompressed offset.
+            let compression = Compression::detect(&mut reader)?;
+            let new_reader: Box<dyn BufRead> = match compression {
+                Some(compression) => {
+                    let mut reader = compression.decoder(reader)?;
+                    skip_bytes(&mut reader, self.file_position)?;
+                    reader
+                }
+                None => {
+                    reader.seek(io::SeekFrom::Start(self.file_position))?;
+                    Box::new(reader)
                 }
-            } else {
-                reader.seek(io::SeekFrom::Start(self.file_position))?;
-                Box::new(reader)
             };
             self.reader = new_reader;
+            self.compressed = compression.is_some();
             self.devno = file_handle.portable_dev()?;
             self.inode = file_handle.portable_ino()?;
         }
@@ -181,6 +186,18 @@ impl FileWatcher {
         self.file_position
     }
 
+    /// Returns the number of bytes between the current position and the end
+    /// of the file, or `None` if it can't be known because the file is
+    /// compressed or its metadata can't be read.
+    pub fn backlog(&self) -> Option<u64> {
+        if self.compressed {
+            return None;
+        }
+        fs::metadata(&self.path)
+            .ok()
+            .map(|metadata| metadata.len().saturating_sub(self.file_position))
+    }
+
     /// Read a single line from the underlying file
     ///
     /// This function will attempt to read a new line from its file, blocking,
@@ -244,13 +261,6 @@ impl FileWatcher {
     }
 }
 
-fn is_gzipped(r: &mut io::BufReader<fs::File>) -> io::Result<bool> {
-    let header_bytes = r.fill_buf()?;
-    // WARN: The paired `BufReader::consume` is not called intentionally. If we
-    // do we'll chop a decent part of the potential gzip stream off.
-    Ok(header_bytes.starts_with(&[0x1f, 0x8b]))
-}
-
 fn null_reader() -> impl BufRead {
     io::Cursor::new(Vec::new())
 }
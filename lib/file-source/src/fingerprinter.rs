@@ -1,10 +1,14 @@
-use crate::{metadata_ext::PortableFileExt, FileSourceInternalEvents};
+use crate::{
+    compression::{open_decompressed, skip_bytes},
+    metadata_ext::PortableFileExt,
+    FileSourceInternalEvents,
+};
 use crc::Crc;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashSet,
-    fs::{self, metadata, File},
-    io::{self, Read, Seek, SeekFrom, Write},
+    fs::{metadata, File},
+    io::{self, Read, Write},
     path::{Path, PathBuf},
 };
 use tracing::trace_span;
@@ -93,8 +97,8 @@ impl Fingerprinter {
                 lines,
             } => {
                 buffer.resize(self.max_line_length, 0u8);
-                let mut fp = fs::File::open(path)?;
-                fp.seek(SeekFrom::Start(ignored_header_bytes as u64))?;
+                let mut fp = open_decompressed(path)?;
+                skip_bytes(&mut fp, ignored_header_bytes as u64)?;
                 fingerprinter_read_until(fp, b'\n', lines, buffer)?;
                 let fingerprint = FINGERPRINT_CRC.checksum(&buffer[..]);
                 Ok(FirstLinesChecksum(fingerprint))
@@ -150,8 +154,8 @@ impl Fingerprinter {
                 lines: _,
             } => {
                 buffer.resize(bytes, 0u8);
-                let mut fp = fs::File::open(path)?;
-                fp.seek(io::SeekFrom::Start(ignored_header_bytes as u64))?;
+                let mut fp = open_decompressed(path)?;
+                skip_bytes(&mut fp, ignored_header_bytes as u64)?;
                 fp.read_exact(&mut buffer[..bytes])?;
                 let fingerprint = FINGERPRINT_CRC.checksum(&buffer[..]);
                 Ok(Some(FileFingerprint::BytesChecksum(fingerprint)))
@@ -178,8 +182,8 @@ impl Fingerprinter {
                 lines,
             } => {
                 buffer.resize(self.max_line_length, 0u8);
-                let mut fp = fs::File::open(path)?;
-                fp.seek(SeekFrom::Start(ignored_header_bytes as u64))?;
+                let mut fp = open_decompressed(path)?;
+                skip_bytes(&mut fp, ignored_header_bytes as u64)?;
                 fingerprinter_read_until(fp, b'\n', lines, buffer)?;
                 let fingerprint = LEGACY_FINGERPRINT_CRC.checksum(&buffer[..]);
                 Ok(Some(FileFingerprint::FirstLinesChecksum(fingerprint)))
@@ -224,7 +228,14 @@ fn fingerprinter_read_until(
 #[cfg(test)]
 mod test {
     use super::{FileSourceInternalEvents, FingerprintStrategy, Fingerprinter};
-    use std::{collections::HashSet, fs, io::Error, path::Path, time::Duration};
+    use flate2::{write::GzEncoder, Compression};
+    use std::{
+        collections::HashSet,
+        fs,
+        io::{Error, Write},
+        path::Path,
+        time::Duration,
+    };
     use tempfile::tempdir;
 
     #[test]
@@ -437,6 +448,59 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_compressed_file_fingerprint() {
+        let fingerprinter = Fingerprinter {
+            strategy: FingerprintStrategy::Checksum {
+                bytes: 256,
+                ignored_header_bytes: 0,
+                lines: 1,
+            },
+            max_line_length: 1024,
+            ignore_not_found: false,
+        };
+
+        let target_dir = tempdir().unwrap();
+        let mut data = vec![b'x'; 256];
+        data.extend_from_slice(b"\nsecond line\n");
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&data).unwrap();
+        let gzipped_data = encoder.finish().unwrap();
+
+        let plain_path = target_dir.path().join("app.log.1");
+        let gzipped_path = target_dir.path().join("app.log.1.gz");
+        let zstd_path = target_dir.path().join("app.log.1.zst");
+        fs::write(&plain_path, &data).unwrap();
+        fs::write(&gzipped_path, &gzipped_data).unwrap();
+        fs::write(&zstd_path, zstd::encode_all(&data[..], 0).unwrap()).unwrap();
+
+        let mut buf = Vec::new();
+        let plain = fingerprinter
+            .get_fingerprint_of_file(&plain_path, &mut buf)
+            .unwrap();
+        assert_eq!(
+            plain,
+            fingerprinter
+                .get_fingerprint_of_file(&gzipped_path, &mut buf)
+                .unwrap()
+        );
+        assert_eq!(
+            plain,
+            fingerprinter
+                .get_fingerprint_of_file(&zstd_path, &mut buf)
+                .unwrap()
+        );
+        assert_eq!(
+            fingerprinter
+                .get_bytes_checksum(&plain_path, &mut buf)
+                .unwrap(),
+            fingerprinter
+                .get_bytes_checksum(&gzipped_path, &mut buf)
+                .unwrap()
+        );
+    }
+
     #[test]
     fn no_error_on_dir() {
         let target_dir = tempdir().unwrap();
@@ -499,5 +563,7 @@ mod test {
         fn emit_files_open(&self, _: usize) {}
 
         fn emit_path_globbing_failed(&self, _: &Path, _: &Error) {}
+
+        fn emit_file_backlog(&self, _: &Path, _: u64) {}
     }
 }
@@ -5,14 +5,16 @@ extern crate scan_fmt;
 
 pub mod buffer;
 mod checkpointer;
+mod compression;
 mod file_server;
 mod file_watcher;
 mod fingerprinter;
 mod internal_events;
 mod metadata_ext;
 pub mod paths_provider;
+mod rate_limiter;
 
-pub use self::checkpointer::{Checkpointer, CheckpointsView};
+pub use self::checkpointer::{Checkpoint, Checkpointer, CheckpointsView, DataDirLock};
 pub use self::file_server::{FileServer, Line, Shutdown as FileServerShutdown};
 pub use self::fingerprinter::{FileFingerprint, FingerprintStrategy, Fingerprinter};
 pub use self::internal_events::FileSourceInternalEvents;
@@ -0,0 +1,80 @@
+use std::time::Instant;
+
+/// A token bucket limiting the number of bytes read per second.
+///
+/// The bucket holds at most one second worth of bytes. Reads are whole lines,
+/// so `consume` may overdraw the bucket, in which case the debt is paid back
+/// before any more bytes become available.
+#[derive(Debug)]
+pub struct RateLimiter {
+    bytes_per_sec: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(bytes_per_sec: usize) -> Self {
+        Self::new_at(bytes_per_sec, Instant::now())
+    }
+
+    fn new_at(bytes_per_sec: usize, now: Instant) -> Self {
+        let bytes_per_sec = bytes_per_sec as f64;
+        Self {
+            bytes_per_sec,
+            tokens: bytes_per_sec,
+            last_refill: now,
+        }
+    }
+
+    /// Returns the number of bytes that may be read right now.
+    pub fn available(&mut self) -> usize {
+        self.available_at(Instant::now())
+    }
+
+    fn available_at(&mut self, now: Instant) -> usize {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * self.bytes_per_sec).min(self.bytes_per_sec);
+        self.last_refill = now;
+        self.tokens.max(0.0) as usize
+    }
+
+    /// Records that `bytes` have been read.
+    pub fn consume(&mut self, bytes: usize) {
+        self.tokens -= bytes as f64;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RateLimiter;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn limits_bytes_per_second() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new_at(100, start);
+
+        assert_eq!(limiter.available_at(start), 100);
+        limiter.consume(60);
+        assert_eq!(limiter.available_at(start), 40);
+
+        // Refills proportionally to the elapsed time.
+        assert_eq!(limiter.available_at(start + Duration::from_millis(200)), 60);
+
+        // Never holds more than one second worth of bytes.
+        assert_eq!(limiter.available_at(start + Duration::from_secs(10)), 100);
+    }
+
+    #[test]
+    fn overdrawn_bucket_pays_back_debt() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new_at(100, start);
+
+        limiter.consume(250);
+        assert_eq!(limiter.available_at(start), 0);
+        assert_eq!(limiter.available_at(start + Duration::from_secs(1)), 0);
+        assert_eq!(limiter.available_at(start + Duration::from_millis(1500)), 0);
+        assert_eq!(limiter.available_at(start + Duration::from_secs(2)), 50);
+    }
+}
@@ -14,6 +14,8 @@ use tokio::{
 };
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
+#[cfg(feature = "file-source")]
+use crate::checkpoints;
 #[cfg(feature = "api-client")]
 use crate::tap;
 #[cfg(feature = "api-client")]
@@ -138,6 +140,8 @@ impl Application {
                         SubCommand::Top(t) => top::cmd(&t).await,
                         #[cfg(feature = "api-client")]
                         SubCommand::Tap(t) => tap::cmd(&t).await,
+                        #[cfg(feature = "file-source")]
+                        SubCommand::Checkpoints(c) => checkpoints::cmd(&c),
                         SubCommand::Validate(v) => validate::validate(&v, color).await,
                         #[cfg(feature = "vrl-cli")]
                         SubCommand::Vrl(s) => vrl_cli::cmd::cmd(&s),
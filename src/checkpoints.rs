@@ -0,0 +1,194 @@
+use colored::*;
+use file_source::{Checkpointer, DataDirLock, FileFingerprint, FingerprintStrategy, Fingerprinter};
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub enum Opts {
+    /// List the checkpoints of a `file` or `kubernetes_logs` source.
+    List {
+        /// The data directory of the source, that is `<data_dir>/<source id>`.
+        #[structopt(parse(from_os_str))]
+        data_dir: PathBuf,
+
+        /// Format the list in an encoding scheme.
+        #[structopt(long, default_value = "text", possible_values = &["text", "json"])]
+        format: Format,
+    },
+
+    /// Remove the checkpoint of a file, so that it's read again from where `read_from` points to.
+    Reset {
+        #[structopt(flatten)]
+        file: FileOpts,
+    },
+
+    /// Set the position from which a file is read.
+    Set {
+        #[structopt(flatten)]
+        file: FileOpts,
+
+        /// The position, in bytes, to read the file from.
+        position: u64,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub struct FileOpts {
+    /// The data directory of the source, that is `<data_dir>/<source id>`.
+    #[structopt(parse(from_os_str))]
+    data_dir: PathBuf,
+
+    /// The file whose checkpoint to edit.
+    #[structopt(parse(from_os_str))]
+    file: PathBuf,
+
+    /// The `fingerprint.lines` option of the source.
+    #[structopt(long, default_value = "1")]
+    lines: usize,
+
+    /// The `fingerprint.ignored_header_bytes` option of the source.
+    #[structopt(long, default_value = "0")]
+    ignored_header_bytes: usize,
+
+    /// The deprecated `fingerprint.bytes` option of the source.
+    #[structopt(long, default_value = "256")]
+    bytes: usize,
+
+    /// The `max_line_bytes` option of the source.
+    #[structopt(long, default_value = "102400")]
+    max_line_bytes: usize,
+
+    /// Set if the source uses the `device_and_inode` fingerprint strategy.
+    #[structopt(long)]
+    device_and_inode: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            s => Err(format!(
+                "{} is not a valid option, expected `text` or `json`",
+                s
+            )),
+        }
+    }
+}
+
+impl FileOpts {
+    fn fingerprint(&self) -> Result<FileFingerprint, String> {
+        let strategy = if self.device_and_inode {
+            FingerprintStrategy::DevInode
+        } else {
+            FingerprintStrategy::Checksum {
+                bytes: self.bytes,
+                ignored_header_bytes: self.ignored_header_bytes,
+                lines: self.lines,
+            }
+        };
+        let fingerprinter = Fingerprinter {
+            strategy,
+            max_line_length: self.max_line_bytes,
+            ignore_not_found: false,
+        };
+        fingerprinter
+            .get_fingerprint_of_file(&self.file, &mut Vec::new())
+            .map_err(|error| format!("Unable to fingerprint {:?}: {}", self.file, error))
+    }
+}
+
+pub fn cmd(opts: &Opts) -> exitcode::ExitCode {
+    match run(opts) {
+        Ok(()) => exitcode::OK,
+        Err(error) => {
+            eprintln!("{}", error.red());
+            exitcode::SOFTWARE
+        }
+    }
+}
+
+fn run(opts: &Opts) -> Result<(), String> {
+    match opts {
+        Opts::List { data_dir, format } => {
+            let (_lock, checkpointer) = open(data_dir)?;
+            let checkpoints = checkpointer.view().list();
+            match format {
+                Format::Text => {
+                    for checkpoint in checkpoints {
+                        println!(
+                            "{:?}\tposition={}\tmodified={}",
+                            checkpoint.fingerprint,
+                            checkpoint.position,
+                            checkpoint.modified.to_rfc3339()
+                        );
+                    }
+                }
+                Format::Json => {
+                    println!("{}", serde_json::to_string(&checkpoints).unwrap());
+                }
+            }
+            Ok(())
+        }
+        Opts::Reset { file } => {
+            let fingerprint = file.fingerprint()?;
+            let (_lock, checkpointer) = open(&file.data_dir)?;
+            match checkpointer.view().remove(fingerprint) {
+                Some(position) => {
+                    write(&checkpointer)?;
+                    println!(
+                        "Removed checkpoint of {:?} at position {}.",
+                        file.file, position
+                    );
+                }
+                None => println!("No checkpoint found for {:?}.", file.file),
+            }
+            Ok(())
+        }
+        Opts::Set { file, position } => {
+            let fingerprint = file.fingerprint()?;
+            let (_lock, checkpointer) = open(&file.data_dir)?;
+            checkpointer.view().update(fingerprint, *position);
+            write(&checkpointer)?;
+            println!(
+                "Set checkpoint of {:?} to position {}.",
+                file.file, position
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Locks the data directory, so that the checkpoints aren't edited while a
+/// source is running, and reads the checkpoints from it.
+fn open(data_dir: &Path) -> Result<(DataDirLock, Checkpointer), String> {
+    if !data_dir.is_dir() {
+        return Err(format!("Data directory {:?} does not exist.", data_dir));
+    }
+    let lock = DataDirLock::acquire(data_dir).map_err(|error| {
+        format!(
+            "Unable to lock data directory, make sure no Vector instance is using it: {}",
+            error
+        )
+    })?;
+    let mut checkpointer = Checkpointer::new(data_dir);
+    checkpointer.read_checkpoints(None);
+    Ok((lock, checkpointer))
+}
+
+fn write(checkpointer: &Checkpointer) -> Result<(), String> {
+    checkpointer
+        .write_checkpoints()
+        .map(|_| ())
+        .map_err(|error| format!("Unable to write checkpoints: {}", error))
+}
@@ -7,6 +7,8 @@ use crate::tap;
 #[cfg(feature = "api-client")]
 use crate::top;
 
+#[cfg(feature = "file-source")]
+use crate::checkpoints;
 #[cfg(windows)]
 use crate::service;
 
@@ -203,6 +205,10 @@ pub enum SubCommand {
     #[cfg(feature = "api-client")]
     Tap(tap::Opts),
 
+    /// Inspect and edit the checkpoints of file based sources. Vector must not be running.
+    #[cfg(feature = "file-source")]
+    Checkpoints(checkpoints::Opts),
+
     /// Manage the vector service.
     #[cfg(windows)]
     Service(service::Opts),
@@ -0,0 +1,133 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct AmqpEventReceived<'a> {
+    pub byte_size: usize,
+    pub exchange: &'a str,
+    pub routing_key: &'a str,
+}
+
+impl<'a> InternalEvent for AmqpEventReceived<'a> {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Received one event.",
+            exchange = %self.exchange,
+            routing_key = %self.routing_key,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", 1);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpEventFailed {
+    pub error: lapin::Error,
+}
+
+impl InternalEvent for AmqpEventFailed {
+    fn emit_logs(&self) {
+        error!(message = "Failed to read message.", error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_failed_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpConnectionLost;
+
+impl InternalEvent for AmqpConnectionLost {
+    fn emit_logs(&self) {
+        error!(message = "Connection to the broker lost; reconnecting.");
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpConnectionFailed {
+    pub error: crate::Error,
+}
+
+impl InternalEvent for AmqpConnectionFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Unable to connect to the broker.",
+            error = %self.error,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_failed_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpAckFailed {
+    pub error: lapin::Error,
+}
+
+impl InternalEvent for AmqpAckFailed {
+    fn emit_logs(&self) {
+        error!(message = "Unable to acknowledge message.", error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_send_ack_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpEventSendSuccess {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for AmqpEventSendSuccess {
+    fn emit_logs(&self) {
+        trace!(message = "Processed one event.");
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpEventSendFail {
+    pub error: lapin::Error,
+}
+
+impl InternalEvent for AmqpEventSendFail {
+    fn emit_logs(&self) {
+        error!(message = "Failed to send message.", error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("send_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpDeliveryNacked;
+
+impl InternalEvent for AmqpDeliveryNacked {
+    fn emit_logs(&self) {
+        error!(
+            message = "Broker rejected message.",
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("send_errors_total", 1);
+    }
+}
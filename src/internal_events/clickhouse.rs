@@ -0,0 +1,51 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct ClickhouseFieldCoercionFailed<'a> {
+    pub field: &'a str,
+    pub column_type: &'a str,
+    pub error: String,
+}
+
+impl<'a> InternalEvent for ClickhouseFieldCoercionFailed<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Field could not be coerced to the type of its column; dropping event.",
+            field = %self.field,
+            column_type = %self.column_type,
+            error = %self.error,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "processing_errors_total", 1,
+            "error_type" => "field_coercion_failed",
+        );
+        counter!("events_discarded_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct ClickhouseSchemaFetchFailed {
+    pub error: crate::Error,
+}
+
+impl InternalEvent for ClickhouseSchemaFetchFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Unable to describe the table; retrying.",
+            error = %self.error,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "processing_errors_total", 1,
+            "error_type" => "schema_fetch_failed",
+        );
+    }
+}
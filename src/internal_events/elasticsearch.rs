@@ -16,3 +16,78 @@ impl InternalEvent for ElasticSearchEventEncoded {
         counter!("processed_bytes_total", self.byte_size as u64);
     }
 }
+
+#[derive(Debug)]
+pub struct ElasticSearchDocumentFailed<'a> {
+    pub error_type: &'a str,
+    pub reason: &'a str,
+    pub status: u16,
+}
+
+impl<'a> InternalEvent for ElasticSearchDocumentFailed<'a> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Document failed to be indexed.",
+            error_type = %self.error_type,
+            reason = %self.reason,
+            status = %self.status,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "processing_errors_total", 1,
+            "error_type" => self.error_type.to_owned(),
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct ElasticSearchEndpointDown<'a> {
+    pub endpoint: &'a str,
+    pub error: String,
+}
+
+impl<'a> InternalEvent for ElasticSearchEndpointDown<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Endpoint marked down.",
+            endpoint = %self.endpoint,
+            error = %self.error,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct ElasticSearchEndpointUp<'a> {
+    pub endpoint: &'a str,
+}
+
+impl<'a> InternalEvent for ElasticSearchEndpointUp<'a> {
+    fn emit_logs(&self) {
+        info!(message = "Endpoint healthy again.", endpoint = %self.endpoint);
+    }
+}
+
+#[derive(Debug)]
+pub struct ElasticSearchDiscoveryFailed {
+    pub error: crate::Error,
+}
+
+impl InternalEvent for ElasticSearchDiscoveryFailed {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Failed to discover the nodes of the cluster.",
+            error = %self.error,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "processing_errors_total", 1,
+            "error_type" => "discovery_failed",
+        );
+    }
+}
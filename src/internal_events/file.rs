@@ -19,7 +19,7 @@ impl InternalEvent for FileOpen {
 mod source {
     use super::{FileOpen, InternalEvent};
     use file_source::FileSourceInternalEvents;
-    use metrics::counter;
+    use metrics::{counter, gauge};
     use std::{io::Error, path::Path, time::Duration};
 
     #[derive(Debug)]
@@ -284,6 +284,21 @@ mod source {
         }
     }
 
+    #[derive(Debug)]
+    pub struct FileBacklog<'a> {
+        pub path: &'a Path,
+        pub bytes: u64,
+    }
+
+    impl<'a> InternalEvent for FileBacklog<'a> {
+        fn emit_metrics(&self) {
+            gauge!(
+                "file_backlog_bytes", self.bytes as f64,
+                "file" => self.path.to_string_lossy().into_owned(),
+            );
+        }
+    }
+
     #[derive(Clone)]
     pub struct FileSourceInternalEventsEmitter;
 
@@ -338,5 +353,9 @@ mod source {
         fn emit_path_globbing_failed(&self, path: &Path, error: &Error) {
             emit!(PathGlobbingFailed { path, error });
         }
+
+        fn emit_file_backlog(&self, path: &Path, bytes: u64) {
+            emit!(FileBacklog { path, bytes });
+        }
     }
 }
@@ -0,0 +1,43 @@
+use super::InternalEvent;
+use crate::sinks::gelf::GelfEncodeError;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct GelfInvalidEvent {
+    pub error: GelfEncodeError,
+}
+
+impl InternalEvent for GelfInvalidEvent {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Event can't be sent as a GELF message; dropping event.",
+            error = %self.error,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "invalid_event");
+    }
+}
+
+#[derive(Debug)]
+pub struct GelfMessageTooLarge {
+    pub byte_size: usize,
+    pub max_byte_size: usize,
+}
+
+impl InternalEvent for GelfMessageTooLarge {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Message needs more than 128 chunks; dropping event.",
+            byte_size = %self.byte_size,
+            max_byte_size = %self.max_byte_size,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "message_too_large");
+    }
+}
@@ -0,0 +1,103 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct GelfMessageReceived {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for GelfMessageReceived {
+    fn emit_logs(&self) {
+        trace!(message = "Received message.", byte_size = %self.byte_size);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", 1);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct GelfInvalidMessage {
+    pub error: crate::sources::gelf::GelfError,
+    pub byte_size: usize,
+}
+
+impl InternalEvent for GelfInvalidMessage {
+    fn emit_logs(&self) {
+        error!(
+            message = "Invalid GELF message, discarding.",
+            error = %self.error,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("invalid_record_total", 1);
+        counter!("invalid_record_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct GelfChunksExpired {
+    pub count: usize,
+}
+
+impl InternalEvent for GelfChunksExpired {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Dropped messages whose chunks didn't all arrive in time.",
+            count = %self.count,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "processing_errors_total", self.count as u64,
+            "error_type" => "chunks_expired",
+        );
+    }
+}
+
+#[derive(Debug)]
+enum GelfSocketErrorType {
+    Bind,
+    Read,
+}
+
+#[derive(Debug)]
+pub struct GelfSocketError {
+    r#type: GelfSocketErrorType,
+    pub error: std::io::Error,
+}
+
+impl GelfSocketError {
+    pub fn bind(error: std::io::Error) -> Self {
+        Self {
+            r#type: GelfSocketErrorType::Bind,
+            error,
+        }
+    }
+
+    pub fn read(error: std::io::Error) -> Self {
+        Self {
+            r#type: GelfSocketErrorType::Read,
+            error,
+        }
+    }
+}
+
+impl InternalEvent for GelfSocketError {
+    fn emit_logs(&self) {
+        let message = match self.r#type {
+            GelfSocketErrorType::Bind => "Failed to bind to UDP listener socket.",
+            GelfSocketErrorType::Read => "Failed to read UDP datagram.",
+        };
+        error!(message, error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1);
+    }
+}
@@ -0,0 +1,102 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct GraphiteEventsReceived {
+    pub count: usize,
+    pub byte_size: usize,
+}
+
+impl InternalEvent for GraphiteEventsReceived {
+    fn emit_logs(&self) {
+        trace!(message = "Received events.", count = %self.count, byte_size = %self.byte_size);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", self.count as u64);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct GraphiteInvalidRecord<'a> {
+    pub error: crate::sources::graphite::parser::ParseError,
+    pub text: &'a str,
+}
+
+impl InternalEvent for GraphiteInvalidRecord<'_> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Invalid metric from graphite, discarding.",
+            error = %self.error,
+            text = %self.text,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("invalid_record_total", 1);
+        counter!("invalid_record_bytes_total", self.text.len() as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct GraphitePickleDecodeError {
+    pub error: serde_pickle::Error,
+}
+
+impl InternalEvent for GraphitePickleDecodeError {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to unpickle frame, discarding.",
+            error = %self.error,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "decode_failed");
+    }
+}
+
+#[derive(Debug)]
+enum GraphiteSocketErrorType {
+    Bind,
+    Read,
+}
+
+#[derive(Debug)]
+pub struct GraphiteSocketError {
+    r#type: GraphiteSocketErrorType,
+    pub error: std::io::Error,
+}
+
+impl GraphiteSocketError {
+    pub fn bind(error: std::io::Error) -> Self {
+        Self {
+            r#type: GraphiteSocketErrorType::Bind,
+            error,
+        }
+    }
+
+    pub fn read(error: std::io::Error) -> Self {
+        Self {
+            r#type: GraphiteSocketErrorType::Read,
+            error,
+        }
+    }
+}
+
+impl InternalEvent for GraphiteSocketError {
+    fn emit_logs(&self) {
+        let message = match self.r#type {
+            GraphiteSocketErrorType::Bind => "Failed to bind to UDP listener socket.",
+            GraphiteSocketErrorType::Read => "Failed to read UDP datagram.",
+        };
+        error!(message, error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1);
+    }
+}
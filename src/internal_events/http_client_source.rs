@@ -0,0 +1,92 @@
+use super::InternalEvent;
+use metrics::{counter, histogram};
+use std::time::Instant;
+
+#[derive(Debug)]
+pub struct HttpClientEventsReceived<'a> {
+    pub byte_size: usize,
+    pub count: usize,
+    pub url: &'a str,
+}
+
+impl<'a> InternalEvent for HttpClientEventsReceived<'a> {
+    fn emit_logs(&self) {
+        debug!(message = "Scraped events.", count = ?self.count);
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "events_in_total", self.count as u64,
+            "uri" => self.url.to_owned(),
+        );
+        counter!(
+            "processed_bytes_total", self.byte_size as u64,
+            "uri" => self.url.to_owned(),
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct HttpClientRequestCompleted {
+    pub start: Instant,
+    pub end: Instant,
+}
+
+impl InternalEvent for HttpClientRequestCompleted {
+    fn emit_logs(&self) {
+        debug!(message = "Request completed.");
+    }
+
+    fn emit_metrics(&self) {
+        counter!("requests_completed_total", 1);
+        histogram!("request_duration_seconds", self.end - self.start);
+    }
+}
+
+#[derive(Debug)]
+pub struct HttpClientParseError<'a> {
+    pub error: &'a str,
+    pub url: &'a str,
+}
+
+impl InternalEvent for HttpClientParseError<'_> {
+    fn emit_logs(&self) {
+        error!(message = "Parsing error.", url = %self.url, error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("parse_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct HttpClientErrorResponse<'a> {
+    pub code: hyper::StatusCode,
+    pub url: &'a str,
+}
+
+impl InternalEvent for HttpClientErrorResponse<'_> {
+    fn emit_logs(&self) {
+        error!(message = "HTTP error response.", url = %self.url, code = %self.code);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("http_error_response_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct HttpClientHttpError<'a> {
+    pub error: crate::Error,
+    pub url: &'a str,
+}
+
+impl InternalEvent for HttpClientHttpError<'_> {
+    fn emit_logs(&self) {
+        error!(message = "HTTP request processing error.", url = %self.url, error = ?self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("http_request_errors_total", 1);
+    }
+}
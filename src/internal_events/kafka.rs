@@ -48,6 +48,36 @@ impl InternalEvent for KafkaEventFailed {
     }
 }
 
+#[derive(Debug)]
+pub struct KafkaAvroDecodingFailed {
+    pub error: crate::Error,
+}
+
+impl InternalEvent for KafkaAvroDecodingFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to decode Avro message, keeping the raw message.",
+            error = %self.error,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "avro_decode_failed");
+    }
+}
+
+#[derive(Debug)]
+pub struct KafkaPartitionPauseFailed {
+    pub error: rdkafka::error::KafkaError,
+}
+
+impl InternalEvent for KafkaPartitionPauseFailed {
+    fn emit_logs(&self) {
+        warn!(message = "Unable to pause consumption of partition past its end offset.", error = ?self.error);
+    }
+}
+
 #[derive(Debug)]
 pub struct KafkaKeyExtractionFailed<'a> {
     pub key_field: &'a str,
@@ -59,6 +89,21 @@ impl InternalEvent for KafkaKeyExtractionFailed<'_> {
     }
 }
 
+#[derive(Debug)]
+pub struct KafkaHeaderExtractionFailed<'a> {
+    pub header_field: &'a str,
+}
+
+impl InternalEvent for KafkaHeaderExtractionFailed<'_> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to extract headers, expected the field to be a map.",
+            header_field = %self.header_field,
+            internal_log_rate_secs = 10
+        );
+    }
+}
+
 #[derive(Debug)]
 pub struct KafkaStatisticsReceived<'a> {
     pub statistics: &'a rdkafka::Statistics,
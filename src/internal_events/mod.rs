@@ -4,6 +4,8 @@ mod adaptive_concurrency;
 mod add_fields;
 mod add_tags;
 mod aggregate;
+#[cfg(any(feature = "sources-amqp", feature = "sinks-amqp"))]
+mod amqp;
 mod ansi_stripper;
 #[cfg(feature = "sources-apache_metrics")]
 mod apache_metrics;
@@ -26,6 +28,8 @@ mod aws_sqs;
 #[cfg(feature = "sinks-azure_blob")]
 pub(crate) mod azure_blob;
 mod blackhole;
+#[cfg(feature = "sinks-clickhouse")]
+mod clickhouse;
 #[cfg(feature = "transforms-coercer")]
 mod coercer;
 #[cfg(feature = "transforms-concat")]
@@ -53,10 +57,18 @@ mod exec;
 mod filter;
 #[cfg(feature = "sources-fluent")]
 mod fluent;
+#[cfg(feature = "sinks-gelf")]
+mod gelf_sink;
+#[cfg(feature = "sources-gelf")]
+mod gelf_source;
 #[cfg(feature = "sources-generator")]
 mod generator;
 #[cfg(feature = "transforms-geoip")]
 mod geoip;
+#[cfg(feature = "sinks-graphite")]
+mod graphite_sink;
+#[cfg(feature = "sources-graphite")]
+mod graphite_source;
 #[cfg(feature = "transforms-grok_parser")]
 mod grok_parser;
 mod heartbeat;
@@ -64,6 +76,8 @@ mod heartbeat;
 mod host_metrics;
 mod http;
 pub mod http_client;
+#[cfg(feature = "sources-http_client")]
+mod http_client_source;
 #[cfg(all(unix, feature = "sources-journald"))]
 mod journald;
 #[cfg(feature = "transforms-json_parser")]
@@ -85,11 +99,15 @@ mod lua;
 mod metric_to_log;
 #[cfg(feature = "sources-mongodb_metrics")]
 mod mongodb_metrics;
+#[cfg(any(feature = "sources-mqtt", feature = "sinks-mqtt"))]
+mod mqtt;
 #[cfg(any(feature = "sources-nats", feature = "sinks-nats"))]
 mod nats;
 #[cfg(feature = "sources-nginx_metrics")]
 mod nginx_metrics;
 mod open;
+#[cfg(feature = "parquet")]
+mod parquet;
 #[cfg(feature = "sources-postgresql_metrics")]
 mod postgresql_metrics;
 mod process;
@@ -134,6 +152,8 @@ mod unix;
 mod vector;
 #[cfg(feature = "wasm")]
 mod wasm;
+#[cfg(any(feature = "sources-websocket", feature = "sinks-websocket"))]
+mod websocket;
 
 pub mod kubernetes;
 
@@ -141,6 +161,8 @@ pub use self::adaptive_concurrency::*;
 pub use self::add_fields::*;
 pub use self::add_tags::*;
 pub use self::aggregate::*;
+#[cfg(any(feature = "sources-amqp", feature = "sinks-amqp"))]
+pub use self::amqp::*;
 pub use self::ansi_stripper::*;
 #[cfg(feature = "sources-apache_metrics")]
 pub use self::apache_metrics::*;
@@ -159,6 +181,8 @@ pub use self::aws_kinesis_streams::*;
 #[cfg(feature = "sinks-aws_sqs")]
 pub use self::aws_sqs::*;
 pub use self::blackhole::*;
+#[cfg(feature = "sinks-clickhouse")]
+pub use self::clickhouse::*;
 #[cfg(feature = "transforms-coercer")]
 pub(crate) use self::coercer::*;
 #[cfg(feature = "transforms-concat")]
@@ -192,10 +216,18 @@ pub use self::file::*;
 pub use self::filter::*;
 #[cfg(feature = "sources-fluent")]
 pub use self::fluent::*;
+#[cfg(feature = "sinks-gelf")]
+pub use self::gelf_sink::*;
+#[cfg(feature = "sources-gelf")]
+pub use self::gelf_source::*;
 #[cfg(feature = "sources-generator")]
 pub use self::generator::*;
 #[cfg(feature = "transforms-geoip")]
 pub(crate) use self::geoip::*;
+#[cfg(feature = "sinks-graphite")]
+pub use self::graphite_sink::*;
+#[cfg(feature = "sources-graphite")]
+pub use self::graphite_source::*;
 #[cfg(feature = "transforms-grok_parser")]
 pub(crate) use self::grok_parser::*;
 pub use self::heartbeat::*;
@@ -203,6 +235,8 @@ pub use self::heartbeat::*;
 pub(crate) use self::host_metrics::*;
 #[cfg(any(feature = "sources-utils-http", feature = "sinks-http"))]
 pub(crate) use self::http::*;
+#[cfg(feature = "sources-http_client")]
+pub(crate) use self::http_client_source::*;
 #[cfg(all(unix, feature = "sources-journald"))]
 pub(crate) use self::journald::*;
 #[cfg(feature = "transforms-json_parser")]
@@ -222,11 +256,15 @@ pub use self::logplex::*;
 pub use self::lua::*;
 #[cfg(feature = "transforms-metric_to_log")]
 pub(crate) use self::metric_to_log::*;
+#[cfg(any(feature = "sources-mqtt", feature = "sinks-mqtt"))]
+pub use self::mqtt::*;
 #[cfg(any(feature = "sources-nats", feature = "sinks-nats"))]
 pub use self::nats::*;
 #[cfg(feature = "sources-nginx_metrics")]
 pub(crate) use self::nginx_metrics::*;
 pub use self::open::*;
+#[cfg(feature = "parquet")]
+pub(crate) use self::parquet::*;
 #[cfg(feature = "sources-postgresql_metrics")]
 pub(crate) use self::postgresql_metrics::*;
 pub use self::process::*;
@@ -271,6 +309,8 @@ pub use self::unix::*;
 pub use self::vector::*;
 #[cfg(feature = "wasm")]
 pub use self::wasm::*;
+#[cfg(any(feature = "sources-websocket", feature = "sinks-websocket"))]
+pub use self::websocket::*;
 #[cfg(windows)]
 pub use self::windows::*;
 #[cfg(feature = "sources-mongodb_metrics")]
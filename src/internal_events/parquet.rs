@@ -0,0 +1,26 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct ParquetFieldTypeMismatch<'a> {
+    pub field: &'a str,
+    pub count: usize,
+}
+
+impl<'a> InternalEvent for ParquetFieldTypeMismatch<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Values don't match the type of their column; writing them as nulls.",
+            field = %self.field,
+            count = %self.count,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "processing_errors_total", self.count as u64,
+            "error_type" => "type_mismatch",
+        );
+    }
+}
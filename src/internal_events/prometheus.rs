@@ -165,3 +165,44 @@ impl InternalEvent for PrometheusServerRequestComplete {
         counter!("requests_received_total", 1);
     }
 }
+
+#[cfg(feature = "sources-prometheus")]
+#[derive(Debug)]
+pub struct PrometheusFileSdError<'a> {
+    pub error: crate::Error,
+    pub path: &'a std::path::Path,
+}
+
+#[cfg(feature = "sources-prometheus")]
+impl<'a> InternalEvent for PrometheusFileSdError<'a> {
+    fn emit_logs(&self) {
+        error!(message = "Failed to discover targets.", path = ?self.path, error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "file_sd_failed");
+    }
+}
+
+#[cfg(feature = "sources-prometheus")]
+#[derive(Debug)]
+pub struct PrometheusInvalidTarget {
+    pub url: String,
+    pub error: http::uri::InvalidUri,
+}
+
+#[cfg(feature = "sources-prometheus")]
+impl InternalEvent for PrometheusInvalidTarget {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Invalid target URL; skipping target.",
+            url = %self.url,
+            error = %self.error,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "invalid_target");
+    }
+}
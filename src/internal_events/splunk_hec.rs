@@ -1,4 +1,5 @@
 use super::InternalEvent;
+use crate::event::metric::{MetricKind, MetricValue};
 use metrics::counter;
 use serde_json::Error;
 
@@ -35,6 +36,65 @@ impl InternalEvent for SplunkEventEncodeError {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct SplunkInvalidMetricReceived<'a> {
+    pub value: &'a MetricValue,
+    pub kind: &'a MetricKind,
+}
+
+impl<'a> InternalEvent for SplunkInvalidMetricReceived<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Invalid metric received; dropping event.",
+            value = ?self.value,
+            kind = ?self.kind,
+            internal_log_rate_secs = 30,
+        )
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "invalid_metric");
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct SplunkIndexerAcknowledgementFailed {
+    pub error: crate::Error,
+}
+
+impl InternalEvent for SplunkIndexerAcknowledgementFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to query indexer acknowledgements.",
+            error = %self.error,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("http_request_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct SplunkIndexerAcknowledgementTimedOut {
+    pub ack_id: u64,
+}
+
+impl InternalEvent for SplunkIndexerAcknowledgementTimedOut {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Indexer acknowledgement timed out; events will be reported as errored.",
+            ack_id = %self.ack_id,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("send_errors_total", 1);
+    }
+}
+
 #[cfg(feature = "sources-splunk_hec")]
 mod source {
     use super::InternalEvent;
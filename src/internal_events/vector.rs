@@ -32,3 +32,45 @@ impl InternalEvent for VectorProtoDecodeError {
         counter!("protobuf_decode_errors_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct VectorPeerEjected<'a> {
+    pub address: &'a str,
+    pub consecutive_failures: u32,
+}
+
+impl<'a> InternalEvent for VectorPeerEjected<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Ejecting unhealthy peer.",
+            address = %self.address,
+            consecutive_failures = %self.consecutive_failures,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("peer_ejections_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct VectorPeerResolveFailed<'a> {
+    pub host: &'a str,
+    pub error: crate::dns::DnsError,
+}
+
+impl<'a> InternalEvent for VectorPeerResolveFailed<'a> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to resolve peers; keeping the previous ones.",
+            host = %self.host,
+            error = %self.error,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("dns_lookup_errors_total", 1);
+    }
+}
@@ -0,0 +1,144 @@
+use super::InternalEvent;
+use metrics::counter;
+use std::{fmt::Display, net::SocketAddr};
+
+#[derive(Debug)]
+pub struct WebSocketConnectionEstablished {
+    pub peer_addr: SocketAddr,
+}
+
+impl InternalEvent for WebSocketConnectionEstablished {
+    fn emit_logs(&self) {
+        debug!(message = "Connected.", peer_addr = %self.peer_addr);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_established_total", 1, "mode" => "websocket");
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocketConnectionFailed<E> {
+    pub error: E,
+}
+
+impl<E: Display> InternalEvent for WebSocketConnectionFailed<E> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Unable to connect.",
+            error = %self.error,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_failed_total", 1, "mode" => "websocket");
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocketConnectionShutdown;
+
+impl InternalEvent for WebSocketConnectionShutdown {
+    fn emit_logs(&self) {
+        debug!(message = "Connection closed by the peer.");
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_shutdown_total", 1, "mode" => "websocket");
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocketConnectionError<E> {
+    pub error: E,
+}
+
+impl<E: Display> InternalEvent for WebSocketConnectionError<E> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Connection error.",
+            error = %self.error,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1, "mode" => "websocket");
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocketPongTimeout;
+
+impl InternalEvent for WebSocketPongTimeout {
+    fn emit_logs(&self) {
+        warn!(
+            message = "No pong received in time; reconnecting.",
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1, "mode" => "websocket");
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocketMessageReceived {
+    pub count: usize,
+    pub byte_size: usize,
+}
+
+impl InternalEvent for WebSocketMessageReceived {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Received message.",
+            count = %self.count,
+            byte_size = %self.byte_size
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", self.count as u64);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[cfg(feature = "sources-websocket")]
+#[derive(Debug)]
+pub struct WebSocketInvalidMessage {
+    pub error: crate::sources::websocket::WebSocketDecodeError,
+    pub byte_size: usize,
+}
+
+#[cfg(feature = "sources-websocket")]
+impl InternalEvent for WebSocketInvalidMessage {
+    fn emit_logs(&self) {
+        error!(
+            message = "Invalid WebSocket message, discarding.",
+            error = %self.error,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("invalid_record_total", 1);
+        counter!("invalid_record_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocketEventSent {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for WebSocketEventSent {
+    fn emit_logs(&self) {
+        trace!(message = "Sent message.", byte_size = %self.byte_size);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
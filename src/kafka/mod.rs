@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 use snafu::Snafu;
 use std::path::{Path, PathBuf};
 
+pub(crate) mod schema_registry;
+
 #[derive(Debug, Snafu)]
 enum KafkaError {
     #[snafu(display("invalid path: {:?}", path))]
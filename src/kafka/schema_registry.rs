@@ -0,0 +1,359 @@
+//! A client for Confluent compatible schema registries, and the Confluent wire
+//! format of Avro encoded Kafka records: a zero magic byte, the big endian id
+//! of the schema in the registry, and the Avro datum.
+//!
+//! The sink only registers and encodes, and the source only decodes.
+#![cfg_attr(
+    not(all(feature = "sinks-kafka", feature = "sources-kafka")),
+    allow(dead_code)
+)]
+
+use crate::{
+    config::ProxyConfig,
+    http::{Auth, HttpClient},
+    tls::{TlsOptions, TlsSettings},
+};
+use http::{Method, Request, StatusCode, Uri};
+use hyper::Body;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+const MAGIC_BYTE: u8 = 0;
+const CONTENT_TYPE: &str = "application/vnd.schemaregistry.v1+json";
+const LATEST_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Snafu)]
+pub(crate) enum SchemaRegistryError {
+    #[snafu(display("invalid schema registry url: {}", source))]
+    InvalidUrl { source: http::uri::InvalidUri },
+    #[snafu(display("schema registry request failed: {}", source))]
+    SendRequest { source: crate::http::HttpError },
+    #[snafu(display("failed to read schema registry response: {}", source))]
+    ReadResponse { source: hyper::Error },
+    #[snafu(display("schema registry responded with {}: {}", status, body))]
+    ErrorResponse { status: StatusCode, body: String },
+    #[snafu(display("invalid schema registry response: {}", source))]
+    ParseResponse { source: serde_json::Error },
+    #[snafu(display("invalid Avro schema: {}", source))]
+    InvalidSchema { source: avro_rs::Error },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct SchemaRegistryConfig {
+    pub url: String,
+    pub auth: Option<Auth>,
+    pub tls: Option<TlsOptions>,
+}
+
+/// A schema, along with its id in the registry.
+#[derive(Debug)]
+pub(crate) struct RegisteredSchema {
+    pub id: u32,
+    pub schema: avro_rs::Schema,
+}
+
+#[derive(Serialize)]
+struct SchemaRequest<'a> {
+    schema: &'a str,
+}
+
+#[derive(Deserialize)]
+struct IdResponse {
+    id: u32,
+}
+
+#[derive(Deserialize)]
+struct SchemaResponse {
+    schema: String,
+}
+
+#[derive(Deserialize)]
+struct SubjectVersionResponse {
+    id: u32,
+    schema: String,
+}
+
+/// Schemas never change once registered, so registrations and lookups by id
+/// are cached for the lifetime of the client. The latest schema of a subject
+/// changes as the subject evolves, so it's only cached for `LATEST_TTL`.
+pub(crate) struct SchemaRegistry {
+    client: HttpClient,
+    url: String,
+    auth: Option<Auth>,
+    latest_ttl: Duration,
+    subjects: Mutex<HashMap<String, Arc<RegisteredSchema>>>,
+    latest: Mutex<HashMap<String, (Instant, Arc<RegisteredSchema>)>>,
+    ids: Mutex<HashMap<u32, Arc<avro_rs::Schema>>>,
+}
+
+impl SchemaRegistry {
+    pub fn new(config: &SchemaRegistryConfig, proxy: &ProxyConfig) -> crate::Result<Self> {
+        config.url.parse::<Uri>().context(InvalidUrl)?;
+        let tls = TlsSettings::from_options(&config.tls)?;
+        let client = HttpClient::new(tls, proxy)?;
+        Ok(Self {
+            client,
+            url: config.url.trim_end_matches('/').to_owned(),
+            auth: config.auth.clone(),
+            latest_ttl: LATEST_TTL,
+            subjects: Mutex::new(HashMap::new()),
+            latest: Mutex::new(HashMap::new()),
+            ids: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Registers `schema` under `subject`, returning the id of the existing
+    /// schema if it was already registered.
+    pub async fn register(
+        &self,
+        subject: &str,
+        schema: &str,
+    ) -> Result<Arc<RegisteredSchema>, SchemaRegistryError> {
+        if let Some(registered) = self.subjects.lock().unwrap().get(subject) {
+            return Ok(Arc::clone(registered));
+        }
+
+        let parsed = avro_rs::Schema::parse_str(schema).context(InvalidSchema)?;
+        let body = serde_json::to_vec(&SchemaRequest { schema }).expect("serializing can't fail");
+        let path = format!("/subjects/{}/versions", subject);
+        let response: IdResponse = self.request(Method::POST, &path, Body::from(body)).await?;
+
+        let registered = Arc::new(RegisteredSchema {
+            id: response.id,
+            schema: parsed,
+        });
+        self.subjects
+            .lock()
+            .unwrap()
+            .insert(subject.to_owned(), Arc::clone(&registered));
+        Ok(registered)
+    }
+
+    /// Fetches the latest version of the schema registered under `subject`.
+    pub async fn latest(
+        &self,
+        subject: &str,
+    ) -> Result<Arc<RegisteredSchema>, SchemaRegistryError> {
+        if let Some((fetched_at, registered)) = self.latest.lock().unwrap().get(subject) {
+            if fetched_at.elapsed() < self.latest_ttl {
+                return Ok(Arc::clone(registered));
+            }
+        }
+
+        let path = format!("/subjects/{}/versions/latest", subject);
+        let response: SubjectVersionResponse =
+            self.request(Method::GET, &path, Body::empty()).await?;
+
+        let registered = Arc::new(RegisteredSchema {
+            id: response.id,
+            schema: avro_rs::Schema::parse_str(&response.schema).context(InvalidSchema)?,
+        });
+        self.latest.lock().unwrap().insert(
+            subject.to_owned(),
+            (Instant::now(), Arc::clone(&registered)),
+        );
+        Ok(registered)
+    }
+
+    /// Drops the cached latest schema of `subject`, so the next lookup fetches
+    /// it from the registry again.
+    pub fn forget_latest(&self, subject: &str) {
+        self.latest.lock().unwrap().remove(subject);
+    }
+
+    /// Fetches the schema with the given id.
+    pub async fn schema(&self, id: u32) -> Result<Arc<avro_rs::Schema>, SchemaRegistryError> {
+        if let Some(schema) = self.ids.lock().unwrap().get(&id) {
+            return Ok(Arc::clone(schema));
+        }
+
+        let path = format!("/schemas/ids/{}", id);
+        let response: SchemaResponse = self.request(Method::GET, &path, Body::empty()).await?;
+
+        let schema = Arc::new(avro_rs::Schema::parse_str(&response.schema).context(InvalidSchema)?);
+        self.ids.lock().unwrap().insert(id, Arc::clone(&schema));
+        Ok(schema)
+    }
+
+    async fn request<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Body,
+    ) -> Result<T, SchemaRegistryError> {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(format!("{}{}", self.url, path))
+            .header("Content-Type", CONTENT_TYPE)
+            .header("Accept", CONTENT_TYPE)
+            .body(body)
+            .expect("building request can't fail");
+        if let Some(auth) = &self.auth {
+            auth.apply(&mut request);
+        }
+
+        let response = self.client.send(request).await.context(SendRequest)?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .context(ReadResponse)?;
+        if !status.is_success() {
+            return Err(SchemaRegistryError::ErrorResponse {
+                status,
+                body: String::from_utf8_lossy(&body).into_owned(),
+            });
+        }
+        serde_json::from_slice(&body).context(ParseResponse)
+    }
+}
+
+/// Prefixes an Avro datum with the id of its schema.
+pub(crate) fn encode_wire_format(id: u32, datum: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(5 + datum.len());
+    bytes.push(MAGIC_BYTE);
+    bytes.extend_from_slice(&id.to_be_bytes());
+    bytes.extend_from_slice(datum);
+    bytes
+}
+
+/// Splits a record in the wire format into the id of its schema and the Avro
+/// datum, or returns `None` if it isn't in the wire format.
+pub(crate) fn decode_wire_format(bytes: &[u8]) -> Option<(u32, &[u8])> {
+    if bytes.len() < 5 || bytes[0] != MAGIC_BYTE {
+        return None;
+    }
+    let id = u32::from_be_bytes(bytes[1..5].try_into().ok()?);
+    Some((id, &bytes[5..]))
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::test_util::next_addr;
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Response, Server,
+    };
+    use std::{
+        convert::Infallible,
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    pub(crate) const SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "event",
+        "fields": [
+            {"name": "message", "type": "string"},
+            {"name": "count", "type": "long"}
+        ]
+    }"#;
+
+    /// Starts a schema registry serving `SCHEMA` with id 42 for any subject.
+    pub(crate) fn mock_registry() -> SchemaRegistryConfig {
+        let addr = next_addr();
+        let make_service = make_service_fn(|_| async {
+            Ok::<_, Infallible>(service_fn(|request: Request<Body>| async move {
+                let body = match (request.method(), request.uri().path()) {
+                    (&Method::POST, path) if path.ends_with("/versions") => {
+                        serde_json::json!({ "id": 42 })
+                    }
+                    (&Method::GET, path) if path.ends_with("/versions/latest") => {
+                        serde_json::json!({ "id": 42, "version": 1, "schema": SCHEMA })
+                    }
+                    (&Method::GET, "/schemas/ids/42") => serde_json::json!({ "schema": SCHEMA }),
+                    _ => {
+                        return Ok::<_, Infallible>(
+                            Response::builder()
+                                .status(StatusCode::NOT_FOUND)
+                                .body(Body::from(r#"{"error_code":40401}"#))
+                                .unwrap(),
+                        )
+                    }
+                };
+                Ok(Response::new(Body::from(body.to_string())))
+            }))
+        });
+        tokio::spawn(Server::bind(&addr).serve(make_service));
+
+        SchemaRegistryConfig {
+            url: format!("http://{}", addr),
+            auth: None,
+            tls: None,
+        }
+    }
+
+    #[test]
+    fn wire_format_round_trip() {
+        let bytes = encode_wire_format(42, b"datum");
+        assert_eq!(&bytes[..5], &[0, 0, 0, 0, 42]);
+        assert_eq!(decode_wire_format(&bytes), Some((42, &b"datum"[..])));
+        assert_eq!(decode_wire_format(b"{\"json\":true}"), None);
+        assert_eq!(decode_wire_format(&[0, 0, 1]), None);
+    }
+
+    #[tokio::test]
+    async fn looks_up_schemas() {
+        let registry = SchemaRegistry::new(&mock_registry(), &ProxyConfig::default()).unwrap();
+
+        let registered = registry.register("topic-value", SCHEMA).await.unwrap();
+        assert_eq!(registered.id, 42);
+
+        let latest = registry.latest("other-value").await.unwrap();
+        assert_eq!(latest.id, 42);
+        assert_eq!(latest.schema, registered.schema);
+
+        assert!(matches!(
+            registry.register("invalid-value", "{").await,
+            Err(SchemaRegistryError::InvalidSchema { .. })
+        ));
+
+        let schema = registry.schema(42).await.unwrap();
+        assert_eq!(*schema, registered.schema);
+
+        assert!(matches!(
+            registry.schema(7).await,
+            Err(SchemaRegistryError::ErrorResponse { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn refetches_latest_schemas() {
+        let addr = next_addr();
+        let version = Arc::new(AtomicU32::new(0));
+        let make_service = make_service_fn(move |_| {
+            let version = Arc::clone(&version);
+            async move {
+                Ok::<_, Infallible>(service_fn(move |_: Request<Body>| {
+                    let id = version.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        let body = serde_json::json!({ "id": id, "version": id, "schema": SCHEMA });
+                        Ok::<_, Infallible>(Response::new(Body::from(body.to_string())))
+                    }
+                }))
+            }
+        });
+        tokio::spawn(Server::bind(&addr).serve(make_service));
+        let config = SchemaRegistryConfig {
+            url: format!("http://{}", addr),
+            auth: None,
+            tls: None,
+        };
+        let mut registry = SchemaRegistry::new(&config, &ProxyConfig::default()).unwrap();
+
+        assert_eq!(registry.latest("topic-value").await.unwrap().id, 0);
+        assert_eq!(registry.latest("topic-value").await.unwrap().id, 0);
+
+        registry.forget_latest("topic-value");
+        assert_eq!(registry.latest("topic-value").await.unwrap().id, 1);
+
+        registry.latest_ttl = Duration::from_secs(0);
+        assert_eq!(registry.latest("topic-value").await.unwrap().id, 2);
+    }
+}
@@ -31,11 +31,15 @@ pub mod generate;
 pub mod wasm;
 #[macro_use]
 pub mod internal_events;
+#[cfg(any(feature = "sources-amqp", feature = "sinks-amqp"))]
+pub(crate) mod amqp;
 #[cfg(feature = "api")]
 pub mod api;
 pub mod app;
 pub mod async_read;
 pub mod buffers;
+#[cfg(feature = "file-source")]
+pub mod checkpoints;
 pub mod encoding_transcode;
 pub mod graph;
 pub mod heartbeat;
@@ -45,6 +49,8 @@ pub mod kafka;
 pub mod kubernetes;
 pub mod line_agg;
 pub mod list;
+#[cfg(any(feature = "sources-mqtt", feature = "sinks-mqtt"))]
+pub(crate) mod mqtt;
 pub(crate) mod pipeline;
 pub(crate) mod proto;
 pub mod providers;
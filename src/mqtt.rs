@@ -0,0 +1,199 @@
+use crate::tls::TlsConfig;
+use rumqttc::{Key, MqttOptions, QoS, TlsConfiguration, Transport};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Snafu)]
+enum MqttError {
+    #[snafu(display("TLS for MQTT requires `tls.ca_file` to be set"))]
+    MissingCaFile,
+    #[snafu(display("`tls.crt_file` and `tls.key_file` must be set together"))]
+    IncompleteClientAuth,
+    #[snafu(display("`user` and `password` must be set together"))]
+    IncompleteCredentials,
+    #[snafu(display("Could not read {:?}: {}", path, source))]
+    ReadFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// Connection settings shared by the `mqtt` source and sink.
+#[derive(Clone, Debug, Derivative, Deserialize, Serialize)]
+#[derivative(Default)]
+pub(crate) struct MqttConnectionConfig {
+    pub host: String,
+    #[serde(default = "default_port")]
+    #[derivative(Default(value = "default_port()"))]
+    pub port: u16,
+    #[serde(default = "default_client_id")]
+    #[derivative(Default(value = "default_client_id()"))]
+    pub client_id: String,
+    #[serde(default = "default_keep_alive_secs")]
+    #[derivative(Default(value = "default_keep_alive_secs()"))]
+    pub keep_alive_secs: u16,
+    #[serde(default = "default_clean_session")]
+    #[derivative(Default(value = "default_clean_session()"))]
+    pub clean_session: bool,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub tls: Option<TlsConfig>,
+}
+
+const fn default_port() -> u16 {
+    1883
+}
+
+fn default_client_id() -> String {
+    "vector".to_owned()
+}
+
+const fn default_keep_alive_secs() -> u16 {
+    60
+}
+
+const fn default_clean_session() -> bool {
+    true
+}
+
+impl MqttConnectionConfig {
+    pub(crate) fn options(&self) -> crate::Result<MqttOptions> {
+        let mut options = MqttOptions::new(&self.client_id, &self.host, self.port);
+        options.set_keep_alive(self.keep_alive_secs);
+        options.set_clean_session(self.clean_session);
+
+        match (&self.user, &self.password) {
+            (Some(user), Some(password)) => {
+                options.set_credentials(user, password);
+            }
+            (None, None) => (),
+            _ => return Err(MqttError::IncompleteCredentials.into()),
+        }
+
+        let tls_enabled = self
+            .tls
+            .as_ref()
+            .and_then(|tls| tls.enabled)
+            .unwrap_or(false);
+        if tls_enabled {
+            let tls = &self.tls.as_ref().unwrap().options;
+            let ca = read_file(tls.ca_file.as_ref().ok_or(MqttError::MissingCaFile)?)?;
+            let client_auth = match (&tls.crt_file, &tls.key_file) {
+                (Some(crt_file), Some(key_file)) => {
+                    let key = read_file(key_file)?;
+                    // rustls needs to know the kind of key up front.
+                    let key = if String::from_utf8_lossy(&key).contains("BEGIN EC PRIVATE KEY") {
+                        Key::ECC(key)
+                    } else {
+                        Key::RSA(key)
+                    };
+                    Some((read_file(crt_file)?, key))
+                }
+                (None, None) => None,
+                _ => return Err(MqttError::IncompleteClientAuth.into()),
+            };
+            options.set_transport(Transport::Tls(TlsConfiguration::Simple {
+                ca,
+                alpn: None,
+                client_auth,
+            }));
+        }
+
+        Ok(options)
+    }
+}
+
+fn read_file(path: &Path) -> crate::Result<Vec<u8>> {
+    Ok(std::fs::read(path).with_context(|| ReadFile { path })?)
+}
+
+/// How long to wait before polling the event loop again after a connection
+/// error, as `rumqttc` reconnects on the next poll.
+pub(crate) const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// The MQTT quality of service levels supported by Vector. `2` (exactly once)
+/// is not supported.
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(try_from = "u8", into = "u8")]
+pub(crate) enum MqttQos {
+    #[derivative(Default)]
+    AtMostOnce,
+    AtLeastOnce,
+}
+
+impl TryFrom<u8> for MqttQos {
+    type Error = String;
+
+    fn try_from(qos: u8) -> Result<Self, Self::Error> {
+        match qos {
+            0 => Ok(Self::AtMostOnce),
+            1 => Ok(Self::AtLeastOnce),
+            _ => Err(format!("unsupported MQTT QoS {}, expected 0 or 1", qos)),
+        }
+    }
+}
+
+impl From<MqttQos> for u8 {
+    fn from(qos: MqttQos) -> Self {
+        match qos {
+            MqttQos::AtMostOnce => 0,
+            MqttQos::AtLeastOnce => 1,
+        }
+    }
+}
+
+impl From<MqttQos> for QoS {
+    fn from(qos: MqttQos) -> Self {
+        match qos {
+            MqttQos::AtMostOnce => QoS::AtMostOnce,
+            MqttQos::AtLeastOnce => QoS::AtLeastOnce,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Config {
+        qos: MqttQos,
+    }
+
+    #[test]
+    fn parse_qos() {
+        let config: Config = toml::from_str("qos = 1").unwrap();
+        assert_eq!(config.qos, MqttQos::AtLeastOnce);
+        assert!(toml::from_str::<Config>("qos = 2").is_err());
+    }
+
+    #[test]
+    fn tls_requires_ca_file() {
+        let config = MqttConnectionConfig {
+            host: "localhost".into(),
+            tls: Some(TlsConfig::enabled()),
+            ..Default::default()
+        };
+        assert!(config.options().is_err());
+    }
+
+    #[test]
+    fn credentials_require_user_and_password() {
+        let config = MqttConnectionConfig {
+            host: "localhost".into(),
+            user: Some("vector".into()),
+            ..Default::default()
+        };
+        assert!(config.options().is_err());
+
+        let config = MqttConnectionConfig {
+            password: Some("secret".into()),
+            ..config
+        };
+        assert!(config.options().is_ok());
+    }
+}
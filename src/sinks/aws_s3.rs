@@ -8,6 +8,7 @@ use crate::{
     serde::to_string,
     sinks::util::{
         batch::{BatchConfig, BatchSettings},
+        buffer::parquet::{self, ParquetBuffer, ParquetConfig, ParquetService},
         encoding::{EncodingConfig, EncodingConfiguration},
         retries::RetryLogic,
         sink::Response,
@@ -54,6 +55,11 @@ pub struct S3SinkConfig {
     #[serde(flatten)]
     pub region: RegionOrEndpoint,
     pub encoding: EncodingConfig<Encoding>,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub parquet: ParquetConfig,
     #[serde(default = "Compression::gzip_default")]
     pub compression: Compression,
     #[serde(default)]
@@ -123,6 +129,7 @@ enum S3StorageClass {
 pub enum Encoding {
     Text,
     Ndjson,
+    Parquet,
 }
 
 inventory::submit! {
@@ -140,6 +147,7 @@ impl GenerateConfig for S3SinkConfig {
             options: S3Options::default(),
             region: RegionOrEndpoint::default(),
             encoding: Encoding::Text.into(),
+            parquet: ParquetConfig::default(),
             compression: Compression::gzip_default(),
             batch: BatchConfig::default(),
             request: TowerRequestConfig::default(),
@@ -191,8 +199,14 @@ impl S3SinkConfig {
         });
 
         let encoding = self.encoding.clone();
+        let is_parquet = *encoding.codec() == Encoding::Parquet;
 
-        let compression = self.compression;
+        // Parquet files are compressed page by page instead.
+        let compression = if is_parquet {
+            Compression::None
+        } else {
+            self.compression
+        };
         let filename_time_format = self
             .filename_time_format
             .clone()
@@ -208,9 +222,15 @@ impl S3SinkConfig {
 
         let s3 = S3Sink { client };
 
-        let filename_extension = self.filename_extension.clone();
+        let filename_extension = self
+            .filename_extension
+            .clone()
+            .or_else(|| is_parquet.then(|| "parquet".into()));
         let bucket = self.bucket.clone();
-        let options = self.options.clone();
+        let mut options = self.options.clone();
+        if is_parquet && options.content_type.is_none() {
+            options.content_type = Some("application/octet-stream".into());
+        }
 
         let svc = ServiceBuilder::new()
             .map(move |req| {
@@ -227,7 +247,27 @@ impl S3SinkConfig {
             .settings(request, S3RetryLogic)
             .service(s3);
 
-        let buffer = PartitionBuffer::new(Buffer::new(batch.size, self.compression));
+        if is_parquet {
+            let buffer =
+                PartitionBuffer::new(ParquetBuffer::new(batch.into().size, &self.parquet)?);
+            let svc = ParquetService::new(svc);
+
+            let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
+                .with_flat_map(move |e| {
+                    stream::iter(parquet::encode_event(
+                        e,
+                        &key_prefix,
+                        "key_prefix",
+                        &encoding,
+                    ))
+                    .map(Ok)
+                })
+                .sink_map_err(|error| error!(message = "Sink failed to flush.", %error));
+
+            return Ok(super::VectorSink::Sink(Box::new(sink)));
+        }
+
+        let buffer = PartitionBuffer::new(Buffer::new(batch.size, compression));
 
         let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
             .with_flat_map(move |e| stream::iter(encode_event(e, &key_prefix, &encoding)).map(Ok))
@@ -427,6 +467,7 @@ fn encode_event(
             bytes.push(b'\n');
             bytes
         }
+        Encoding::Parquet => unreachable!("Parquet events are encoded by the buffer"),
     };
 
     Some(EncodedEvent {
@@ -584,7 +625,7 @@ mod integration_tests {
     use futures::Stream;
     use pretty_assertions::assert_eq;
     use rusoto_core::region::Region;
-    use std::io::{BufRead, BufReader};
+    use std::io::{BufRead, BufReader, Read};
     use vector_core::event::{BatchNotifier, BatchStatus, BatchStatusReceiver, LogEvent};
 
     #[tokio::test]
@@ -705,6 +746,62 @@ mod integration_tests {
         assert_eq!(lines, response_lines.await);
     }
 
+    #[tokio::test]
+    async fn s3_parquet() {
+        use ::parquet::{
+            file::reader::{FileReader, SerializedFileReader},
+            record::RowAccessor,
+            util::cursor::SliceableCursor,
+        };
+
+        let cx = SinkContext::new_test();
+
+        let bucket = uuid::Uuid::new_v4().to_string();
+
+        create_bucket(&bucket, false).await;
+
+        let config = S3SinkConfig {
+            encoding: Encoding::Parquet.into(),
+            compression: Compression::gzip_default(),
+            ..config(&bucket, 1000000)
+        };
+        let prefix = config.key_prefix.clone();
+        let client = config.create_client(&cx.globals.proxy).unwrap();
+        let sink = config.new(client, cx).unwrap();
+
+        let (lines, events, mut receiver) = make_events_batch(100, 10);
+        sink.run(events).await.unwrap();
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+
+        let keys = get_keys(&bucket, prefix.unwrap()).await;
+        assert_eq!(keys.len(), 1);
+        assert!(keys[0].ends_with(".parquet"));
+
+        let obj = get_object(&bucket, keys[0].clone()).await;
+        assert_eq!(obj.content_encoding, Some("identity".to_string()));
+        assert_eq!(
+            obj.content_type,
+            Some("application/octet-stream".to_string())
+        );
+
+        let mut body = Vec::new();
+        get_object_output_body(obj)
+            .await
+            .read_to_end(&mut body)
+            .unwrap();
+        let reader = SerializedFileReader::new(SliceableCursor::new(body)).unwrap();
+        let schema = reader.metadata().file_metadata().schema_descr();
+        let message = (0..schema.num_columns())
+            .position(|i| schema.column(i).name() == log_schema().message_key())
+            .unwrap();
+        let response_lines = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.get_string(message).unwrap().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(lines, response_lines);
+    }
+
     // NOTE: this test doesn't actually validate anything because localstack doesn't enforce the
     // required Content-MD5 header on the request for buckets with object lock enabled
     // https://github.com/localstack/localstack/issues/4166
@@ -827,6 +924,7 @@ mod integration_tests {
             options: S3Options::default(),
             region: RegionOrEndpoint::with_endpoint("http://localhost:4566".to_owned()),
             encoding: Encoding::Text.into(),
+            parquet: ParquetConfig::default(),
             compression: Compression::None,
             batch: BatchConfig {
                 max_bytes: Some(batch_size),
@@ -7,6 +7,7 @@ use crate::{
     },
     sinks::{
         util::{
+            buffer::parquet::{self, ParquetBuffer, ParquetConfig, ParquetService},
             encoding::{EncodingConfig, EncodingConfiguration},
             retries::RetryLogic,
             sink::Response,
@@ -54,6 +55,11 @@ pub struct AzureBlobSinkConfig {
     pub blob_time_format: Option<String>,
     pub blob_append_uuid: Option<bool>,
     pub encoding: EncodingConfig<Encoding>,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub parquet: ParquetConfig,
     #[serde(default = "Compression::gzip_default")]
     pub compression: Compression,
     #[serde(default)]
@@ -79,6 +85,7 @@ struct AzureBlobSinkRequest {
 pub enum Encoding {
     Ndjson,
     Text,
+    Parquet,
 }
 
 #[derive(Debug, Snafu)]
@@ -100,6 +107,7 @@ impl GenerateConfig for AzureBlobSinkConfig {
             blob_time_format: Some(String::from("%s")),
             blob_append_uuid: Some(true),
             encoding: Encoding::Ndjson.into(),
+            parquet: ParquetConfig::default(),
             compression: Compression::gzip_default(),
             batch: BatchConfig::default(),
             request: TowerRequestConfig::default(),
@@ -138,7 +146,13 @@ impl AzureBlobSinkConfig {
             .bytes(10 * 1024 * 1024)
             .timeout(300)
             .parse_config(self.batch)?;
-        let compression = self.compression;
+        let encoding = self.encoding.clone();
+        let codec = encoding.codec().clone();
+        // Parquet files are compressed page by page instead.
+        let compression = match codec {
+            Encoding::Parquet => Compression::None,
+            _ => self.compression,
+        };
         let container_name = self.container_name.clone();
         let blob_time_format = self.blob_time_format.clone().unwrap_or_else(|| "%s".into());
         let blob_append_uuid = self.blob_append_uuid.unwrap_or(true);
@@ -147,6 +161,7 @@ impl AzureBlobSinkConfig {
             .map(move |partition| {
                 build_request(
                     partition,
+                    &codec,
                     compression,
                     container_name.clone(),
                     blob_time_format.clone(),
@@ -156,9 +171,28 @@ impl AzureBlobSinkConfig {
             .settings(request, AzureBlobRetryLogic)
             .service(blob);
 
-        let encoding = self.encoding.clone();
         let blob_prefix = self.blob_prefix.as_deref().unwrap_or("blob/%F/");
         let blob_prefix = Template::try_from(blob_prefix)?;
+
+        if *encoding.codec() == Encoding::Parquet {
+            let buffer =
+                PartitionBuffer::new(ParquetBuffer::new(batch.into().size, &self.parquet)?);
+            let svc = ParquetService::new(svc);
+            let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
+                .with_flat_map(move |event| {
+                    stream::iter(parquet::encode_event(
+                        event,
+                        &blob_prefix,
+                        "blob_prefix",
+                        &encoding,
+                    ))
+                    .map(Ok)
+                })
+                .sink_map_err(|error| error!(message = "Sink failed to flush.", %error));
+
+            return Ok(super::VectorSink::Sink(Box::new(sink)));
+        }
+
         let buffer = PartitionBuffer::new(Buffer::new(batch.size, compression));
         let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
             .with_flat_map(move |event| {
@@ -305,6 +339,7 @@ fn encode_event(
             bytes.push(b'\n');
             bytes
         }
+        Encoding::Parquet => unreachable!("Parquet events are encoded by the buffer"),
     };
 
     Some(EncodedEvent::new(PartitionInnerBuffer::new(
@@ -315,6 +350,7 @@ fn encode_event(
 
 fn build_request(
     partition: PartitionInnerBuffer<Vec<u8>, Bytes>,
+    codec: &Encoding,
     compression: Compression,
     container_name: String,
     blob_time_format: String,
@@ -331,8 +367,12 @@ fn build_request(
             time_format.to_string()
         }
     };
+    let (extension, content_type) = match codec {
+        Encoding::Parquet => ("parquet", "application/octet-stream"),
+        _ => (compression.extension(), compression.content_type()),
+    };
     let blob = String::from_utf8_lossy(&key[..]).into_owned();
-    let blob = format!("{}{}.{}", blob, filename, compression.extension());
+    let blob = format!("{}{}.{}", blob, filename, extension);
 
     debug!(
         message = "Sending events.",
@@ -346,7 +386,7 @@ fn build_request(
         blob_data: inner,
         blob_name: blob,
         content_encoding: compression.content_encoding(),
-        content_type: compression.content_type(),
+        content_type,
     }
 }
 
@@ -435,6 +475,7 @@ mod tests {
 
         let request = build_request(
             partition,
+            &Encoding::Text,
             compression,
             container_name,
             blob_time_format,
@@ -457,6 +498,7 @@ mod tests {
 
         let request = build_request(
             partition,
+            &Encoding::Text,
             compression,
             container_name,
             blob_time_format,
@@ -479,6 +521,7 @@ mod tests {
 
         let request = build_request(
             partition,
+            &Encoding::Text,
             compression,
             container_name,
             blob_time_format,
@@ -504,6 +547,7 @@ mod tests {
 
         let request = build_request(
             partition,
+            &Encoding::Text,
             compression,
             container_name,
             blob_time_format,
@@ -515,6 +559,28 @@ mod tests {
         assert_eq!(request.content_encoding, None);
         assert_eq!(request.content_type, "text/plain");
     }
+
+    #[test]
+    fn azure_blob_build_request_with_parquet() {
+        let partition = PartitionInnerBuffer::new(vec![0u8; 10], Bytes::from("blob"));
+        let compression = Compression::None;
+        let container_name = String::from("logs");
+        let blob_time_format = String::from("");
+        let blob_append_uuid = false;
+
+        let request = build_request(
+            partition,
+            &Encoding::Parquet,
+            compression,
+            container_name,
+            blob_time_format,
+            blob_append_uuid,
+        );
+
+        assert_eq!(request.blob_name, "blob.parquet".to_string());
+        assert_eq!(request.content_encoding, None);
+        assert_eq!(request.content_type, "application/octet-stream");
+    }
 }
 
 #[cfg(feature = "azure-blob-integration-tests")]
@@ -709,6 +775,7 @@ mod integration_tests {
                 blob_time_format: None,
                 blob_append_uuid: None,
                 encoding: Encoding::Text.into(),
+                parquet: Default::default(),
                 compression: Compression::None,
                 batch: Default::default(),
                 request: TowerRequestConfig::default(),
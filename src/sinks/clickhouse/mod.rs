@@ -1,21 +1,39 @@
+mod row_binary;
+
+use self::row_binary::{Schema, SchemaError};
 use crate::{
     config::{DataType, SinkConfig, SinkContext, SinkDescription},
+    emit,
     event::Event,
     http::{Auth, HttpClient, HttpError, MaybeAuth},
+    internal_events::{ClickhouseFieldCoercionFailed, ClickhouseSchemaFetchFailed},
     sinks::util::{
+        buffer::GZIP_FAST,
         encoding::{EncodingConfigWithDefault, EncodingConfiguration},
         http::{BatchedHttpSink, HttpRetryLogic, HttpSink},
-        retries::{RetryAction, RetryLogic},
-        sink, BatchConfig, BatchSettings, Buffer, Compression, TowerRequestConfig, UriSerde,
+        retries::{ExponentialBackoff, RetryAction, RetryLogic},
+        sink, BatchConfig, BatchSettings, Buffer, Compression, EncodedLength, TowerRequestConfig,
+        UriSerde, VecBuffer,
     },
     tls::{TlsOptions, TlsSettings},
 };
 use bytes::Bytes;
+use flate2::write::GzEncoder;
 use futures::{FutureExt, SinkExt};
 use http::{Request, StatusCode, Uri};
 use hyper::Body;
 use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::Mutex;
+use vector_core::{event::LogEvent, ByteSizeOf};
 
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields)]
@@ -54,6 +72,7 @@ impl_generate_config_from_default!(ClickhouseConfig);
 pub enum Encoding {
     #[derivative(Default)]
     Default,
+    RowBinary,
 }
 
 #[async_trait::async_trait]
@@ -63,10 +82,6 @@ impl SinkConfig for ClickhouseConfig {
         &self,
         cx: SinkContext,
     ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
-        let batch = BatchSettings::default()
-            .bytes(bytesize::mib(10u64))
-            .timeout(1)
-            .parse_config(self.batch)?;
         let request = self.request.unwrap_with(&TowerRequestConfig::default());
         let tls_settings = TlsSettings::from_options(&self.tls)?;
         let client = HttpClient::new(tls_settings, &cx.proxy)?;
@@ -75,22 +90,60 @@ impl SinkConfig for ClickhouseConfig {
             auth: self.auth.choose_one(&self.endpoint.auth)?,
             ..self.clone()
         };
-
-        let sink = BatchedHttpSink::with_logic(
-            config.clone(),
-            Buffer::new(batch.size, self.compression),
-            ClickhouseRetryLogic::default(),
-            request,
-            batch.timeout,
-            client.clone(),
-            cx.acker(),
-            sink::StdServiceLogic::default(),
-        )
-        .sink_map_err(|error| error!(message = "Fatal clickhouse sink error.", %error));
+        let sink = match config.encoding.codec() {
+            Encoding::Default => {
+                let batch = BatchSettings::default()
+                    .bytes(bytesize::mib(10u64))
+                    .timeout(1)
+                    .parse_config(self.batch)?;
+                let sink = BatchedHttpSink::with_logic(
+                    ClickhouseSink {
+                        config: config.clone(),
+                    },
+                    Buffer::new(batch.size, self.compression),
+                    ClickhouseRetryLogic::default(),
+                    request,
+                    batch.timeout,
+                    client.clone(),
+                    cx.acker(),
+                    sink::StdServiceLogic::default(),
+                )
+                .sink_map_err(|error| error!(message = "Fatal clickhouse sink error.", %error));
+                super::VectorSink::Sink(Box::new(sink))
+            }
+            Encoding::RowBinary => {
+                // The rows are encoded with the types of the columns of the
+                // table, which are only described once requests are sent.
+                let batch = BatchSettings::default()
+                    .bytes(bytesize::mib(10u64))
+                    .timeout(1)
+                    .parse_config(self.batch)?;
+                let schema = Arc::new(SchemaCache::default());
+                let sink = BatchedHttpSink::with_logic(
+                    RowBinarySink {
+                        config: config.clone(),
+                        client: client.clone(),
+                        schema: Arc::clone(&schema),
+                    },
+                    VecBuffer::new(batch.size),
+                    ClickhouseRetryLogic {
+                        schema: Some(schema),
+                        ..Default::default()
+                    },
+                    request,
+                    batch.timeout,
+                    client.clone(),
+                    cx.acker(),
+                    sink::StdServiceLogic::default(),
+                )
+                .sink_map_err(|error| error!(message = "Fatal clickhouse sink error.", %error));
+                super::VectorSink::Sink(Box::new(sink))
+            }
+        };
 
         let healthcheck = healthcheck(client, config).boxed();
 
-        Ok((super::VectorSink::Sink(Box::new(sink)), healthcheck))
+        Ok((sink, healthcheck))
     }
 
     fn input_type(&self) -> DataType {
@@ -102,13 +155,23 @@ impl SinkConfig for ClickhouseConfig {
     }
 }
 
+impl ClickhouseConfig {
+    fn database(&self) -> &str {
+        self.database.as_deref().unwrap_or("default")
+    }
+}
+
+struct ClickhouseSink {
+    config: ClickhouseConfig,
+}
+
 #[async_trait::async_trait]
-impl HttpSink for ClickhouseConfig {
+impl HttpSink for ClickhouseSink {
     type Input = Vec<u8>;
     type Output = Vec<u8>;
 
     fn encode_event(&self, mut event: Event) -> Option<Self::Input> {
-        self.encoding.apply_rules(&mut event);
+        self.config.encoding.apply_rules(&mut event);
         let log = event.into_log();
 
         let mut body = serde_json::to_vec(&log).expect("Events should be valid json!");
@@ -118,38 +181,139 @@ impl HttpSink for ClickhouseConfig {
     }
 
     async fn build_request(&self, events: Self::Output) -> crate::Result<http::Request<Vec<u8>>> {
-        let database = if let Some(database) = &self.database {
-            database.as_str()
-        } else {
-            "default"
-        };
+        Ok(insert_request(&self.config, None, events))
+    }
+}
 
-        let uri = set_uri_query(
-            &self.endpoint.uri,
-            database,
-            &self.table,
-            self.skip_unknown_fields,
-        )
-        .expect("Unable to encode uri");
+/// A log waiting to be encoded as a row, once the schema of the table is
+/// known.
+#[derive(Clone, Debug)]
+struct Row(LogEvent);
+
+impl EncodedLength for Row {
+    fn encoded_length(&self) -> usize {
+        self.0.size_of()
+    }
+}
+
+struct RowBinarySink {
+    config: ClickhouseConfig,
+    client: HttpClient,
+    schema: Arc<SchemaCache>,
+}
 
-        let mut builder = Request::post(&uri).header("Content-Type", "application/x-ndjson");
+#[async_trait::async_trait]
+impl HttpSink for RowBinarySink {
+    type Input = Row;
+    type Output = Vec<Row>;
+
+    fn encode_event(&self, mut event: Event) -> Option<Self::Input> {
+        self.config.encoding.apply_rules(&mut event);
+        Some(Row(event.into_log()))
+    }
+
+    async fn build_request(&self, rows: Self::Output) -> crate::Result<http::Request<Vec<u8>>> {
+        let schema = self.schema.get(&self.client, &self.config).await?;
+
+        let mut body = Vec::new();
+        for Row(log) in &rows {
+            match schema.encode(log) {
+                Ok(row) => body.extend_from_slice(&row),
+                Err(error) => emit!(ClickhouseFieldCoercionFailed {
+                    field: &error.column.name,
+                    column_type: &error.column.type_name,
+                    error: error.reason,
+                }),
+            }
+        }
 
-        if let Some(ce) = self.compression.content_encoding() {
-            builder = builder.header("Content-Encoding", ce);
+        if let Compression::Gzip(level) = self.config.compression {
+            let level = level.unwrap_or(GZIP_FAST) as u32;
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::new(level));
+            encoder.write_all(&body).expect("Writing to Vec can't fail");
+            body = encoder.finish().expect("Writing to Vec can't fail");
         }
 
-        let mut request = builder.body(events).unwrap();
+        Ok(insert_request(&self.config, Some(&schema), body))
+    }
+}
+
+fn insert_request(
+    config: &ClickhouseConfig,
+    schema: Option<&Schema>,
+    body: Vec<u8>,
+) -> http::Request<Vec<u8>> {
+    let uri = set_uri_query(
+        &config.endpoint.uri,
+        config.database(),
+        &config.table,
+        config.skip_unknown_fields,
+        schema,
+    )
+    .expect("Unable to encode uri");
+
+    let content_type = match schema {
+        Some(_) => "application/octet-stream",
+        None => "application/x-ndjson",
+    };
+    let mut builder = Request::post(&uri).header("Content-Type", content_type);
 
-        if let Some(auth) = &self.auth {
-            auth.apply(&mut request);
+    if let Some(ce) = config.compression.content_encoding() {
+        builder = builder.header("Content-Encoding", ce);
+    }
+
+    let mut request = builder.body(body).unwrap();
+
+    if let Some(auth) = &config.auth {
+        auth.apply(&mut request);
+    }
+
+    request
+}
+
+/// The schema of the table, described on the first request and described
+/// again once an insert failed because of a mismatch with the table.
+#[derive(Debug, Default)]
+struct SchemaCache {
+    schema: Mutex<Option<Arc<Schema>>>,
+    stale: AtomicBool,
+}
+
+impl SchemaCache {
+    async fn get(
+        &self,
+        client: &HttpClient,
+        config: &ClickhouseConfig,
+    ) -> crate::Result<Arc<Schema>> {
+        let mut schema = self.schema.lock().await;
+        let stale = self.stale.swap(false, Ordering::AcqRel);
+        match schema.as_ref() {
+            Some(current) if !stale => return Ok(Arc::clone(current)),
+            _ => (),
         }
 
-        Ok(request)
+        let fetched = fetch_schema_with_retry(client, config).await?;
+        if stale && schema.as_deref() == Some(&fetched) {
+            // The rows don't match the table for another reason, so the
+            // request would keep failing.
+            return Err("Insert failed although the table didn't change".into());
+        }
+        let fetched = Arc::new(fetched);
+        *schema = Some(Arc::clone(&fetched));
+        Ok(fetched)
+    }
+
+    fn invalidate(&self) {
+        self.stale.store(true, Ordering::Release);
     }
 }
 
 async fn healthcheck(client: HttpClient, config: ClickhouseConfig) -> crate::Result<()> {
-    // TODO: check if table exists?
+    if *config.encoding.codec() == Encoding::RowBinary {
+        // Checks that the table exists, with columns of supported types.
+        return fetch_schema(&client, &config).await.map(|_| ());
+    }
+
     let uri = format!("{}/?query=SELECT%201", config.endpoint);
     let mut request = Request::get(uri).body(Body::empty()).unwrap();
 
@@ -165,18 +329,88 @@ async fn healthcheck(client: HttpClient, config: ClickhouseConfig) -> crate::Res
     }
 }
 
-fn set_uri_query(uri: &Uri, database: &str, table: &str, skip_unknown: bool) -> crate::Result<Uri> {
+async fn fetch_schema(client: &HttpClient, config: &ClickhouseConfig) -> crate::Result<Schema> {
     let query = url::form_urlencoded::Serializer::new(String::new())
         .append_pair(
             "query",
-            format!(
-                "INSERT INTO \"{}\".\"{}\" FORMAT JSONEachRow",
-                database,
-                table.replace("\"", "\\\"")
-            )
-            .as_str(),
+            &format!(
+                "DESCRIBE TABLE {} FORMAT JSONEachRow",
+                table_name(config.database(), &config.table)
+            ),
         )
         .finish();
+    let uri = format!("{}/?{}", config.endpoint, query);
+    let mut request = Request::get(uri).body(Body::empty()).unwrap();
+
+    if let Some(auth) = &config.auth {
+        auth.apply(&mut request);
+    }
+
+    let response = client.send(request).await?;
+    let status = response.status();
+    let body = hyper::body::to_bytes(response.into_body()).await?;
+
+    match status {
+        StatusCode::OK => Ok(Schema::parse(&body)?),
+        status => Err(format!(
+            "Unexpected status {} describing the table: {}",
+            status,
+            String::from_utf8_lossy(&body)
+        )
+        .into()),
+    }
+}
+
+/// Describes the table until it succeeds, unless the table has columns of
+/// types that aren't supported.
+async fn fetch_schema_with_retry(
+    client: &HttpClient,
+    config: &ClickhouseConfig,
+) -> crate::Result<Schema> {
+    let mut backoff = ExponentialBackoff::from_millis(2)
+        .factor(250)
+        .max_delay(Duration::from_secs(60));
+    loop {
+        match fetch_schema(client, config).await {
+            Ok(schema) => return Ok(schema),
+            Err(error) if error.is::<SchemaError>() => return Err(error),
+            Err(error) => {
+                emit!(ClickhouseSchemaFetchFailed { error });
+                tokio::time::sleep(backoff.next().unwrap()).await;
+            }
+        }
+    }
+}
+
+fn table_name(database: &str, table: &str) -> String {
+    format!("\"{}\".\"{}\"", database, table.replace("\"", "\\\""))
+}
+
+fn set_uri_query(
+    uri: &Uri,
+    database: &str,
+    table: &str,
+    skip_unknown: bool,
+    schema: Option<&Schema>,
+) -> crate::Result<Uri> {
+    let insert = match schema {
+        Some(schema) => format!(
+            "INSERT INTO {} ({}) FORMAT RowBinary",
+            table_name(database, table),
+            schema
+                .column_names()
+                .map(|name| format!("\"{}\"", name.replace("\"", "\\\"")))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        None => format!(
+            "INSERT INTO {} FORMAT JSONEachRow",
+            table_name(database, table)
+        ),
+    };
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("query", &insert)
+        .finish();
 
     let mut uri = uri.to_string();
     if !uri.ends_with('/') {
@@ -196,6 +430,9 @@ fn set_uri_query(uri: &Uri, database: &str, table: &str, skip_unknown: bool) ->
 #[derive(Debug, Default, Clone)]
 struct ClickhouseRetryLogic {
     inner: HttpRetryLogic,
+    /// Set with the `row_binary` encoding, whose rows are encoded again with
+    /// the new schema of the table when it changed.
+    schema: Option<Arc<SchemaCache>>,
 }
 
 impl RetryLogic for ClickhouseRetryLogic {
@@ -219,12 +456,18 @@ impl RetryLogic for ClickhouseRetryLogic {
                 // Error code definitions: https://github.com/ClickHouse/ClickHouse/blob/master/dbms/src/Common/ErrorCodes.cpp
                 //
                 // Fix already merged: https://github.com/ClickHouse/ClickHouse/pull/6271
-                if body.starts_with(b"Code: 117") {
-                    RetryAction::DontRetry("incorrect data".into())
-                } else if body.starts_with(b"Code: 53") {
-                    RetryAction::DontRetry("type mismatch".into())
-                } else {
-                    RetryAction::Retry(String::from_utf8_lossy(body).to_string())
+                match (error_code(body), &self.schema) {
+                    // Unknown columns, or rows that can't be read as the types
+                    // of the columns, once the table changed.
+                    (Some(16), Some(schema))
+                    | (Some(33), Some(schema))
+                    | (Some(53), Some(schema)) => {
+                        schema.invalidate();
+                        RetryAction::Retry("schema mismatch".into())
+                    }
+                    (Some(117), _) => RetryAction::DontRetry("incorrect data".into()),
+                    (Some(53), None) => RetryAction::DontRetry("type mismatch".into()),
+                    _ => RetryAction::Retry(String::from_utf8_lossy(body).to_string()),
                 }
             }
             _ => self.inner.should_retry_response(response),
@@ -232,6 +475,13 @@ impl RetryLogic for ClickhouseRetryLogic {
     }
 }
 
+/// The code of a ClickHouse error, from a body starting with `Code: {code_num}`.
+fn error_code(body: &[u8]) -> Option<u32> {
+    let code = body.strip_prefix(b"Code: ")?;
+    let digits = code.iter().take_while(|byte| byte.is_ascii_digit()).count();
+    std::str::from_utf8(&code[..digits]).ok()?.parse().ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -248,6 +498,7 @@ mod tests {
             "my_database",
             "my_table",
             false,
+            None,
         )
         .unwrap();
         assert_eq!(uri.to_string(), "http://localhost:80/?input_format_import_nested_json=1&query=INSERT+INTO+%22my_database%22.%22my_table%22+FORMAT+JSONEachRow");
@@ -257,11 +508,69 @@ mod tests {
             "my_database",
             "my_\"table\"",
             false,
+            None,
         )
         .unwrap();
         assert_eq!(uri.to_string(), "http://localhost:80/?input_format_import_nested_json=1&query=INSERT+INTO+%22my_database%22.%22my_%5C%22table%5C%22%22+FORMAT+JSONEachRow");
     }
 
+    #[test]
+    fn encode_valid_row_binary() {
+        let schema = Schema::parse(
+            concat!(
+                "{\"name\":\"message\",\"type\":\"String\"}\n",
+                "{\"name\":\"timestamp\",\"type\":\"DateTime64(3)\"}\n",
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+        let uri = set_uri_query(
+            &"http://localhost:80".parse().unwrap(),
+            "my_database",
+            "my_table",
+            true,
+            Some(&schema),
+        )
+        .unwrap();
+        assert_eq!(uri.to_string(), "http://localhost:80/?input_format_import_nested_json=1&input_format_skip_unknown_fields=1&query=INSERT+INTO+%22my_database%22.%22my_table%22+%28%22message%22%2C+%22timestamp%22%29+FORMAT+RowBinary");
+    }
+
+    #[test]
+    fn retries_schema_mismatches_with_row_binary() {
+        let response = |body: &'static str| {
+            http::Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Bytes::from(body))
+                .unwrap()
+        };
+        let mismatch = response("Code: 53. DB::Exception: Type mismatch");
+
+        let retry_logic = ClickhouseRetryLogic::default();
+        assert!(matches!(
+            retry_logic.should_retry_response(&mismatch),
+            RetryAction::DontRetry(_)
+        ));
+
+        let schema = Arc::new(SchemaCache::default());
+        let retry_logic = ClickhouseRetryLogic {
+            schema: Some(Arc::clone(&schema)),
+            ..Default::default()
+        };
+        assert!(matches!(
+            retry_logic.should_retry_response(&mismatch),
+            RetryAction::Retry(_)
+        ));
+        assert!(schema.stale.load(Ordering::Acquire));
+        assert!(matches!(
+            retry_logic.should_retry_response(&response("Code: 117. DB::Exception")),
+            RetryAction::DontRetry(_)
+        ));
+        assert!(matches!(
+            retry_logic.should_retry_response(&response("Code: 160. DB::Exception")),
+            RetryAction::Retry(_)
+        ));
+    }
+
     #[test]
     fn encode_invalid() {
         set_uri_query(
@@ -269,6 +578,7 @@ mod tests {
             "my_database",
             "my_table",
             false,
+            None,
         )
         .unwrap_err();
     }
@@ -518,6 +828,121 @@ timestamp_format = "unix""#,
         assert_eq!(expected, output.data[0]);
     }
 
+    #[tokio::test]
+    async fn insert_events_row_binary() {
+        trace_init();
+
+        let table = gen_table();
+        let host = String::from("http://localhost:8123");
+        let encoding = EncodingConfigWithDefault {
+            codec: Encoding::RowBinary,
+            ..Default::default()
+        };
+
+        let config = ClickhouseConfig {
+            endpoint: host.parse().unwrap(),
+            table: table.clone(),
+            compression: Compression::None,
+            encoding,
+            batch: BatchConfig {
+                max_events: Some(1),
+                ..Default::default()
+            },
+            request: TowerRequestConfig {
+                retry_attempts: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let client = ClickhouseClient::new(host);
+        client
+            .create_table(
+                &table,
+                "host LowCardinality(String), timestamp DateTime64(3, 'UTC'), message String, \
+                 count UInt16, items Array(String), missing Nullable(String)",
+            )
+            .await;
+
+        let (sink, hc) = config.build(SinkContext::new_test()).await.unwrap();
+        hc.await.expect("Health check failed");
+
+        let (mut input_event, mut receiver) = make_event();
+        input_event.as_mut_log().insert("count", "42");
+        input_event
+            .as_mut_log()
+            .insert("items", vec!["item1", "item2"]);
+
+        sink.run(stream::once(ready(input_event.clone())))
+            .await
+            .unwrap();
+
+        let output = client.select_all(&table).await;
+        assert_eq!(1, output.rows);
+
+        let log = input_event.as_log();
+        let timestamp = log
+            .get(log_schema().timestamp_key())
+            .unwrap()
+            .as_timestamp()
+            .unwrap();
+        assert_eq!(
+            output.data[0],
+            serde_json::json!({
+                "host": "example.com",
+                "timestamp": timestamp.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+                "message": "raw log line",
+                "count": 42,
+                "items": ["item1", "item2"],
+                "missing": null,
+            })
+        );
+
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+    }
+
+    #[tokio::test]
+    async fn insert_events_row_binary_table_created_later() {
+        trace_init();
+
+        let table = gen_table();
+        let host = String::from("http://localhost:8123");
+        let encoding = EncodingConfigWithDefault {
+            codec: Encoding::RowBinary,
+            ..Default::default()
+        };
+
+        let config = ClickhouseConfig {
+            endpoint: host.parse().unwrap(),
+            table: table.clone(),
+            compression: Compression::None,
+            encoding,
+            batch: BatchConfig {
+                max_events: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // The table is only described once events are sent.
+        let (sink, _hc) = config.build(SinkContext::new_test()).await.unwrap();
+
+        let client = ClickhouseClient::new(host);
+        client.create_table(&table, "message String").await;
+
+        let (input_event, mut receiver) = make_event();
+        sink.run(stream::once(ready(input_event))).await.unwrap();
+
+        let output = client.select_all(&table).await;
+        assert_eq!(1, output.rows);
+        assert_eq!(
+            output.data[0],
+            serde_json::json!({"message": "raw log line"})
+        );
+
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+    }
+
     #[tokio::test]
     async fn no_retry_on_incorrect_data() {
         trace_init();
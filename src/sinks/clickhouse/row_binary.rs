@@ -0,0 +1,609 @@
+//! Encoding of the events as rows of the `RowBinary` format, with their fields
+//! coerced to the types of the columns of the table.
+//!
+//! Reference: https://clickhouse.com/docs/en/interfaces/formats/#rowbinary
+
+use crate::event::{LogEvent, Value};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use serde::Deserialize;
+use snafu::Snafu;
+use std::{convert::TryFrom, str::FromStr};
+use uuid::Uuid;
+
+#[derive(Debug, Snafu)]
+pub enum SchemaError {
+    #[snafu(display("Invalid description of the table: {}", source))]
+    InvalidDescription { source: serde_json::Error },
+    #[snafu(display("Unsupported type {:?} of column {:?}", column_type, column))]
+    UnsupportedType { column: String, column_type: String },
+    #[snafu(display("The table has no column that can be inserted"))]
+    NoColumns,
+}
+
+/// The type of a column, as far as the encoding of its values goes.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum ColumnType {
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    Bool,
+    String,
+    FixedString(usize),
+    Date,
+    DateTime,
+    DateTime64(u32),
+    Uuid,
+    Nullable(Box<ColumnType>),
+    Array(Box<ColumnType>),
+    Map(Box<ColumnType>, Box<ColumnType>),
+}
+
+impl ColumnType {
+    fn parse(name: &str) -> Option<Self> {
+        let name = name.trim();
+        if let Some(inner) = wrapped(name, "LowCardinality") {
+            // Low cardinality columns are encoded as their inner type.
+            return Self::parse(inner);
+        }
+        if let Some(inner) = wrapped(name, "Nullable") {
+            return Self::parse(inner).map(|inner| Self::Nullable(Box::new(inner)));
+        }
+        if let Some(inner) = wrapped(name, "Array") {
+            return Self::parse(inner).map(|inner| Self::Array(Box::new(inner)));
+        }
+        if let Some(inner) = wrapped(name, "Map") {
+            let (key, value) = split_arguments(inner)?;
+            return Some(Self::Map(
+                Box::new(Self::parse(key)?),
+                Box::new(Self::parse(value)?),
+            ));
+        }
+        if let Some(inner) = wrapped(name, "FixedString") {
+            return inner.trim().parse().ok().map(Self::FixedString);
+        }
+        if let Some(inner) = wrapped(name, "DateTime64") {
+            let precision = inner.split(',').next()?.trim().parse().ok()?;
+            return if precision <= 9 {
+                Some(Self::DateTime64(precision))
+            } else {
+                None
+            };
+        }
+        if wrapped(name, "DateTime").is_some() {
+            // The time zone only matters for the display of the values.
+            return Some(Self::DateTime);
+        }
+
+        Some(match name {
+            "UInt8" => Self::UInt8,
+            "UInt16" => Self::UInt16,
+            "UInt32" => Self::UInt32,
+            "UInt64" => Self::UInt64,
+            "Int8" => Self::Int8,
+            "Int16" => Self::Int16,
+            "Int32" => Self::Int32,
+            "Int64" => Self::Int64,
+            "Float32" => Self::Float32,
+            "Float64" => Self::Float64,
+            "Bool" | "Boolean" => Self::Bool,
+            "String" => Self::String,
+            "Date" => Self::Date,
+            "DateTime" => Self::DateTime,
+            "UUID" => Self::Uuid,
+            _ => return None,
+        })
+    }
+
+    /// The size of the values of the types encoded on a fixed number of bytes.
+    fn fixed_size(&self) -> usize {
+        match self {
+            Self::UInt8 | Self::Int8 | Self::Bool => 1,
+            Self::UInt16 | Self::Int16 | Self::Date => 2,
+            Self::UInt32 | Self::Int32 | Self::Float32 | Self::DateTime => 4,
+            Self::UInt64 | Self::Int64 | Self::Float64 | Self::DateTime64(_) => 8,
+            Self::Uuid => 16,
+            Self::FixedString(size) => *size,
+            Self::String | Self::Nullable(_) | Self::Array(_) | Self::Map(_, _) => 0,
+        }
+    }
+}
+
+/// Returns the arguments of `name` if it's the type `wrapper` with arguments.
+fn wrapped<'a>(name: &'a str, wrapper: &str) -> Option<&'a str> {
+    name.strip_prefix(wrapper)?
+        .strip_prefix('(')?
+        .strip_suffix(')')
+}
+
+/// Splits the two arguments of a type, which can contain commas themselves.
+fn split_arguments(arguments: &str) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    for (index, c) in arguments.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => return Some((&arguments[..index], &arguments[index + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Column {
+    pub(super) name: String,
+    pub(super) type_name: String,
+    column_type: ColumnType,
+}
+
+/// A field that couldn't be coerced to the type of its column.
+#[derive(Debug)]
+pub(super) struct CoercionError<'a> {
+    pub(super) column: &'a Column,
+    pub(super) reason: String,
+}
+
+#[derive(Deserialize)]
+struct DescribedColumn {
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+    #[serde(default)]
+    default_type: String,
+}
+
+/// The columns of the table that can be inserted.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Schema {
+    columns: Vec<Column>,
+}
+
+impl Schema {
+    /// Parses the response of `DESCRIBE TABLE` in the `JSONEachRow` format.
+    pub(super) fn parse(description: &[u8]) -> Result<Self, SchemaError> {
+        let mut columns = Vec::new();
+        for line in description.split(|byte| *byte == b'\n') {
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let described = serde_json::from_slice::<DescribedColumn>(line)
+                .map_err(|source| SchemaError::InvalidDescription { source })?;
+            // The values of these columns are computed by ClickHouse.
+            if matches!(
+                described.default_type.as_str(),
+                "MATERIALIZED" | "ALIAS" | "EPHEMERAL"
+            ) {
+                continue;
+            }
+            let column_type = ColumnType::parse(&described.type_name).ok_or_else(|| {
+                SchemaError::UnsupportedType {
+                    column: described.name.clone(),
+                    column_type: described.type_name.clone(),
+                }
+            })?;
+            columns.push(Column {
+                name: described.name,
+                type_name: described.type_name,
+                column_type,
+            });
+        }
+
+        if columns.is_empty() {
+            Err(SchemaError::NoColumns)
+        } else {
+            Ok(Self { columns })
+        }
+    }
+
+    pub(super) fn column_names(&self) -> impl Iterator<Item = &str> {
+        self.columns.iter().map(|column| column.name.as_str())
+    }
+
+    /// Encodes the fields of the log named after the columns as a row. The
+    /// missing fields get the default value of the type of their column.
+    pub(super) fn encode(&self, log: &LogEvent) -> Result<Vec<u8>, CoercionError<'_>> {
+        let mut row = Vec::new();
+        for column in &self.columns {
+            encode_value(&column.column_type, log.get(&column.name), &mut row)
+                .map_err(|reason| CoercionError { column, reason })?;
+        }
+        Ok(row)
+    }
+}
+
+fn encode_value(
+    column_type: &ColumnType,
+    value: Option<&Value>,
+    buf: &mut Vec<u8>,
+) -> Result<(), String> {
+    let value = match (column_type, value) {
+        (ColumnType::Nullable(inner), value) => {
+            return match value {
+                None | Some(Value::Null) => {
+                    buf.push(1);
+                    Ok(())
+                }
+                Some(value) => {
+                    buf.push(0);
+                    encode_value(inner, Some(value), buf)
+                }
+            };
+        }
+        (_, None) | (_, Some(Value::Null)) => {
+            write_default(column_type, buf);
+            return Ok(());
+        }
+        (_, Some(value)) => value,
+    };
+
+    match column_type {
+        ColumnType::UInt8 => buf.extend_from_slice(&integer::<u8>(value)?.to_le_bytes()),
+        ColumnType::UInt16 => buf.extend_from_slice(&integer::<u16>(value)?.to_le_bytes()),
+        ColumnType::UInt32 => buf.extend_from_slice(&integer::<u32>(value)?.to_le_bytes()),
+        ColumnType::UInt64 => buf.extend_from_slice(&integer::<u64>(value)?.to_le_bytes()),
+        ColumnType::Int8 => buf.extend_from_slice(&integer::<i8>(value)?.to_le_bytes()),
+        ColumnType::Int16 => buf.extend_from_slice(&integer::<i16>(value)?.to_le_bytes()),
+        ColumnType::Int32 => buf.extend_from_slice(&integer::<i32>(value)?.to_le_bytes()),
+        ColumnType::Int64 => buf.extend_from_slice(&integer::<i64>(value)?.to_le_bytes()),
+        ColumnType::Float32 => buf.extend_from_slice(&(float(value)? as f32).to_le_bytes()),
+        ColumnType::Float64 => buf.extend_from_slice(&float(value)?.to_le_bytes()),
+        ColumnType::Bool => buf.push(boolean(value)? as u8),
+        ColumnType::String => {
+            let string = string(value);
+            write_varint(string.len() as u64, buf);
+            buf.extend_from_slice(&string);
+        }
+        ColumnType::FixedString(size) => {
+            let string = string(value);
+            if string.len() > *size {
+                return Err(format!("{} bytes don't fit in {}", string.len(), size));
+            }
+            buf.extend_from_slice(&string);
+            buf.resize(buf.len() + size - string.len(), 0);
+        }
+        ColumnType::Date => {
+            let days = date(value)?;
+            let days = u16::try_from(days).map_err(|_| format!("{} is out of range", days))?;
+            buf.extend_from_slice(&days.to_le_bytes());
+        }
+        ColumnType::DateTime => {
+            let seconds = timestamp(value)?.timestamp();
+            let seconds =
+                u32::try_from(seconds).map_err(|_| format!("{} is out of range", seconds))?;
+            buf.extend_from_slice(&seconds.to_le_bytes());
+        }
+        ColumnType::DateTime64(precision) => {
+            let timestamp = timestamp(value)?;
+            let ticks = timestamp
+                .timestamp()
+                .checked_mul(10i64.pow(*precision))
+                .ok_or_else(|| format!("{} is out of range", timestamp))?
+                + i64::from(timestamp.timestamp_subsec_nanos() / 10u32.pow(9 - precision));
+            buf.extend_from_slice(&ticks.to_le_bytes());
+        }
+        ColumnType::Uuid => {
+            let uuid = Uuid::parse_str(&String::from_utf8_lossy(&string(value)))
+                .map_err(|error| error.to_string())?
+                .as_u128();
+            // The UUIDs are encoded as their two halves in little endian.
+            buf.extend_from_slice(&((uuid >> 64) as u64).to_le_bytes());
+            buf.extend_from_slice(&(uuid as u64).to_le_bytes());
+        }
+        ColumnType::Array(inner) => match value {
+            Value::Array(items) => {
+                write_varint(items.len() as u64, buf);
+                for item in items {
+                    encode_value(inner, Some(item), buf)?;
+                }
+            }
+            value => return Err(format!("{} is not an array", kind(value))),
+        },
+        ColumnType::Map(key_type, value_type) => match value {
+            Value::Map(map) => {
+                write_varint(map.len() as u64, buf);
+                for (key, value) in map {
+                    encode_value(key_type, Some(&Value::from(key.as_str())), buf)?;
+                    encode_value(value_type, Some(value), buf)?;
+                }
+            }
+            value => return Err(format!("{} is not a map", kind(value))),
+        },
+        ColumnType::Nullable(_) => unreachable!("Nullable values are encoded above"),
+    }
+    Ok(())
+}
+
+fn write_default(column_type: &ColumnType, buf: &mut Vec<u8>) {
+    match column_type {
+        // A null value, or an empty string, array or map.
+        ColumnType::Nullable(_) => buf.push(1),
+        ColumnType::String | ColumnType::Array(_) | ColumnType::Map(_, _) => buf.push(0),
+        column_type => buf.resize(buf.len() + column_type.fixed_size(), 0),
+    }
+}
+
+/// Writes an unsigned LEB128 integer, the encoding of the lengths.
+fn write_varint(mut value: u64, buf: &mut Vec<u8>) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Bytes(_) => "string",
+        Value::Integer(_) => "integer",
+        Value::Float(_) => "float",
+        Value::Boolean(_) => "boolean",
+        Value::Timestamp(_) => "timestamp",
+        Value::Map(_) => "map",
+        Value::Array(_) => "array",
+        Value::Null => "null",
+    }
+}
+
+fn parse<T: FromStr>(value: &Value, expected: &str) -> Result<T, String> {
+    match value {
+        Value::Bytes(bytes) => std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|string| string.trim().parse().ok())
+            .ok_or_else(|| {
+                format!(
+                    "{:?} is not {}",
+                    String::from_utf8_lossy(bytes).as_ref(),
+                    expected
+                )
+            }),
+        value => Err(format!("{} is not {}", kind(value), expected)),
+    }
+}
+
+fn integer<T: TryFrom<i64> + FromStr>(value: &Value) -> Result<T, String> {
+    let integer = match value {
+        Value::Integer(integer) => *integer,
+        Value::Float(float) if float.fract() == 0.0 => *float as i64,
+        Value::Boolean(boolean) => *boolean as i64,
+        Value::Timestamp(timestamp) => timestamp.timestamp(),
+        value => return parse(value, "an integer"),
+    };
+    T::try_from(integer).map_err(|_| format!("{} is out of range", integer))
+}
+
+fn float(value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Float(float) => Ok(*float),
+        Value::Integer(integer) => Ok(*integer as f64),
+        value => parse(value, "a float"),
+    }
+}
+
+fn boolean(value: &Value) -> Result<bool, String> {
+    match value {
+        Value::Boolean(boolean) => Ok(*boolean),
+        Value::Integer(0) => Ok(false),
+        Value::Integer(1) => Ok(true),
+        Value::Bytes(bytes) if bytes.as_ref() == b"0" => Ok(false),
+        Value::Bytes(bytes) if bytes.as_ref() == b"1" => Ok(true),
+        value => parse(value, "a boolean"),
+    }
+}
+
+fn string(value: &Value) -> Vec<u8> {
+    match value {
+        Value::Bytes(bytes) => bytes.to_vec(),
+        Value::Integer(integer) => integer.to_string().into_bytes(),
+        Value::Float(float) => float.to_string().into_bytes(),
+        Value::Boolean(boolean) => boolean.to_string().into_bytes(),
+        Value::Timestamp(timestamp) => timestamp
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+            .into_bytes(),
+        value => serde_json::to_vec(value).expect("Values should be valid json!"),
+    }
+}
+
+/// Returns the number of days since the epoch of a date.
+fn date(value: &Value) -> Result<i64, String> {
+    let epoch = NaiveDate::from_ymd(1970, 1, 1);
+    match value {
+        Value::Integer(days) => Ok(*days),
+        Value::Bytes(bytes) => {
+            let string = String::from_utf8_lossy(bytes);
+            match NaiveDate::parse_from_str(string.trim(), "%Y-%m-%d") {
+                Ok(date) => Ok(date.signed_duration_since(epoch).num_days()),
+                Err(_) => Ok(timestamp(value)?.timestamp().div_euclid(86400)),
+            }
+        }
+        value => Ok(timestamp(value)?.timestamp().div_euclid(86400)),
+    }
+}
+
+/// Returns the timestamp of a value, the numbers being seconds since the epoch.
+fn timestamp(value: &Value) -> Result<DateTime<Utc>, String> {
+    match value {
+        Value::Timestamp(timestamp) => Ok(*timestamp),
+        Value::Integer(seconds) => Utc
+            .timestamp_opt(*seconds, 0)
+            .single()
+            .ok_or_else(|| format!("{} is out of range", seconds)),
+        Value::Float(seconds) => Utc
+            .timestamp_opt(
+                seconds.floor() as i64,
+                (seconds.fract() * 1_000_000_000.0) as u32,
+            )
+            .single()
+            .ok_or_else(|| format!("{} is out of range", seconds)),
+        Value::Bytes(bytes) => {
+            let string = String::from_utf8_lossy(bytes);
+            let string = string.trim();
+            DateTime::parse_from_rfc3339(string)
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .or_else(|_| {
+                    NaiveDateTime::parse_from_str(string, "%Y-%m-%d %H:%M:%S%.f")
+                        .map(|timestamp| DateTime::from_utc(timestamp, Utc))
+                })
+                .map_err(|_| format!("{:?} is not a timestamp", string))
+        }
+        value => Err(format!("{} is not a timestamp", kind(value))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn schema(columns: &[(&str, &str)]) -> Schema {
+        let description = columns
+            .iter()
+            .map(|(name, type_name)| {
+                format!(
+                    "{{\"name\":\"{}\",\"type\":\"{}\",\"default_type\":\"\"}}\n",
+                    name, type_name
+                )
+            })
+            .collect::<String>();
+        Schema::parse(description.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn parses_column_types() {
+        let cases = [
+            ("UInt16", ColumnType::UInt16),
+            ("LowCardinality(String)", ColumnType::String),
+            ("DateTime('UTC')", ColumnType::DateTime),
+            ("DateTime64(3, 'Europe/Paris')", ColumnType::DateTime64(3)),
+            ("FixedString(4)", ColumnType::FixedString(4)),
+            (
+                "Array(Nullable(Int32))",
+                ColumnType::Array(Box::new(ColumnType::Nullable(Box::new(ColumnType::Int32)))),
+            ),
+            (
+                "Map(LowCardinality(String), Array(UInt8))",
+                ColumnType::Map(
+                    Box::new(ColumnType::String),
+                    Box::new(ColumnType::Array(Box::new(ColumnType::UInt8))),
+                ),
+            ),
+        ];
+        for (name, expected) in cases.iter() {
+            assert_eq!(ColumnType::parse(name).as_ref(), Some(expected), "{}", name);
+        }
+        assert_eq!(ColumnType::parse("Decimal(9, 2)"), None);
+        assert_eq!(ColumnType::parse("DateTime64(12)"), None);
+    }
+
+    #[test]
+    fn skips_computed_columns() {
+        let description = concat!(
+            "{\"name\":\"message\",\"type\":\"String\",\"default_type\":\"\"}\n",
+            "{\"name\":\"length\",\"type\":\"UInt64\",\"default_type\":\"MATERIALIZED\"}\n",
+        );
+        let schema = Schema::parse(description.as_bytes()).unwrap();
+        assert_eq!(schema.column_names().collect::<Vec<_>>(), vec!["message"]);
+
+        let description = "{\"name\":\"flags\",\"type\":\"Enum8('a' = 1)\"}\n";
+        assert!(matches!(
+            Schema::parse(description.as_bytes()),
+            Err(SchemaError::UnsupportedType { .. })
+        ));
+    }
+
+    #[test]
+    fn encodes_coerced_fields() {
+        let schema = schema(&[
+            ("count", "UInt16"),
+            ("ratio", "Float32"),
+            ("message", "LowCardinality(String)"),
+            ("timestamp", "DateTime64(3, 'UTC')"),
+            ("tags", "Array(String)"),
+            ("nested.flag", "Bool"),
+        ]);
+        let mut log = LogEvent::default();
+        log.insert("count", "513");
+        log.insert("ratio", 1);
+        log.insert("message", 42);
+        log.insert("timestamp", Utc.ymd(2021, 8, 1).and_hms_milli(0, 0, 1, 500));
+        log.insert("tags", vec!["a", "bc"]);
+        log.insert("nested.flag", true);
+
+        let mut expected = vec![0x01, 0x02];
+        expected.extend_from_slice(&1.0f32.to_le_bytes());
+        expected.extend_from_slice(&[2, b'4', b'2']);
+        expected.extend_from_slice(&1_627_776_001_500i64.to_le_bytes());
+        expected.extend_from_slice(&[2, 1, b'a', 2, b'b', b'c']);
+        expected.push(1);
+        assert_eq!(schema.encode(&log).unwrap(), expected);
+    }
+
+    #[test]
+    fn encodes_missing_fields_as_defaults() {
+        let schema = schema(&[
+            ("id", "UInt32"),
+            ("name", "Nullable(String)"),
+            ("labels", "Map(String, String)"),
+            ("date", "Date"),
+            ("code", "FixedString(3)"),
+        ]);
+        let mut log = LogEvent::default();
+        let mut labels = BTreeMap::new();
+        labels.insert("k".to_owned(), Value::from("v"));
+        log.insert("labels", labels);
+        log.insert("date", "1970-01-03");
+        log.insert("code", "ab");
+
+        let expected = vec![
+            0, 0, 0, 0, // id
+            1, // name
+            1, 1, b'k', 1, b'v', // labels
+            2, 0, // date
+            b'a', b'b', 0, // code
+        ];
+        assert_eq!(schema.encode(&log).unwrap(), expected);
+    }
+
+    #[test]
+    fn encodes_uuids_as_two_halves() {
+        let schema = schema(&[("id", "UUID")]);
+        let mut log = LogEvent::default();
+        log.insert("id", "61f0c404-5cb3-11e7-907b-a6006ad3dba0");
+
+        let expected = vec![
+            0xe7, 0x11, 0xb3, 0x5c, 0x04, 0xc4, 0xf0, 0x61, 0xa0, 0xdb, 0xd3, 0x6a, 0x00, 0xa6,
+            0x7b, 0x90,
+        ];
+        assert_eq!(schema.encode(&log).unwrap(), expected);
+    }
+
+    #[test]
+    fn reports_the_field_failing_coercion() {
+        let schema = schema(&[("message", "String"), ("status", "UInt8")]);
+        let mut log = LogEvent::default();
+        log.insert("message", "ok");
+        log.insert("status", 300);
+
+        let error = schema.encode(&log).unwrap_err();
+        assert_eq!(error.column.name, "status");
+        assert_eq!(error.column.type_name, "UInt8");
+        assert_eq!(error.reason, "300 is out of range");
+
+        log.insert("status", "high");
+        let error = schema.encode(&log).unwrap_err();
+        assert_eq!(error.reason, "\"high\" is not an integer");
+    }
+
+    #[test]
+    fn writes_varints() {
+        let mut buf = Vec::new();
+        write_varint(300, &mut buf);
+        assert_eq!(buf, vec![0xac, 0x02]);
+    }
+}
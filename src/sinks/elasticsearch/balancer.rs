@@ -0,0 +1,492 @@
+//! Distribution of the bulk requests over the endpoints of the cluster.
+//!
+//! Each endpoint has its own adaptive concurrency controller, and requests go
+//! to the healthy endpoint with the fewest requests in flight. An endpoint
+//! failing a request is marked down and probed until it's healthy again,
+//! while the retries of the request go to the other endpoints.
+
+use super::{
+    retry::ElasticSearchRetryLogic,
+    service::{BulkRequest, ElasticSearchService},
+    ElasticSearchCommon, ElasticSearchConfig,
+};
+use crate::{
+    emit,
+    http::{Auth, HttpClient},
+    internal_events::{
+        ElasticSearchDiscoveryFailed, ElasticSearchEndpointDown, ElasticSearchEndpointUp,
+    },
+    sinks::util::{
+        adaptive_concurrency::{AdaptiveConcurrencyLimit, AdaptiveConcurrencyLimitLayer},
+        service::TowerRequestSettings,
+    },
+};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock, Weak,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tower::{timeout::Timeout, Service, ServiceBuilder};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Derivative, PartialEq)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields)]
+pub struct DiscoveryConfig {
+    /// How often the nodes of the cluster are listed.
+    #[serde(default = "default_discovery_interval_secs")]
+    #[derivative(Default(value = "default_discovery_interval_secs()"))]
+    pub interval_secs: u64,
+}
+
+const fn default_discovery_interval_secs() -> u64 {
+    300
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Derivative, PartialEq)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields)]
+pub struct HealthConfig {
+    /// The delay before probing an endpoint marked down for the first time.
+    #[serde(default = "default_retry_initial_backoff_secs")]
+    #[derivative(Default(value = "default_retry_initial_backoff_secs()"))]
+    pub retry_initial_backoff_secs: u64,
+    /// The maximum delay between the probes of an endpoint marked down.
+    #[serde(default = "default_retry_max_duration_secs")]
+    #[derivative(Default(value = "default_retry_max_duration_secs()"))]
+    pub retry_max_duration_secs: u64,
+}
+
+const fn default_retry_initial_backoff_secs() -> u64 {
+    1
+}
+
+const fn default_retry_max_duration_secs() -> u64 {
+    300
+}
+
+type EndpointService =
+    AdaptiveConcurrencyLimit<Timeout<ElasticSearchService>, ElasticSearchRetryLogic>;
+
+struct Endpoint {
+    common: Arc<ElasticSearchCommon>,
+    service: Mutex<EndpointService>,
+    in_flight: AtomicUsize,
+    healthy: AtomicBool,
+}
+
+impl Endpoint {
+    /// Creates the endpoint of `url`, authenticated with `authorization`
+    /// when neither `url` nor the configuration has credentials.
+    fn new(
+        config: &ElasticSearchConfig,
+        url: &str,
+        authorization: Option<&Auth>,
+        client: &HttpClient,
+        request: &TowerRequestSettings,
+    ) -> crate::Result<Self> {
+        let mut common = ElasticSearchCommon::parse_endpoint(config, url)?;
+        if common.authorization.is_none() {
+            common.authorization = authorization.cloned();
+        }
+        let common = Arc::new(common);
+        let service = ServiceBuilder::new()
+            .layer(AdaptiveConcurrencyLimitLayer::new(
+                request.concurrency,
+                request.adaptive_concurrency,
+                ElasticSearchRetryLogic,
+            ))
+            .timeout(request.timeout)
+            .service(ElasticSearchService::new(
+                client.clone(),
+                Arc::clone(&common),
+            ));
+        Ok(Self {
+            common,
+            service: Mutex::new(service),
+            in_flight: AtomicUsize::new(0),
+            healthy: AtomicBool::new(true),
+        })
+    }
+}
+
+type Endpoints = Arc<RwLock<Vec<Arc<Endpoint>>>>;
+
+pub(super) struct Balancer {
+    endpoints: Endpoints,
+    next: Arc<AtomicUsize>,
+    client: HttpClient,
+    health: HealthConfig,
+    ready: Option<(Arc<Endpoint>, EndpointService)>,
+}
+
+impl Balancer {
+    pub(super) fn new(
+        config: &ElasticSearchConfig,
+        urls: &[String],
+        client: HttpClient,
+        request: &TowerRequestSettings,
+    ) -> crate::Result<Self> {
+        let endpoints = urls
+            .iter()
+            .map(|url| Endpoint::new(config, url, None, &client, request).map(Arc::new))
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(Self {
+            endpoints: Arc::new(RwLock::new(endpoints)),
+            next: Arc::new(AtomicUsize::new(0)),
+            client,
+            health: config.health,
+            ready: None,
+        })
+    }
+
+    /// Returns the healthy endpoint with the fewest requests in flight,
+    /// rotating between the ones with as many. All the endpoints are
+    /// considered when none is healthy.
+    fn pick(&self) -> Arc<Endpoint> {
+        let endpoints = self.endpoints.read().unwrap();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let rotated =
+            || (0..endpoints.len()).map(|offset| &endpoints[(start + offset) % endpoints.len()]);
+        least_loaded(rotated().filter(|endpoint| endpoint.healthy.load(Ordering::Relaxed)))
+            .or_else(|| least_loaded(rotated()))
+            .expect("There's at least one endpoint")
+    }
+
+    /// Lists the nodes of the cluster every `interval`, and replaces the
+    /// endpoints with them, keeping the state of the ones already known.
+    pub(super) fn spawn_discovery(
+        &self,
+        config: ElasticSearchConfig,
+        request: TowerRequestSettings,
+        interval: Duration,
+    ) {
+        let endpoints = Arc::downgrade(&self.endpoints);
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                let endpoints = match endpoints.upgrade() {
+                    Some(endpoints) => endpoints,
+                    None => break,
+                };
+                let seed = {
+                    let endpoints = endpoints.read().unwrap();
+                    endpoints
+                        .iter()
+                        .find(|endpoint| endpoint.healthy.load(Ordering::Relaxed))
+                        .unwrap_or(&endpoints[0])
+                        .clone()
+                };
+                match discover(&seed.common, &client).await {
+                    Ok(urls) if !urls.is_empty() => {
+                        if let Err(error) = replace_endpoints(
+                            &endpoints,
+                            &urls,
+                            &seed.common,
+                            &config,
+                            &client,
+                            &request,
+                        ) {
+                            emit!(ElasticSearchDiscoveryFailed { error });
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(error) => emit!(ElasticSearchDiscoveryFailed { error }),
+                }
+            }
+        });
+    }
+}
+
+impl Clone for Balancer {
+    fn clone(&self) -> Self {
+        Self {
+            endpoints: Arc::clone(&self.endpoints),
+            next: Arc::clone(&self.next),
+            client: self.client.clone(),
+            health: self.health,
+            ready: None,
+        }
+    }
+}
+
+impl Service<BulkRequest> for Balancer {
+    type Response = http::Response<Bytes>;
+    type Error = crate::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.ready.is_none() {
+            let endpoint = self.pick();
+            let service = endpoint.service.lock().unwrap().clone();
+            self.ready = Some((endpoint, service));
+        }
+        let (_, service) = self.ready.as_mut().unwrap();
+        service.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: BulkRequest) -> Self::Future {
+        let (endpoint, mut service) = self
+            .ready
+            .take()
+            .expect("Balancer not ready; poll_ready must be called first");
+        let client = self.client.clone();
+        let health = self.health;
+
+        endpoint.in_flight.fetch_add(1, Ordering::Relaxed);
+        let response = service.call(request);
+        Box::pin(async move {
+            let response = response.await;
+            endpoint.in_flight.fetch_sub(1, Ordering::Relaxed);
+
+            let error = match &response {
+                Ok(response) if response.status().is_server_error() => {
+                    Some(format!("Response status: {}", response.status()))
+                }
+                Ok(_) => None,
+                Err(error) => Some(error.to_string()),
+            };
+            if let Some(error) = error {
+                if endpoint.healthy.swap(false, Ordering::Relaxed) {
+                    emit!(ElasticSearchEndpointDown {
+                        endpoint: &endpoint.common.base_url,
+                        error,
+                    });
+                    tokio::spawn(probe(Arc::downgrade(&endpoint), client, health));
+                }
+            }
+
+            response
+        })
+    }
+}
+
+fn least_loaded<'a>(endpoints: impl Iterator<Item = &'a Arc<Endpoint>>) -> Option<Arc<Endpoint>> {
+    endpoints
+        .min_by_key(|endpoint| endpoint.in_flight.load(Ordering::Relaxed))
+        .cloned()
+}
+
+/// Checks the health of the endpoint marked down, with an increasing backoff,
+/// until it's healthy again or it's no longer used.
+async fn probe(endpoint: Weak<Endpoint>, client: HttpClient, health: HealthConfig) {
+    let max_backoff = Duration::from_secs(health.retry_max_duration_secs);
+    let mut backoff = Duration::from_secs(health.retry_initial_backoff_secs);
+    loop {
+        tokio::time::sleep(backoff).await;
+        let endpoint = match endpoint.upgrade() {
+            Some(endpoint) => endpoint,
+            None => break,
+        };
+        if endpoint.common.healthcheck(client.clone()).await.is_ok() {
+            endpoint.healthy.store(true, Ordering::Relaxed);
+            emit!(ElasticSearchEndpointUp {
+                endpoint: &endpoint.common.base_url,
+            });
+            break;
+        }
+        backoff = cmp::min(backoff * 2, max_backoff);
+    }
+}
+
+#[derive(Deserialize)]
+struct NodesResponse {
+    nodes: HashMap<String, NodeInfo>,
+}
+
+#[derive(Deserialize)]
+struct NodeInfo {
+    http: Option<NodeHttp>,
+}
+
+#[derive(Deserialize)]
+struct NodeHttp {
+    publish_address: String,
+}
+
+/// Lists the HTTP addresses of the nodes of the cluster, as URLs with the
+/// scheme of `seed`.
+async fn discover(seed: &ElasticSearchCommon, client: &HttpClient) -> crate::Result<Vec<String>> {
+    let request = seed.get_request("/_nodes/http").await?;
+    let response = client.send(request).await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("Unexpected status listing the nodes: {}", status).into());
+    }
+    let body = hyper::body::to_bytes(response.into_body()).await?;
+    let nodes = serde_json::from_slice::<NodesResponse>(&body)?;
+
+    let scheme = seed
+        .base_url
+        .split("://")
+        .next()
+        .unwrap_or("http")
+        .to_owned();
+    let mut urls = nodes
+        .nodes
+        .values()
+        .filter_map(|node| node.http.as_ref())
+        .map(|http| format!("{}://{}", scheme, publish_address(&http.publish_address)))
+        .collect::<Vec<_>>();
+    urls.sort();
+    Ok(urls)
+}
+
+/// Returns the address of a `publish_address`, which is either `ip:port` or
+/// `hostname/ip:port`.
+fn publish_address(publish_address: &str) -> &str {
+    publish_address
+        .rsplit('/')
+        .next()
+        .unwrap_or(publish_address)
+}
+
+/// Replaces the endpoints with the ones of `urls`, the new ones using the
+/// credentials of `seed` unless the configuration has its own.
+fn replace_endpoints(
+    endpoints: &RwLock<Vec<Arc<Endpoint>>>,
+    urls: &[String],
+    seed: &ElasticSearchCommon,
+    config: &ElasticSearchConfig,
+    client: &HttpClient,
+    request: &TowerRequestSettings,
+) -> crate::Result<()> {
+    let known = endpoints
+        .read()
+        .unwrap()
+        .iter()
+        .map(|endpoint| (endpoint.common.base_url.clone(), Arc::clone(endpoint)))
+        .collect::<HashMap<_, _>>();
+    let updated = urls
+        .iter()
+        .map(|url| match known.get(url.trim_end_matches('/')) {
+            Some(endpoint) => Ok(Arc::clone(endpoint)),
+            None => Endpoint::new(config, url, seed.authorization.as_ref(), client, request)
+                .map(Arc::new),
+        })
+        .collect::<crate::Result<Vec<_>>>()?;
+    *endpoints.write().unwrap() = updated;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sinks::util::TowerRequestConfig;
+
+    fn balancer(urls: &[&str]) -> Balancer {
+        let config = ElasticSearchConfig {
+            endpoints: urls.iter().map(|url| url.to_string()).collect(),
+            ..Default::default()
+        };
+        let urls = config.endpoints().unwrap();
+        let client = HttpClient::new(None, &Default::default()).unwrap();
+        let request = TowerRequestConfig::default().unwrap_with(&Default::default());
+        Balancer::new(&config, &urls, client, &request).unwrap()
+    }
+
+    fn picked(balancer: &Balancer) -> String {
+        balancer.pick().common.base_url.clone()
+    }
+
+    #[tokio::test]
+    async fn picks_least_loaded_healthy_endpoint() {
+        let balancer = balancer(&["http://a:9200", "http://b:9200", "http://c:9200"]);
+        let endpoints = balancer.endpoints.read().unwrap().clone();
+
+        // Endpoints with as many requests in flight are used in turn.
+        let mut picks = (0..3).map(|_| picked(&balancer)).collect::<Vec<_>>();
+        picks.sort();
+        assert_eq!(
+            picks,
+            vec!["http://a:9200", "http://b:9200", "http://c:9200"]
+        );
+
+        endpoints[0].in_flight.store(2, Ordering::Relaxed);
+        endpoints[1].in_flight.store(1, Ordering::Relaxed);
+        endpoints[2].healthy.store(false, Ordering::Relaxed);
+        assert_eq!(picked(&balancer), "http://b:9200");
+
+        // All the endpoints are used when none is healthy.
+        endpoints[0].healthy.store(false, Ordering::Relaxed);
+        endpoints[1].healthy.store(false, Ordering::Relaxed);
+        assert_eq!(picked(&balancer), "http://c:9200");
+    }
+
+    #[tokio::test]
+    async fn replaces_discovered_endpoints() {
+        let balancer = balancer(&["http://a:9200", "http://b:9200"]);
+        let b = Arc::clone(&balancer.endpoints.read().unwrap()[1]);
+        b.healthy.store(false, Ordering::Relaxed);
+
+        let config = ElasticSearchConfig {
+            endpoint: "http://a:9200".into(),
+            ..Default::default()
+        };
+        let request = TowerRequestConfig::default().unwrap_with(&Default::default());
+        let urls = vec!["http://b:9200".to_owned(), "http://c:9200".to_owned()];
+        let seed = Arc::clone(&balancer.endpoints.read().unwrap()[0].common);
+        replace_endpoints(
+            &balancer.endpoints,
+            &urls,
+            &seed,
+            &config,
+            &balancer.client,
+            &request,
+        )
+        .unwrap();
+
+        let endpoints = balancer.endpoints.read().unwrap();
+        assert_eq!(endpoints.len(), 2);
+        assert!(Arc::ptr_eq(&endpoints[0], &b));
+        assert_eq!(endpoints[1].common.base_url, "http://c:9200");
+    }
+
+    #[tokio::test]
+    async fn discovered_endpoints_inherit_seed_credentials() {
+        let balancer = balancer(&["http://user:secret@a:9200"]);
+        let seed = Arc::clone(&balancer.endpoints.read().unwrap()[0].common);
+        assert_eq!(seed.base_url, "http://a:9200");
+
+        let config = ElasticSearchConfig {
+            endpoint: "http://user:secret@a:9200".into(),
+            ..Default::default()
+        };
+        let request = TowerRequestConfig::default().unwrap_with(&Default::default());
+        let urls = vec!["http://b:9200".to_owned()];
+        replace_endpoints(
+            &balancer.endpoints,
+            &urls,
+            &seed,
+            &config,
+            &balancer.client,
+            &request,
+        )
+        .unwrap();
+
+        let endpoints = balancer.endpoints.read().unwrap();
+        assert_eq!(endpoints[0].common.base_url, "http://b:9200");
+        assert_eq!(
+            endpoints[0].common.authorization,
+            Some(Auth::Basic {
+                user: "user".into(),
+                password: "secret".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn parses_publish_addresses() {
+        assert_eq!(publish_address("10.0.0.1:9200"), "10.0.0.1:9200");
+        assert_eq!(publish_address("es-1/10.0.0.1:9200"), "10.0.0.1:9200");
+    }
+}
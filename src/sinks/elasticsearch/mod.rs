@@ -1,6 +1,13 @@
+mod balancer;
 mod retry;
+mod service;
 
-use self::retry::{ElasticSearchRetryLogic, ElasticSearchServiceLogic};
+pub use self::balancer::{DiscoveryConfig, HealthConfig};
+use self::{
+    balancer::Balancer,
+    retry::{ElasticSearchRetryLogic, ElasticSearchServiceLogic},
+    service::BulkRequest,
+};
 use crate::{
     config::{log_schema, DataType, SinkConfig, SinkContext, SinkDescription},
     emit,
@@ -8,15 +15,18 @@ use crate::{
     internal_events::{ElasticSearchEventEncoded, TemplateRenderingFailed},
     rusoto::{self, region_from_endpoint, AwsAuthentication, RegionOrEndpoint},
     sinks::util::{
+        buffer::GZIP_FAST,
         encoding::{EncodingConfigWithDefault, EncodingConfiguration},
-        http::{BatchedHttpSink, HttpSink, RequestConfig},
-        BatchConfig, BatchSettings, Buffer, Compression, TowerRequestConfig, UriSerde,
+        http::{HttpSink, RequestConfig},
+        BatchConfig, BatchSettings, BatchSink, Buffer, Compression, EncodedEvent,
+        ServiceBuilderExt, TowerRequestConfig, UriSerde,
     },
     template::{Template, TemplateParseError},
     tls::{TlsOptions, TlsSettings},
     transforms::metric_to_log::{MetricToLog, MetricToLogConfig},
 };
-use futures::{FutureExt, SinkExt};
+use flate2::write::GzEncoder;
+use futures::{stream, FutureExt, SinkExt};
 use http::{
     header::{HeaderName, HeaderValue},
     uri::InvalidUri,
@@ -32,6 +42,9 @@ use serde_json::json;
 use snafu::{ResultExt, Snafu};
 use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
+use std::io::Write;
+use std::time::Duration;
+use tower::ServiceBuilder;
 use vector_core::event::{Event, Value};
 
 /// The field name for the timestamp required by data stream mode
@@ -41,8 +54,16 @@ const DATA_STREAM_TIMESTAMP_KEY: &str = "@timestamp";
 #[serde(deny_unknown_fields)]
 pub struct ElasticSearchConfig {
     // Deprecated name
-    #[serde(alias = "host")]
+    #[serde(alias = "host", default)]
     pub endpoint: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endpoints: Vec<String>,
+    pub discovery: Option<DiscoveryConfig>,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub health: HealthConfig,
     // Deprecated, use normal.index instead
     pub index: Option<String>,
     pub doc_type: Option<String>,
@@ -78,6 +99,15 @@ pub struct ElasticSearchConfig {
 }
 
 impl ElasticSearchConfig {
+    fn endpoints(&self) -> crate::Result<Vec<String>> {
+        match (self.endpoint.is_empty(), self.endpoints.is_empty()) {
+            (false, true) => Ok(vec![self.endpoint.clone()]),
+            (true, false) => Ok(self.endpoints.clone()),
+            (true, true) => Err(ParseError::NoEndpoints.into()),
+            (false, false) => Err(ParseError::EndpointAndEndpoints.into()),
+        }
+    }
+
     fn bulk_action(&self) -> crate::Result<Option<Template>> {
         Ok(self
             .normal
@@ -362,13 +392,16 @@ impl SinkConfig for ElasticSearchConfig {
         &self,
         cx: SinkContext,
     ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
-        let common = ElasticSearchCommon::parse_config(self)?;
-        let client = HttpClient::new(common.tls_settings.clone(), cx.proxy())?;
+        let endpoints = self.endpoints()?;
+        let commons = endpoints
+            .iter()
+            .map(|endpoint| ElasticSearchCommon::parse_endpoint(self, endpoint))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let client = HttpClient::new(commons[0].tls_settings.clone(), cx.proxy())?;
 
-        let healthcheck = common.healthcheck(client.clone()).boxed();
+        let healthcheck = healthcheck(commons, client.clone()).boxed();
 
         let common = ElasticSearchCommon::parse_config(self)?;
-        let compression = common.compression;
         let batch = BatchSettings::default()
             .bytes(bytesize::mib(10u64))
             .timeout(1)
@@ -378,16 +411,40 @@ impl SinkConfig for ElasticSearchConfig {
             .tower
             .unwrap_with(&TowerRequestConfig::default());
 
-        let sink = BatchedHttpSink::with_logic(
-            common,
-            Buffer::new(batch.size, compression),
-            ElasticSearchRetryLogic,
-            request,
+        let balancer = Balancer::new(self, &endpoints, client, &request)?;
+        if let Some(discovery) = self.discovery {
+            balancer.spawn_discovery(
+                self.clone(),
+                request.clone(),
+                Duration::from_secs(discovery.interval_secs),
+            );
+        }
+        // The retries go through the balancer, so that they go to another
+        // endpoint when the one of the request is down.
+        let service = ServiceBuilder::new()
+            .map(BulkRequest::new)
+            .rate_limit(request.rate_limit_num, request.rate_limit_duration)
+            .retry(request.retry_policy(ElasticSearchRetryLogic))
+            .service(balancer);
+
+        // The documents are compressed when building each request, as the
+        // retries only send the documents rejected with a retriable error.
+        let buffer = Buffer::new(batch.size, Compression::None);
+        let sink = BatchSink::new_with_logic(
+            service,
+            buffer,
             batch.timeout,
-            client,
             cx.acker(),
             ElasticSearchServiceLogic,
         )
+        .with_flat_map(move |mut event: Event| {
+            let finalizers = event.metadata_mut().take_finalizers();
+            stream::iter(
+                common
+                    .encode_event(event)
+                    .map(|item| Ok(EncodedEvent { item, finalizers })),
+            )
+        })
         .sink_map_err(|error| error!(message = "Fatal elasticsearch sink error.", %error));
 
         Ok((super::VectorSink::Sink(Box::new(sink)), healthcheck))
@@ -487,6 +544,10 @@ enum ParseError {
     IndexTemplate { source: TemplateParseError },
     #[snafu(display("Batch action template parse error: {}", source))]
     BatchActionTemplate { source: TemplateParseError },
+    #[snafu(display("One of `endpoint` or `endpoints` must be set"))]
+    NoEndpoints,
+    #[snafu(display("Only one of `endpoint` or `endpoints` can be set"))]
+    EndpointAndEndpoints,
 }
 
 impl ElasticSearchCommon {
@@ -546,6 +607,18 @@ impl HttpSink for ElasticSearchCommon {
 
     async fn build_request(&self, events: Self::Output) -> crate::Result<http::Request<Vec<u8>>> {
         let mut builder = Request::post(&self.bulk_uri);
+        let events = match self.compression {
+            Compression::None => events,
+            Compression::Gzip(level) => {
+                let level = level.unwrap_or(GZIP_FAST);
+                let mut encoder = GzEncoder::new(
+                    Vec::with_capacity(events.len()),
+                    flate2::Compression::new(level as u32),
+                );
+                encoder.write_all(&events)?;
+                encoder.finish()?
+            }
+        };
 
         if let Some(credentials_provider) = &self.credentials {
             let mut request = self.signed_request("POST", &self.bulk_uri, true);
@@ -595,15 +668,20 @@ impl HttpSink for ElasticSearchCommon {
 }
 
 impl ElasticSearchCommon {
+    /// Parses the configuration for its first endpoint.
     pub fn parse_config(config: &ElasticSearchConfig) -> crate::Result<Self> {
+        Self::parse_endpoint(config, &config.endpoints()?[0])
+    }
+
+    fn parse_endpoint(config: &ElasticSearchConfig, endpoint: &str) -> crate::Result<Self> {
         // Test the configured host, but ignore the result
-        let uri = format!("{}/_test", &config.endpoint);
-        let uri = uri.parse::<Uri>().with_context(|| InvalidHost {
-            host: &config.endpoint,
-        })?;
+        let uri = format!("{}/_test", endpoint);
+        let uri = uri
+            .parse::<Uri>()
+            .with_context(|| InvalidHost { host: endpoint })?;
         if uri.host().is_none() {
             return Err(ParseError::HostMustIncludeHostname {
-                host: config.endpoint.clone(),
+                host: endpoint.to_owned(),
             }
             .into());
         }
@@ -615,7 +693,7 @@ impl ElasticSearchCommon {
             }),
             _ => None,
         };
-        let uri = config.endpoint.parse::<UriSerde>()?;
+        let uri = endpoint.parse::<UriSerde>()?;
         let authorization = authorization.choose_one(&uri.auth)?;
         let base_url = uri.uri.to_string().trim_end_matches('/').to_owned();
 
@@ -696,8 +774,18 @@ impl ElasticSearchCommon {
         request
     }
 
-    async fn healthcheck(self, client: HttpClient) -> crate::Result<()> {
-        let mut builder = Request::get(format!("{}/_cluster/health", self.base_url));
+    async fn healthcheck(&self, client: HttpClient) -> crate::Result<()> {
+        let request = self.get_request("/_cluster/health").await?;
+        let response = client.send(request).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(()),
+            status => Err(super::HealthcheckError::UnexpectedStatus { status }.into()),
+        }
+    }
+
+    async fn get_request(&self, path: &str) -> crate::Result<Request<Body>> {
+        let mut builder = Request::get(format!("{}{}", self.base_url, path));
 
         match &self.credentials {
             None => {
@@ -710,14 +798,21 @@ impl ElasticSearchCommon {
                 builder = finish_signer(&mut signer, credentials_provider, builder).await?;
             }
         }
-        let request = builder.body(Body::empty())?;
-        let response = client.send(request).await?;
+        Ok(builder.body(Body::empty())?)
+    }
+}
 
-        match response.status() {
-            StatusCode::OK => Ok(()),
-            status => Err(super::HealthcheckError::UnexpectedStatus { status }.into()),
+/// Checks the health of the endpoints, the sink being healthy as long as one
+/// of them is.
+async fn healthcheck(commons: Vec<ElasticSearchCommon>, client: HttpClient) -> crate::Result<()> {
+    let mut result = Ok(());
+    for common in commons {
+        result = common.healthcheck(client.clone()).await;
+        if result.is_ok() {
+            break;
         }
     }
+    result
 }
 
 async fn finish_signer(
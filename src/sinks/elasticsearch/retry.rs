@@ -1,5 +1,7 @@
 use crate::{
+    emit,
     http::HttpError,
+    internal_events::ElasticSearchDocumentFailed,
     sinks::util::{
         retries::{RetryAction, RetryLogic},
         sink::{Response, ServiceLogic},
@@ -8,6 +10,7 @@ use crate::{
 use bytes::Bytes;
 use http::StatusCode;
 use serde::Deserialize;
+use serde_json::Value;
 use vector_core::event::EventStatus;
 
 #[derive(Deserialize, Debug)]
@@ -24,7 +27,7 @@ enum EsResultItem {
 }
 
 impl EsResultItem {
-    fn result(self) -> EsIndexResult {
+    fn result(&self) -> &EsIndexResult {
         match self {
             EsResultItem::Index(r) => r,
             EsResultItem::Create(r) => r,
@@ -34,9 +37,27 @@ impl EsResultItem {
 
 #[derive(Deserialize, Debug)]
 struct EsIndexResult {
+    #[serde(default)]
+    status: u16,
     error: Option<EsErrorDetails>,
 }
 
+impl EsIndexResult {
+    /// Whether the document was rejected for a transient reason, like a full
+    /// write queue (`429 es_rejected_execution_exception`).
+    fn is_retriable(&self) -> bool {
+        self.error.is_some()
+            && (self.status == StatusCode::TOO_MANY_REQUESTS.as_u16()
+                || (500..600).contains(&self.status))
+    }
+}
+
+/// Returns whether `item`, from the `items` of a bulk response, is the result
+/// of a document to send again.
+pub(super) fn is_retriable_item(item: &Value) -> bool {
+    EsResultItem::deserialize(item).map_or(false, |item| item.result().is_retriable())
+}
+
 #[derive(Deserialize, Debug)]
 struct EsErrorDetails {
     reason: String,
@@ -76,7 +97,22 @@ impl RetryLogic for ElasticSearchRetryLogic {
                 let body = String::from_utf8_lossy(response.body());
 
                 if body.contains("\"errors\":true") {
-                    RetryAction::DontRetry(get_error_reason(&body))
+                    let retriable = serde_json::from_slice::<EsResultResponse>(response.body())
+                        .map(|resp| {
+                            resp.items
+                                .iter()
+                                .filter(|item| item.result().is_retriable())
+                                .count()
+                        })
+                        .unwrap_or(0);
+                    if retriable > 0 {
+                        RetryAction::Retry(format!(
+                            "{} documents failed with a retriable error",
+                            retriable
+                        ))
+                    } else {
+                        RetryAction::DontRetry(get_error_reason(&body))
+                    }
                 } else {
                     RetryAction::Successful
                 }
@@ -98,6 +134,7 @@ impl ServiceLogic for ElasticSearchServiceLogic {
                     let body = String::from_utf8_lossy(response.body());
                     if body.contains("\"errors\":true") {
                         error!(message = "Response contained errors.", ?response);
+                        emit_document_failures(response.body());
                         EventStatus::Failed
                     } else {
                         trace!(message = "Response successful.", ?response);
@@ -119,13 +156,31 @@ impl ServiceLogic for ElasticSearchServiceLogic {
     }
 }
 
+fn emit_document_failures(body: &[u8]) {
+    if let Ok(resp) = serde_json::from_slice::<EsResultResponse>(body) {
+        for result in resp.items.iter().map(EsResultItem::result) {
+            if let Some(error) = &result.error {
+                emit!(ElasticSearchDocumentFailed {
+                    error_type: &error.err_type,
+                    reason: &error.reason,
+                    status: result.status,
+                });
+            }
+        }
+    }
+}
+
 fn get_error_reason(body: &str) -> String {
     match serde_json::from_str::<EsResultResponse>(body) {
         Err(json_error) => format!(
             "some messages failed, could not parse response, error: {}",
             json_error
         ),
-        Ok(resp) => match resp.items.into_iter().find_map(|item| item.result().error) {
+        Ok(resp) => match resp
+            .items
+            .iter()
+            .find_map(|item| item.result().error.as_ref())
+        {
             Some(error) => format!("error type: {}, reason: {}", error.err_type, error.reason),
             None => format!("error response: {}", body),
         },
@@ -151,6 +206,20 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn retries_rejected_documents() {
+        let json = r#"{"took":3,"errors":true,"items":[{"index":{"_index":"test","_id":"1","status":201}},{"index":{"_index":"test","_id":"2","status":429,"error":{"type":"es_rejected_execution_exception","reason":"rejected execution"}}}]}"#;
+        let response = http::Response::builder()
+            .status(StatusCode::OK)
+            .body(Bytes::from(json))
+            .unwrap();
+        let logic = ElasticSearchRetryLogic;
+        assert!(matches!(
+            logic.should_retry_response(&response),
+            RetryAction::Retry(_)
+        ));
+    }
+
     #[test]
     fn get_index_error_reason() {
         let json = "{\"took\":185,\"errors\":true,\"items\":[{\"index\":{\"_index\":\"test-hgw28jv10u\",\"_type\":\"log_lines\",\"_id\":\"3GhQLXEBE62DvOOUKdFH\",\"status\":400,\"error\":{\"type\":\"illegal_argument_exception\",\"reason\":\"mapper [message] of different type, current_type [long], merged_type [text]\"}}}]}";
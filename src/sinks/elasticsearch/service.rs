@@ -0,0 +1,196 @@
+use super::{retry::is_retriable_item, ElasticSearchCommon};
+use crate::{
+    http::HttpClient,
+    sinks::util::http::{HttpBatchService, HttpSink},
+};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+use std::{
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tower::Service;
+
+/// A bulk request, along with the results of its documents so far.
+///
+/// The retry layer sends clones of the request, which share these results:
+/// documents that were indexed or failed for good aren't sent again, only the
+/// ones rejected with a retriable error are.
+#[derive(Clone, Debug)]
+pub(super) struct BulkRequest {
+    state: Arc<Mutex<BulkState>>,
+}
+
+#[derive(Debug)]
+struct BulkState {
+    /// The action and source lines of each document.
+    documents: Vec<Bytes>,
+    /// The latest item of the bulk responses for each document.
+    results: Vec<Option<Value>>,
+}
+
+impl BulkState {
+    fn pending(&self) -> impl Iterator<Item = usize> + '_ {
+        self.results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| result.as_ref().map_or(true, is_retriable_item))
+            .map(|(index, _)| index)
+    }
+}
+
+impl BulkRequest {
+    pub(super) fn new(body: Vec<u8>) -> Self {
+        let body = Bytes::from(body);
+        let mut documents = Vec::new();
+        let mut start = 0;
+        // Each document is an action line followed by a source line.
+        let ends = body
+            .iter()
+            .enumerate()
+            .filter(|(_, byte)| **byte == b'\n')
+            .map(|(index, _)| index + 1)
+            .skip(1)
+            .step_by(2);
+        for end in ends {
+            documents.push(body.slice(start..end));
+            start = end;
+        }
+        if start < body.len() {
+            documents.push(body.slice(start..));
+        }
+
+        let results = vec![None; documents.len()];
+        Self {
+            state: Arc::new(Mutex::new(BulkState { documents, results })),
+        }
+    }
+
+    /// Returns the body of the documents left to index.
+    fn body(&self) -> Vec<u8> {
+        let state = self.state.lock().unwrap();
+        state
+            .pending()
+            .flat_map(|index| state.documents[index].iter().copied())
+            .collect()
+    }
+
+    /// Records the results of the documents sent, and returns the response
+    /// with the results of all the documents of the request, so that it
+    /// reflects the whole request to the retry and service logic.
+    fn record(&self, response: http::Response<Bytes>) -> http::Response<Bytes> {
+        if !response.status().is_success() {
+            return response;
+        }
+        let mut body = match serde_json::from_slice::<Value>(response.body()) {
+            Ok(body) => body,
+            Err(_) => return response,
+        };
+        let items = match body.get_mut("items").map(Value::take) {
+            Some(Value::Array(items)) => items,
+            _ => return response,
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let pending = state.pending().collect::<Vec<_>>();
+        if pending.len() != items.len() {
+            return response;
+        }
+        for (index, item) in pending.into_iter().zip(items) {
+            state.results[index] = Some(item);
+        }
+
+        let results = state.results.iter().flatten().collect::<Vec<_>>();
+        let errors = results.iter().any(|item| {
+            item.as_object()
+                .and_then(|item| item.values().next())
+                .map_or(false, |result| result.get("error").is_some())
+        });
+        let body = json!({
+            "took": body.get("took"),
+            "errors": errors,
+            "items": results,
+        });
+
+        let (parts, _) = response.into_parts();
+        http::Response::from_parts(parts, Bytes::from(serde_json::to_vec(&body).unwrap()))
+    }
+}
+
+type RequestBuilder = BoxFuture<'static, crate::Result<http::Request<Vec<u8>>>>;
+
+#[derive(Clone)]
+pub(super) struct ElasticSearchService {
+    batch_service: HttpBatchService<RequestBuilder>,
+}
+
+impl ElasticSearchService {
+    pub(super) fn new(client: HttpClient, common: Arc<ElasticSearchCommon>) -> Self {
+        let batch_service = HttpBatchService::new(client, move |body| -> RequestBuilder {
+            let common = Arc::clone(&common);
+            Box::pin(async move { common.build_request(body).await })
+        });
+        Self { batch_service }
+    }
+}
+
+impl Service<BulkRequest> for ElasticSearchService {
+    type Response = http::Response<Bytes>;
+    type Error = crate::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.batch_service.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: BulkRequest) -> Self::Future {
+        let response = self.batch_service.call(request.body());
+        Box::pin(async move { Ok(request.record(response.await?)) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(items: Value) -> http::Response<Bytes> {
+        let body = json!({ "took": 1, "errors": true, "items": items });
+        http::Response::new(Bytes::from(serde_json::to_vec(&body).unwrap()))
+    }
+
+    fn item(id: &str, status: u16) -> Value {
+        if status < 300 {
+            json!({ "index": { "_id": id, "status": status } })
+        } else {
+            json!({ "index": { "_id": id, "status": status, "error": { "type": "error", "reason": "failed" } } })
+        }
+    }
+
+    #[test]
+    fn sends_only_retriable_documents_again() {
+        let body =
+            "{\"index\":{}}\n{\"a\":1}\n{\"index\":{}}\n{\"b\":2}\n{\"index\":{}}\n{\"c\":3}\n";
+        let request = BulkRequest::new(body.into());
+        assert_eq!(request.body(), body.as_bytes());
+
+        let merged = request.record(response(json!([
+            item("a", 201),
+            item("b", 429),
+            item("c", 400)
+        ])));
+        assert_eq!(request.body(), "{\"index\":{}}\n{\"b\":2}\n".as_bytes());
+        let merged: Value = serde_json::from_slice(merged.body()).unwrap();
+        assert_eq!(merged["errors"], true);
+        assert_eq!(merged["items"].as_array().unwrap().len(), 3);
+
+        // The retry shares the results of the first attempt.
+        let retry = request.clone();
+        let merged = retry.record(response(json!([item("b", 201)])));
+        assert!(retry.body().is_empty());
+        let merged: Value = serde_json::from_slice(merged.body()).unwrap();
+        assert_eq!(merged["items"][1], item("b", 201));
+        assert_eq!(merged["items"][2], item("c", 400));
+        assert_eq!(merged["errors"], true);
+    }
+}
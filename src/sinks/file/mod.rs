@@ -20,16 +20,28 @@ use futures::{
     FutureExt,
 };
 use serde::{Deserialize, Serialize};
-use std::time::{Duration, Instant};
+use snafu::Snafu;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant, SystemTime},
+};
 
 use tokio::{
     fs::{self, File},
     io::AsyncWriteExt,
 };
 mod bytes_path;
+mod rotation;
 use bytes_path::BytesPath;
+pub use rotation::RotationConfig;
 use std::convert::TryFrom;
 
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("Rotated files can't be compressed if `compression` is already enabled"))]
+    DoubleCompression,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct FileSinkConfig {
@@ -41,6 +53,7 @@ pub struct FileSinkConfig {
         skip_serializing_if = "crate::serde::skip_serializing_if_default"
     )]
     pub compression: Compression,
+    pub rotation: Option<RotationConfig>,
 }
 
 inventory::submit! {
@@ -54,6 +67,7 @@ impl GenerateConfig for FileSinkConfig {
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
             compression: Default::default(),
+            rotation: None,
         })
         .unwrap()
     }
@@ -121,6 +135,28 @@ impl OutFile {
     }
 }
 
+/// How often the age of the open files is checked.
+const ROTATION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An open file, along with what's needed to tell when to rotate it.
+struct OpenFile {
+    out: OutFile,
+    size: u64,
+    created_at: SystemTime,
+}
+
+impl OpenFile {
+    async fn close(&mut self) -> Result<(), std::io::Error> {
+        self.out.close().await
+    }
+
+    fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.created_at)
+            .unwrap_or_default()
+    }
+}
+
 #[async_trait::async_trait]
 #[typetag::serde(name = "file")]
 impl SinkConfig for FileSinkConfig {
@@ -128,6 +164,12 @@ impl SinkConfig for FileSinkConfig {
         &self,
         cx: SinkContext,
     ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        if let Some(rotation) = &self.rotation {
+            if rotation.compression != Compression::None && self.compression != Compression::None {
+                return Err(BuildError::DoubleCompression.into());
+            }
+        }
+
         let sink = FileSink::new(self, cx.acker());
         Ok((
             super::VectorSink::Stream(Box::new(sink)),
@@ -150,8 +192,11 @@ pub struct FileSink {
     path: Template,
     encoding: EncodingConfig<Encoding>,
     idle_timeout: Duration,
-    files: ExpiringHashMap<Bytes, OutFile>,
+    files: ExpiringHashMap<Bytes, OpenFile>,
+    /// When the files were created, for the filesystems that don't record it.
+    created: HashMap<Bytes, SystemTime>,
     compression: Compression,
+    rotation: Option<RotationConfig>,
 }
 
 impl FileSink {
@@ -162,7 +207,9 @@ impl FileSink {
             encoding: config.encoding.clone(),
             idle_timeout: Duration::from_secs(config.idle_timeout_secs.unwrap_or(30)),
             files: ExpiringHashMap::default(),
+            created: HashMap::new(),
             compression: config.compression,
+            rotation: config.rotation.clone(),
         }
     }
 
@@ -191,6 +238,11 @@ impl FileSink {
     }
 
     async fn run(&mut self, mut input: BoxStream<'_, Event>) -> crate::Result<()> {
+        let check_age = self
+            .rotation
+            .as_ref()
+            .map_or(false, |rotation| rotation.max_age_secs.is_some());
+        let mut rotation_check = tokio::time::interval(ROTATION_CHECK_INTERVAL);
         loop {
             tokio::select! {
                 event = input.next() => {
@@ -243,6 +295,7 @@ impl FileSink {
                         ),
                     }
                 }
+                _ = rotation_check.tick(), if check_age => self.rotate_due_files().await,
             }
         }
 
@@ -264,52 +317,137 @@ impl FileSink {
         let next_deadline = self.deadline_at();
         trace!(message = "Computed next deadline.", next_deadline = ?next_deadline, path = ?path);
 
-        let file = if let Some(file) = self.files.reset_at(&path, next_deadline) {
+        // A file opened again may be due for rotation already, as its age
+        // counts from when it was created.
+        if !self.open_file(&path, next_deadline).await {
+            return;
+        }
+        if self.is_rotation_due(&path) {
+            self.rotate_file(&path).await;
+            if !self.open_file(&path, next_deadline).await {
+                return;
+            }
+        }
+        let file = self.files.reset_at(&path, next_deadline).unwrap();
+
+        trace!(message = "Writing an event to file.", path = ?path);
+        match write_event_to_file(&mut file.out, event, &self.encoding).await {
+            Ok(written) => file.size += written as u64,
+            Err(error) => error!(message = "Failed to write file.", path = ?path, %error),
+        }
+    }
+
+    /// Opens the file at `path` unless it's already open, returning whether
+    /// it's open.
+    async fn open_file(&mut self, path: &Bytes, deadline: Instant) -> bool {
+        if self.files.get(path).is_some() {
             trace!(message = "Working with an already opened file.", path = ?path);
-            file
-        } else {
-            trace!(message = "Opening new file.", ?path);
-            let file = match open_file(BytesPath::new(path.clone())).await {
-                Ok(file) => file,
-                Err(error) => {
-                    // We couldn't open the file for this event.
-                    // Maybe other events will work though! Just log
-                    // the error and skip this event.
-                    error!(message = "Unable to open the file.", path = ?path, %error);
-                    return;
-                }
-            };
+            return true;
+        }
 
-            let outfile = OutFile::new(file, self.compression);
+        trace!(message = "Opening new file.", ?path);
+        let (file, size, created_at) = match open_file(BytesPath::new(path.clone())).await {
+            Ok(file) => file,
+            Err(error) => {
+                // We couldn't open the file for this event.
+                // Maybe other events will work though! Just log
+                // the error and skip this event.
+                error!(message = "Unable to open the file.", path = ?path, %error);
+                return false;
+            }
+        };
+        let created_at = match created_at {
+            Some(created_at) => created_at,
+            None => *self
+                .created
+                .entry(path.clone())
+                .or_insert_with(SystemTime::now),
+        };
+
+        let file = OpenFile {
+            out: OutFile::new(file, self.compression),
+            size,
+            created_at,
+        };
+
+        self.files.insert_at(path.clone(), file, deadline);
+        emit!(FileOpen {
+            count: self.files.len()
+        });
+        true
+    }
+
+    fn is_rotation_due(&self, path: &Bytes) -> bool {
+        match (&self.rotation, self.files.get(path)) {
+            (Some(rotation), Some(file)) => rotation.is_due(file.size, file.age()),
+            _ => false,
+        }
+    }
+
+    /// Rotates the open files that are due for it, so that files that aren't
+    /// written to are still rotated once old enough.
+    async fn rotate_due_files(&mut self) {
+        let rotation = match &self.rotation {
+            Some(rotation) => rotation,
+            None => return,
+        };
+        let due = self
+            .files
+            .iter_mut()
+            .filter(|(_, file)| rotation.is_due(file.size, file.age()))
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        for path in due {
+            self.rotate_file(&path).await;
+        }
+    }
 
-            self.files.insert_at(path.clone(), outfile, next_deadline);
+    /// Closes the file at `path`, and rotates it. The next event written to
+    /// `path` opens a new file.
+    async fn rotate_file(&mut self, path: &Bytes) {
+        let rotation = match &self.rotation {
+            Some(rotation) => rotation,
+            None => return,
+        };
+        if let Some((mut file, _)) = self.files.remove(path) {
+            if let Err(error) = file.close().await {
+                error!(message = "Failed to close file.", path = ?path, %error);
+            }
             emit!(FileOpen {
                 count: self.files.len()
             });
-            self.files.get_mut(&path).unwrap()
-        };
+        }
 
-        trace!(message = "Writing an event to file.", path = ?path);
-        if let Err(error) = write_event_to_file(file, event, &self.encoding).await {
-            error!(message = "Failed to write file.", path = ?path, %error);
+        self.created.remove(path);
+
+        debug!(message = "Rotating file.", path = ?path);
+        if let Err(error) = rotation::rotate(BytesPath::new(path.clone()).as_ref(), rotation).await
+        {
+            error!(message = "Failed to rotate file.", path = ?path, %error);
         }
     }
 }
 
-async fn open_file(path: impl AsRef<std::path::Path>) -> std::io::Result<File> {
+/// Opens the file at `path` for appending, returning it along with its size
+/// and, if the filesystem records it, its creation time.
+async fn open_file(
+    path: impl AsRef<std::path::Path>,
+) -> std::io::Result<(File, u64, Option<SystemTime>)> {
     let parent = path.as_ref().parent();
 
     if let Some(parent) = parent {
         fs::create_dir_all(parent).await?;
     }
 
-    fs::OpenOptions::new()
+    let file = fs::OpenOptions::new()
         .read(false)
         .write(true)
         .create(true)
         .append(true)
         .open(path)
-        .await
+        .await?;
+    let metadata = file.metadata().await?;
+    Ok((file, metadata.len(), metadata.created().ok()))
 }
 
 pub fn encode_event(encoding: &EncodingConfig<Encoding>, mut event: Event) -> Vec<u8> {
@@ -328,10 +466,11 @@ async fn write_event_to_file(
     file: &mut OutFile,
     event: Event,
     encoding: &EncodingConfig<Encoding>,
-) -> Result<(), std::io::Error> {
+) -> Result<usize, std::io::Error> {
     let mut buf = encode_event(encoding, event);
     buf.push(b'\n');
-    file.write_all(&buf[..]).await
+    file.write_all(&buf[..]).await?;
+    Ok(buf.len())
 }
 
 #[async_trait]
@@ -368,6 +507,7 @@ mod tests {
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
             compression: Compression::None,
+            rotation: None,
         };
 
         let mut sink = FileSink::new(&config, Acker::Null);
@@ -393,6 +533,7 @@ mod tests {
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
             compression: Compression::Gzip,
+            rotation: None,
         };
 
         let mut sink = FileSink::new(&config, Acker::Null);
@@ -407,6 +548,117 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn rotation_by_size() {
+        trace_init();
+
+        let template = temp_file();
+
+        let config = FileSinkConfig {
+            path: template.clone().try_into().unwrap(),
+            idle_timeout_secs: None,
+            encoding: Encoding::Text.into(),
+            compression: Compression::None,
+            rotation: Some(RotationConfig {
+                max_file_size: Some(30),
+                compression: Compression::Gzip,
+                max_files: Some(2),
+                ..Default::default()
+            }),
+        };
+
+        let mut sink = FileSink::new(&config, Acker::Null);
+        let input = (0..8).map(|i| format!("line {}", i)).collect::<Vec<_>>();
+
+        let events = Box::pin(stream::iter(input.clone().into_iter().map(Event::from)));
+        sink.run(events).await.unwrap();
+
+        // The file reaches 30 bytes after 5 lines of 7 bytes, and is rotated
+        // before the next one is written.
+        let rotated = |index| {
+            let mut path = template.clone().into_os_string();
+            path.push(format!(".{}.gz", index));
+            std::path::PathBuf::from(path)
+        };
+        assert_eq!(lines_from_file(&template), input[5..].to_vec());
+        assert_eq!(lines_from_gzip_file(rotated(1)), input[..5].to_vec());
+        assert!(!rotated(2).exists());
+    }
+
+    #[tokio::test]
+    async fn rotation_by_age() {
+        trace_init();
+
+        let template = temp_file();
+
+        let config = FileSinkConfig {
+            path: template.clone().try_into().unwrap(),
+            idle_timeout_secs: Some(1),
+            encoding: Encoding::Text.into(),
+            compression: Compression::None,
+            rotation: Some(RotationConfig {
+                max_age_secs: Some(2),
+                ..Default::default()
+            }),
+        };
+
+        let mut sink = FileSink::new(&config, Acker::Null);
+        let (mut tx, rx) = futures::channel::mpsc::channel(1);
+        let run = tokio::spawn(async move { sink.run(Box::pin(rx)).await });
+
+        // The file is closed while idle, which doesn't reset its age, and is
+        // rotated even though nothing is written to it anymore.
+        tx.send(Event::from("line 0")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        tx.send(Event::from("line 1")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(2500)).await;
+
+        let mut rotated = template.clone().into_os_string();
+        rotated.push(".1");
+        assert_eq!(
+            lines_from_file(std::path::PathBuf::from(rotated)),
+            vec!["line 0".to_owned(), "line 1".to_owned()]
+        );
+
+        drop(tx);
+        run.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn rotation_retention() {
+        trace_init();
+
+        let template = temp_file();
+
+        let config = FileSinkConfig {
+            path: template.clone().try_into().unwrap(),
+            idle_timeout_secs: None,
+            encoding: Encoding::Text.into(),
+            compression: Compression::None,
+            rotation: Some(RotationConfig {
+                max_file_size: Some(1),
+                max_files: Some(2),
+                ..Default::default()
+            }),
+        };
+
+        let mut sink = FileSink::new(&config, Acker::Null);
+        let input = (0..5).map(|i| format!("line {}", i)).collect::<Vec<_>>();
+
+        let events = Box::pin(stream::iter(input.clone().into_iter().map(Event::from)));
+        sink.run(events).await.unwrap();
+
+        let rotated = |index| {
+            let mut path = template.clone().into_os_string();
+            path.push(format!(".{}", index));
+            std::path::PathBuf::from(path)
+        };
+        assert_eq!(lines_from_file(&template), vec!["line 4".to_owned()]);
+        assert_eq!(lines_from_file(rotated(1)), vec!["line 3".to_owned()]);
+        assert_eq!(lines_from_file(rotated(2)), vec!["line 2".to_owned()]);
+        assert!(!rotated(3).exists());
+    }
+
     #[tokio::test]
     async fn many_partitions() {
         trace_init();
@@ -423,6 +675,7 @@ mod tests {
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
             compression: Compression::None,
+            rotation: None,
         };
 
         let mut sink = FileSink::new(&config, Acker::Null);
@@ -504,6 +757,7 @@ mod tests {
             idle_timeout_secs: Some(1),
             encoding: Encoding::Text.into(),
             compression: Compression::None,
+            rotation: None,
         };
 
         let mut sink = FileSink::new(&config, Acker::Null);
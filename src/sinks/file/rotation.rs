@@ -0,0 +1,116 @@
+//! Rotation of the files written by the sink, logrotate style: the current
+//! file is renamed to `<path>.1`, and previously rotated files are shifted to
+//! the next number.
+
+use super::Compression;
+use async_compression::tokio::write::GzipEncoder;
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::{
+    fs,
+    io::{self, AsyncWriteExt},
+};
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RotationConfig {
+    pub max_file_size: Option<u64>,
+    pub max_age_secs: Option<u64>,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub compression: Compression,
+    pub max_files: Option<usize>,
+}
+
+impl RotationConfig {
+    /// Returns whether a file of `size` bytes, created `age` ago, is due for
+    /// rotation.
+    pub fn is_due(&self, size: u64, age: Duration) -> bool {
+        self.max_file_size.map_or(false, |max| size >= max)
+            || self
+                .max_age_secs
+                .map_or(false, |max| age >= Duration::from_secs(max))
+    }
+}
+
+fn rotated_path(path: &Path, index: usize, compression: Compression) -> PathBuf {
+    let mut rotated = OsString::from(path.as_os_str());
+    rotated.push(format!(".{}", index));
+    if compression == Compression::Gzip {
+        rotated.push(".gz");
+    }
+    rotated.into()
+}
+
+async fn find_rotated(path: &Path, index: usize) -> Option<Compression> {
+    for compression in &[Compression::None, Compression::Gzip] {
+        if fs::metadata(rotated_path(path, index, *compression))
+            .await
+            .is_ok()
+        {
+            return Some(*compression);
+        }
+    }
+    None
+}
+
+/// Rotates the closed file at `path`, removing the rotated files beyond
+/// `max_files`, and compresses it if configured to.
+pub async fn rotate(path: &Path, config: &RotationConfig) -> io::Result<()> {
+    let mut rotated = Vec::new();
+    while let Some(compression) = find_rotated(path, rotated.len() + 1).await {
+        rotated.push(compression);
+    }
+
+    let max_files = config.max_files.unwrap_or(usize::MAX);
+    for (index, compression) in rotated.into_iter().enumerate().rev() {
+        let from = rotated_path(path, index + 1, compression);
+        if index + 1 >= max_files {
+            fs::remove_file(from).await?;
+        } else {
+            fs::rename(from, rotated_path(path, index + 2, compression)).await?;
+        }
+    }
+
+    if max_files == 0 {
+        fs::remove_file(path).await?;
+    } else {
+        let first = rotated_path(path, 1, Compression::None);
+        fs::rename(path, &first).await?;
+        if config.compression == Compression::Gzip {
+            compress(&first, &rotated_path(path, 1, Compression::Gzip)).await?;
+        }
+    }
+
+    sync_dir(path).await
+}
+
+async fn compress(from: &Path, to: &Path) -> io::Result<()> {
+    let mut input = fs::File::open(from).await?;
+    let mut output = GzipEncoder::new(fs::File::create(to).await?);
+    io::copy(&mut input, &mut output).await?;
+    output.shutdown().await?;
+    output.get_mut().sync_all().await?;
+    fs::remove_file(from).await
+}
+
+/// Syncs the directory of `path`, so that the renames survive a crash.
+#[cfg(unix)]
+async fn sync_dir(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::File::open(dir).await?.sync_all().await
+}
+
+#[cfg(not(unix))]
+async fn sync_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
@@ -8,6 +8,7 @@ use crate::{
     sinks::{
         util::{
             batch::{BatchConfig, BatchSettings},
+            buffer::parquet::{self, ParquetBuffer, ParquetConfig, ParquetService},
             encoding::{EncodingConfig, EncodingConfiguration},
             retries::{RetryAction, RetryLogic},
             Buffer, Compression, Concurrency, EncodedEvent, PartitionBatchSink, PartitionBuffer,
@@ -63,6 +64,11 @@ pub struct GcsSinkConfig {
     filename_append_uuid: Option<bool>,
     filename_extension: Option<String>,
     encoding: EncodingConfig<Encoding>,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    parquet: ParquetConfig,
     #[serde(default)]
     compression: Compression,
     #[serde(default)]
@@ -86,6 +92,7 @@ fn default_config(e: Encoding) -> GcsSinkConfig {
         filename_append_uuid: Default::default(),
         filename_extension: Default::default(),
         encoding: e.into(),
+        parquet: Default::default(),
         compression: Compression::gzip_default(),
         batch: Default::default(),
         request: Default::default(),
@@ -123,6 +130,7 @@ enum GcsStorageClass {
 enum Encoding {
     Text,
     Ndjson,
+    Parquet,
 }
 
 impl Encoding {
@@ -130,6 +138,7 @@ impl Encoding {
         match self {
             Self::Text => "text/plain",
             Self::Ndjson => "application/x-ndjson",
+            Self::Parquet => "application/octet-stream",
         }
     }
 }
@@ -169,6 +178,16 @@ impl SinkConfig for GcsSinkConfig {
     }
 }
 
+impl GcsSinkConfig {
+    fn object_compression(&self) -> Compression {
+        // Parquet files are compressed page by page instead.
+        match self.encoding.codec() {
+            Encoding::Parquet => Compression::None,
+            _ => self.compression,
+        }
+    }
+}
+
 #[derive(Debug, Snafu)]
 enum HealthcheckError {
     #[snafu(display("Invalid credentials"))]
@@ -222,7 +241,27 @@ impl GcsSink {
             .settings(request, GcsRetryLogic)
             .service(self);
 
-        let buffer = PartitionBuffer::new(Buffer::new(batch.size, config.compression));
+        if *encoding.codec() == Encoding::Parquet {
+            let buffer =
+                PartitionBuffer::new(ParquetBuffer::new(batch.into().size, &config.parquet)?);
+            let svc = ParquetService::new(svc);
+
+            let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
+                .sink_map_err(|error| error!(message = "Fatal gcp_cloud_storage error.", %error))
+                .with_flat_map(move |event| {
+                    stream::iter(parquet::encode_event(
+                        event,
+                        &key_prefix,
+                        "key_prefix",
+                        &encoding,
+                    ))
+                    .map(Ok)
+                });
+
+            return Ok(VectorSink::Sink(Box::new(sink)));
+        }
+
+        let buffer = PartitionBuffer::new(Buffer::new(batch.size, config.object_compression()));
 
         let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
             .sink_map_err(|error| error!(message = "Fatal gcp_cloud_storage error.", %error))
@@ -351,7 +390,7 @@ impl RequestSettings {
             .map(|acl| HeaderValue::from_str(&to_string(acl)).unwrap());
         let content_type = HeaderValue::from_str(config.encoding.codec().content_type()).unwrap();
         let content_encoding = config
-            .compression
+            .object_compression()
             .content_encoding()
             .map(|ce| HeaderValue::from_str(&to_string(ce)).unwrap());
         let storage_class = config.storage_class.unwrap_or_default();
@@ -366,10 +405,14 @@ impl RequestSettings {
                     .collect::<Result<Vec<_>, _>>()
             })
             .unwrap_or_else(|| Ok(vec![]))?;
-        let extension = config
-            .filename_extension
-            .clone()
-            .unwrap_or_else(|| config.compression.extension().into());
+        let extension =
+            config
+                .filename_extension
+                .clone()
+                .unwrap_or_else(|| match config.encoding.codec() {
+                    Encoding::Parquet => "parquet".into(),
+                    _ => config.object_compression().extension().into(),
+                });
         let time_format = config
             .filename_time_format
             .clone()
@@ -428,6 +471,7 @@ fn encode_event(
             bytes.push(b'\n');
             bytes
         }
+        Encoding::Parquet => unreachable!("Parquet events are encoded by the buffer"),
     };
 
     Some(EncodedEvent::new(PartitionInnerBuffer::new(
@@ -566,4 +610,20 @@ mod tests {
         );
         assert_ne!(req.key, "key/date.log.gz".to_string());
     }
+
+    #[test]
+    fn gcs_build_parquet_request() {
+        let settings = RequestSettings::new(&GcsSinkConfig {
+            filename_time_format: Some("date".into()),
+            filename_append_uuid: Some(false),
+            ..default_config(Encoding::Parquet)
+        })
+        .unwrap();
+        assert_eq!(settings.content_type, "application/octet-stream");
+        assert_eq!(settings.content_encoding, None);
+
+        let buf = PartitionInnerBuffer::new(vec![0u8; 10], Bytes::from("key/"));
+        let req = RequestWrapper::new(buf, settings);
+        assert_eq!(req.key, "key/date.parquet".to_string());
+    }
 }
@@ -0,0 +1,409 @@
+use crate::{
+    config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::{Event, LogEvent, Value},
+    internal_events::{GelfInvalidEvent, GelfMessageTooLarge},
+    sinks::util::{tcp::TcpSinkConfig, udp::UdpSinkConfig},
+};
+use bytes::{BufMut, Bytes, BytesMut};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number};
+use snafu::Snafu;
+use std::io::Write;
+
+/// The two bytes starting the chunks of a message.
+const CHUNK_MAGIC: [u8; 2] = [0x1e, 0x0f];
+/// Magic bytes, message id, sequence number and sequence count.
+const CHUNK_HEADER_LENGTH: usize = 12;
+const MAX_CHUNKS: usize = 128;
+
+/// The fields of the GELF specification, which aren't prefixed with an
+/// underscore like additional fields.
+const STANDARD_FIELDS: [&str; 4] = ["full_message", "facility", "line", "file"];
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display(
+        "`max_chunk_size` must be larger than the {} bytes chunk header",
+        CHUNK_HEADER_LENGTH
+    ))]
+    InvalidMaxChunkSize,
+}
+
+#[derive(Debug, PartialEq, Snafu)]
+pub enum GelfEncodeError {
+    #[snafu(display("Missing or empty `{}` field", field))]
+    MissingField { field: &'static str },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum GelfSinkConfig {
+    Tcp(TcpSinkConfig),
+    Udp(GelfUdpConfig),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GelfUdpConfig {
+    #[serde(flatten)]
+    pub udp: UdpSinkConfig,
+    #[serde(default)]
+    pub compression: GelfCompression,
+    /// Messages larger than this are split into chunks of this size.
+    #[serde(default = "default_max_chunk_size")]
+    pub max_chunk_size: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Derivative, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GelfCompression {
+    None,
+    #[derivative(Default)]
+    Gzip,
+    Zlib,
+}
+
+fn default_max_chunk_size() -> usize {
+    1420
+}
+
+inventory::submit! {
+    SinkDescription::new::<GelfSinkConfig>("gelf")
+}
+
+impl GenerateConfig for GelfSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(&Self::Udp(GelfUdpConfig {
+            udp: UdpSinkConfig::from_address("127.0.0.1:12201".to_owned()),
+            compression: GelfCompression::default(),
+            max_chunk_size: default_max_chunk_size(),
+        }))
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "gelf")]
+impl SinkConfig for GelfSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        match self {
+            Self::Tcp(config) => config.build(cx, |event| {
+                let mut message = encode_event(event)?;
+                message.push(b'\0');
+                Some(message.into())
+            }),
+            Self::Udp(config) => {
+                if config.max_chunk_size <= CHUNK_HEADER_LENGTH {
+                    return Err(BuildError::InvalidMaxChunkSize.into());
+                }
+                let compression = config.compression;
+                let max_chunk_size = config.max_chunk_size;
+                config.udp.build_datagrams(cx, move |event| {
+                    encode_event(event)
+                        .map(|message| chunk(compress(message, compression), max_chunk_size))
+                        .unwrap_or_default()
+                })
+            }
+        }
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "gelf"
+    }
+}
+
+fn encode_event(event: Event) -> Option<Vec<u8>> {
+    match to_gelf(&event.into_log()) {
+        Ok(message) => Some(serde_json::to_vec(&message).expect("Serializing JSON can't fail")),
+        Err(error) => {
+            emit!(GelfInvalidEvent { error });
+            None
+        }
+    }
+}
+
+/// Maps a log event onto a GELF message. The fields of the log schema give
+/// the `short_message`, `host` and `timestamp`, and the fields which aren't
+/// part of the specification become additional fields.
+fn to_gelf(log: &LogEvent) -> Result<Map<String, serde_json::Value>, GelfEncodeError> {
+    let required = |key: &str, field: &'static str| {
+        log.get(key)
+            .map(Value::to_string_lossy)
+            .filter(|value| !value.is_empty())
+            .ok_or(GelfEncodeError::MissingField { field })
+    };
+
+    let mut message = Map::new();
+    message.insert("version".into(), "1.1".into());
+    message.insert(
+        "host".into(),
+        required(log_schema().host_key(), "host")?.into(),
+    );
+    message.insert(
+        "short_message".into(),
+        required(log_schema().message_key(), "short_message")?.into(),
+    );
+    if let Some(timestamp) = log
+        .get(log_schema().timestamp_key())
+        .and_then(Value::as_timestamp)
+    {
+        let timestamp =
+            timestamp.timestamp() as f64 + f64::from(timestamp.timestamp_subsec_millis()) / 1000.0;
+        if let Some(timestamp) = Number::from_f64(timestamp) {
+            message.insert("timestamp".into(), timestamp.into());
+        }
+    }
+
+    let schema_keys = [
+        log_schema().host_key(),
+        log_schema().message_key(),
+        log_schema().timestamp_key(),
+    ];
+    for (key, value) in log.all_fields() {
+        if schema_keys.contains(&key.as_str()) {
+            continue;
+        }
+        let value = match value {
+            Value::Integer(value) => (*value).into(),
+            Value::Float(value) => match Number::from_f64(*value) {
+                Some(value) => value.into(),
+                None => continue,
+            },
+            Value::Null => continue,
+            value => value.to_string_lossy().into(),
+        };
+        let name = match key.as_str() {
+            "level" => match syslog_level(&value) {
+                Some(level) => {
+                    message.insert("level".into(), level.into());
+                    continue;
+                }
+                None => "_level".into(),
+            },
+            key if STANDARD_FIELDS.contains(&key) => key.into(),
+            // `_id` is reserved.
+            "id" => "__id".into(),
+            key => format!("_{}", sanitize_name(key)),
+        };
+        message.insert(name, value);
+    }
+    Ok(message)
+}
+
+/// Reads the syslog level of the `level` field, given either as a number or
+/// as the name of the level.
+fn syslog_level(level: &serde_json::Value) -> Option<u64> {
+    match level {
+        serde_json::Value::Number(level) => level.as_u64().filter(|level| *level <= 7),
+        serde_json::Value::String(level) => match level.to_lowercase().as_str() {
+            "emerg" | "emergency" | "panic" => Some(0),
+            "alert" => Some(1),
+            "crit" | "critical" => Some(2),
+            "err" | "error" => Some(3),
+            "warn" | "warning" => Some(4),
+            "notice" => Some(5),
+            "info" | "informational" => Some(6),
+            "debug" => Some(7),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Replaces the characters which aren't allowed in the names of additional
+/// fields.
+fn sanitize_name(name: &str) -> String {
+    name.replace(
+        |c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')),
+        "_",
+    )
+}
+
+fn compress(message: Vec<u8>, compression: GelfCompression) -> Vec<u8> {
+    match compression {
+        GelfCompression::None => message,
+        GelfCompression::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder
+                .write_all(&message)
+                .and_then(|_| encoder.finish())
+                .expect("Writing to a Vec can't fail")
+        }
+        GelfCompression::Zlib => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder
+                .write_all(&message)
+                .and_then(|_| encoder.finish())
+                .expect("Writing to a Vec can't fail")
+        }
+    }
+}
+
+/// Splits messages larger than `max_chunk_size` into chunks sharing a random
+/// message id, dropping those needing more chunks than allowed.
+fn chunk(message: Vec<u8>, max_chunk_size: usize) -> Vec<Bytes> {
+    if message.len() <= max_chunk_size {
+        return vec![message.into()];
+    }
+
+    let payload_size = max_chunk_size - CHUNK_HEADER_LENGTH;
+    let count = (message.len() + payload_size - 1) / payload_size;
+    if count > MAX_CHUNKS {
+        emit!(GelfMessageTooLarge {
+            byte_size: message.len(),
+            max_byte_size: MAX_CHUNKS * payload_size,
+        });
+        return Vec::new();
+    }
+
+    let message_id = rand::random::<u64>();
+    message
+        .chunks(payload_size)
+        .enumerate()
+        .map(|(sequence_number, payload)| {
+            let mut chunk = BytesMut::with_capacity(CHUNK_HEADER_LENGTH + payload.len());
+            chunk.put_slice(&CHUNK_MAGIC);
+            chunk.put_u64(message_id);
+            chunk.put_u8(sequence_number as u8);
+            chunk.put_u8(count as u8);
+            chunk.put_slice(payload);
+            chunk.freeze()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<GelfSinkConfig>();
+    }
+
+    fn log() -> LogEvent {
+        let mut log = LogEvent::from("Connection refused");
+        log.insert(log_schema().host_key(), "web-1");
+        log.insert(
+            log_schema().timestamp_key(),
+            Utc.timestamp(1628000000, 250_000_000),
+        );
+        log
+    }
+
+    #[test]
+    fn maps_log_event_onto_gelf_message() {
+        let mut log = log();
+        log.insert("full_message", "Connection refused\nat db.connect()");
+        log.insert("level", "error");
+        log.insert("user.id", 42);
+        log.insert("user.name", "alice");
+        log.insert("id", "c0ffee");
+        log.insert("ratio", 0.5);
+        log.insert("ok", false);
+        log.insert("tags", vec!["a", "b"]);
+
+        assert_eq!(
+            serde_json::Value::Object(to_gelf(&log).unwrap()),
+            json!({
+                "version": "1.1",
+                "host": "web-1",
+                "short_message": "Connection refused",
+                "timestamp": 1628000000.25,
+                "full_message": "Connection refused\nat db.connect()",
+                "level": 3,
+                "_user.id": 42,
+                "_user.name": "alice",
+                "__id": "c0ffee",
+                "_ratio": 0.5,
+                "_ok": "false",
+                "_tags_0_": "a",
+                "_tags_1_": "b",
+            })
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_levels_as_additional_field() {
+        let mut log = log();
+        log.insert("level", "trace");
+        let message = to_gelf(&log).unwrap();
+        assert_eq!(message.get("level"), None);
+        assert_eq!(message["_level"], json!("trace"));
+    }
+
+    #[test]
+    fn validates_required_fields() {
+        let mut log = log();
+        log.remove(log_schema().host_key());
+        assert_eq!(
+            to_gelf(&log),
+            Err(GelfEncodeError::MissingField { field: "host" })
+        );
+
+        let mut log = self::log();
+        log.insert(log_schema().message_key(), "");
+        assert_eq!(
+            to_gelf(&log),
+            Err(GelfEncodeError::MissingField {
+                field: "short_message"
+            })
+        );
+    }
+
+    #[test]
+    fn chunks_large_messages() {
+        let message = (0..100).collect::<Vec<u8>>();
+        assert_eq!(
+            chunk(message.clone(), 100),
+            vec![Bytes::from(message.clone())]
+        );
+
+        let chunks = chunk(message.clone(), 52);
+        assert_eq!(chunks.len(), 3);
+        let message_id = &chunks[0][2..10];
+        let mut payload = Vec::new();
+        for (sequence_number, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk[..2], CHUNK_MAGIC);
+            assert_eq!(&chunk[2..10], message_id);
+            assert_eq!(chunk[10..12], [sequence_number as u8, 3]);
+            payload.extend_from_slice(&chunk[12..]);
+        }
+        assert_eq!(payload, message);
+
+        assert!(chunk(vec![0; 129 * 40], 52).is_empty());
+    }
+
+    #[test]
+    fn compresses_messages() {
+        use flate2::read::{GzDecoder, ZlibDecoder};
+        use std::io::Read;
+
+        let message = serde_json::to_vec(&to_gelf(&log()).unwrap()).unwrap();
+        assert_eq!(compress(message.clone(), GelfCompression::None), message);
+
+        let mut decompressed = Vec::new();
+        let compressed = compress(message.clone(), GelfCompression::Gzip);
+        GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, message);
+
+        let mut decompressed = Vec::new();
+        let compressed = compress(message.clone(), GelfCompression::Zlib);
+        ZlibDecoder::new(compressed.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, message);
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::metric::{MetricTags, MetricValue},
+    event::Event,
+    internal_events::{GraphiteInvalidMetricReceived, TemplateRenderingFailed},
+    sinks::util::{encode_namespace, tcp::TcpSinkConfig, udp::UdpSinkConfig},
+    template::Template,
+};
+use bytes::Bytes;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+// TODO: add back when serde-rs/serde#1358 is addressed
+// #[serde(deny_unknown_fields)]
+pub struct GraphiteSinkConfig {
+    #[serde(alias = "namespace")]
+    pub default_namespace: Option<String>,
+    /// Renders the path of the metrics, which defaults to their namespace
+    /// and name joined by dots.
+    pub path: Option<Template>,
+    /// Appends the tags to the path as `;name=value`, as understood by
+    /// Graphite 1.1 and later.
+    #[serde(default = "crate::serde::default_true")]
+    pub tagged_series: bool,
+    #[serde(flatten)]
+    pub mode: Mode,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Mode {
+    Tcp(TcpSinkConfig),
+    Udp(UdpSinkConfig),
+}
+
+inventory::submit! {
+    SinkDescription::new::<GraphiteSinkConfig>("graphite")
+}
+
+impl GenerateConfig for GraphiteSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(&Self {
+            default_namespace: None,
+            path: None,
+            tagged_series: true,
+            mode: Mode::Tcp(TcpSinkConfig::from_address("127.0.0.1:2003".to_owned())),
+        })
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "graphite")]
+impl SinkConfig for GraphiteSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        let encoder = GraphiteEncoder {
+            default_namespace: self.default_namespace.clone(),
+            path: self.path.clone(),
+            tagged_series: self.tagged_series,
+        };
+        let encode_event = move |event: Event| encoder.encode_event(event);
+        match &self.mode {
+            Mode::Tcp(config) => config.build(cx, encode_event),
+            Mode::Udp(config) => config.build(cx, encode_event),
+        }
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Metric
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "graphite"
+    }
+}
+
+struct GraphiteEncoder {
+    default_namespace: Option<String>,
+    path: Option<Template>,
+    tagged_series: bool,
+}
+
+impl GraphiteEncoder {
+    /// Renders a metric as Carbon plaintext lines. Aggregated histograms and
+    /// summaries become a `count` and a `sum` line.
+    fn encode_event(&self, event: Event) -> Option<Bytes> {
+        let path = match &self.path {
+            Some(template) => template
+                .render_string(&event)
+                .map_err(|error| {
+                    emit!(TemplateRenderingFailed {
+                        error,
+                        field: Some("path"),
+                        drop_event: true,
+                    })
+                })
+                .ok()?,
+            None => {
+                let metric = event.as_metric();
+                encode_namespace(
+                    metric
+                        .namespace()
+                        .or_else(|| self.default_namespace.as_deref()),
+                    '.',
+                    metric.name(),
+                )
+            }
+        };
+
+        let metric = event.into_metric();
+        let values = match metric.value() {
+            MetricValue::Counter { value } | MetricValue::Gauge { value } => vec![(None, *value)],
+            MetricValue::Set { values } => vec![(None, values.len() as f64)],
+            MetricValue::AggregatedHistogram { count, sum, .. }
+            | MetricValue::AggregatedSummary { count, sum, .. } => {
+                vec![(Some("count"), f64::from(*count)), (Some("sum"), *sum)]
+            }
+            MetricValue::Distribution { .. } => {
+                emit!(GraphiteInvalidMetricReceived {
+                    value: metric.value(),
+                    kind: &metric.kind(),
+                });
+                return None;
+            }
+        };
+
+        let path = sanitize(&path, |c| c.is_whitespace() || c == ';');
+        let tags = match metric.tags() {
+            Some(tags) if self.tagged_series => encode_tags(tags),
+            _ => String::new(),
+        };
+        let timestamp = metric.timestamp().unwrap_or_else(Utc::now).timestamp();
+
+        let mut body = String::new();
+        for (suffix, value) in values {
+            let suffix = suffix.map(|s| format!(".{}", s)).unwrap_or_default();
+            body.push_str(&format!(
+                "{}{}{} {} {}\n",
+                path, suffix, tags, value, timestamp
+            ));
+        }
+        Some(body.into())
+    }
+}
+
+/// Encodes the tags as `;name=value`, leaving out those without a value as
+/// Graphite doesn't accept them.
+fn encode_tags(tags: &MetricTags) -> String {
+    tags.iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(name, value)| {
+            format!(
+                ";{}={}",
+                sanitize(name, |c| c.is_whitespace()
+                    || matches!(c, ';' | '!' | '^' | '=')),
+                sanitize(value, |c| c.is_whitespace() || matches!(c, ';' | '~')),
+            )
+        })
+        .collect()
+}
+
+fn sanitize(s: &str, invalid: impl Fn(char) -> bool) -> String {
+    s.replace(invalid, "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::metric::{Metric, MetricKind, StatisticKind};
+    use chrono::{DateTime, TimeZone};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<GraphiteSinkConfig>();
+    }
+
+    fn encoder(path: Option<&str>, tagged_series: bool) -> GraphiteEncoder {
+        GraphiteEncoder {
+            default_namespace: Some("vector".into()),
+            path: path.map(|path| Template::try_from(path).unwrap()),
+            tagged_series,
+        }
+    }
+
+    fn timestamp() -> DateTime<Utc> {
+        Utc.timestamp(1628000000, 0)
+    }
+
+    fn gauge() -> Metric {
+        Metric::new(
+            "cpu load",
+            MetricKind::Absolute,
+            MetricValue::Gauge { value: 0.5 },
+        )
+        .with_tags(Some(
+            vec![
+                ("host".to_owned(), "web-1".to_owned()),
+                ("dc;zone".to_owned(), "us east".to_owned()),
+                ("empty".to_owned(), "".to_owned()),
+            ]
+            .into_iter()
+            .collect(),
+        ))
+        .with_timestamp(Some(timestamp()))
+    }
+
+    fn encode(encoder: &GraphiteEncoder, metric: Metric) -> Option<String> {
+        encoder
+            .encode_event(metric.into())
+            .map(|bytes| String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn encodes_tagged_series() {
+        assert_eq!(
+            encode(&encoder(None, true), gauge()),
+            Some("vector.cpu_load;dc_zone=us_east;host=web-1 0.5 1628000000\n".into())
+        );
+    }
+
+    #[test]
+    fn encodes_templated_path() {
+        assert_eq!(
+            encode(
+                &encoder(Some("servers.{{ tags.host }}.{{ name }}"), false),
+                gauge()
+            ),
+            Some("servers.web-1.cpu_load 0.5 1628000000\n".into())
+        );
+        assert_eq!(
+            encode(&encoder(Some("servers.{{ tags.missing }}"), false), gauge()),
+            None
+        );
+    }
+
+    #[test]
+    fn encodes_aggregated_values() {
+        let histogram = Metric::new(
+            "latency",
+            MetricKind::Absolute,
+            MetricValue::AggregatedHistogram {
+                buckets: vector_core::buckets![1.0 => 2],
+                count: 2,
+                sum: 1.5,
+            },
+        )
+        .with_namespace(Some("http"))
+        .with_timestamp(Some(timestamp()));
+        assert_eq!(
+            encode(&encoder(None, true), histogram),
+            Some("http.latency.count 2 1628000000\nhttp.latency.sum 1.5 1628000000\n".into())
+        );
+
+        let distribution = Metric::new(
+            "latency",
+            MetricKind::Incremental,
+            MetricValue::Distribution {
+                samples: vector_core::samples![1.0 => 1],
+                statistic: StatisticKind::Histogram,
+            },
+        );
+        assert_eq!(encode(&encoder(None, true), distribution), None);
+    }
+
+    #[cfg(feature = "sources-graphite")]
+    #[test]
+    fn roundtrips_through_source_parser() {
+        use crate::sources::graphite::parser::Parser;
+        use shared::assert_event_data_eq;
+
+        let metric = Metric::new(
+            "load",
+            MetricKind::Absolute,
+            MetricValue::Gauge { value: 0.5 },
+        )
+        .with_namespace(Some("cpu"))
+        .with_tags(Some(
+            vec![("host".to_owned(), "web-1".to_owned())]
+                .into_iter()
+                .collect(),
+        ))
+        .with_timestamp(Some(timestamp()));
+        let line = encode(&encoder(None, true), metric.clone()).unwrap();
+
+        let parser = Parser::new(&["namespace.name".to_owned()], ".").unwrap();
+        assert_event_data_eq!(parser.parse(&line), Ok(metric));
+    }
+}
@@ -2,20 +2,22 @@ use crate::{
     config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
     event::Event,
     http::{Auth, HttpClient, MaybeAuth},
-    internal_events::{HttpEventEncoded, HttpEventMissingMessage},
+    internal_events::{HttpEventEncoded, HttpEventMissingMessage, TemplateRenderingFailed},
     sinks::util::{
         buffer::compression::GZIP_DEFAULT,
         encoding::{EncodingConfig, EncodingConfiguration},
-        http::{BatchedHttpSink, HttpSink, RequestConfig},
-        BatchConfig, BatchSettings, Buffer, Compression, TowerRequestConfig, UriSerde,
+        http::{HttpSink, PartitionHttpSink, RequestConfig},
+        BatchConfig, BatchSettings, Buffer, Compression, PartitionBuffer, PartitionInnerBuffer,
+        TowerRequestConfig, UriSerde,
     },
+    template::Template,
     tls::{TlsOptions, TlsSettings},
 };
 use flate2::write::GzEncoder;
 use futures::{future, FutureExt, SinkExt};
 use http::{
     header::{self, HeaderName, HeaderValue},
-    Method, Request, StatusCode, Uri,
+    Method, Request, StatusCode,
 };
 use hyper::Body;
 use indexmap::IndexMap;
@@ -23,6 +25,8 @@ use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
 use std::io::Write;
 
+pub use crate::http::HttpMethod;
+
 #[derive(Debug, Snafu)]
 enum BuildError {
     #[snafu(display("{}: {}", source, name))]
@@ -35,12 +39,17 @@ enum BuildError {
         value: String,
         source: header::InvalidHeaderValue,
     },
+    #[snafu(display("Invalid URI {:?}: {}", uri, source))]
+    InvalidUri {
+        uri: String,
+        source: http::uri::InvalidUri,
+    },
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct HttpSinkConfig {
-    pub uri: UriSerde,
+    pub uri: String,
     pub method: Option<HttpMethod>,
     pub auth: Option<Auth>,
     // Deprecated, moved to request.
@@ -48,6 +57,9 @@ pub struct HttpSinkConfig {
     #[serde(default)]
     pub compression: Compression,
     pub encoding: EncodingConfig<Encoding>,
+    pub payload_prefix: Option<String>,
+    pub payload_suffix: Option<String>,
+    pub payload_separator: Option<String>,
     #[serde(default)]
     pub batch: BatchConfig,
     #[serde(default)]
@@ -58,33 +70,21 @@ pub struct HttpSinkConfig {
 #[cfg(test)]
 fn default_config(e: Encoding) -> HttpSinkConfig {
     HttpSinkConfig {
-        uri: Default::default(),
+        uri: "http://localhost:8080/frames".into(),
         method: Default::default(),
         auth: Default::default(),
         headers: Default::default(),
         compression: Default::default(),
         batch: Default::default(),
         encoding: e.into(),
+        payload_prefix: Default::default(),
+        payload_suffix: Default::default(),
+        payload_separator: Default::default(),
         request: Default::default(),
         tls: Default::default(),
     }
 }
 
-#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Derivative)]
-#[serde(rename_all = "snake_case")]
-#[derivative(Default)]
-pub enum HttpMethod {
-    #[derivative(Default)]
-    Get,
-    Head,
-    Post,
-    Put,
-    Delete,
-    Options,
-    Trace,
-    Patch,
-}
-
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum Encoding {
@@ -93,6 +93,25 @@ pub enum Encoding {
     Json,
 }
 
+impl Encoding {
+    /// Returns the default payload prefix, suffix and separator between
+    /// events of the codec.
+    fn framing(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Encoding::Text | Encoding::Ndjson => ("", "\n", "\n"),
+            Encoding::Json => ("[", "]", ","),
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            Encoding::Text => "text/plain",
+            Encoding::Ndjson => "application/x-ndjson",
+            Encoding::Json => "application/json",
+        }
+    }
+}
+
 inventory::submit! {
     SinkDescription::new::<HttpSinkConfig>("http")
 }
@@ -112,6 +131,47 @@ impl HttpSinkConfig {
         let tls = TlsSettings::from_options(&self.tls)?;
         Ok(HttpClient::new(tls, cx.proxy())?)
     }
+
+    fn build_sink(&self) -> crate::Result<HttpBatchSink> {
+        // The URI and header values only render fields, as `%` is common in
+        // them, for example in percent-encoded paths.
+        let uri = Template::fields_only(self.uri.as_str());
+
+        // Credentials embedded in the URI are picked for each request, as
+        // those of a dynamic URI are only known once it is rendered.
+        let auth = if uri.is_dynamic() {
+            self.auth.clone()
+        } else {
+            let uri = parse_uri(&self.uri)?;
+            self.auth.choose_one(&uri.auth)?
+        };
+
+        let mut request = self.request.clone();
+        request.add_old_option(self.headers.clone());
+        validate_headers(&request.headers, &auth)?;
+        let headers = request
+            .headers
+            .into_iter()
+            .map(|(name, value)| (name, Template::fields_only(value)))
+            .collect();
+
+        let (prefix, suffix, separator) = self.encoding.codec().framing();
+        Ok(HttpBatchSink {
+            uri,
+            method: Method::from(self.method.clone().unwrap_or(HttpMethod::Post)),
+            auth: self.auth.clone(),
+            headers,
+            compression: self.compression,
+            encoding: self.encoding.clone(),
+            payload_prefix: self.payload_prefix.as_deref().unwrap_or(prefix).into(),
+            payload_suffix: self.payload_suffix.as_deref().unwrap_or(suffix).into(),
+            payload_separator: self
+                .payload_separator
+                .as_deref()
+                .unwrap_or(separator)
+                .into(),
+        })
+    }
 }
 
 #[async_trait::async_trait]
@@ -130,27 +190,20 @@ impl SinkConfig for HttpSinkConfig {
             None => future::ok(()).boxed(),
         };
 
-        let mut config = HttpSinkConfig {
-            auth: self.auth.choose_one(&self.uri.auth)?,
-            uri: self.uri.with_default_parts(),
-            ..self.clone()
-        };
-
-        config.request.add_old_option(config.headers.take());
-        validate_headers(&config.request.headers, &config.auth)?;
+        let sink = self.build_sink()?;
 
         let batch = BatchSettings::default()
             .bytes(bytesize::mib(10u64))
             .timeout(1)
-            .parse_config(config.batch)?;
-        let request = config.request.tower.unwrap_with(&TowerRequestConfig {
+            .parse_config(self.batch)?;
+        let request = self.request.tower.unwrap_with(&TowerRequestConfig {
             timeout_secs: Some(30),
             ..Default::default()
         });
 
-        let sink = BatchedHttpSink::new(
-            config,
-            Buffer::new(batch.size, Compression::None),
+        let sink = PartitionHttpSink::new(
+            sink,
+            PartitionBuffer::new(Buffer::new(batch.size, Compression::None)),
             request,
             batch.timeout,
             client,
@@ -172,79 +225,108 @@ impl SinkConfig for HttpSinkConfig {
     }
 }
 
+/// Events are batched by the rendered URI and values of the templated
+/// headers.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct PartitionKey {
+    uri: String,
+    headers: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+struct HttpBatchSink {
+    uri: Template,
+    method: Method,
+    auth: Option<Auth>,
+    headers: IndexMap<String, Template>,
+    compression: Compression,
+    encoding: EncodingConfig<Encoding>,
+    payload_prefix: String,
+    payload_suffix: String,
+    payload_separator: String,
+}
+
+impl HttpBatchSink {
+    fn render_key(&self, event: &Event) -> Option<PartitionKey> {
+        let render = |template: &Template, field: &str| {
+            template
+                .render_string(event)
+                .map_err(|error| {
+                    emit!(TemplateRenderingFailed {
+                        error,
+                        field: Some(field),
+                        drop_event: true,
+                    })
+                })
+                .ok()
+        };
+
+        let uri = render(&self.uri, "uri")?;
+        let headers = self
+            .headers
+            .iter()
+            .filter(|(_, template)| template.is_dynamic())
+            .map(|(name, template)| render(template, name.as_str()))
+            .collect::<Option<_>>()?;
+        Some(PartitionKey { uri, headers })
+    }
+}
+
 #[async_trait::async_trait]
-impl HttpSink for HttpSinkConfig {
-    type Input = Vec<u8>;
-    type Output = Vec<u8>;
+impl HttpSink for HttpBatchSink {
+    type Input = PartitionInnerBuffer<Vec<u8>, PartitionKey>;
+    type Output = PartitionInnerBuffer<Vec<u8>, PartitionKey>;
 
     fn encode_event(&self, mut event: Event) -> Option<Self::Input> {
+        let key = self.render_key(&event)?;
+
         self.encoding.apply_rules(&mut event);
         let event = event.into_log();
 
-        let body = match &self.encoding.codec() {
+        let mut body = match &self.encoding.codec() {
             Encoding::Text => {
                 if let Some(v) = event.get(crate::config::log_schema().message_key()) {
-                    let mut b = v.to_string_lossy().into_bytes();
-                    b.push(b'\n');
-                    b
+                    v.to_string_lossy().into_bytes()
                 } else {
                     emit!(HttpEventMissingMessage);
                     return None;
                 }
             }
 
-            Encoding::Ndjson => {
-                let mut b = serde_json::to_vec(&event)
-                    .map_err(|error| panic!("Unable to encode into JSON: {}", error))
-                    .ok()?;
-                b.push(b'\n');
-                b
-            }
-
-            Encoding::Json => {
-                let mut b = serde_json::to_vec(&event)
-                    .map_err(|error| panic!("Unable to encode into JSON: {}", error))
-                    .ok()?;
-                b.push(b',');
-                b
-            }
+            Encoding::Ndjson | Encoding::Json => serde_json::to_vec(&event)
+                .map_err(|error| panic!("Unable to encode into JSON: {}", error))
+                .ok()?,
         };
+        body.extend_from_slice(self.payload_separator.as_bytes());
 
         emit!(HttpEventEncoded {
             byte_size: body.len(),
         });
 
-        Some(body)
+        Some(PartitionInnerBuffer::new(body, key))
     }
 
-    async fn build_request(&self, mut body: Self::Output) -> crate::Result<http::Request<Vec<u8>>> {
-        let method = match &self.method.clone().unwrap_or(HttpMethod::Post) {
-            HttpMethod::Get => Method::GET,
-            HttpMethod::Head => Method::HEAD,
-            HttpMethod::Post => Method::POST,
-            HttpMethod::Put => Method::PUT,
-            HttpMethod::Delete => Method::DELETE,
-            HttpMethod::Options => Method::OPTIONS,
-            HttpMethod::Trace => Method::TRACE,
-            HttpMethod::Patch => Method::PATCH,
-        };
-        let uri: Uri = self.uri.uri.clone();
+    async fn build_request(&self, output: Self::Output) -> crate::Result<http::Request<Vec<u8>>> {
+        let (events, key) = output.into_parts();
 
-        let ct = match self.encoding.codec() {
-            Encoding::Text => "text/plain",
-            Encoding::Ndjson => "application/x-ndjson",
-            Encoding::Json => {
-                body.insert(0, b'[');
-                body.pop(); // remove trailing comma from last record
-                body.push(b']');
-                "application/json"
-            }
-        };
+        let uri = parse_uri(&key.uri)?;
+        let auth = self.auth.choose_one(&uri.auth)?;
+        let uri = uri.with_default_parts();
+
+        // Every event is followed by the separator, drop the one after the
+        // last event.
+        let events = &events[..events.len() - self.payload_separator.len()];
+        let mut body = Vec::with_capacity(
+            self.payload_prefix.len() + events.len() + self.payload_suffix.len(),
+        );
+        body.extend_from_slice(self.payload_prefix.as_bytes());
+        body.extend_from_slice(events);
+        body.extend_from_slice(self.payload_suffix.as_bytes());
 
         let mut builder = Request::builder()
-            .method(method)
-            .uri(uri)
-            .header("Content-Type", ct);
+            .method(self.method.clone())
+            .uri(uri.uri)
+            .header("Content-Type", self.encoding.codec().content_type());
 
         match self.compression {
             Compression::Gzip(level) => {
@@ -258,13 +340,19 @@ impl HttpSink for HttpSinkConfig {
             Compression::None => {}
         }
 
-        for (header, value) in self.request.headers.iter() {
-            builder = builder.header(header.as_str(), value.as_str());
+        let mut rendered = key.headers.iter();
+        for (header, template) in self.headers.iter() {
+            let value = if template.is_dynamic() {
+                rendered.next().expect("Missing rendered header value")
+            } else {
+                template.get_ref()
+            };
+            builder = builder.header(header.as_str(), value);
         }
 
-        let mut request = builder.body(body).unwrap();
+        let mut request = builder.body(body)?;
 
-        if let Some(auth) = &self.auth {
+        if let Some(auth) = auth {
             auth.apply(&mut request);
         }
 
@@ -272,6 +360,10 @@ impl HttpSink for HttpSinkConfig {
     }
 }
 
+fn parse_uri(uri: &str) -> crate::Result<UriSerde> {
+    Ok(uri.parse::<UriSerde>().context(InvalidUri { uri })?)
+}
+
 async fn healthcheck(uri: UriSerde, auth: Option<Auth>, client: HttpClient) -> crate::Result<()> {
     let auth = auth.choose_one(&uri.auth)?;
     let uri = uri.with_default_parts();
@@ -340,7 +432,12 @@ mod tests {
 
         let mut config = default_config(Encoding::Text);
         config.encoding = encoding;
-        let bytes = config.encode_event(event).unwrap();
+        let (bytes, _) = config
+            .build_sink()
+            .unwrap()
+            .encode_event(event)
+            .unwrap()
+            .into_parts();
 
         assert_eq!(bytes, Vec::from("hello world\n"));
     }
@@ -352,7 +449,12 @@ mod tests {
 
         let mut config = default_config(Encoding::Json);
         config.encoding = encoding;
-        let bytes = config.encode_event(event).unwrap();
+        let (bytes, _) = config
+            .build_sink()
+            .unwrap()
+            .encode_event(event)
+            .unwrap()
+            .into_parts();
 
         #[derive(Deserialize, Debug)]
         #[serde(deny_unknown_fields)]
@@ -644,6 +746,157 @@ mod tests {
         assert_eq!(input_lines, output_lines);
     }
 
+    #[tokio::test]
+    async fn http_frames_payload() {
+        let mut config = default_config(Encoding::Json);
+        config.payload_prefix = Some(r#"{"events":["#.into());
+        config.payload_suffix = Some("]}".into());
+        config.payload_separator = Some(", ".into());
+        let sink = config.build_sink().unwrap();
+
+        let mut body = Vec::new();
+        let mut key = None;
+        for message in &["one", "two"] {
+            let (bytes, k) = sink
+                .encode_event(Event::from(*message))
+                .unwrap()
+                .into_parts();
+            body.extend(bytes);
+            key = Some(k);
+        }
+        let request = sink
+            .build_request(PartitionInnerBuffer::new(body, key.unwrap()))
+            .await
+            .unwrap();
+
+        let body = String::from_utf8(request.into_body()).unwrap();
+        assert!(body.starts_with(r#"{"events":[{"#));
+        assert!(body.ends_with("}]}"));
+        let output: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let messages = output["events"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| event["message"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, vec!["one", "two"]);
+    }
+
+    #[test]
+    fn http_drops_events_failing_to_render() {
+        let mut config = default_config(Encoding::Text);
+        config.uri = "http://localhost:8080/{{ id }}".into();
+        config
+            .request
+            .headers
+            .insert("X-Tenant".into(), "{{ tenant }}".into());
+        let sink = config.build_sink().unwrap();
+
+        let mut event = Event::from("hello world");
+        assert!(sink.encode_event(event.clone()).is_none());
+
+        event.as_mut_log().insert("id", "frames");
+        assert!(sink.encode_event(event.clone()).is_none());
+
+        event.as_mut_log().insert("tenant", "vector");
+        let (_, key) = sink.encode_event(event).unwrap().into_parts();
+        assert_eq!(key.uri, "http://localhost:8080/frames");
+        assert_eq!(key.headers, vec!["vector".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn http_keeps_percent_signs_in_uri_and_headers() {
+        let config: HttpSinkConfig = toml::from_str(
+            r#"
+            uri = "http://localhost:8080/frames%2F{{ id }}?q=a%20b"
+            encoding = "text"
+            [request.headers]
+            X-Tenant = "%7B{{ tenant }}%7D"
+            X-Static = "100%"
+            "#,
+        )
+        .unwrap();
+        let sink = config.build_sink().unwrap();
+
+        let mut event = Event::from("hello world");
+        event.as_mut_log().insert("id", "one");
+        event.as_mut_log().insert("tenant", "vector");
+        let output = sink.encode_event(event).unwrap();
+        let (_, key) = output.clone().into_parts();
+        assert_eq!(key.uri, "http://localhost:8080/frames%2Fone?q=a%20b");
+        assert_eq!(key.headers, vec!["%7Bvector%7D".to_owned()]);
+
+        let request = sink.build_request(output).await.unwrap();
+        assert_eq!(request.uri().path(), "/frames%2Fone");
+        assert_eq!(request.uri().query(), Some("q=a%20b"));
+        assert_eq!(request.headers()["X-Tenant"], "%7Bvector%7D");
+        assert_eq!(request.headers()["X-Static"], "100%");
+    }
+
+    #[tokio::test]
+    async fn http_partitions_by_templated_uri_and_headers() {
+        let num_lines = 100;
+
+        let in_addr = next_addr();
+        let config = format!(
+            r#"
+                uri = "http://{addr}/frames/{{{{ tenant }}}}"
+                compression = "gzip"
+                encoding = "ndjson"
+                [request.headers]
+                X-Tenant = "{{{{ tenant }}}}"
+                X-Static = "static"
+            "#,
+            addr = in_addr
+        );
+        let config: HttpSinkConfig = toml::from_str(&config).unwrap();
+        let (sink, _) = config.build(SinkContext::new_test()).await.unwrap();
+
+        let (rx, trigger, server) = build_test_server(in_addr);
+
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        let (mut input_lines, events) = random_lines_with_stream(100, num_lines, Some(batch));
+        let mut tenant = 0;
+        let events = events.map(move |mut event| {
+            tenant += 1;
+            event
+                .as_mut_log()
+                .insert("tenant", format!("tenant{}", tenant % 2));
+            event
+        });
+        let pump = sink.run(events);
+
+        tokio::spawn(server);
+        pump.await.unwrap();
+        drop(trigger);
+
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+
+        let mut output_lines = rx
+            .flat_map(|(parts, body)| {
+                let tenant = parts.headers["X-Tenant"].to_str().unwrap().to_owned();
+                assert_eq!(format!("/frames/{}", tenant), parts.uri.path());
+                assert_eq!("static", parts.headers["X-Static"]);
+
+                stream::iter(
+                    BufReader::new(MultiGzDecoder::new(body.reader()))
+                        .lines()
+                        .map(move |line| (tenant.clone(), line.unwrap())),
+                )
+            })
+            .map(|(tenant, line)| {
+                let val: serde_json::Value = serde_json::from_str(&line).unwrap();
+                assert_eq!(val["tenant"], serde_json::json!(tenant));
+                val["message"].as_str().unwrap().to_owned()
+            })
+            .collect::<Vec<_>>()
+            .await;
+
+        output_lines.sort();
+        input_lines.sort();
+        assert_eq!(input_lines, output_lines);
+    }
+
     async fn get_received(
         rx: mpsc::Receiver<(Parts, Bytes)>,
         assert_parts: impl Fn(Parts),
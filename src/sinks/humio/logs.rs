@@ -92,6 +92,7 @@ impl HumioLogsConfig {
             batch: self.batch,
             request: self.request,
             tls: self.tls.clone(),
+            indexer_acknowledgements: Default::default(),
         }
     }
 }
@@ -1,14 +1,19 @@
 use crate::{
     buffers::Acker,
-    config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
-    internal_events::TemplateRenderingFailed,
-    kafka::{KafkaAuthConfig, KafkaCompression, KafkaStatisticsContext},
+    config::{
+        log_schema, DataType, GenerateConfig, ProxyConfig, SinkConfig, SinkContext, SinkDescription,
+    },
+    internal_events::{KafkaHeaderExtractionFailed, TemplateRenderingFailed},
+    kafka::{
+        schema_registry::{encode_wire_format, SchemaRegistry, SchemaRegistryConfig},
+        KafkaAuthConfig, KafkaCompression, KafkaStatisticsContext,
+    },
     serde::to_string,
     sinks::util::{
         encoding::{EncodingConfig, EncodingConfiguration},
         BatchConfig,
     },
-    template::{Template, TemplateParseError},
+    template::Template,
 };
 use futures::{
     channel::oneshot::Canceled, future::BoxFuture, ready, stream::FuturesUnordered, FutureExt,
@@ -17,20 +22,20 @@ use futures::{
 use rdkafka::{
     consumer::{BaseConsumer, Consumer},
     error::{KafkaError, RDKafkaErrorCode},
+    message::OwnedHeaders,
     producer::{DeliveryFuture, FutureProducer, FutureRecord},
     ClientConfig,
 };
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
 use std::{
-    collections::{HashMap, HashSet},
-    convert::TryFrom,
+    collections::{BTreeMap, HashMap, HashSet},
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
 };
 use tokio::time::{sleep, Duration};
-use vector_core::event::{Event, EventMetadata, EventStatus};
+use vector_core::event::{Event, EventMetadata, EventStatus, Value};
 
 // Maximum number of futures blocked by [send_result](https://docs.rs/rdkafka/0.24.0/rdkafka/producer/future_producer/struct.FutureProducer.html#method.send_result)
 const SEND_RESULT_LIMIT: usize = 5;
@@ -39,16 +44,20 @@ const SEND_RESULT_LIMIT: usize = 5;
 enum BuildError {
     #[snafu(display("creating kafka producer failed: {}", source))]
     KafkaCreateFailed { source: KafkaError },
-    #[snafu(display("invalid topic template: {}", source))]
-    TopicTemplate { source: TemplateParseError },
+    #[snafu(display("Avro encoding requires a `schema_registry`"))]
+    MissingSchemaRegistry,
+    #[snafu(display("invalid Avro schema: {}", source))]
+    InvalidAvroSchema { source: avro_rs::Error },
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct KafkaSinkConfig {
     bootstrap_servers: String,
-    topic: String,
+    topic: Template,
     key_field: Option<String>,
+    headers_key: Option<String>,
     encoding: EncodingConfig<Encoding>,
+    schema_registry: Option<SchemaRegistryConfig>,
     /// These batching options will **not** override librdkafka_options values.
     #[serde(default)]
     batch: BatchConfig,
@@ -77,22 +86,24 @@ fn default_message_timeout_ms() -> u64 {
 pub enum Encoding {
     Text,
     Json,
+    Avro,
 }
 
 pub struct KafkaSink {
     producer: Arc<FutureProducer<KafkaStatisticsContext>>,
     topic: Template,
     key_field: Option<String>,
+    headers_key: Option<String>,
     encoding: EncodingConfig<Encoding>,
-    delivery_fut: FuturesUnordered<
-        BoxFuture<'static, (usize, Result<DeliveryFuture, KafkaError>, EventMetadata)>,
-    >,
+    schema_registry: Option<Arc<SchemaRegistry>>,
+    delivery_fut:
+        FuturesUnordered<BoxFuture<'static, (usize, crate::Result<DeliveryFuture>, EventMetadata)>>,
     in_flight: FuturesUnordered<
         BoxFuture<
             'static,
             (
                 usize,
-                Result<Result<(i32, i64), KafkaError>, Canceled>,
+                Result<crate::Result<(i32, i64)>, Canceled>,
                 EventMetadata,
             ),
         >,
@@ -127,13 +138,16 @@ impl SinkConfig for KafkaSinkConfig {
         &self,
         cx: SinkContext,
     ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
-        let sink = KafkaSink::new(self.clone(), cx.acker())?;
+        let sink = KafkaSink::new(self.clone(), cx.acker(), &cx.proxy)?;
         let hc = healthcheck(self.clone()).boxed();
         Ok((super::VectorSink::Sink(Box::new(sink)), hc))
     }
 
     fn input_type(&self) -> DataType {
-        DataType::Any
+        match self.encoding.codec() {
+            Encoding::Avro => DataType::Log,
+            _ => DataType::Any,
+        }
     }
 
     fn sink_type(&self) -> &'static str {
@@ -234,16 +248,32 @@ impl KafkaSinkConfig {
 }
 
 impl KafkaSink {
-    fn new(config: KafkaSinkConfig, acker: Acker) -> crate::Result<Self> {
+    fn new(config: KafkaSinkConfig, acker: Acker, proxy: &ProxyConfig) -> crate::Result<Self> {
+        let schema_registry = match config.encoding.codec() {
+            Encoding::Avro => {
+                let registry = config
+                    .schema_registry
+                    .as_ref()
+                    .ok_or(MissingSchemaRegistry)?;
+                if let Some(schema) = config.encoding.schema() {
+                    avro_rs::Schema::parse_str(schema).context(InvalidAvroSchema)?;
+                }
+                Some(Arc::new(SchemaRegistry::new(registry, proxy)?))
+            }
+            _ => None,
+        };
+
         let producer_config = config.to_rdkafka(KafkaRole::Producer)?;
         let producer = producer_config
             .create_with_context(KafkaStatisticsContext)
             .context(KafkaCreateFailed)?;
         Ok(KafkaSink {
             producer: Arc::new(producer),
-            topic: Template::try_from(config.topic).context(TopicTemplate)?,
+            topic: config.topic,
             key_field: config.key_field,
+            headers_key: config.headers_key,
             encoding: config.encoding,
+            schema_registry,
             delivery_fut: FuturesUnordered::new(),
             in_flight: FuturesUnordered::new(),
             acker,
@@ -261,8 +291,10 @@ impl KafkaSink {
             self.in_flight.push(Box::pin(async move {
                 let result = match result {
                     Ok(fut) => {
-                        fut.map_ok(|result| result.map_err(|(error, _owned_message)| error))
-                            .await
+                        fut.map_ok(|result| {
+                            result.map_err(|(error, _owned_message)| crate::Error::from(error))
+                        })
+                        .await
                     }
                     Err(error) => Ok(Err(error)),
                 };
@@ -307,7 +339,33 @@ impl Sink<Event> for KafkaSink {
             Event::Metric(metric) => metric.timestamp(),
         }
         .map(|ts| ts.timestamp_millis());
-        let (key, body, metadata) = encode_event(item, &self.key_field, &self.encoding);
+        let headers = self
+            .headers_key
+            .as_ref()
+            .and_then(|headers_key| encode_headers(&item, headers_key));
+
+        // Avro encoding needs the schema, which may first have to be fetched
+        // from the registry, so it's deferred to the delivery future.
+        let (key, body, metadata) = match &self.schema_registry {
+            Some(registry) => {
+                let key = encode_key(&item, &self.key_field);
+                let mut item = item;
+                self.encoding.apply_rules(&mut item);
+                let (fields, metadata) = item.into_log().into_parts();
+                let body = encode_avro(
+                    Arc::clone(registry),
+                    format!("{}-value", topic),
+                    self.encoding.schema().clone(),
+                    fields,
+                )
+                .boxed();
+                (key, body, metadata)
+            }
+            None => {
+                let (key, body, metadata) = encode_event(item, &self.key_field, &self.encoding);
+                (key, futures::future::ok(body).boxed(), metadata)
+            }
+        };
 
         let seqno = self.seq_head;
         self.seq_head += 1;
@@ -315,6 +373,11 @@ impl Sink<Event> for KafkaSink {
         let producer = Arc::clone(&self.producer);
         let kf = self.key_field.is_some();
         self.delivery_fut.push(Box::pin(async move {
+            let body = match body.await {
+                Ok(body) => body,
+                Err(error) => return (seqno, Err(error), metadata),
+            };
+
             let mut record = if kf {
                 FutureRecord::to(&topic).key(&key).payload(&body[..])
             } else {
@@ -323,8 +386,11 @@ impl Sink<Event> for KafkaSink {
             if let Some(timestamp) = timestamp_ms {
                 record = record.timestamp(timestamp);
             }
+            if let Some(headers) = headers {
+                record = record.headers(headers);
+            }
 
-            let result = loop {
+            let result: crate::Result<DeliveryFuture> = loop {
                 debug!(message = "Sending event.", count = 1);
                 match producer.send_result(record) {
                     Ok(future) => break Ok(future),
@@ -338,7 +404,7 @@ impl Sink<Event> for KafkaSink {
                         record = future_record;
                         sleep(Duration::from_millis(10)).await;
                     }
-                    Err((error, _)) => break Err(error),
+                    Err((error, _)) => break Err(error.into()),
                 }
             };
 
@@ -395,10 +461,7 @@ impl Sink<Event> for KafkaSink {
 async fn healthcheck(config: KafkaSinkConfig) -> crate::Result<()> {
     trace!("Healthcheck started.");
     let client = config.to_rdkafka(KafkaRole::Consumer).unwrap();
-    let topic = match Template::try_from(config.topic)
-        .context(TopicTemplate)?
-        .render_string(&Event::from(""))
-    {
+    let topic = match config.topic.render_string(&Event::from("")) {
         Ok(topic) => Some(topic),
         Err(error) => {
             warn!(
@@ -422,21 +485,76 @@ async fn healthcheck(config: KafkaSinkConfig) -> crate::Result<()> {
     Ok(())
 }
 
-fn encode_event(
-    mut event: Event,
-    key_field: &Option<String>,
-    encoding: &EncodingConfig<Encoding>,
-) -> (Vec<u8>, Vec<u8>, EventMetadata) {
-    let key = key_field
+fn encode_key(event: &Event, key_field: &Option<String>) -> Vec<u8> {
+    key_field
         .as_ref()
-        .and_then(|f| match &event {
+        .and_then(|f| match event {
             Event::Log(log) => log.get(f).map(|value| value.as_bytes().to_vec()),
             Event::Metric(metric) => metric
                 .tags()
                 .and_then(|tags| tags.get(f))
                 .map(|value| value.clone().into_bytes()),
         })
-        .unwrap_or_default();
+        .unwrap_or_default()
+}
+
+fn encode_headers(event: &Event, headers_key: &str) -> Option<OwnedHeaders> {
+    let value = match event {
+        Event::Log(log) => log.get(headers_key)?,
+        Event::Metric(_) => return None,
+    };
+    match value {
+        Value::Map(map) => Some(
+            map.iter()
+                .fold(OwnedHeaders::new(), |headers, (key, value)| {
+                    headers.add(key.as_str(), &value.as_bytes()[..])
+                }),
+        ),
+        _ => {
+            emit!(KafkaHeaderExtractionFailed {
+                header_field: headers_key
+            });
+            None
+        }
+    }
+}
+
+/// Encodes the fields of a log event into the Confluent wire format. The
+/// configured schema is registered under `subject`, otherwise the latest
+/// schema of `subject` is used, and forgotten if the fields don't match it.
+async fn encode_avro(
+    registry: Arc<SchemaRegistry>,
+    subject: String,
+    schema: Option<String>,
+    fields: BTreeMap<String, Value>,
+) -> crate::Result<Vec<u8>> {
+    let latest = schema.is_none();
+    let registered = match schema {
+        Some(schema) => registry.register(&subject, &schema).await?,
+        None => registry.latest(&subject).await?,
+    };
+    let datum = avro_rs::to_value(fields)
+        .and_then(|value| value.resolve(&registered.schema))
+        .and_then(|value| avro_rs::to_avro_datum(&registered.schema, value));
+    match datum {
+        Ok(datum) => Ok(encode_wire_format(registered.id, &datum)),
+        Err(error) => {
+            // The subject may have evolved since its latest schema was
+            // fetched, so the next event looks it up again.
+            if latest {
+                registry.forget_latest(&subject);
+            }
+            Err(error.into())
+        }
+    }
+}
+
+fn encode_event(
+    mut event: Event,
+    key_field: &Option<String>,
+    encoding: &EncodingConfig<Encoding>,
+) -> (Vec<u8>, Vec<u8>, EventMetadata) {
+    let key = encode_key(&event, key_field);
 
     encoding.apply_rules(&mut event);
 
@@ -447,10 +565,12 @@ fn encode_event(
                 .get(log_schema().message_key())
                 .map(|v| v.as_bytes().to_vec())
                 .unwrap_or_default(),
+            Encoding::Avro => unreachable!("Avro is encoded by `encode_avro`"),
         },
         Event::Metric(metric) => match encoding.codec() {
             Encoding::Json => serde_json::to_vec(&metric).unwrap(),
             Encoding::Text => metric.to_string().into_bytes(),
+            Encoding::Avro => unreachable!("Avro is encoded by `encode_avro`"),
         },
     };
 
@@ -461,8 +581,11 @@ fn encode_event(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::event::{Metric, MetricKind, MetricValue};
-    use std::collections::BTreeMap;
+    use crate::{
+        event::{Metric, MetricKind, MetricValue},
+        kafka::schema_registry::test::{mock_registry, SCHEMA},
+    };
+    use rdkafka::message::Headers;
 
     #[test]
     fn generate_config() {
@@ -566,6 +689,72 @@ mod tests {
         assert_eq!(&key[..], b"value");
         assert!(!map.contains_key("key"));
     }
+
+    #[test]
+    fn kafka_encode_headers() {
+        let mut event = Event::from("hello");
+        event.as_mut_log().insert("headers.foo", "bar");
+        event.as_mut_log().insert("headers.baz", 1);
+        event.as_mut_log().insert("not_a_map", "value");
+
+        let headers = encode_headers(&event, "headers").unwrap();
+        let mut headers = (0..headers.count())
+            .map(|i| headers.get(i).unwrap())
+            .map(|(key, value)| (key.to_owned(), value.to_vec()))
+            .collect::<Vec<_>>();
+        headers.sort();
+        assert_eq!(
+            headers,
+            vec![
+                ("baz".to_owned(), b"1".to_vec()),
+                ("foo".to_owned(), b"bar".to_vec())
+            ]
+        );
+
+        assert!(encode_headers(&event, "not_a_map").is_none());
+        assert!(encode_headers(&event, "missing").is_none());
+    }
+
+    #[tokio::test]
+    async fn kafka_encode_avro() {
+        let registry = SchemaRegistry::new(&mock_registry(), &ProxyConfig::default()).unwrap();
+        let mut fields = BTreeMap::new();
+        fields.insert("message".to_owned(), Value::from("hello"));
+        fields.insert("count".to_owned(), Value::from(3));
+        fields.insert("ignored".to_owned(), Value::from(true));
+
+        let bytes = encode_avro(Arc::new(registry), "topic-value".into(), None, fields)
+            .await
+            .unwrap();
+
+        assert_eq!(&bytes[..5], &[0, 0, 0, 0, 42]);
+        let schema = avro_rs::Schema::parse_str(SCHEMA).unwrap();
+        let value = avro_rs::from_avro_datum(&schema, &mut &bytes[5..], None).unwrap();
+        assert_eq!(
+            value,
+            avro_rs::types::Value::Record(vec![
+                (
+                    "message".into(),
+                    avro_rs::types::Value::String("hello".into())
+                ),
+                ("count".into(), avro_rs::types::Value::Long(3)),
+            ])
+        );
+    }
+
+    #[test]
+    fn kafka_avro_requires_schema_registry() {
+        let config: KafkaSinkConfig = toml::from_str(
+            r#"
+            bootstrap_servers = "localhost:9092"
+            topic = "topic"
+            encoding.codec = "avro"
+            "#,
+        )
+        .unwrap();
+        let (acker, _) = Acker::new_for_testing();
+        assert!(KafkaSink::new(config, acker, &ProxyConfig::default()).is_err());
+    }
 }
 
 #[cfg(feature = "kafka-integration-tests")]
@@ -583,7 +772,7 @@ mod integration_test {
         consumer::{BaseConsumer, Consumer},
         Message, Offset, TopicPartitionList,
     };
-    use std::{future::ready, thread, time::Duration};
+    use std::{convert::TryFrom, future::ready, thread, time::Duration};
     use vector_core::event::{BatchNotifier, BatchStatus};
 
     #[tokio::test]
@@ -593,9 +782,11 @@ mod integration_test {
 
         let config = KafkaSinkConfig {
             bootstrap_servers: "localhost:9091".into(),
-            topic: topic.clone(),
+            topic: Template::try_from(topic.clone()).unwrap(),
             key_field: None,
+            headers_key: None,
             encoding: EncodingConfig::from(Encoding::Text),
+            schema_registry: None,
             batch: BatchConfig::default(),
             compression: KafkaCompression::None,
             auth: KafkaAuthConfig::default(),
@@ -644,10 +835,12 @@ mod integration_test {
         let topic = format!("test-{}", random_string(10));
         let config = KafkaSinkConfig {
             bootstrap_servers: "localhost:9091".to_string(),
-            topic: format!("{}-%Y%m%d", topic),
+            topic: Template::try_from(format!("{}-%Y%m%d", topic)).unwrap(),
             compression: KafkaCompression::None,
             encoding: Encoding::Text.into(),
+            schema_registry: None,
             key_field: None,
+            headers_key: None,
             auth: KafkaAuthConfig {
                 sasl: None,
                 tls: None,
@@ -661,7 +854,7 @@ mod integration_test {
         config.clone().to_rdkafka(KafkaRole::Consumer)?;
         config.clone().to_rdkafka(KafkaRole::Producer)?;
         super::healthcheck(config.clone()).await?;
-        KafkaSink::new(config, acker)
+        KafkaSink::new(config, acker, &ProxyConfig::default())
     }
 
     #[tokio::test]
@@ -798,9 +991,11 @@ mod integration_test {
         let kafka_auth = KafkaAuthConfig { sasl, tls };
         let config = KafkaSinkConfig {
             bootstrap_servers: server.to_string(),
-            topic: format!("{}-%Y%m%d", topic),
+            topic: Template::try_from(format!("{}-%Y%m%d", topic)).unwrap(),
             key_field: None,
+            headers_key: None,
             encoding: EncodingConfig::from(Encoding::Text),
+            schema_registry: None,
             batch: BatchConfig::default(),
             compression,
             auth: kafka_auth.clone(),
@@ -810,7 +1005,7 @@ mod integration_test {
         };
         let topic = format!("{}-{}", topic, chrono::Utc::now().format("%Y%m%d"));
         let (acker, ack_counter) = Acker::new_for_testing();
-        let sink = KafkaSink::new(config, acker).unwrap();
+        let sink = KafkaSink::new(config, acker, &ProxyConfig::default()).unwrap();
 
         let num_events = 1000;
         let (batch, mut receiver) = BatchNotifier::new_with_receiver();
@@ -1,13 +1,17 @@
 //! Loki sink
 //!
 //! This sink provides downstream support for `Loki` via
-//! the v1 http json endpoint.
+//! the v1 http push endpoint, either in json or in snappy compressed
+//! protobuf.
 //!
 //! https://github.com/grafana/loki/blob/master/docs/api.md
 //!
-//! This sink does not use `PartitionBatching` but elects to do
-//! stream multiplexing by organizing the streams in the `build_request`
-//! phase. There must be at least one valid set of labels.
+//! The events are partitioned by tenant and stream, the requests of
+//! each partition being sent in order so that the events of a stream
+//! are pushed in order even with concurrent requests. There must be
+//! at least one valid set of labels. At most `MAX_PARTITIONS` streams
+//! are batched at once, the batch of a stream being sent early to make
+//! room for another one.
 //!
 //! If an event produces no labels, this can happen if the template
 //! does not match, we will add a default label `{agent="vector"}`.
@@ -17,10 +21,11 @@ use crate::{
     event::{self, Event, Value},
     http::{Auth, HttpClient, MaybeAuth},
     sinks::util::{
-        buffer::loki::{GlobalTimestamps, LokiBuffer, LokiEvent, LokiRecord, PartitionKey},
+        buffer::loki::{
+            GlobalTimestamps, Labels, LokiBatch, LokiBuffer, LokiEvent, LokiRecord, PartitionKey,
+        },
         encoding::{EncodingConfig, EncodingConfiguration},
         http::{HttpSink, PartitionHttpSink},
-        service::ConcurrencyOption,
         BatchConfig, BatchSettings, PartitionBuffer, PartitionInnerBuffer, TowerRequestConfig,
         UriSerde,
     },
@@ -28,8 +33,16 @@ use crate::{
     tls::{TlsOptions, TlsSettings},
 };
 use futures::{FutureExt, SinkExt};
+use prost::Message;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+/// The maximum number of streams batched at once.
+const MAX_PARTITIONS: usize = 1000;
+
+mod proto {
+    include!(concat!(env!("OUT_DIR"), "/logproto.rs"));
+}
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
@@ -46,6 +59,8 @@ pub struct LokiConfig {
     remove_timestamp: bool,
     #[serde(default)]
     out_of_order_action: OutOfOrderAction,
+    #[serde(default)]
+    compression: LokiCompression,
 
     auth: Option<Auth>,
 
@@ -67,6 +82,17 @@ pub enum OutOfOrderAction {
     RewriteTimestamp,
 }
 
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, PartialEq, Eq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+enum LokiCompression {
+    /// The streams are sent in json.
+    #[derivative(Default)]
+    None,
+    /// The streams are sent in protobuf, compressed with snappy.
+    Snappy,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 enum Encoding {
@@ -99,12 +125,17 @@ impl SinkConfig for LokiConfig {
         if self.labels.is_empty() {
             return Err("`labels` must include at least one label.".into());
         }
-
-        if self.request.concurrency.is_some() {
-            warn!("Option `request.concurrency` is not supported.");
+        for (key, value) in &self.labels {
+            if key.get_ref().ends_with('*') && field_reference(value).is_none() {
+                return Err(format!(
+                    "The value of the label {:?} must be a single field, like `{{{{ field }}}}`.",
+                    key.get_ref()
+                )
+                .into());
+            }
         }
-        let mut request_settings = self.request.unwrap_with(&TowerRequestConfig::default());
-        request_settings.concurrency = Some(1);
+
+        let request_settings = self.request.unwrap_with(&TowerRequestConfig::default());
 
         let batch_settings = BatchSettings::default()
             .bytes(102_400)
@@ -133,6 +164,8 @@ impl SinkConfig for LokiConfig {
             client.clone(),
             cx.acker(),
         )
+        .ordered()
+        .max_partitions(MAX_PARTITIONS)
         .sink_map_err(|error| error!(message = "Fatal loki sink error.", %error));
 
         let healthcheck = healthcheck(config, client).boxed();
@@ -158,6 +191,7 @@ struct LokiSink {
 
     remove_label_fields: bool,
     remove_timestamp: bool,
+    compression: LokiCompression,
 
     auth: Option<Auth>,
 }
@@ -171,6 +205,7 @@ impl LokiSink {
             labels: config.labels,
             remove_label_fields: config.remove_label_fields,
             remove_timestamp: config.remove_timestamp,
+            compression: config.compression,
             auth: config.auth,
         }
     }
@@ -179,7 +214,7 @@ impl LokiSink {
 #[async_trait::async_trait]
 impl HttpSink for LokiSink {
     type Input = PartitionInnerBuffer<LokiRecord, PartitionKey>;
-    type Output = PartitionInnerBuffer<serde_json::Value, PartitionKey>;
+    type Output = PartitionInnerBuffer<LokiBatch, PartitionKey>;
 
     fn encode_event(&self, mut event: Event) -> Option<Self::Input> {
         let tenant_id = self.tenant_id.as_ref().and_then(|t| {
@@ -193,16 +228,37 @@ impl HttpSink for LokiSink {
                 })
                 .ok()
         });
-        let key = PartitionKey { tenant_id };
 
-        let mut labels = Vec::new();
+        let mut labels = BTreeMap::new();
+
+        // The labels of the objects come first, to be overridden by the
+        // labels with the same name.
+        for (key_template, value_template) in &self.labels {
+            let prefix = match key_template.render_string(&event) {
+                Ok(key) if key.ends_with('*') => key,
+                _ => continue,
+            };
+            let prefix = &prefix[..prefix.len() - 1];
+            let object =
+                field_reference(value_template).and_then(|field| event.as_log().get(field));
+            if let Some(Value::Map(object)) = object {
+                for (key, value) in object {
+                    labels.insert(
+                        label_name(&format!("{}{}", prefix, key)),
+                        value.to_string_lossy(),
+                    );
+                }
+            }
+        }
 
         for (key_template, value_template) in &self.labels {
             if let (Ok(key), Ok(value)) = (
                 key_template.render_string(&event),
                 value_template.render_string(&event),
             ) {
-                labels.push((key, value));
+                if !key.ends_with('*') {
+                    labels.insert(key, value);
+                }
             }
         }
 
@@ -240,8 +296,13 @@ impl HttpSink for LokiSink {
         // `{agent="vector"}` label. This can happen if the only
         // label is a templatable one but the event doesn't match.
         if labels.is_empty() {
-            labels = vec![("agent".to_string(), "vector".to_string())]
+            labels.insert("agent".to_string(), "vector".to_string());
         }
+        let labels = labels.into_iter().collect::<Labels>();
+        let key = PartitionKey {
+            tenant_id,
+            labels: labels.clone(),
+        };
 
         let event = LokiEvent { timestamp, event };
         Some(PartitionInnerBuffer::new(
@@ -255,14 +316,28 @@ impl HttpSink for LokiSink {
     }
 
     async fn build_request(&self, output: Self::Output) -> crate::Result<http::Request<Vec<u8>>> {
-        let (json, key) = output.into_parts();
+        let (batch, key) = output.into_parts();
         let tenant_id = key.tenant_id;
 
-        let body = serde_json::to_vec(&json).unwrap();
+        let (body, content_type) = match self.compression {
+            LokiCompression::None => (
+                serde_json::to_vec(&batch.to_json()).unwrap(),
+                "application/json",
+            ),
+            LokiCompression::Snappy => {
+                let request = push_request(batch);
+                let mut body = Vec::with_capacity(request.encoded_len());
+                request.encode(&mut body).expect("Out of memory");
+                let body = snap::raw::Encoder::new()
+                    .compress_vec(&body)
+                    .expect("Out of memory");
+                (body, "application/x-protobuf")
+            }
+        };
 
         let uri = format!("{}loki/api/v1/push", self.endpoint.uri);
 
-        let mut req = http::Request::post(uri).header("Content-Type", "application/json");
+        let mut req = http::Request::post(uri).header("Content-Type", content_type);
 
         if let Some(tenant_id) = tenant_id {
             req = req.header("X-Scope-OrgID", tenant_id);
@@ -278,6 +353,72 @@ impl HttpSink for LokiSink {
     }
 }
 
+/// Returns the field of a template made of a single field, like `{{ field }}`.
+fn field_reference(template: &Template) -> Option<&str> {
+    let field = template
+        .get_ref()
+        .trim()
+        .strip_prefix("{{")?
+        .strip_suffix("}}")?
+        .trim();
+    if field.is_empty() || field.contains("{{") || field.contains("}}") {
+        None
+    } else {
+        Some(field)
+    }
+}
+
+/// Replaces the characters not allowed in the names of the labels by `_`.
+fn label_name(name: &str) -> String {
+    name.chars()
+        .enumerate()
+        .map(|(index, c)| {
+            if c.is_ascii_alphabetic() || c == '_' || (index > 0 && c.is_ascii_digit()) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Returns the push request of a batch, the labels of its streams being in
+/// the Prometheus format: `{key="value", ...}`.
+fn push_request(batch: LokiBatch) -> proto::PushRequest {
+    let streams = batch
+        .streams
+        .into_iter()
+        .map(|(labels, events)| proto::StreamAdapter {
+            labels: format!(
+                "{{{}}}",
+                labels
+                    .iter()
+                    .map(|(key, value)| format!("{}=\"{}\"", key, escape_label_value(value)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            entries: events
+                .into_iter()
+                .map(|event| proto::EntryAdapter {
+                    timestamp: Some(prost_types::Timestamp {
+                        seconds: event.timestamp.div_euclid(1_000_000_000),
+                        nanos: event.timestamp.rem_euclid(1_000_000_000) as i32,
+                    }),
+                    line: event.event,
+                })
+                .collect(),
+        })
+        .collect();
+    proto::PushRequest { streams }
+}
+
 async fn healthcheck(config: LokiConfig, client: HttpClient) -> crate::Result<()> {
     let uri = format!("{}ready", config.endpoint.uri);
 
@@ -303,7 +444,9 @@ mod tests {
     use crate::event::Event;
     use crate::sinks::util::http::HttpSink;
     use crate::sinks::util::test::{build_test_server, load_sink};
+    use crate::sinks::util::Batch;
     use crate::test_util;
+    use chrono::TimeZone;
     use futures::StreamExt;
 
     #[test]
@@ -380,6 +523,105 @@ mod tests {
         assert_eq!(record.labels[0], ("bar".to_string(), "bar".to_string()));
     }
 
+    #[test]
+    fn expand_wildcard_labels() {
+        let (config, _cx) = load_sink::<LokiConfig>(
+            r#"
+            endpoint = "http://localhost:3100"
+            labels."pod_labels_*" = "{{ kubernetes.pod_labels }}"
+            labels.pod_labels_app = "static"
+            encoding = "text"
+        "#,
+        )
+        .unwrap();
+        let sink = LokiSink::new(config);
+
+        let mut pod_labels = BTreeMap::new();
+        pod_labels.insert("app".to_string(), Value::from("overridden"));
+        pod_labels.insert("app.kubernetes.io/name".to_string(), Value::from("vector"));
+        let mut e1 = Event::from("hello world");
+        e1.as_mut_log().insert("kubernetes.pod_labels", pod_labels);
+
+        let (record, key) = sink.encode_event(e1).unwrap().into_parts();
+        let expected = vec![
+            ("pod_labels_app".to_string(), "static".to_string()),
+            (
+                "pod_labels_app_kubernetes_io_name".to_string(),
+                "vector".to_string(),
+            ),
+        ];
+        assert_eq!(record.labels, expected);
+        // Each stream is a partition of its own.
+        assert_eq!(key.labels, expected);
+    }
+
+    #[tokio::test]
+    async fn wildcard_label_requires_a_field() {
+        let (config, cx) = load_sink::<LokiConfig>(
+            r#"
+            endpoint = "http://localhost:3100"
+            labels."pod_labels_*" = "static"
+            encoding = "text"
+        "#,
+        )
+        .unwrap();
+        assert!(config.build(cx).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn encode_snappy_protobuf() {
+        let (config, _cx) = load_sink::<LokiConfig>(
+            r#"
+            endpoint = "http://localhost:3100"
+            labels = {app = "{{ app }}", static = "some \"quoted\" value"}
+            encoding = "text"
+            compression = "snappy"
+        "#,
+        )
+        .unwrap();
+        let sink = LokiSink::new(config);
+
+        let mut buffer = PartitionBuffer::new(LokiBuffer::new(
+            BatchSettings::default().size,
+            Default::default(),
+            Default::default(),
+        ));
+        for (timestamp, line) in [(1_500_000_002, "second"), (1_500_000_001, "first")].iter() {
+            let mut event = Event::from(*line);
+            event.as_mut_log().insert("app", "vector");
+            event.as_mut_log().insert(
+                log_schema().timestamp_key(),
+                chrono::Utc.timestamp_nanos(*timestamp),
+            );
+            let _ = buffer.push(sink.encode_event(event).unwrap());
+        }
+
+        let request = sink.build_request(buffer.finish()).await.unwrap();
+        assert_eq!(request.headers()["Content-Type"], "application/x-protobuf");
+        let body = snap::raw::Decoder::new()
+            .decompress_vec(request.body())
+            .unwrap();
+        let push = proto::PushRequest::decode(body.as_slice()).unwrap();
+
+        assert_eq!(push.streams.len(), 1);
+        assert_eq!(
+            push.streams[0].labels,
+            r#"{app="vector", static="some \"quoted\" value"}"#
+        );
+        let entries = push.streams[0]
+            .entries
+            .iter()
+            .map(|entry| {
+                let timestamp = entry.timestamp.clone().unwrap();
+                (timestamp.seconds, timestamp.nanos, entry.line.as_str())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![(1, 500_000_001, "first"), (1, 500_000_002, "second")]
+        );
+    }
+
     #[tokio::test]
     async fn healthcheck_includes_auth() {
         let (mut config, _cx) = load_sink::<LokiConfig>(
@@ -437,7 +679,7 @@ mod integration_tests {
     use std::convert::TryFrom;
     use vector_core::event::{BatchNotifier, BatchStatus, Event, LogEvent};
 
-    async fn build_sink(encoding: &str) -> (uuid::Uuid, VectorSink) {
+    async fn build_sink(encoding: &str, compression: &str) -> (uuid::Uuid, VectorSink) {
         let stream = uuid::Uuid::new_v4();
 
         let config = format!(
@@ -447,8 +689,9 @@ mod integration_tests {
             encoding = "{}"
             remove_timestamp = false
             tenant_id = "default"
+            compression = "{}"
         "#,
-            encoding
+            encoding, compression
         );
 
         let (mut config, cx) = load_sink::<LokiConfig>(&config).unwrap();
@@ -468,7 +711,7 @@ mod integration_tests {
 
     #[tokio::test]
     async fn text() {
-        let (stream, sink) = build_sink("text").await;
+        let (stream, sink) = build_sink("text", "none").await;
 
         let lines = random_lines(100).take(10).collect::<Vec<_>>();
 
@@ -491,9 +734,31 @@ mod integration_tests {
         }
     }
 
+    #[tokio::test]
+    async fn text_snappy() {
+        let (stream, sink) = build_sink("text", "snappy").await;
+
+        let lines = random_lines(100).take(10).collect::<Vec<_>>();
+
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        let events = lines
+            .clone()
+            .into_iter()
+            .map(move |line| Event::from(LogEvent::from(line).with_batch_notifier(&batch)));
+        let _ = sink
+            .into_sink()
+            .send_all(&mut stream::iter(events).map(Ok))
+            .await
+            .unwrap();
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+
+        let (_, outputs) = fetch_stream(stream.to_string(), "default").await;
+        assert_eq!(lines, outputs);
+    }
+
     #[tokio::test]
     async fn json() {
-        let (stream, sink) = build_sink("json").await;
+        let (stream, sink) = build_sink("json", "none").await;
 
         let events = random_lines(100)
             .take(10)
@@ -520,7 +785,7 @@ mod integration_tests {
     // https://github.com/timberio/vector/issues/7815
     #[tokio::test]
     async fn json_nested_fields() {
-        let (stream, sink) = build_sink("json").await;
+        let (stream, sink) = build_sink("json", "none").await;
 
         let events = random_lines(100)
             .take(10)
@@ -0,0 +1,456 @@
+use crate::{
+    buffers::Acker,
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    emit,
+    event::Event,
+    internal_events::{
+        MqttConnectionFailed, MqttEventSendFail, MqttEventSendSuccess, TemplateRenderingFailed,
+    },
+    mqtt::{MqttConnectionConfig, MqttQos, RECONNECT_DELAY},
+    sinks::util::{
+        encoding::{EncodingConfig, EncodingConfiguration},
+        StreamSink,
+    },
+    template::{Template, TemplateParseError},
+};
+use async_trait::async_trait;
+use futures::{stream::BoxStream, FutureExt, StreamExt};
+use rumqttc::{AsyncClient, Event as MqttEvent, EventLoop, Outgoing, Packet};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{collections::VecDeque, convert::TryFrom, time::Duration};
+use tokio::sync::mpsc;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("invalid topic template: {}", source))]
+    TopicTemplate { source: TemplateParseError },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MqttSinkConfig {
+    #[serde(flatten)]
+    connection: MqttConnectionConfig,
+    topic: String,
+    #[serde(default)]
+    qos: MqttQos,
+    #[serde(default)]
+    retain: bool,
+    encoding: EncodingConfig<Encoding>,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    Text,
+    Json,
+}
+
+inventory::submit! {
+    SinkDescription::new::<MqttSinkConfig>("mqtt")
+}
+
+impl GenerateConfig for MqttSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"
+            host = "127.0.0.1"
+            topic = "vector"
+            encoding.codec = "json""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "mqtt")]
+impl SinkConfig for MqttSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        let sink = MqttSink::new(self, cx.acker())?;
+        let healthcheck = healthcheck(self.connection.clone()).boxed();
+        Ok((super::VectorSink::Stream(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "mqtt"
+    }
+}
+
+async fn healthcheck(connection: MqttConnectionConfig) -> crate::Result<()> {
+    let mut options = connection.options()?;
+    // Don't clash with the client id of the sink itself.
+    options.set_client_id(format!("{}-healthcheck", connection.client_id));
+    let (client, mut eventloop) = AsyncClient::new(options, 1);
+
+    let connected = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            if let MqttEvent::Incoming(Packet::ConnAck(_)) = eventloop.poll().await? {
+                return Ok::<_, rumqttc::ConnectionError>(());
+            }
+        }
+    })
+    .await
+    .map_err(|_| "timed out connecting to the broker")?;
+
+    let _ = client.try_disconnect();
+    Ok(connected?)
+}
+
+pub struct MqttSink {
+    connection: MqttConnectionConfig,
+    topic: Template,
+    qos: MqttQos,
+    retain: bool,
+    encoding: EncodingConfig<Encoding>,
+    acker: Acker,
+}
+
+impl MqttSink {
+    fn new(config: &MqttSinkConfig, acker: Acker) -> crate::Result<Self> {
+        // Fail early on invalid connection settings.
+        config.connection.options()?;
+        Ok(Self {
+            connection: config.connection.clone(),
+            topic: Template::try_from(config.topic.as_str()).context(TopicTemplate)?,
+            qos: config.qos,
+            retain: config.retain,
+            encoding: config.encoding.clone(),
+            acker,
+        })
+    }
+}
+
+/// The progress of a message handed to the MQTT client.
+#[derive(Debug, Eq, PartialEq)]
+enum Pending {
+    /// Queued, but not yet written to the connection.
+    Queued,
+    /// Written with the given packet id and waiting for the broker's PUBACK.
+    Sent(u16),
+    /// Delivered, or dropped before being handed to the client.
+    Done,
+}
+
+/// Tracks messages in the order they were received so that the acker, which
+/// only counts in-order acknowledgements, is advanced once every earlier
+/// message is done as well.
+#[derive(Debug, Default)]
+struct Inflight {
+    pending: VecDeque<Pending>,
+}
+
+impl Inflight {
+    fn push(&mut self, pending: Pending) {
+        self.pending.push_back(pending);
+    }
+
+    /// The client writes publishes in the order they were queued. QoS 0
+    /// publishes carry packet id 0 and are done as soon as they are written.
+    fn sent(&mut self, pkid: u16) {
+        if pkid != 0 && self.pending.contains(&Pending::Sent(pkid)) {
+            // A retransmission after reconnecting.
+            return;
+        }
+        if let Some(entry) = self.pending.iter_mut().find(|p| **p == Pending::Queued) {
+            *entry = if pkid == 0 {
+                Pending::Done
+            } else {
+                Pending::Sent(pkid)
+            };
+        }
+    }
+
+    fn acked(&mut self, pkid: u16) {
+        if let Some(entry) = self.pending.iter_mut().find(|p| **p == Pending::Sent(pkid)) {
+            *entry = Pending::Done;
+        }
+    }
+
+    /// Removes the leading completed messages, returning how many there were.
+    fn take_done(&mut self) -> usize {
+        let mut count = 0;
+        while self.pending.front() == Some(&Pending::Done) {
+            self.pending.pop_front();
+            count += 1;
+        }
+        count
+    }
+}
+
+enum Notification {
+    Sent(u16),
+    Acked(u16),
+}
+
+/// Drives the connection, forwarding what the sink needs to know about the
+/// delivery of its messages. The event loop is polled from its own task as
+/// polling it is not cancellation safe.
+async fn run_eventloop(
+    mut eventloop: EventLoop,
+    notifications: mpsc::UnboundedSender<Notification>,
+) {
+    loop {
+        let notification = match eventloop.poll().await {
+            Ok(MqttEvent::Outgoing(Outgoing::Publish(pkid))) => Notification::Sent(pkid),
+            Ok(MqttEvent::Incoming(Packet::PubAck(ack))) => Notification::Acked(ack.pkid),
+            Ok(MqttEvent::Outgoing(Outgoing::Disconnect)) => break,
+            Ok(_) => continue,
+            Err(error) => {
+                emit!(MqttConnectionFailed { error });
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+        if notifications.send(notification).is_err() {
+            break;
+        }
+    }
+}
+
+#[async_trait]
+impl StreamSink for MqttSink {
+    async fn run(&mut self, input: BoxStream<'_, Event>) -> Result<(), ()> {
+        let options = self.connection.options().map_err(|error| {
+            error!(message = "Invalid MQTT connection settings.", %error);
+        })?;
+        let (client, eventloop) = AsyncClient::new(options, 1024);
+        let (sender, mut notifications) = mpsc::unbounded_channel();
+        tokio::spawn(run_eventloop(eventloop, sender));
+
+        let mut inflight = Inflight::default();
+        let mut input = input.fuse();
+
+        loop {
+            tokio::select! {
+                event = input.next() => match event {
+                    Some(event) => self.publish(&client, event, &mut inflight).await,
+                    None => break,
+                },
+                notification = notifications.recv() => match notification {
+                    Some(Notification::Sent(pkid)) => inflight.sent(pkid),
+                    Some(Notification::Acked(pkid)) => inflight.acked(pkid),
+                    None => break,
+                },
+            }
+            self.ack(&mut inflight);
+        }
+
+        // Wait for the outstanding messages to be delivered before shutting
+        // down the connection.
+        while !inflight.pending.is_empty() {
+            match notifications.recv().await {
+                Some(Notification::Sent(pkid)) => inflight.sent(pkid),
+                Some(Notification::Acked(pkid)) => inflight.acked(pkid),
+                None => break,
+            }
+            self.ack(&mut inflight);
+        }
+        let _ = client.disconnect().await;
+
+        Ok(())
+    }
+}
+
+impl MqttSink {
+    async fn publish(&self, client: &AsyncClient, event: Event, inflight: &mut Inflight) {
+        let topic = match self.topic.render_string(&event) {
+            Ok(topic) => topic,
+            Err(error) => {
+                emit!(TemplateRenderingFailed {
+                    error,
+                    field: Some("topic"),
+                    drop_event: true,
+                });
+                inflight.push(Pending::Done);
+                return;
+            }
+        };
+
+        let payload = encode_event(event, &self.encoding);
+        let byte_size = payload.len();
+
+        match client
+            .publish(topic, self.qos.into(), self.retain, payload)
+            .await
+        {
+            Ok(()) => {
+                emit!(MqttEventSendSuccess { byte_size });
+                inflight.push(Pending::Queued);
+            }
+            Err(error) => {
+                emit!(MqttEventSendFail { error });
+                inflight.push(Pending::Done);
+            }
+        }
+    }
+
+    fn ack(&self, inflight: &mut Inflight) {
+        let done = inflight.take_done();
+        if done > 0 {
+            self.acker.ack(done);
+        }
+    }
+}
+
+fn encode_event(mut event: Event, encoding: &EncodingConfig<Encoding>) -> Vec<u8> {
+    encoding.apply_rules(&mut event);
+
+    match encoding.codec() {
+        Encoding::Json => serde_json::to_vec(event.as_log()).unwrap(),
+        Encoding::Text => event
+            .as_log()
+            .get(crate::config::log_schema().message_key())
+            .map(|v| v.as_bytes().to_vec())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{Event, Value};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<MqttSinkConfig>();
+    }
+
+    #[test]
+    fn encodes_raw_logs() {
+        let event = Event::from("foo");
+        assert_eq!(
+            b"foo".to_vec(),
+            encode_event(event, &EncodingConfig::from(Encoding::Text))
+        );
+    }
+
+    #[test]
+    fn encodes_log_events() {
+        let mut event = Event::new_empty_log();
+        let log = event.as_mut_log();
+        log.insert("x", Value::from("23"));
+        log.insert("z", Value::from(25));
+        log.insert("a", Value::from("0"));
+
+        let bytes = encode_event(event, &EncodingConfig::from(Encoding::Json));
+        let map: BTreeMap<String, Value> = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(map.get("x"), Some(&Value::from("23")));
+        assert_eq!(map.get("z"), Some(&Value::from(25)));
+        assert_eq!(map.get("a"), Some(&Value::from("0")));
+    }
+
+    #[test]
+    fn inflight_acks_in_order() {
+        let mut inflight = Inflight::default();
+        inflight.push(Pending::Queued);
+        inflight.push(Pending::Queued);
+        inflight.push(Pending::Done);
+        inflight.push(Pending::Queued);
+
+        inflight.sent(1);
+        inflight.sent(2);
+        inflight.sent(0);
+        // The second message is acknowledged first, but the first one is
+        // still waiting for its PUBACK.
+        inflight.acked(2);
+        assert_eq!(inflight.take_done(), 0);
+
+        // Retransmissions don't consume queued entries.
+        inflight.sent(1);
+        inflight.acked(1);
+        assert_eq!(inflight.take_done(), 4);
+        assert!(inflight.pending.is_empty());
+    }
+}
+
+#[cfg(feature = "mqtt-integration-tests")]
+#[cfg(test)]
+mod integration_tests {
+    use super::*;
+    use crate::test_util::{random_lines_with_stream, random_string, trace_init};
+    use rumqttc::{MqttOptions, QoS};
+
+    async fn send_and_receive(qos: u8) {
+        trace_init();
+
+        let topic = format!("test-{}", random_string(10));
+        let config: MqttSinkConfig = toml::from_str(&format!(
+            r#"
+            host = "127.0.0.1"
+            client_id = "vector-sink-{}"
+            topic = "{}/{{{{ kind }}}}"
+            qos = {}
+            encoding.codec = "text""#,
+            random_string(10),
+            topic,
+            qos
+        ))
+        .unwrap();
+
+        let options = MqttOptions::new(
+            format!("subscriber-{}", random_string(10)),
+            "127.0.0.1",
+            1883,
+        );
+        let (client, mut eventloop) = AsyncClient::new(options, 10);
+        client
+            .subscribe(format!("{}/#", topic), QoS::AtLeastOnce)
+            .await
+            .unwrap();
+        // Wait for the subscription to be in place.
+        loop {
+            if let MqttEvent::Incoming(Packet::SubAck(_)) = eventloop.poll().await.unwrap() {
+                break;
+            }
+        }
+
+        healthcheck(config.connection.clone())
+            .await
+            .expect("healthcheck failed");
+
+        let (acker, ack_counter) = Acker::new_for_testing();
+        let sink = MqttSink::new(&config, acker).unwrap();
+
+        let num_events = 100;
+        let (input, events) = random_lines_with_stream(100, num_events, None);
+        let events = events.map(|mut event| {
+            event.as_mut_log().insert("kind", "lines");
+            event
+        });
+        super::super::VectorSink::Stream(Box::new(sink))
+            .run(events)
+            .await
+            .unwrap();
+        assert_eq!(
+            ack_counter.load(std::sync::atomic::Ordering::Relaxed),
+            num_events
+        );
+
+        let mut output = Vec::new();
+        while output.len() < num_events {
+            if let MqttEvent::Incoming(Packet::Publish(publish)) = eventloop.poll().await.unwrap() {
+                assert_eq!(publish.topic, format!("{}/lines", topic));
+                output.push(String::from_utf8(publish.payload.to_vec()).unwrap());
+            }
+        }
+        assert_eq!(input, output);
+    }
+
+    #[tokio::test]
+    async fn mqtt_qos0() {
+        send_and_receive(0).await;
+    }
+
+    #[tokio::test]
+    async fn mqtt_qos1() {
+        send_and_receive(1).await;
+    }
+}
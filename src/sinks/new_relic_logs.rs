@@ -9,7 +9,6 @@ use crate::{
         },
     },
 };
-use http::Uri;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use snafu::Snafu;
@@ -129,8 +128,8 @@ impl NewRelicLogsConfig {
         }
 
         let uri = match self.region.as_ref().unwrap_or(&NewRelicLogsRegion::Us) {
-            NewRelicLogsRegion::Us => Uri::from_static("https://log-api.newrelic.com/log/v1"),
-            NewRelicLogsRegion::Eu => Uri::from_static("https://log-api.eu.newrelic.com/log/v1"),
+            NewRelicLogsRegion::Us => "https://log-api.newrelic.com/log/v1",
+            NewRelicLogsRegion::Eu => "https://log-api.eu.newrelic.com/log/v1",
         };
 
         let batch = self.batch.use_size_as_bytes()?;
@@ -154,13 +153,16 @@ impl NewRelicLogsConfig {
         let request = RequestConfig { tower, headers };
 
         Ok(HttpSinkConfig {
-            uri: uri.into(),
+            uri: uri.to_owned(),
             method: Some(HttpMethod::Post),
             auth: None,
             headers: None,
             compression: self.compression,
             encoding: EncodingConfig::<Encoding>::from(self.encoding.clone()).into_encoding(),
             batch,
+            payload_prefix: None,
+            payload_suffix: None,
+            payload_separator: None,
             request,
             tls: None,
         })
@@ -211,7 +213,7 @@ mod tests {
         let http_config = nr_config.create_config().unwrap();
 
         assert_eq!(
-            format!("{}", http_config.uri.uri),
+            http_config.uri,
             "https://log-api.newrelic.com/log/v1".to_string()
         );
         assert_eq!(http_config.method, Some(HttpMethod::Post));
@@ -245,7 +247,7 @@ mod tests {
         let http_config = nr_config.create_config().unwrap();
 
         assert_eq!(
-            format!("{}", http_config.uri.uri),
+            http_config.uri,
             "https://log-api.eu.newrelic.com/log/v1".to_string()
         );
         assert_eq!(http_config.method, Some(HttpMethod::Post));
@@ -283,7 +285,7 @@ mod tests {
         let http_config = nr_config.create_config().unwrap();
 
         assert_eq!(
-            format!("{}", http_config.uri.uri),
+            http_config.uri,
             "https://log-api.eu.newrelic.com/log/v1".to_string()
         );
         assert_eq!(http_config.method, Some(HttpMethod::Post));
@@ -329,10 +331,7 @@ mod tests {
         let mut nr_config = NewRelicLogsConfig::with_encoding(Encoding::Json);
         nr_config.license_key = Some("foo".to_owned());
         let mut http_config = nr_config.create_config().unwrap();
-        http_config.uri = format!("http://{}/fake_nr", in_addr)
-            .parse::<http::Uri>()
-            .unwrap()
-            .into();
+        http_config.uri = format!("http://{}/fake_nr", in_addr);
 
         let (sink, _healthcheck) = http_config.build(SinkContext::new_test()).await.unwrap();
         let (rx, trigger, server) = build_test_server(in_addr);
@@ -2,7 +2,7 @@ use crate::{
     event::metric::{Metric, MetricKind, MetricValue, StatisticKind},
     sinks::util::{encode_namespace, statistic::DistributionStatistic},
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use indexmap::map::IndexMap;
 use prometheus_parser::{proto, METRIC_NAME_LABEL};
 use std::collections::BTreeMap;
@@ -183,23 +183,39 @@ pub(super) trait MetricCollector {
     }
 }
 
+/// The text formats the metrics can be exposed with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum TextFormat {
+    /// The classic Prometheus text format, version 0.0.4.
+    Prometheus,
+    /// The OpenMetrics text format, version 1.0.0.
+    OpenMetrics,
+}
+
+struct MetricFamily {
+    r#type: proto::MetricType,
+    text: String,
+}
+
 pub(super) struct StringCollector {
+    format: TextFormat,
     // BTreeMap ensures we get sorted output, which whilst not required is preferable
-    processed: BTreeMap<String, String>,
+    processed: BTreeMap<String, MetricFamily>,
 }
 
 impl MetricCollector for StringCollector {
     type Output = String;
 
     fn new() -> Self {
-        let processed = BTreeMap::new();
-        Self { processed }
+        Self::with_format(TextFormat::Prometheus)
     }
 
     fn emit_metadata(&mut self, name: &str, fullname: &str, value: &MetricValue) {
         if !self.processed.contains_key(fullname) {
-            let header = Self::encode_header(name, fullname, value);
-            self.processed.insert(fullname.into(), header);
+            let r#type = prometheus_metric_type(value);
+            let text = self.encode_header(name, fullname, r#type);
+            self.processed
+                .insert(fullname.into(), MetricFamily { r#type, text });
         }
     }
 
@@ -212,26 +228,97 @@ impl MetricCollector for StringCollector {
         tags: Option<&BTreeMap<String, String>>,
         extra: Option<(&str, String)>,
     ) {
-        let result = self
+        let format = self.format;
+        let family = self
             .processed
             .get_mut(name)
             .expect("metric metadata not encoded");
+        let result = &mut family.text;
 
-        result.push_str(name);
-        result.push_str(suffix);
+        match format {
+            TextFormat::Prometheus => {
+                result.push_str(name);
+                result.push_str(suffix);
+            }
+            TextFormat::OpenMetrics => {
+                // OpenMetrics summaries are only made of quantiles, a sum, a
+                // count and a created time.
+                if matches!(suffix, "_min" | "_max" | "_avg") {
+                    return;
+                }
+                result.push_str(openmetrics_family_name(name, family.r#type));
+                match (family.r#type, suffix) {
+                    (proto::MetricType::Counter, "") => result.push_str("_total"),
+                    _ => result.push_str(suffix),
+                }
+            }
+        }
         Self::encode_tags(result, tags, extra);
-        let _ = match timestamp_millis {
-            None => writeln!(result, " {}", value),
-            Some(timestamp) => writeln!(result, " {} {}", value, timestamp),
+        let _ = match (timestamp_millis, format) {
+            (None, _) => writeln!(result, " {}", value),
+            (Some(timestamp), TextFormat::Prometheus) => {
+                writeln!(result, " {} {}", value, timestamp)
+            }
+            (Some(timestamp), TextFormat::OpenMetrics) => writeln!(
+                result,
+                " {} {}.{:03}",
+                value,
+                timestamp.div_euclid(1000),
+                timestamp.rem_euclid(1000)
+            ),
         };
     }
 
     fn finish(self) -> String {
-        self.processed.into_iter().map(|(_, value)| value).collect()
+        let mut output: String = self
+            .processed
+            .into_iter()
+            .map(|(_, family)| family.text)
+            .collect();
+        if self.format == TextFormat::OpenMetrics {
+            output.push_str("# EOF\n");
+        }
+        output
     }
 }
 
 impl StringCollector {
+    pub(super) fn with_format(format: TextFormat) -> Self {
+        Self {
+            format,
+            processed: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the `_created` sample of a counter, histogram or summary, which
+    /// only the OpenMetrics format has. It must follow the other samples of
+    /// the metric.
+    pub(super) fn emit_created(
+        &mut self,
+        default_namespace: Option<&str>,
+        metric: &Metric,
+        created: DateTime<Utc>,
+    ) {
+        if self.format != TextFormat::OpenMetrics {
+            return;
+        }
+        let name = encode_namespace(metric.namespace().or(default_namespace), '_', metric.name());
+        let family = match self.processed.get_mut(&name) {
+            Some(family) if family.r#type != proto::MetricType::Gauge => family,
+            _ => return,
+        };
+        let result = &mut family.text;
+        result.push_str(openmetrics_family_name(&name, family.r#type));
+        result.push_str("_created");
+        Self::encode_tags(result, metric.tags(), None);
+        let _ = writeln!(
+            result,
+            " {}.{:03}",
+            created.timestamp(),
+            created.timestamp_subsec_millis()
+        );
+    }
+
     fn encode_tags(
         result: &mut String,
         tags: Option<&BTreeMap<String, String>>,
@@ -257,12 +344,35 @@ impl StringCollector {
         .ok();
     }
 
-    fn encode_header(name: &str, fullname: &str, value: &MetricValue) -> String {
-        let r#type = prometheus_metric_type(value).as_str();
-        format!(
-            "# HELP {} {}\n# TYPE {} {}\n",
-            fullname, name, fullname, r#type
-        )
+    fn encode_header(&self, name: &str, fullname: &str, r#type: proto::MetricType) -> String {
+        match self.format {
+            TextFormat::Prometheus => format!(
+                "# HELP {} {}\n# TYPE {} {}\n",
+                fullname,
+                name,
+                fullname,
+                r#type.as_str()
+            ),
+            TextFormat::OpenMetrics => {
+                let family = openmetrics_family_name(fullname, r#type);
+                format!(
+                    "# TYPE {} {}\n# HELP {} {}\n",
+                    family,
+                    r#type.as_str(),
+                    family,
+                    name
+                )
+            }
+        }
+    }
+}
+
+/// OpenMetrics counter families are named without the `_total` suffix of
+/// their samples.
+fn openmetrics_family_name(name: &str, r#type: proto::MetricType) -> &str {
+    match r#type {
+        proto::MetricType::Counter => name.strip_suffix("_total").unwrap_or(name),
+        _ => name,
     }
 }
 
@@ -780,6 +890,83 @@ mod tests {
         )
     }
 
+    #[test]
+    fn encodes_counter_openmetrics() {
+        let metric = Metric::new(
+            "hits_total".to_owned(),
+            MetricKind::Absolute,
+            MetricValue::Counter { value: 10.0 },
+        )
+        .with_tags(Some(tags()))
+        .with_timestamp(Some(timestamp()));
+        let mut s = StringCollector::with_format(TextFormat::OpenMetrics);
+        s.encode_metric(Some("vector"), &[], &[], false, &metric);
+        s.emit_created(Some("vector"), &metric, timestamp());
+
+        assert_eq!(
+            s.finish(),
+            indoc! { r#"
+                # TYPE vector_hits counter
+                # HELP vector_hits hits_total
+                vector_hits_total{code="200"} 10 1612325106.789
+                vector_hits_created{code="200"} 1612325106.789
+                # EOF
+            "#}
+        );
+    }
+
+    #[test]
+    fn encodes_distribution_summary_openmetrics() {
+        let metric = Metric::new(
+            "requests".to_owned(),
+            MetricKind::Absolute,
+            MetricValue::Distribution {
+                samples: vector_core::samples![1.0 => 3, 2.0 => 3, 3.0 => 2],
+                statistic: StatisticKind::Summary,
+            },
+        )
+        .with_tags(Some(tags()));
+        let mut s = StringCollector::with_format(TextFormat::OpenMetrics);
+        s.encode_metric(Some("ns"), &[], &[0.5, 0.99], false, &metric);
+        s.emit_created(Some("ns"), &metric, timestamp());
+
+        assert_eq!(
+            s.finish(),
+            indoc! {r#"
+                # TYPE ns_requests summary
+                # HELP ns_requests requests
+                ns_requests{code="200",quantile="0.5"} 2
+                ns_requests{code="200",quantile="0.99"} 3
+                ns_requests_sum{code="200"} 15
+                ns_requests_count{code="200"} 8
+                ns_requests_created{code="200"} 1612325106.789
+                # EOF
+            "#}
+        );
+    }
+
+    #[test]
+    fn skips_created_of_gauges_openmetrics() {
+        let metric = Metric::new(
+            "temperature".to_owned(),
+            MetricKind::Absolute,
+            MetricValue::Gauge { value: -1.1 },
+        );
+        let mut s = StringCollector::with_format(TextFormat::OpenMetrics);
+        s.encode_metric(None, &[], &[], false, &metric);
+        s.emit_created(None, &metric, timestamp());
+
+        assert_eq!(
+            s.finish(),
+            indoc! {r#"
+                # TYPE temperature gauge
+                # HELP temperature temperature
+                temperature -1.1
+                # EOF
+            "#}
+        );
+    }
+
     #[test]
     fn encodes_timestamp_text() {
         assert_eq!(
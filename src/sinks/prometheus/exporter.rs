@@ -1,7 +1,7 @@
 use crate::{
     buffers::Acker,
     config::{DataType, GenerateConfig, Resource, SinkConfig, SinkContext, SinkDescription},
-    event::metric::{Metric, MetricData, MetricKind, MetricValue},
+    event::metric::{Metric, MetricData, MetricKind, MetricValue, StatisticKind},
     event::Event,
     internal_events::PrometheusServerRequestComplete,
     sinks::{
@@ -11,10 +11,10 @@ use crate::{
     tls::{MaybeTlsSettings, TlsConfig},
 };
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use futures::{future, stream::BoxStream, FutureExt, StreamExt};
 use hyper::{
-    header::HeaderValue,
+    header::{HeaderValue, ACCEPT},
     service::{make_service_fn, service_fn},
     Body, Method, Request, Response, Server, StatusCode,
 };
@@ -28,10 +28,11 @@ use std::{
     net::SocketAddr,
     ops::{Deref, DerefMut},
     sync::{Arc, RwLock},
+    time::Duration,
 };
 use stream_cancel::{Trigger, Tripwire};
 
-use super::collector::{self, MetricCollector as _};
+use super::collector::{self, MetricCollector as _, TextFormat};
 
 const MIN_FLUSH_PERIOD_SECS: u64 = 1;
 
@@ -39,6 +40,8 @@ const MIN_FLUSH_PERIOD_SECS: u64 = 1;
 enum BuildError {
     #[snafu(display("Flush period for sets must be greater or equal to {} secs", min))]
     FlushPeriodTooShort { min: u64 },
+    #[snafu(display("Time to live of series must be greater or equal to {} secs", min))]
+    TtlTooShort { min: u64 },
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -55,6 +58,14 @@ pub struct PrometheusExporterConfig {
     pub quantiles: Vec<f64>,
     #[serde(default = "default_flush_period_secs")]
     pub flush_period_secs: u64,
+    /// Series which weren't updated for this long are removed. They are kept
+    /// until Vector restarts by default.
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    /// Exposes the distributions as summaries, computing their quantiles
+    /// from the samples, instead of as histograms.
+    #[serde(default)]
+    pub distributions_as_summaries: bool,
 }
 
 impl std::default::Default for PrometheusExporterConfig {
@@ -66,6 +77,8 @@ impl std::default::Default for PrometheusExporterConfig {
             buckets: super::default_histogram_buckets(),
             quantiles: super::default_summary_quantiles(),
             flush_period_secs: default_flush_period_secs(),
+            ttl_secs: None,
+            distributions_as_summaries: false,
         }
     }
 }
@@ -103,6 +116,11 @@ impl SinkConfig for PrometheusExporterConfig {
                 min: MIN_FLUSH_PERIOD_SECS,
             }));
         }
+        if matches!(self.ttl_secs, Some(ttl_secs) if ttl_secs < MIN_FLUSH_PERIOD_SECS) {
+            return Err(Box::new(BuildError::TtlTooShort {
+                min: MIN_FLUSH_PERIOD_SECS,
+            }));
+        }
 
         validate_quantiles(&self.quantiles)?;
 
@@ -161,34 +179,69 @@ struct PrometheusExporter {
 }
 
 struct ExpiringMetrics {
-    map: IndexMap<MetricEntry, bool>,
+    map: IndexMap<MetricEntry, EntryState>,
     last_flush_timestamp: i64,
 }
 
+struct EntryState {
+    is_incremental_set: bool,
+    /// When the exporter started accumulating the series from incremental
+    /// metrics, exposed as its created time.
+    created: Option<DateTime<Utc>>,
+    last_update_timestamp: i64,
+}
+
+impl ExpiringMetrics {
+    /// Resets the incremental sets, so they don't grow forever.
+    fn flush(&mut self, now: i64) {
+        self.last_flush_timestamp = now;
+        for (MetricEntry(metric), state) in self.map.iter_mut() {
+            if state.is_incremental_set {
+                metric.zero();
+            }
+        }
+    }
+
+    /// Removes the series which weren't updated for `ttl_secs`.
+    fn remove_expired(&mut self, now: i64, ttl_secs: u64) {
+        self.map
+            .retain(|_, state| now - state.last_update_timestamp < ttl_secs as i64);
+    }
+}
+
 fn handle(
     req: Request<Body>,
     default_namespace: Option<&str>,
     buckets: &[f64],
     quantiles: &[f64],
     expired: bool,
-    metrics: &IndexMap<MetricEntry, bool>,
+    metrics: &IndexMap<MetricEntry, EntryState>,
 ) -> Response<Body> {
     let mut response = Response::new(Body::empty());
 
     match (req.method(), req.uri().path()) {
         (&Method::GET, "/metrics") => {
-            let mut s = collector::StringCollector::new();
+            let format = negotiate_format(&req);
+            let mut s = collector::StringCollector::with_format(format);
 
-            for (MetricEntry(metric), _) in metrics {
+            for (MetricEntry(metric), state) in metrics {
                 s.encode_metric(default_namespace, buckets, quantiles, expired, metric);
+                if let Some(created) = state.created {
+                    s.emit_created(default_namespace, metric, created);
+                }
             }
 
             *response.body_mut() = s.finish().into();
 
-            response.headers_mut().insert(
-                "Content-Type",
-                HeaderValue::from_static("text/plain; version=0.0.4"),
-            );
+            let content_type = match format {
+                TextFormat::Prometheus => "text/plain; version=0.0.4",
+                TextFormat::OpenMetrics => {
+                    "application/openmetrics-text; version=1.0.0; charset=utf-8"
+                }
+            };
+            response
+                .headers_mut()
+                .insert("Content-Type", HeaderValue::from_static(content_type));
         }
         _ => {
             *response.status_mut() = StatusCode::NOT_FOUND;
@@ -198,6 +251,42 @@ fn handle(
     response
 }
 
+/// Picks the OpenMetrics format when the scraper accepts it, as Prometheus
+/// does since version 2.5.
+fn negotiate_format(req: &Request<Body>) -> TextFormat {
+    let accepts_openmetrics = req
+        .headers()
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|media_range| {
+            let mut params = media_range.split(';').map(str::trim);
+            // A quality of zero means the media type isn't acceptable.
+            params.next() == Some("application/openmetrics-text")
+                && params.all(|param| {
+                    param
+                        .strip_prefix("q=")
+                        .and_then(|quality| quality.parse::<f64>().ok())
+                        != Some(0.0)
+                })
+        });
+    if accepts_openmetrics {
+        TextFormat::OpenMetrics
+    } else {
+        TextFormat::Prometheus
+    }
+}
+
+/// Converts the distributions into summaries of their samples.
+fn distribution_to_summary(metric: Metric) -> Metric {
+    let (series, mut data, metadata) = metric.into_parts();
+    if let MetricValue::Distribution { statistic, .. } = &mut data.value {
+        *statistic = StatisticKind::Summary;
+    }
+    Metric::from_parts(series, data, metadata)
+}
+
 impl PrometheusExporter {
     fn new(config: PrometheusExporterConfig, acker: Acker) -> Self {
         Self {
@@ -290,46 +379,76 @@ impl PrometheusExporter {
 impl StreamSink for PrometheusExporter {
     async fn run(&mut self, mut input: BoxStream<'_, Event>) -> Result<(), ()> {
         self.start_server_if_needed().await;
-        while let Some(event) = input.next().await {
-            let item = event.into_metric();
+
+        // With a time to live, the series of sources which went away are
+        // removed, as they would otherwise accumulate forever.
+        let ttl_secs = self.config.ttl_secs.unwrap_or(0);
+        let mut expire = tokio::time::interval(Duration::from_secs(ttl_secs.max(1)));
+
+        loop {
+            let event = tokio::select! {
+                _ = expire.tick(), if ttl_secs > 0 => {
+                    self.metrics
+                        .write()
+                        .unwrap()
+                        .remove_expired(Utc::now().timestamp(), ttl_secs);
+                    continue;
+                }
+                event = input.next() => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+            };
+
+            let mut item = event.into_metric();
+            if self.config.distributions_as_summaries {
+                item = distribution_to_summary(item);
+            }
             let mut metrics = self.metrics.write().unwrap();
+            let now = Utc::now();
 
             // sets need to be expired from time to time
             // because otherwise they could grow infinitelly
-            let now = Utc::now().timestamp();
-            let interval = now - metrics.last_flush_timestamp;
+            let interval = now.timestamp() - metrics.last_flush_timestamp;
             if interval > self.config.flush_period_secs as i64 {
-                metrics.last_flush_timestamp = now;
-
-                metrics.map = metrics
-                    .map
-                    .drain(..)
-                    .map(|(MetricEntry(mut metric), is_incremental_set)| {
-                        if is_incremental_set {
-                            metric.zero();
-                        }
-                        (MetricEntry(metric), is_incremental_set)
-                    })
-                    .collect();
+                metrics.flush(now.timestamp());
             }
 
             match item.kind() {
                 MetricKind::Incremental => {
                     let mut entry = MetricEntry(item.into_absolute());
-                    if let Some((MetricEntry(mut existing), _)) = metrics.map.remove_entry(&entry) {
+                    let mut created = now;
+                    if let Some((MetricEntry(mut existing), state)) =
+                        metrics.map.remove_entry(&entry)
+                    {
                         if existing.update(&entry) {
                             entry = MetricEntry(existing);
+                            created = state.created.unwrap_or(now);
                         } else {
                             warn!(message = "Metric changed type, dropping old value.", series = %entry.series());
                         }
                     }
                     let is_set = matches!(entry.value(), MetricValue::Set { .. });
-                    metrics.map.insert(entry, is_set);
+                    metrics.map.insert(
+                        entry,
+                        EntryState {
+                            is_incremental_set: is_set,
+                            created: Some(created),
+                            last_update_timestamp: now.timestamp(),
+                        },
+                    );
                 }
                 MetricKind::Absolute => {
                     let new = MetricEntry(item);
                     metrics.map.remove(&new);
-                    metrics.map.insert(new, false);
+                    metrics.map.insert(
+                        new,
+                        EntryState {
+                            is_incremental_set: false,
+                            created: None,
+                            last_update_timestamp: now.timestamp(),
+                        },
+                    );
                 }
             };
 
@@ -489,6 +608,14 @@ mod tests {
     }
 
     async fn export_and_fetch(tls_config: Option<TlsConfig>, events: Vec<Event>) -> String {
+        export_and_fetch_with_accept(tls_config, events, None).await
+    }
+
+    async fn export_and_fetch_with_accept(
+        tls_config: Option<TlsConfig>,
+        events: Vec<Event>,
+        accept: Option<&str>,
+    ) -> String {
         trace_init();
 
         let client_settings = MaybeTlsSettings::from_config(&tls_config, false).unwrap();
@@ -513,7 +640,11 @@ mod tests {
 
         time::sleep(time::Duration::from_millis(100)).await;
 
-        let request = Request::get(format!("{}://{}/metrics", proto, address))
+        let mut request = Request::get(format!("{}://{}/metrics", proto, address));
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+        let request = request
             .body(Body::empty())
             .expect("Error creating request.");
         let proxy = ProxyConfig::default();
@@ -632,6 +763,137 @@ mod tests {
             &MetricValue::Counter { value: 33. }
         );
     }
+
+    #[test]
+    fn negotiates_openmetrics_format() {
+        let format = |accept: Option<&str>| {
+            let mut request = Request::get("/metrics");
+            if let Some(accept) = accept {
+                request = request.header(ACCEPT, accept);
+            }
+            negotiate_format(&request.body(Body::empty()).unwrap())
+        };
+
+        assert_eq!(format(None), TextFormat::Prometheus);
+        assert_eq!(
+            format(Some("text/plain;version=0.0.4;q=1,*/*;q=0.1")),
+            TextFormat::Prometheus
+        );
+        assert_eq!(
+            format(Some(
+                "application/openmetrics-text; version=0.0.1,text/plain;version=0.0.4;q=0.5"
+            )),
+            TextFormat::OpenMetrics
+        );
+        assert_eq!(
+            format(Some("application/openmetrics-text;q=0,text/plain")),
+            TextFormat::Prometheus
+        );
+    }
+
+    #[tokio::test]
+    async fn exports_openmetrics() {
+        let (name, event) = create_metric(None, MetricValue::Counter { value: 1.5 });
+
+        let body = export_and_fetch_with_accept(
+            None,
+            vec![event],
+            Some("application/openmetrics-text;version=1.0.0"),
+        )
+        .await;
+
+        let mut lines = body.lines();
+        assert_eq!(lines.next(), Some(&*format!("# TYPE {} counter", name)));
+        assert_eq!(lines.next(), Some(&*format!("# HELP {} {}", name, name)));
+        assert_eq!(
+            lines.next(),
+            Some(&*format!("{}_total{{some_tag=\"some_value\"}} 1.5", name))
+        );
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with(&format!("{}_created{{some_tag=\"some_value\"}} ", name)));
+        assert_eq!(lines.next(), Some("# EOF"));
+    }
+
+    #[test]
+    fn expires_stale_series() {
+        let mut metrics = ExpiringMetrics {
+            map: IndexMap::new(),
+            last_flush_timestamp: 100,
+        };
+        let state = |last_update_timestamp, is_incremental_set| EntryState {
+            is_incremental_set,
+            created: None,
+            last_update_timestamp,
+        };
+        let (_, stale) = create_metric_gauge(None, 1.0);
+        let (_, fresh) = create_metric_gauge(None, 2.0);
+        let (_, set) = create_metric_set(None, vec!["0", "1"]);
+        let set = set.into_metric().into_absolute();
+        metrics.map.insert(
+            MetricEntry(stale.into_metric().into_absolute()),
+            state(99, false),
+        );
+        metrics.map.insert(
+            MetricEntry(fresh.into_metric().into_absolute()),
+            state(100, false),
+        );
+        metrics
+            .map
+            .insert(MetricEntry(set.clone()), state(105, true));
+
+        metrics.flush(110);
+
+        assert_eq!(metrics.last_flush_timestamp, 110);
+        assert_eq!(metrics.map.len(), 3);
+        assert_eq!(
+            metrics.map.get_full(&MetricEntry(set)).unwrap().1.value(),
+            &MetricValue::Set {
+                values: Default::default()
+            }
+        );
+
+        metrics.remove_expired(110, 11);
+        assert_eq!(metrics.map.len(), 2);
+
+        metrics.remove_expired(120, 10);
+        assert!(metrics.map.is_empty());
+    }
+
+    #[tokio::test]
+    async fn exports_distributions_as_summaries() {
+        let config = PrometheusExporterConfig {
+            address: next_addr(), // Not actually bound, just needed to fill config
+            distributions_as_summaries: true,
+            ..Default::default()
+        };
+        let cx = SinkContext::new_test();
+
+        let mut sink = PrometheusExporter::new(config, cx.acker());
+
+        let (_, event) = create_metric(
+            None,
+            MetricValue::Distribution {
+                samples: vector_core::samples![1.0 => 2, 3.0 => 1],
+                statistic: StatisticKind::Histogram,
+            },
+        );
+        sink.run(Box::pin(futures::stream::iter(vec![event])))
+            .await
+            .unwrap();
+
+        let metrics = sink.metrics.read().unwrap();
+        let (MetricEntry(metric), state) = metrics.map.get_index(0).unwrap();
+        assert_eq!(
+            metric.value(),
+            &MetricValue::Distribution {
+                samples: vector_core::samples![1.0 => 2, 3.0 => 1],
+                statistic: StatisticKind::Summary,
+            }
+        );
+        assert!(state.created.is_some());
+    }
 }
 
 #[cfg(all(test, feature = "prometheus-integration-tests"))]
@@ -0,0 +1,263 @@
+use super::build_uri;
+use crate::{
+    http::HttpClient,
+    internal_events::{SplunkIndexerAcknowledgementFailed, SplunkIndexerAcknowledgementTimedOut},
+    sinks::util::sink::{ServiceLogic, StdServiceLogic},
+};
+use bytes::Bytes;
+use futures::{future::BoxFuture, FutureExt};
+use http::{Request, StatusCode};
+use hyper::Body;
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{sync::oneshot, time::Instant};
+use vector_core::event::{EventFinalizers, EventStatus};
+
+pub(super) const CHANNEL_HEADER: &str = "X-Splunk-Request-Channel";
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Derivative, PartialEq)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct HecAcknowledgementsConfig {
+    pub enabled: bool,
+    #[derivative(Default(value = "10"))]
+    pub query_interval_secs: u64,
+    /// Time after which a request that Splunk hasn't acknowledged is
+    /// considered failed.
+    #[derivative(Default(value = "300"))]
+    pub timeout_secs: u64,
+}
+
+#[derive(Debug, Snafu)]
+enum AckQueryError {
+    #[snafu(display("Unexpected status: {}", status))]
+    UnexpectedStatus { status: StatusCode },
+}
+
+#[derive(Serialize)]
+struct HecAckStatusRequest<'a> {
+    acks: &'a [u64],
+}
+
+#[derive(Deserialize)]
+struct HecAckStatusResponse {
+    acks: HashMap<u64, bool>,
+}
+
+#[derive(Deserialize)]
+struct HecEventResponse {
+    #[serde(rename = "ackId")]
+    ack_id: Option<u64>,
+}
+
+struct PendingAck {
+    deadline: Instant,
+    sender: oneshot::Sender<EventStatus>,
+}
+
+/// Polls Splunk for the status of the ack ids returned for our requests.
+pub(super) struct HecAckClient {
+    pending: Mutex<HashMap<u64, PendingAck>>,
+    timeout: Duration,
+    client: HttpClient,
+    endpoint: String,
+    token: String,
+    channel: String,
+}
+
+impl HecAckClient {
+    /// Creates the client along with the task polling Splunk, which runs for
+    /// as long as the client is in use or has pending acks.
+    pub(super) fn spawn(
+        config: HecAcknowledgementsConfig,
+        client: HttpClient,
+        endpoint: String,
+        token: String,
+        channel: String,
+    ) -> Arc<Self> {
+        let ack_client = Arc::new(Self {
+            pending: Mutex::new(HashMap::new()),
+            timeout: Duration::from_secs(config.timeout_secs),
+            client,
+            endpoint,
+            token,
+            channel,
+        });
+        let query_interval = Duration::from_secs(config.query_interval_secs);
+        tokio::spawn(Arc::clone(&ack_client).run(query_interval));
+        ack_client
+    }
+
+    /// Returns a receiver resolving once Splunk acknowledged `ack_id`, or
+    /// once it is given up on.
+    fn add(&self, ack_id: u64) -> oneshot::Receiver<EventStatus> {
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(
+            ack_id,
+            PendingAck {
+                deadline: Instant::now() + self.timeout,
+                sender,
+            },
+        );
+        receiver
+    }
+
+    async fn run(self: Arc<Self>, query_interval: Duration) {
+        let mut interval = tokio::time::interval(query_interval);
+        loop {
+            interval.tick().await;
+
+            let ack_ids = self
+                .pending
+                .lock()
+                .unwrap()
+                .keys()
+                .copied()
+                .collect::<Vec<_>>();
+            if ack_ids.is_empty() {
+                // Nothing else holds on to the client once the sink is gone.
+                if Arc::strong_count(&self) == 1 {
+                    break;
+                }
+                continue;
+            }
+
+            let acked = self.query(&ack_ids).await.unwrap_or_else(|error| {
+                emit!(SplunkIndexerAcknowledgementFailed { error });
+                HashMap::new()
+            });
+            self.update(&ack_ids, &acked);
+        }
+    }
+
+    async fn query(&self, ack_ids: &[u64]) -> crate::Result<HashMap<u64, bool>> {
+        let uri = build_uri(&self.endpoint, "/services/collector/ack")?;
+        let body = serde_json::to_vec(&HecAckStatusRequest { acks: ack_ids })?;
+        let request = Request::post(uri)
+            .header("Content-Type", "application/json")
+            .header("Authorization", format!("Splunk {}", self.token))
+            .header(CHANNEL_HEADER, &self.channel)
+            .body(Body::from(body))?;
+
+        let response = self.client.send(request).await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(AckQueryError::UnexpectedStatus { status }.into());
+        }
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        let response: HecAckStatusResponse = serde_json::from_slice(&body)?;
+        Ok(response.acks)
+    }
+
+    fn update(&self, ack_ids: &[u64], acked: &HashMap<u64, bool>) {
+        let now = Instant::now();
+        let mut pending = self.pending.lock().unwrap();
+        for ack_id in ack_ids {
+            if acked.get(ack_id).copied().unwrap_or(false) {
+                if let Some(ack) = pending.remove(ack_id) {
+                    let _ = ack.sender.send(EventStatus::Delivered);
+                }
+            } else if pending.get(ack_id).map_or(false, |ack| ack.deadline <= now) {
+                emit!(SplunkIndexerAcknowledgementTimedOut { ack_id: *ack_id });
+                let ack = pending.remove(ack_id).unwrap();
+                let _ = ack.sender.send(EventStatus::Errored);
+            }
+        }
+    }
+}
+
+/// Finalizes the events of a request, and lets them be acknowledged to the
+/// buffer, only once Splunk acknowledged it, when indexer acknowledgements
+/// are enabled and the response carries an ack id.
+#[derive(Clone)]
+pub(super) struct HecServiceLogic {
+    pub(super) ack_client: Option<Arc<HecAckClient>>,
+}
+
+impl ServiceLogic for HecServiceLogic {
+    type Response = http::Response<Bytes>;
+
+    fn result_status(&self, result: crate::Result<Self::Response>) -> EventStatus {
+        StdServiceLogic::default().result_status(result)
+    }
+
+    fn update_finalizers(
+        &self,
+        result: crate::Result<Self::Response>,
+        finalizers: EventFinalizers,
+    ) -> BoxFuture<'static, ()> {
+        let ack_id = match (&self.ack_client, &result) {
+            (Some(_), Ok(response)) if response.status().is_success() => {
+                parse_ack_id(response.body())
+            }
+            _ => None,
+        };
+
+        match (&self.ack_client, ack_id) {
+            (Some(ack_client), Some(ack_id)) => {
+                let receiver = ack_client.add(ack_id);
+                async move {
+                    let status = receiver.await.unwrap_or(EventStatus::Errored);
+                    finalizers.update_status(status);
+                }
+                .boxed()
+            }
+            _ => StdServiceLogic::default().update_finalizers(result, finalizers),
+        }
+    }
+}
+
+fn parse_ack_id(body: &[u8]) -> Option<u64> {
+    serde_json::from_slice::<HecEventResponse>(body)
+        .ok()?
+        .ack_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ack_id() {
+        assert_eq!(
+            parse_ack_id(br#"{"text":"Success","code":0,"ackId":3}"#),
+            Some(3)
+        );
+        assert_eq!(parse_ack_id(br#"{"text":"Success","code":0}"#), None);
+        assert_eq!(parse_ack_id(b"not json"), None);
+    }
+
+    #[test]
+    fn gives_up_after_timeout() {
+        let ack_client = HecAckClient {
+            pending: Mutex::new(HashMap::new()),
+            timeout: Duration::from_secs(3600),
+            client: HttpClient::new(None, &Default::default()).unwrap(),
+            endpoint: "http://localhost:8088".into(),
+            token: "token".into(),
+            channel: "channel".into(),
+        };
+        let mut delivered = ack_client.add(0);
+        let mut timed_out = ack_client.add(1);
+
+        let acked = vec![(0, true)].into_iter().collect();
+        ack_client.update(&[0, 1], &acked);
+        assert_eq!(delivered.try_recv(), Ok(EventStatus::Delivered));
+        assert!(timed_out.try_recv().is_err());
+
+        ack_client
+            .pending
+            .lock()
+            .unwrap()
+            .get_mut(&1)
+            .unwrap()
+            .deadline = Instant::now();
+        ack_client.update(&[1], &HashMap::new());
+        assert_eq!(timed_out.try_recv(), Ok(EventStatus::Errored));
+    }
+}
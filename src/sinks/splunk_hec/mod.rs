@@ -1,11 +1,16 @@
 use crate::{
     config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
-    event::{Event, LogEvent, Value},
+    event::{Event, LogEvent, Metric, MetricValue, Value},
     http::HttpClient,
-    internal_events::{SplunkEventEncodeError, SplunkEventSent, TemplateRenderingFailed},
+    internal_events::{
+        SplunkEventEncodeError, SplunkEventSent, SplunkInvalidMetricReceived,
+        TemplateRenderingFailed,
+    },
     sinks::util::{
+        encode_namespace,
         encoding::{EncodingConfig, EncodingConfiguration},
-        http::{BatchedHttpSink, HttpSink},
+        http::{BatchedHttpSink, HttpRetryLogic, HttpSink},
+        statistic::DistributionStatistic,
         BatchConfig, BatchSettings, Buffer, Compression, Concurrency, TowerRequestConfig,
     },
     template::Template,
@@ -15,9 +20,15 @@ use futures::{FutureExt, SinkExt};
 use http::{Request, StatusCode, Uri};
 use hyper::Body;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value as JsonValue};
 use snafu::{ResultExt, Snafu};
 use std::convert::TryFrom;
+use uuid::Uuid;
+
+mod acknowledgements;
+
+pub use self::acknowledgements::HecAcknowledgementsConfig;
+use self::acknowledgements::{HecAckClient, HecServiceLogic, CHANNEL_HEADER};
 
 #[derive(Debug, Snafu)]
 pub enum BuildError {
@@ -47,6 +58,8 @@ pub struct HecSinkConfig {
     #[serde(default)]
     pub request: TowerRequestConfig,
     pub tls: Option<TlsOptions>,
+    #[serde(default)]
+    pub indexer_acknowledgements: HecAcknowledgementsConfig,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Derivative)]
@@ -79,6 +92,7 @@ impl GenerateConfig for HecSinkConfig {
             batch: BatchConfig::default(),
             request: TowerRequestConfig::default(),
             tls: None,
+            indexer_acknowledgements: HecAcknowledgementsConfig::default(),
         })
         .unwrap()
     }
@@ -105,13 +119,33 @@ impl SinkConfig for HecSinkConfig {
         let tls_settings = TlsSettings::from_options(&self.tls)?;
         let client = HttpClient::new(tls_settings, &cx.proxy)?;
 
-        let sink = BatchedHttpSink::new(
-            self.clone(),
+        // Splunk requires a channel to track the acknowledgements of requests.
+        let channel = self
+            .indexer_acknowledgements
+            .enabled
+            .then(|| Uuid::new_v4().to_hyphenated().to_string());
+        let ack_client = channel.clone().map(|channel| {
+            HecAckClient::spawn(
+                self.indexer_acknowledgements,
+                client.clone(),
+                self.endpoint.clone(),
+                self.token.clone(),
+                channel,
+            )
+        });
+
+        let sink = BatchedHttpSink::with_logic(
+            HecSink {
+                config: self.clone(),
+                channel,
+            },
             Buffer::new(batch.size, self.compression),
+            HttpRetryLogic,
             request,
             batch.timeout,
             client.clone(),
             cx.acker(),
+            HecServiceLogic { ack_client },
         )
         .sink_map_err(|error| error!(message = "Fatal splunk_hec sink error.", %error));
 
@@ -121,7 +155,7 @@ impl SinkConfig for HecSinkConfig {
     }
 
     fn input_type(&self) -> DataType {
-        DataType::Log
+        DataType::Any
     }
 
     fn sink_type(&self) -> &'static str {
@@ -135,47 +169,59 @@ impl HttpSink for HecSinkConfig {
     type Output = Vec<u8>;
 
     fn encode_event(&self, event: Event) -> Option<Self::Input> {
-        let sourcetype = self.sourcetype.as_ref().and_then(|sourcetype| {
-            sourcetype
-                .render_string(&event)
-                .map_err(|error| {
-                    emit!(TemplateRenderingFailed {
-                        error,
-                        field: Some("sourcetype"),
-                        drop_event: false,
-                    });
-                })
-                .ok()
-        });
+        let sourcetype = render_template(self.sourcetype.as_ref(), &event, "sourcetype");
+        let source = render_template(self.source.as_ref(), &event, "source");
+        let index = render_template(self.index.as_ref(), &event, "index");
 
-        let source = self.source.as_ref().and_then(|source| {
-            source
-                .render_string(&event)
-                .map_err(|error| {
-                    emit!(TemplateRenderingFailed {
-                        error,
-                        field: Some("source"),
-                        drop_event: false,
-                    });
-                })
-                .ok()
-        });
+        let mut body = match event {
+            Event::Log(log) => self.encode_log(log),
+            Event::Metric(metric) => self.encode_metric(metric)?,
+        };
 
-        let index = self.index.as_ref().and_then(|index| {
-            index
-                .render_string(&event)
-                .map_err(|error| {
-                    emit!(TemplateRenderingFailed {
-                        error,
-                        field: Some("index"),
-                        drop_event: false,
-                    });
-                })
-                .ok()
-        });
+        if let Some(index) = index {
+            body["index"] = json!(index);
+        }
+
+        if let Some(source) = source {
+            body["source"] = json!(source);
+        }
+
+        if let Some(sourcetype) = &sourcetype {
+            body["sourcetype"] = json!(sourcetype);
+        }
+
+        match serde_json::to_vec(&body) {
+            Ok(value) => {
+                emit!(SplunkEventSent {
+                    byte_size: value.len()
+                });
+                Some(value)
+            }
+            Err(error) => {
+                emit!(SplunkEventEncodeError { error });
+                None
+            }
+        }
+    }
 
-        let mut event = event.into_log();
+    async fn build_request(&self, events: Self::Output) -> crate::Result<Request<Vec<u8>>> {
+        let uri =
+            build_uri(&self.endpoint, "/services/collector/event").expect("Unable to parse URI");
+
+        let mut builder = Request::post(uri)
+            .header("Content-Type", "application/json")
+            .header("Authorization", format!("Splunk {}", self.token));
+
+        if let Some(ce) = self.compression.content_encoding() {
+            builder = builder.header("Content-Encoding", ce);
+        }
 
+        builder.body(events).map_err(Into::into)
+    }
+}
+
+impl HecSinkConfig {
+    fn encode_log(&self, mut event: LogEvent) -> JsonValue {
         let host = event.get(self.host_key.to_owned()).cloned();
 
         let timestamp = match event.remove(log_schema().timestamp_key()) {
@@ -213,45 +259,147 @@ impl HttpSink for HecSinkConfig {
             body["host"] = json!(host);
         }
 
-        if let Some(index) = index {
-            body["index"] = json!(index);
-        }
+        body
+    }
 
-        if let Some(source) = source {
-            body["source"] = json!(source);
+    /// Encodes the metric in the HEC multi-metric format, with its tags as
+    /// dimensions. Metrics that consist of several values are flattened into
+    /// one measurement per value.
+    fn encode_metric(&self, metric: Metric) -> Option<JsonValue> {
+        let values = match metric_values(metric.value()) {
+            Some(values) => values,
+            None => {
+                emit!(SplunkInvalidMetricReceived {
+                    value: metric.value(),
+                    kind: &metric.kind(),
+                });
+                return None;
+            }
+        };
+
+        let timestamp = metric.timestamp().unwrap_or_else(chrono::Utc::now);
+        let timestamp = (timestamp.timestamp_millis() as f64) / 1000f64;
+
+        let mut fields = metric
+            .tags()
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| (key.clone(), json!(value)))
+            .collect::<serde_json::Map<_, _>>();
+        let host = fields.remove(&self.host_key);
+
+        let name = encode_namespace(metric.namespace(), '.', metric.name());
+        for (suffix, value) in values {
+            fields.insert(format!("metric_name:{}{}", name, suffix), json!(value));
         }
 
-        if let Some(sourcetype) = &sourcetype {
-            body["sourcetype"] = json!(sourcetype);
+        let mut body = json!({
+            "event": "metric",
+            "fields": fields,
+            "time": timestamp
+        });
+
+        if let Some(host) = host {
+            body["host"] = host;
         }
 
-        match serde_json::to_vec(&body) {
-            Ok(value) => {
-                emit!(SplunkEventSent {
-                    byte_size: value.len()
-                });
-                Some(value)
-            }
-            Err(error) => {
-                emit!(SplunkEventEncodeError { error });
-                None
-            }
+        Some(body)
+    }
+}
+
+/// The values of a metric along with the suffix of their measurement names,
+/// or `None` if the metric has no value to send.
+fn metric_values(value: &MetricValue) -> Option<Vec<(String, f64)>> {
+    match value {
+        MetricValue::Counter { value } | MetricValue::Gauge { value } => {
+            Some(vec![(String::new(), *value)])
+        }
+        MetricValue::Set { values } => Some(vec![(String::new(), values.len() as f64)]),
+        MetricValue::Distribution { samples, .. } => {
+            let statistic = DistributionStatistic::from_samples(samples, &[])?;
+            Some(vec![
+                (".count".into(), statistic.count as f64),
+                (".sum".into(), statistic.sum),
+                (".min".into(), statistic.min),
+                (".max".into(), statistic.max),
+                (".median".into(), statistic.median),
+                (".avg".into(), statistic.avg),
+            ])
         }
+        MetricValue::AggregatedHistogram {
+            buckets,
+            count,
+            sum,
+        } => Some(
+            vec![(".count".into(), *count as f64), (".sum".into(), *sum)]
+                .into_iter()
+                .chain(buckets.iter().map(|bucket| {
+                    (
+                        format!(".bucket.{}", bucket.upper_limit),
+                        bucket.count as f64,
+                    )
+                }))
+                .collect(),
+        ),
+        MetricValue::AggregatedSummary {
+            quantiles,
+            count,
+            sum,
+        } => Some(
+            vec![(".count".into(), *count as f64), (".sum".into(), *sum)]
+                .into_iter()
+                .chain(quantiles.iter().map(|quantile| {
+                    (
+                        format!(".quantile.{}", quantile.upper_limit),
+                        quantile.value,
+                    )
+                }))
+                .collect(),
+        ),
     }
+}
 
-    async fn build_request(&self, events: Self::Output) -> crate::Result<Request<Vec<u8>>> {
-        let uri =
-            build_uri(&self.endpoint, "/services/collector/event").expect("Unable to parse URI");
+fn render_template(
+    template: Option<&Template>,
+    event: &Event,
+    field: &'static str,
+) -> Option<String> {
+    template.and_then(|template| {
+        template
+            .render_string(event)
+            .map_err(|error| {
+                emit!(TemplateRenderingFailed {
+                    error,
+                    field: Some(field),
+                    drop_event: false,
+                });
+            })
+            .ok()
+    })
+}
 
-        let mut builder = Request::post(uri)
-            .header("Content-Type", "application/json")
-            .header("Authorization", format!("Splunk {}", self.token));
+struct HecSink {
+    config: HecSinkConfig,
+    channel: Option<String>,
+}
 
-        if let Some(ce) = self.compression.content_encoding() {
-            builder = builder.header("Content-Encoding", ce);
-        }
+#[async_trait::async_trait]
+impl HttpSink for HecSink {
+    type Input = Vec<u8>;
+    type Output = Vec<u8>;
 
-        builder.body(events).map_err(Into::into)
+    fn encode_event(&self, event: Event) -> Option<Self::Input> {
+        self.config.encode_event(event)
+    }
+
+    async fn build_request(&self, events: Self::Output) -> crate::Result<Request<Vec<u8>>> {
+        let mut request = self.config.build_request(events).await?;
+        if let Some(channel) = &self.channel {
+            request
+                .headers_mut()
+                .insert(CHANNEL_HEADER, channel.parse()?);
+        }
+        Ok(request)
     }
 }
 
@@ -297,11 +445,20 @@ fn build_uri(host: &str, path: &str) -> Result<Uri, http::uri::InvalidUri> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::event::Event;
-    use crate::sinks::util::{http::HttpSink, test::load_sink};
-    use chrono::Utc;
+    use crate::event::{
+        metric::{Bucket, MetricKind, StatisticKind},
+        Event,
+    };
+    use crate::sinks::util::{
+        http::HttpSink,
+        test::{build_test_server_generic, load_sink},
+    };
+    use crate::test_util::{next_addr, trace_init};
+    use chrono::{TimeZone, Utc};
+    use futures::{stream, StreamExt};
     use serde::Deserialize;
-    use std::collections::BTreeMap;
+    use std::{collections::BTreeMap, future::ready};
+    use vector_core::event::{BatchNotifier, BatchStatus};
 
     #[test]
     fn generate_config() {
@@ -417,6 +574,122 @@ mod tests {
         assert_eq!((hec_event.time * 1000f64).fract(), 0f64);
     }
 
+    #[test]
+    fn splunk_encode_metric_gauge() {
+        let event = Event::Metric(
+            Metric::new(
+                "usage",
+                MetricKind::Absolute,
+                MetricValue::Gauge { value: 12.5 },
+            )
+            .with_namespace(Some("cpu"))
+            .with_tags(Some(
+                vec![
+                    ("host".to_owned(), "example.com".to_owned()),
+                    ("region".to_owned(), "us-west-1".to_owned()),
+                ]
+                .into_iter()
+                .collect(),
+            ))
+            .with_timestamp(Some(Utc.ymd(2021, 8, 1).and_hms(12, 0, 0))),
+        );
+
+        let (config, _cx) = load_sink::<HecSinkConfig>(
+            r#"
+            host = "test.com"
+            token = "alksjdfo"
+            host_key = "host"
+            index = "metrics"
+            encoding.codec = "json"
+        "#,
+        )
+        .unwrap();
+
+        let bytes = config.encode_event(event).unwrap();
+        let body = serde_json::from_slice::<serde_json::Value>(&bytes[..]).unwrap();
+
+        assert_eq!(
+            body,
+            json!({
+                "event": "metric",
+                "time": 1627819200.0,
+                "host": "example.com",
+                "index": "metrics",
+                "fields": {
+                    "region": "us-west-1",
+                    "metric_name:cpu.usage": 12.5
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn splunk_encode_metric_histogram() {
+        let event = Event::Metric(Metric::new(
+            "latency",
+            MetricKind::Absolute,
+            MetricValue::AggregatedHistogram {
+                buckets: vec![
+                    Bucket {
+                        upper_limit: 0.5,
+                        count: 3,
+                    },
+                    Bucket {
+                        upper_limit: 1.0,
+                        count: 1,
+                    },
+                ],
+                count: 4,
+                sum: 2.5,
+            },
+        ));
+
+        let (config, _cx) = load_sink::<HecSinkConfig>(
+            r#"
+            host = "test.com"
+            token = "alksjdfo"
+            encoding.codec = "json"
+        "#,
+        )
+        .unwrap();
+
+        let bytes = config.encode_event(event).unwrap();
+        let body = serde_json::from_slice::<serde_json::Value>(&bytes[..]).unwrap();
+
+        assert_eq!(
+            body["fields"],
+            json!({
+                "metric_name:latency.count": 4.0,
+                "metric_name:latency.sum": 2.5,
+                "metric_name:latency.bucket.0.5": 3.0,
+                "metric_name:latency.bucket.1": 1.0
+            })
+        );
+    }
+
+    #[test]
+    fn splunk_encode_metric_empty_distribution() {
+        let event = Event::Metric(Metric::new(
+            "latency",
+            MetricKind::Incremental,
+            MetricValue::Distribution {
+                samples: vec![],
+                statistic: StatisticKind::Histogram,
+            },
+        ));
+
+        let (config, _cx) = load_sink::<HecSinkConfig>(
+            r#"
+            host = "test.com"
+            token = "alksjdfo"
+            encoding.codec = "json"
+        "#,
+        )
+        .unwrap();
+
+        assert!(config.encode_event(event).is_none());
+    }
+
     #[test]
     fn splunk_validate_host() {
         let valid = "http://localhost:8888".to_string();
@@ -435,6 +708,50 @@ mod tests {
         assert!(uri.is_ok());
         assert_eq!(format!("{}", uri.unwrap()), "http://test.com/a");
     }
+
+    #[tokio::test]
+    async fn splunk_indexer_acknowledgements() {
+        trace_init();
+
+        let addr = next_addr();
+        let (mut config, cx) = load_sink::<HecSinkConfig>(&format!(
+            r#"
+            endpoint = "http://{}"
+            token = "alksjdfo"
+            encoding.codec = "text"
+            indexer_acknowledgements.enabled = true
+            indexer_acknowledgements.query_interval_secs = 1
+        "#,
+            addr
+        ))
+        .unwrap();
+        config.batch.max_events = Some(1);
+        let (sink, _) = config.build(cx).await.unwrap();
+
+        // The same body answers both the request and the ack query.
+        let (mut rx, trigger, server) = build_test_server_generic(addr, || {
+            hyper::Response::new(Body::from(
+                r#"{"text":"Success","code":0,"ackId":0,"acks":{"0":true}}"#,
+            ))
+        });
+        tokio::spawn(server);
+
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        let event = Event::from("hello").with_batch_notifier(&batch);
+        drop(batch);
+        sink.run(stream::once(ready(event))).await.unwrap();
+        assert_eq!(receiver.await, BatchStatus::Delivered);
+
+        let (parts, _) = rx.next().await.unwrap();
+        assert_eq!(parts.uri.path(), "/services/collector/event");
+        let channel = parts.headers.get(CHANNEL_HEADER).unwrap().clone();
+        let (parts, body) = rx.next().await.unwrap();
+        assert_eq!(parts.uri.path(), "/services/collector/ack");
+        assert_eq!(parts.headers.get(CHANNEL_HEADER), Some(&channel));
+        assert_eq!(&body[..], br#"{"acks":[0]}"#);
+
+        drop(trigger);
+    }
 }
 
 #[cfg(test)]
@@ -496,6 +813,32 @@ mod integration_tests {
         assert!(entry.get("message").is_none());
     }
 
+    #[tokio::test]
+    async fn splunk_insert_message_with_indexer_acknowledgements() {
+        let cx = SinkContext::new_test();
+
+        let mut config = config(Encoding::Text, vec![]).await;
+        config.indexer_acknowledgements = HecAcknowledgementsConfig {
+            enabled: true,
+            query_interval_secs: 1,
+            ..Default::default()
+        };
+        let (sink, _) = config.build(cx).await.unwrap();
+
+        let message = random_string(100);
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        let event = LogEvent::from(message.clone())
+            .with_batch_notifier(&batch)
+            .into();
+        drop(batch);
+        sink.run(stream::once(ready(event))).await.unwrap();
+        assert_eq!(receiver.await, BatchStatus::Delivered);
+
+        let entry = find_entry(message.as_str()).await;
+
+        assert_eq!(message, entry["_raw"].as_str().unwrap());
+    }
+
     #[tokio::test]
     async fn splunk_insert_broken_token() {
         let cx = SinkContext::new_test();
@@ -801,6 +1144,7 @@ mod integration_tests {
             },
             request: TowerRequestConfig::default(),
             tls: None,
+            indexer_acknowledgements: Default::default(),
         }
     }
 
@@ -138,9 +138,9 @@ fn encode_tags(tags: &MetricTags) -> String {
         .iter()
         .map(|(name, value)| {
             if value == "true" {
-                name.to_string()
+                sanitize_tag(name)
             } else {
-                format!("{}:{}", name, value)
+                format!("{}:{}", sanitize_tag(name), sanitize_tag(value))
             }
         })
         .collect();
@@ -148,40 +148,49 @@ fn encode_tags(tags: &MetricTags) -> String {
     parts.join(",")
 }
 
-fn push_event<V: Display>(
-    buf: &mut Vec<String>,
+/// Replaces the characters delimiting the parts of a line and the tags.
+fn sanitize_tag(tag: &str) -> String {
+    tag.replace(|c: char| matches!(c, '|' | ',' | '#' | '\n'), "_")
+}
+
+fn push_line<V: Display>(
+    lines: &mut Vec<String>,
+    name: &str,
     metric: &Metric,
     val: V,
     metric_type: &str,
     sample_rate: Option<u32>,
 ) {
-    buf.push(format!("{}:{}|{}", metric.name(), val, metric_type));
+    let mut line = format!("{}:{}|{}", name, val, metric_type);
 
     if let Some(sample_rate) = sample_rate {
         if sample_rate != 1 {
-            buf.push(format!("@{}", 1.0 / f64::from(sample_rate)))
+            line.push_str(&format!("|@{}", 1.0 / f64::from(sample_rate)));
         }
     };
 
     if let Some(t) = metric.tags() {
-        buf.push(format!("#{}", encode_tags(t)));
+        line.push_str(&format!("|#{}", encode_tags(t)));
     };
+
+    lines.push(line);
 }
 
 fn encode_event(event: Event, default_namespace: Option<&str>) -> Option<Vec<u8>> {
-    let mut buf = Vec::new();
+    let mut lines = Vec::new();
 
     let metric = event.as_metric();
+    let name = encode_namespace(metric.namespace().or(default_namespace), '.', metric.name());
     match metric.value() {
         MetricValue::Counter { value } => {
-            push_event(&mut buf, metric, value, "c", None);
+            push_line(&mut lines, &name, metric, value, "c", None);
         }
         MetricValue::Gauge { value } => {
             match metric.kind() {
                 MetricKind::Incremental => {
-                    push_event(&mut buf, metric, format!("{:+}", value), "g", None)
+                    push_line(&mut lines, &name, metric, format!("{:+}", value), "g", None)
                 }
-                MetricKind::Absolute => push_event(&mut buf, metric, value, "g", None),
+                MetricKind::Absolute => push_line(&mut lines, &name, metric, value, "g", None),
             };
         }
         MetricValue::Distribution { samples, statistic } => {
@@ -189,9 +198,12 @@ fn encode_event(event: Event, default_namespace: Option<&str>) -> Option<Vec<u8>
                 StatisticKind::Histogram => "h",
                 StatisticKind::Summary => "d",
             };
-            for sample in samples {
-                push_event(
-                    &mut buf,
+            // Each sample is a line of its own, as they may have different
+            // sample rates. Samples with a rate of zero don't carry anything.
+            for sample in samples.iter().filter(|sample| sample.rate > 0) {
+                push_line(
+                    &mut lines,
+                    &name,
                     metric,
                     sample.value,
                     metric_type,
@@ -201,7 +213,7 @@ fn encode_event(event: Event, default_namespace: Option<&str>) -> Option<Vec<u8>
         }
         MetricValue::Set { values } => {
             for val in values {
-                push_event(&mut buf, metric, val, "s", None);
+                push_line(&mut lines, &name, metric, val, "s", None);
             }
         }
         _ => {
@@ -214,9 +226,11 @@ fn encode_event(event: Event, default_namespace: Option<&str>) -> Option<Vec<u8>
         }
     };
 
-    let message = encode_namespace(metric.namespace().or(default_namespace), '.', buf.join("|"));
+    if lines.is_empty() {
+        return None;
+    }
 
-    let mut body: Vec<u8> = message.into_bytes();
+    let mut body: Vec<u8> = lines.join("\n").into_bytes();
     body.push(b'\n');
 
     Some(body)
@@ -245,8 +259,10 @@ mod test {
     use tokio::net::UdpSocket;
     use tokio_util::{codec::BytesCodec, udp::UdpFramed};
 
+    use std::str::from_utf8;
+
     #[cfg(feature = "sources-statsd")]
-    use {crate::sources::statsd::parser::parse, std::str::from_utf8};
+    use crate::sources::statsd::parser::parse;
 
     #[test]
     fn generate_config() {
@@ -368,6 +384,47 @@ mod test {
         shared::assert_event_data_eq!(metric1, metric2);
     }
 
+    #[cfg(feature = "sources-statsd")]
+    #[test]
+    fn test_encode_summary_distribution() {
+        let metric1 = Metric::new(
+            "distribution",
+            MetricKind::Incremental,
+            MetricValue::Distribution {
+                samples: vector_core::samples![1.5 => 2],
+                statistic: StatisticKind::Summary,
+            },
+        )
+        .with_tags(Some(tags()));
+        let event = Event::Metric(metric1.clone());
+        let frame = &encode_event(event, None).unwrap();
+        let metric2 = parse(from_utf8(frame).unwrap().trim()).unwrap();
+        shared::assert_event_data_eq!(metric1, metric2);
+    }
+
+    #[test]
+    fn test_encode_distribution_samples_on_separate_lines() {
+        let metric = Metric::new(
+            "distribution",
+            MetricKind::Incremental,
+            MetricValue::Distribution {
+                samples: vector_core::samples![1.5 => 1, 2.5 => 2, 3.5 => 0],
+                statistic: StatisticKind::Summary,
+            },
+        )
+        .with_namespace(Some("vector"))
+        .with_tags(Some(
+            vec![("path".to_owned(), "/a|b,c".to_owned())]
+                .into_iter()
+                .collect(),
+        ));
+        let frame = encode_event(Event::Metric(metric), None).unwrap();
+        assert_eq!(
+            from_utf8(&frame).unwrap(),
+            "vector.distribution:1.5|d|#path:/a_b_c\nvector.distribution:2.5|d|@0.5|#path:/a_b_c\n"
+        );
+    }
+
     #[cfg(feature = "sources-statsd")]
     #[test]
     fn test_encode_set() {
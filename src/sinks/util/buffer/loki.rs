@@ -5,13 +5,12 @@
 //! together for more efficient output.
 
 use super::{
-    err_event_too_large, json::BoxedRawValue, Batch, BatchConfig, BatchError, BatchSettings,
-    BatchSize, PushResult,
+    err_event_too_large, Batch, BatchConfig, BatchError, BatchSettings, BatchSize, PushResult,
 };
 use crate::sinks::loki::OutOfOrderAction;
 use dashmap::DashMap;
 use serde_json::{json, value::to_raw_value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 const WRAPPER_OVERHEAD: usize = r#"{"streams":[]}"#.len();
@@ -33,27 +32,51 @@ pub struct LokiRecord {
     pub event: LokiEvent,
 }
 
-#[derive(Debug)]
-struct LokiEncodedEvent {
-    pub timestamp: i64,
-    pub encoded: BoxedRawValue,
+impl LokiEvent {
+    // The JSON output should be: `[ts, line]`
+    fn to_json(&self) -> serde_json::Value {
+        json!([format!("{}", self.timestamp), self.event])
+    }
+
+    fn json_len(&self) -> usize {
+        to_raw_value(&self.to_json())
+            .expect("JSON encoding should never fail")
+            .get()
+            .len()
+    }
 }
 
-impl From<&LokiEvent> for LokiEncodedEvent {
-    // Pre-encode the record to JSON, but keep the timestamp for sorting at the end.
-    // The final output should be: `[ts, line]'
-    fn from(event: &LokiEvent) -> Self {
-        Self {
-            timestamp: event.timestamp,
-            encoded: to_raw_value(&json!([format!("{}", event.timestamp), event.event]))
-                .expect("JSON encoding should never fail"),
-        }
+/// The streams of a batch, with their events sorted by timestamp.
+#[derive(Clone, Debug)]
+pub struct LokiBatch {
+    pub streams: Vec<(Labels, Vec<LokiEvent>)>,
+}
+
+impl LokiBatch {
+    pub fn to_json(&self) -> serde_json::Value {
+        let streams = self
+            .streams
+            .iter()
+            .map(|(labels, events)| {
+                json!({
+                    "stream": labels.iter().cloned().collect::<BTreeMap<_, _>>(),
+                    "values": events.iter().map(LokiEvent::to_json).collect::<Vec<_>>(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "streams": streams,
+        })
     }
 }
 
+/// The requests of a partition are sent in order, so that the events of its
+/// streams are pushed in order.
 #[derive(Hash, Eq, PartialEq, Clone, Debug)]
 pub struct PartitionKey {
     pub tenant_id: Option<String>,
+    pub labels: Labels,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -78,7 +101,7 @@ impl GlobalTimestamps {
 pub struct LokiBuffer {
     num_bytes: usize,
     num_items: usize,
-    streams: HashMap<Labels, Vec<LokiEncodedEvent>>,
+    streams: HashMap<Labels, Vec<LokiEvent>>,
     settings: BatchSize<Self>,
 
     partition: Option<PartitionKey>,
@@ -112,7 +135,7 @@ impl LokiBuffer {
 
 impl Batch for LokiBuffer {
     type Input = LokiRecord;
-    type Output = serde_json::Value;
+    type Output = LokiBatch;
 
     fn get_settings_defaults(
         config: BatchConfig,
@@ -165,8 +188,7 @@ impl Batch for LokiBuffer {
             .map(|label| label.0.len() + label.1.len() + LABEL_OVERHEAD)
             .sum::<usize>()
             + STREAM_OVERHEAD;
-        let event: LokiEncodedEvent = (&item.event).into();
-        let event_len = event.encoded.get().len();
+        let event_len = item.event.json_len();
 
         if self.is_empty() && WRAPPER_OVERHEAD + labels_len + event_len > self.settings.bytes {
             err_event_too_large(WRAPPER_OVERHEAD + labels_len + event_len)
@@ -178,7 +200,7 @@ impl Batch for LokiBuffer {
             let new_bytes = match self.streams.get_mut(&item.labels) {
                 // Label exists, and we checked the size, just add it
                 Some(stream) => {
-                    stream.push(event);
+                    stream.push(item.event);
                     event_len + 1
                 }
                 None => {
@@ -188,7 +210,7 @@ impl Batch for LokiBuffer {
                     if self.num_bytes + new_bytes > self.settings.bytes {
                         return PushResult::Overflow(item);
                     } else {
-                        self.streams.insert(item.labels, vec![event]);
+                        self.streams.insert(item.labels, vec![item.event]);
                         new_bytes
                     }
                 }
@@ -213,7 +235,7 @@ impl Batch for LokiBuffer {
 
     fn finish(self) -> Self::Output {
         let mut latest_timestamps = self.latest_timestamps.expect("Batch is empty");
-        let streams_json = self
+        let mut streams = self
             .streams
             .into_iter()
             .map(|(labels, mut events)| {
@@ -225,39 +247,14 @@ impl Batch for LokiBuffer {
                     events.last().expect("Batch is empty").timestamp,
                 );
 
-                let labels = labels.into_iter().collect::<HashMap<_, _>>();
-                let events = events.into_iter().map(|e| e.encoded).collect::<Vec<_>>();
-
-                (
-                    to_raw_value(&labels).expect("JSON encoding should never fail"),
-                    events,
-                )
+                (labels, events)
             })
             .collect::<Vec<_>>();
         self.global_timestamps
             .insert(self.partition.expect("Bacth is empty"), latest_timestamps);
 
-        // This is just to guarantee stable key ordering for tests
-        #[cfg(test)]
-        let streams_json = {
-            let mut streams_json = streams_json;
-            streams_json.sort_unstable_by_key(|s| s.0.to_string());
-            streams_json
-        };
-
-        let streams_json = streams_json
-            .into_iter()
-            .map(|(stream, events)| {
-                json!({
-                    "stream": stream,
-                    "values": events,
-                })
-            })
-            .collect::<Vec<_>>();
-
-        json!({
-            "streams": streams_json,
-        })
+        streams.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        LokiBatch { streams }
     }
 
     fn num_items(&self) -> usize {
@@ -271,7 +268,7 @@ mod tests {
 
     fn test_finish(buffer: LokiBuffer, expected_json: &str) {
         let buffer_bytes = buffer.num_bytes;
-        let json = serde_json::to_string(&buffer.finish()).unwrap();
+        let json = serde_json::to_string(&buffer.finish().to_json()).unwrap();
         // Does it track the number of bytes exactly before encoding?
         assert!(
             buffer_bytes == expected_json.len(),
@@ -292,7 +289,10 @@ mod tests {
         );
         assert!(matches!(
             buffer.push(LokiRecord {
-                partition: PartitionKey { tenant_id: None },
+                partition: PartitionKey {
+                    tenant_id: None,
+                    labels: vec![],
+                },
                 labels: vec![("label1".into(), "value1".into())],
                 event: LokiEvent {
                     timestamp: 123456789,
@@ -320,7 +320,10 @@ mod tests {
         for n in 1..4 {
             assert!(matches!(
                 buffer.push(LokiRecord {
-                    partition: PartitionKey { tenant_id: None },
+                    partition: PartitionKey {
+                        tenant_id: None,
+                        labels: vec![],
+                    },
                     labels: vec![("asdf".into(), format!("value{}", n))],
                     event: LokiEvent {
                         timestamp: 123456780 + n,
@@ -349,7 +352,10 @@ mod tests {
         for n in 1..4 {
             assert!(matches!(
                 buffer.push(LokiRecord {
-                    partition: PartitionKey { tenant_id: None },
+                    partition: PartitionKey {
+                        tenant_id: None,
+                        labels: vec![],
+                    },
                     labels: vec![("asdf".into(), "value1".into())],
                     event: LokiEvent {
                         timestamp: 123456780 + n,
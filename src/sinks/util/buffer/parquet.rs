@@ -0,0 +1,560 @@
+//! Batch buffer encoding log events into a Parquet file.
+//!
+//! Each batch is written as a single row group, so the batch settings size
+//! the row groups as well as the files. All the columns are optional, and
+//! events are matched to them by field name.
+//!
+//! Encoding a batch can fail, so the buffer outputs a `Result`, and
+//! `ParquetService` fails the batches that couldn't be encoded instead of
+//! sending them.
+
+use super::{
+    err_event_too_large, Batch, BatchConfig, BatchError, BatchSettings, BatchSize, PushResult,
+};
+use crate::{
+    event::{Event, LogEvent, Value},
+    internal_events::{ParquetFieldTypeMismatch, TemplateRenderingFailed},
+    sinks::util::{encoding::EncodingConfiguration, EncodedEvent, PartitionInnerBuffer},
+    template::Template,
+};
+use bytes::Bytes;
+use futures::{future::BoxFuture, FutureExt, TryFutureExt};
+use indexmap::IndexMap;
+use parquet::{
+    basic::{Compression as ParquetCodec, LogicalType, Repetition, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    data_type::ByteArray,
+    errors::ParquetError,
+    file::{
+        properties::WriterProperties,
+        writer::{FileWriter, InMemoryWriteableCursor, RowGroupWriter, SerializedFileWriter},
+    },
+    schema::types::Type,
+};
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::{
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tower::Service;
+use vector_core::ByteSizeOf;
+
+#[derive(Debug, Snafu)]
+pub enum ParquetSchemaError {
+    #[snafu(display("The Parquet `schema` must have at least one field"))]
+    EmptySchema,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetFieldType {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    Timestamp,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetCompression {
+    None,
+    #[derivative(Default)]
+    Snappy,
+    Zstd,
+}
+
+impl From<ParquetCompression> for ParquetCodec {
+    fn from(compression: ParquetCompression) -> Self {
+        match compression {
+            ParquetCompression::None => ParquetCodec::UNCOMPRESSED,
+            ParquetCompression::Snappy => ParquetCodec::SNAPPY,
+            ParquetCompression::Zstd => ParquetCodec::ZSTD,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ParquetConfig {
+    /// The columns of the files, by field name. If unset, they're inferred
+    /// from the batches: columns keep the type they were first inferred
+    /// with, and the fields first seen in later batches add new columns.
+    pub schema: Option<IndexMap<String, ParquetFieldType>>,
+    #[serde(default)]
+    pub compression: ParquetCompression,
+}
+
+type Columns = Vec<(String, ParquetFieldType)>;
+
+#[derive(Debug)]
+pub struct ParquetBuffer {
+    rows: Vec<LogEvent>,
+    num_bytes: usize,
+    settings: BatchSize<Self>,
+    columns: Arc<Mutex<Columns>>,
+    infer_columns: bool,
+    compression: ParquetCompression,
+}
+
+impl ParquetBuffer {
+    pub fn new(
+        settings: BatchSize<Self>,
+        config: &ParquetConfig,
+    ) -> Result<Self, ParquetSchemaError> {
+        let columns = match &config.schema {
+            Some(schema) if schema.is_empty() => return Err(ParquetSchemaError::EmptySchema),
+            Some(schema) => schema
+                .iter()
+                .map(|(name, field_type)| (name.clone(), *field_type))
+                .collect(),
+            None => Vec::new(),
+        };
+        Ok(Self {
+            rows: Vec::new(),
+            num_bytes: 0,
+            settings,
+            columns: Arc::new(Mutex::new(columns)),
+            infer_columns: config.schema.is_none(),
+            compression: config.compression,
+        })
+    }
+}
+
+impl Batch for ParquetBuffer {
+    type Input = LogEvent;
+    type Output = Result<Vec<u8>, ParquetError>;
+
+    fn get_settings_defaults(
+        config: BatchConfig,
+        defaults: BatchSettings<Self>,
+    ) -> Result<BatchSettings<Self>, BatchError> {
+        Ok(config
+            .use_size_as_bytes()?
+            .get_settings_or_default(defaults))
+    }
+
+    fn push(&mut self, item: Self::Input) -> PushResult<Self::Input> {
+        // The encoded size is only known once the whole batch is encoded, so
+        // the in-memory size of the events is used instead.
+        let size = item.size_of();
+        let new_bytes = self.num_bytes + size;
+        if self.rows.is_empty() && size > self.settings.bytes {
+            err_event_too_large(size)
+        } else if self.rows.len() >= self.settings.events || new_bytes > self.settings.bytes {
+            PushResult::Overflow(item)
+        } else {
+            self.rows.push(item);
+            self.num_bytes = new_bytes;
+            PushResult::Ok(
+                self.rows.len() >= self.settings.events || new_bytes >= self.settings.bytes,
+            )
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    fn fresh(&self) -> Self {
+        Self {
+            rows: Vec::new(),
+            num_bytes: 0,
+            settings: self.settings,
+            columns: Arc::clone(&self.columns),
+            infer_columns: self.infer_columns,
+            compression: self.compression,
+        }
+    }
+
+    fn finish(self) -> Self::Output {
+        let columns = {
+            let mut columns = self.columns.lock().unwrap();
+            if self.infer_columns {
+                for (name, field_type) in infer_columns(&self.rows) {
+                    if !columns.iter().any(|(existing, _)| *existing == name) {
+                        columns.push((name, field_type));
+                    }
+                }
+            }
+            columns.clone()
+        };
+        write_file(&columns, &self.rows, self.compression)
+    }
+
+    fn num_items(&self) -> usize {
+        self.rows.len()
+    }
+}
+
+/// Renders the object key of `event` from `key_prefix`, and applies the
+/// encoding rules to it, for a `PartitionBuffer` of `ParquetBuffer`s.
+pub fn encode_event<E>(
+    mut event: Event,
+    key_prefix: &Template,
+    key_prefix_field: &'static str,
+    encoding: &impl EncodingConfiguration<E>,
+) -> Option<EncodedEvent<PartitionInnerBuffer<LogEvent, Bytes>>> {
+    let key = key_prefix
+        .render_string(&event)
+        .map_err(|error| {
+            emit!(TemplateRenderingFailed {
+                error,
+                field: Some(key_prefix_field),
+                drop_event: true,
+            });
+        })
+        .ok()?;
+
+    encoding.apply_rules(&mut event);
+
+    let mut log = event.into_log();
+    Some(EncodedEvent {
+        finalizers: log.metadata_mut().take_finalizers(),
+        item: PartitionInnerBuffer::new(log, key.into()),
+    })
+}
+
+/// Service sending the files of a `PartitionBuffer` of `ParquetBuffer`s
+/// through `inner`, failing the batches that couldn't be encoded.
+#[derive(Clone)]
+pub struct ParquetService<S> {
+    inner: S,
+}
+
+impl<S> ParquetService<S> {
+    pub const fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+impl<S> Service<PartitionInnerBuffer<Result<Vec<u8>, ParquetError>, Bytes>> for ParquetService<S>
+where
+    S: Service<PartitionInnerBuffer<Vec<u8>, Bytes>>,
+    S::Future: Send + 'static,
+    S::Error: Into<crate::Error>,
+{
+    type Response = S::Response;
+    type Error = crate::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(
+        &mut self,
+        request: PartitionInnerBuffer<Result<Vec<u8>, ParquetError>, Bytes>,
+    ) -> Self::Future {
+        let (file, key) = request.into_parts();
+        match file {
+            Ok(file) => self
+                .inner
+                .call(PartitionInnerBuffer::new(file, key))
+                .err_into()
+                .boxed(),
+            Err(error) => futures::future::err(error.into()).boxed(),
+        }
+    }
+}
+
+/// Infers the columns from the top level fields of `rows`. Fields holding
+/// values of different types, maps or arrays are written as strings.
+fn infer_columns(rows: &[LogEvent]) -> Columns {
+    let mut columns = IndexMap::<String, Option<ParquetFieldType>>::new();
+    for row in rows {
+        for (name, value) in row.as_map() {
+            let field_type = match value {
+                Value::Bytes(_) | Value::Map(_) | Value::Array(_) => ParquetFieldType::String,
+                Value::Integer(_) => ParquetFieldType::Integer,
+                Value::Float(_) => ParquetFieldType::Float,
+                Value::Boolean(_) => ParquetFieldType::Boolean,
+                Value::Timestamp(_) => ParquetFieldType::Timestamp,
+                Value::Null => {
+                    columns.entry(name.clone()).or_insert(None);
+                    continue;
+                }
+            };
+            let column = columns.entry(name.clone()).or_insert(None);
+            *column = match column {
+                None => Some(field_type),
+                Some(existing) if *existing == field_type => Some(field_type),
+                Some(_) => Some(ParquetFieldType::String),
+            };
+        }
+    }
+    columns
+        .into_iter()
+        .map(|(name, field_type)| (name, field_type.unwrap_or(ParquetFieldType::String)))
+        .collect()
+}
+
+fn column_type(name: &str, field_type: ParquetFieldType) -> Result<Type, ParquetError> {
+    let (physical_type, logical_type) = match field_type {
+        ParquetFieldType::String => (PhysicalType::BYTE_ARRAY, LogicalType::UTF8),
+        ParquetFieldType::Integer => (PhysicalType::INT64, LogicalType::NONE),
+        ParquetFieldType::Float => (PhysicalType::DOUBLE, LogicalType::NONE),
+        ParquetFieldType::Boolean => (PhysicalType::BOOLEAN, LogicalType::NONE),
+        ParquetFieldType::Timestamp => (PhysicalType::INT64, LogicalType::TIMESTAMP_MILLIS),
+    };
+    Type::primitive_type_builder(name, physical_type)
+        .with_repetition(Repetition::OPTIONAL)
+        .with_logical_type(logical_type)
+        .build()
+}
+
+fn write_file(
+    columns: &[(String, ParquetFieldType)],
+    rows: &[LogEvent],
+    compression: ParquetCompression,
+) -> Result<Vec<u8>, ParquetError> {
+    let mut fields = columns
+        .iter()
+        .map(|(name, field_type)| column_type(name, *field_type).map(Arc::new))
+        .collect::<Result<Vec<_>, _>>()?;
+    let schema = Type::group_type_builder("event")
+        .with_fields(&mut fields)
+        .build()?;
+    let properties = WriterProperties::builder()
+        .set_compression(compression.into())
+        .build();
+
+    let cursor = InMemoryWriteableCursor::default();
+    let mut writer =
+        SerializedFileWriter::new(cursor.clone(), Arc::new(schema), Arc::new(properties))?;
+    let mut row_group = writer.next_row_group()?;
+    for (name, _) in columns {
+        let mut column = row_group
+            .next_column()?
+            .expect("There's a column writer for each field of the schema");
+        write_column(&mut column, rows, name)?;
+        row_group.close_column(column)?;
+    }
+    writer.close_row_group(row_group)?;
+    writer.close()?;
+
+    Ok(cursor.data())
+}
+
+/// Writes the values of the field `name` of `rows`, coercing them to the
+/// type of the column. Values that can't be coerced are written as nulls,
+/// and reported.
+fn write_column(
+    column: &mut ColumnWriter,
+    rows: &[LogEvent],
+    name: &str,
+) -> Result<(), ParquetError> {
+    let values = rows.iter().map(|row| row.get(name));
+    let mismatched = match column {
+        ColumnWriter::ByteArrayColumnWriter(writer) => {
+            let (values, levels, mismatched) = collect_column(values, |value| match value {
+                Value::Bytes(bytes) => Some(ByteArray::from(bytes.to_vec())),
+                Value::Map(_) | Value::Array(_) => {
+                    serde_json::to_vec(value).ok().map(ByteArray::from)
+                }
+                value => Some(ByteArray::from(value.to_string_lossy().into_bytes())),
+            });
+            writer.write_batch(&values, Some(&levels), None)?;
+            mismatched
+        }
+        ColumnWriter::Int64ColumnWriter(writer) => {
+            let (values, levels, mismatched) = collect_column(values, |value| match value {
+                Value::Integer(value) => Some(*value),
+                Value::Timestamp(timestamp) => Some(timestamp.timestamp_millis()),
+                Value::Bytes(bytes) => std::str::from_utf8(bytes).ok()?.trim().parse().ok(),
+                _ => None,
+            });
+            writer.write_batch(&values, Some(&levels), None)?;
+            mismatched
+        }
+        ColumnWriter::DoubleColumnWriter(writer) => {
+            let (values, levels, mismatched) = collect_column(values, |value| match value {
+                Value::Float(value) => Some(*value),
+                Value::Integer(value) => Some(*value as f64),
+                Value::Bytes(bytes) => std::str::from_utf8(bytes).ok()?.trim().parse().ok(),
+                _ => None,
+            });
+            writer.write_batch(&values, Some(&levels), None)?;
+            mismatched
+        }
+        ColumnWriter::BoolColumnWriter(writer) => {
+            let (values, levels, mismatched) = collect_column(values, |value| match value {
+                Value::Boolean(value) => Some(*value),
+                Value::Bytes(bytes) => std::str::from_utf8(bytes).ok()?.trim().parse().ok(),
+                _ => None,
+            });
+            writer.write_batch(&values, Some(&levels), None)?;
+            mismatched
+        }
+        _ => unreachable!("Columns only have the types of `ParquetFieldType`"),
+    };
+    if mismatched > 0 {
+        emit!(ParquetFieldTypeMismatch {
+            field: name,
+            count: mismatched,
+        });
+    }
+    Ok(())
+}
+
+/// Collects the non-null values of a column, along with the definition
+/// levels telling which rows they belong to, and the number of values that
+/// couldn't be converted.
+fn collect_column<'a, T>(
+    values: impl Iterator<Item = Option<&'a Value>>,
+    convert: impl Fn(&Value) -> Option<T>,
+) -> (Vec<T>, Vec<i16>, usize) {
+    let mut converted = Vec::new();
+    let mut mismatched = 0;
+    let levels = values
+        .map(|value| match value {
+            None | Some(Value::Null) => 0,
+            Some(value) => match convert(value) {
+                Some(value) => {
+                    converted.push(value);
+                    1
+                }
+                None => {
+                    mismatched += 1;
+                    0
+                }
+            },
+        })
+        .collect();
+    (converted, levels, mismatched)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::RowAccessor,
+        util::cursor::SliceableCursor,
+    };
+
+    fn settings() -> BatchSize<ParquetBuffer> {
+        BatchSettings::default().bytes(100_000).events(100).size
+    }
+
+    fn event(message: &str, count: Option<i64>) -> LogEvent {
+        let mut log = LogEvent::default();
+        log.insert("message", message);
+        log.insert("timestamp", Utc.timestamp(1_600_000_000, 0));
+        if let Some(count) = count {
+            log.insert("count", count);
+        }
+        log
+    }
+
+    fn read(bytes: Result<Vec<u8>, ParquetError>) -> SerializedFileReader<SliceableCursor> {
+        SerializedFileReader::new(SliceableCursor::new(bytes.unwrap())).unwrap()
+    }
+
+    #[test]
+    fn infers_schema_from_batches() {
+        let mut buffer = ParquetBuffer::new(settings(), &ParquetConfig::default()).unwrap();
+        assert!(matches!(
+            buffer.push(event("first", Some(1))),
+            PushResult::Ok(false)
+        ));
+        assert!(matches!(
+            buffer.push(event("second", None)),
+            PushResult::Ok(false)
+        ));
+        let next = buffer.fresh();
+
+        let reader = read(buffer.finish());
+        assert_eq!(reader.metadata().num_row_groups(), 1);
+        let schema = reader.metadata().file_metadata().schema_descr();
+        let columns = (0..schema.num_columns())
+            .map(|i| schema.column(i).name().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(columns, vec!["count", "message", "timestamp"]);
+
+        let rows = reader.get_row_iter(None).unwrap().collect::<Vec<_>>();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get_long(0).unwrap(), 1);
+        assert_eq!(rows[0].get_string(1).unwrap(), "first");
+        assert_eq!(rows[0].get_timestamp_millis(2).unwrap(), 1_600_000_000_000);
+        assert!(rows[1].get_long(0).is_err());
+
+        // The following batches keep the inferred columns, and add the new
+        // fields as columns.
+        let mut next = next;
+        let mut extra = event("third", None);
+        extra.insert("count", "many");
+        extra.insert("extra", true);
+        let _ = next.push(extra);
+        let reader = read(next.finish());
+        let schema = reader.metadata().file_metadata().schema_descr();
+        assert_eq!(schema.num_columns(), 4);
+        assert_eq!(schema.column(3).name(), "extra");
+
+        let rows = reader.get_row_iter(None).unwrap().collect::<Vec<_>>();
+        assert!(rows[0].get_long(0).is_err());
+        assert!(rows[0].get_bool(3).unwrap());
+    }
+
+    #[test]
+    fn uses_declared_schema() {
+        let mut schema = IndexMap::new();
+        schema.insert("message".to_owned(), ParquetFieldType::String);
+        schema.insert("count".to_owned(), ParquetFieldType::Float);
+        let config = ParquetConfig {
+            schema: Some(schema),
+            compression: ParquetCompression::Zstd,
+        };
+
+        let mut buffer = ParquetBuffer::new(settings(), &config).unwrap();
+        let _ = buffer.push(event("hello", Some(2)));
+        let reader = read(buffer.finish());
+
+        let rows = reader.get_row_iter(None).unwrap().collect::<Vec<_>>();
+        assert_eq!(rows[0].get_string(0).unwrap(), "hello");
+        assert_eq!(rows[0].get_double(1).unwrap(), 2.0);
+    }
+
+    #[test]
+    fn coerces_values_to_column_types() {
+        let mut schema = IndexMap::new();
+        schema.insert("count".to_owned(), ParquetFieldType::Integer);
+        schema.insert("ok".to_owned(), ParquetFieldType::Boolean);
+        schema.insert("message".to_owned(), ParquetFieldType::String);
+        let config = ParquetConfig {
+            schema: Some(schema),
+            ..Default::default()
+        };
+
+        let mut buffer = ParquetBuffer::new(settings(), &config).unwrap();
+        let mut coerced = LogEvent::default();
+        coerced.insert("count", "42");
+        coerced.insert("ok", "true");
+        coerced.insert("message", 7);
+        let _ = buffer.push(coerced);
+        let mut mismatched = LogEvent::default();
+        mismatched.insert("count", "many");
+        mismatched.insert("ok", 1);
+        let _ = buffer.push(mismatched);
+        let reader = read(buffer.finish());
+
+        let rows = reader.get_row_iter(None).unwrap().collect::<Vec<_>>();
+        assert_eq!(rows[0].get_long(0).unwrap(), 42);
+        assert!(rows[0].get_bool(1).unwrap());
+        assert_eq!(rows[0].get_string(2).unwrap(), "7");
+        assert!(rows[1].get_long(0).is_err());
+        assert!(rows[1].get_bool(1).is_err());
+    }
+
+    #[test]
+    fn rejects_empty_schema() {
+        let config = ParquetConfig {
+            schema: Some(IndexMap::new()),
+            ..Default::default()
+        };
+        assert!(ParquetBuffer::new(settings(), &config).is_err());
+    }
+}
@@ -293,6 +293,28 @@ where
     }
 }
 
+impl<T, B, K, RL> PartitionHttpSink<T, B, K, RL>
+where
+    B: Batch,
+    B::Output: Clone + Send + 'static,
+    B::Input: Partition<K>,
+    K: Hash + Eq + Clone + Send + 'static,
+    RL: RetryLogic<Response = http::Response<Bytes>, Error = HttpError> + Send + 'static,
+    T: HttpSink<Input = B::Input, Output = B::Output>,
+{
+    /// Sends the batches of each partition in order, one request at a time.
+    pub fn ordered(mut self) -> Self {
+        self.inner.ordered();
+        self
+    }
+
+    /// Limits the number of partitions batched at once.
+    pub fn max_partitions(mut self, max: usize) -> Self {
+        self.inner.max_partitions(max);
+        self
+    }
+}
+
 impl<T, B, K, RL> Sink<Event> for PartitionHttpSink<T, B, K, RL>
 where
     B: Batch,
@@ -42,7 +42,10 @@ use crate::{
     event::{EventFinalizers, EventStatus},
 };
 use futures::{
-    future::BoxFuture, ready, stream::FuturesUnordered, FutureExt, Sink, Stream, TryFutureExt,
+    future::{self, BoxFuture},
+    ready,
+    stream::FuturesUnordered,
+    FutureExt, Sink, Stream, TryFutureExt,
 };
 use pin_project::pin_project;
 use std::{
@@ -209,6 +212,8 @@ where
     partitions: HashMap<K, StatefulBatch<FinalizersBatch<B>>>,
     timeout: Duration,
     lingers: HashMap<K, Pin<Box<Sleep>>>,
+    in_flight: Option<HashMap<K, BoxFuture<'static, ()>>>,
+    max_partitions: Option<usize>,
     closing: bool,
 }
 
@@ -254,9 +259,29 @@ where
             partitions: HashMap::new(),
             timeout,
             lingers: HashMap::new(),
+            in_flight: None,
+            max_partitions: None,
             closing: false,
         }
     }
+
+    /// Sends the batches of each partition one at a time, the next batch of a
+    /// partition waiting for the request of the previous one to complete.
+    pub fn ordered(&mut self) {
+        self.in_flight = Some(HashMap::new());
+    }
+
+    /// Limits the number of partitions batched at once. The events of a new
+    /// partition wait for the batch of another one to be sent, which is sent
+    /// early if need be.
+    pub fn max_partitions(&mut self, max: usize) {
+        self.max_partitions = Some(max);
+    }
+
+    fn is_full(&self) -> bool {
+        self.max_partitions
+            .map_or(false, |max| self.partitions.len() >= max)
+    }
 }
 
 impl<S, B, K, SL> Sink<EncodedEvent<B::Input>> for PartitionBatchSink<S, B, K, SL>
@@ -293,6 +318,10 @@ where
         item: EncodedEvent<B::Input>,
     ) -> Result<(), Self::Error> {
         let partition = item.item.partition();
+        if !self.partitions.contains_key(&partition) && self.is_full() {
+            self.buffer = Some((partition, item));
+            return Ok(());
+        }
 
         let batch = loop {
             if let Some(batch) = self.partitions.get_mut(&partition) {
@@ -322,22 +351,44 @@ where
             }
 
             // Try send batches.
+            let make_room = self.is_full()
+                && self.buffer.as_ref().map_or(false, |(partition, _)| {
+                    !self.partitions.contains_key(partition)
+                });
             let this = self.as_mut().project();
+            if let Some(in_flight) = this.in_flight.as_mut() {
+                in_flight.retain(|_, request| request.poll_unpin(cx).is_pending());
+            }
             let mut partitions_ready = vec![];
             for (partition, batch) in this.partitions.iter() {
-                if (*this.closing && !batch.is_empty())
-                    || batch.was_full()
-                    || matches!(
-                        this.lingers
-                            .get_mut(partition)
-                            .expect("linger should exists for poll_flush")
-                            .poll_unpin(cx),
-                        Poll::Ready(())
-                    )
+                let in_flight = this
+                    .in_flight
+                    .as_ref()
+                    .map_or(false, |in_flight| in_flight.contains_key(partition));
+                if !in_flight
+                    && ((*this.closing && !batch.is_empty())
+                        || batch.was_full()
+                        || matches!(
+                            this.lingers
+                                .get_mut(partition)
+                                .expect("linger should exists for poll_flush")
+                                .poll_unpin(cx),
+                            Poll::Ready(())
+                        ))
                 {
                     partitions_ready.push(partition.clone());
                 }
             }
+            if make_room && partitions_ready.is_empty() {
+                // Send a batch early, so that the buffered event's partition
+                // fits in.
+                let in_flight = this.in_flight.as_ref();
+                if let Some(partition) = this.partitions.keys().find(|partition| {
+                    in_flight.map_or(true, |in_flight| !in_flight.contains_key(*partition))
+                }) {
+                    partitions_ready.push(partition.clone());
+                }
+            }
             let mut batch_consumed = false;
             for partition in partitions_ready.iter() {
                 let service_ready = match self.service.poll_ready(cx) {
@@ -353,7 +404,19 @@ where
 
                     let batch_size = batch.num_items();
                     let (batch, finalizers) = batch.finish();
-                    tokio::spawn(self.service.call(batch, batch_size, finalizers));
+                    let request = self.service.call(batch, batch_size, finalizers);
+                    match self.in_flight.as_mut() {
+                        Some(in_flight) => {
+                            let (tx, rx) = oneshot::channel();
+                            tokio::spawn(request.map(move |()| {
+                                let _ = tx.send(());
+                            }));
+                            in_flight.insert(partition.clone(), rx.map(|_| ()).boxed());
+                        }
+                        None => {
+                            tokio::spawn(request);
+                        }
+                    }
 
                     batch_consumed = true;
                 } else {
@@ -366,7 +429,7 @@ where
 
             // Try move item from buffer to batch.
             if let Some((partition, item)) = self.buffer.take() {
-                if self.partitions.contains_key(&partition) {
+                if self.partitions.contains_key(&partition) || self.is_full() {
                     self.buffer = Some((partition, item));
                 } else {
                     self.as_mut().start_send(item)?;
@@ -483,9 +546,8 @@ where
         self.service
             .call(req)
             .err_into()
-            .map(move |result| {
-                logic.update_finalizers(result, finalizers);
-
+            .then(move |result| logic.update_finalizers(result, finalizers))
+            .map(move |()| {
                 // If the rx end is dropped we still completed
                 // the request so this is a weird case that we can
                 // ignore for now.
@@ -540,12 +602,16 @@ pub trait ServiceLogic: Clone {
 
     fn result_status(&self, result: crate::Result<Self::Response>) -> EventStatus;
 
+    /// Updates the finalizers of a request from its result. The events of
+    /// the request are acknowledged to the buffer once the returned future
+    /// resolves.
     fn update_finalizers(
         &self,
         result: crate::Result<Self::Response>,
         finalizers: EventFinalizers,
-    ) {
+    ) -> BoxFuture<'static, ()> {
         finalizers.update_status(self.result_status(result));
+        future::ready(()).boxed()
     }
 }
 
@@ -1027,6 +1093,74 @@ mod tests {
         assert_eq!(&*output, &vec![vec![1]]);
     }
 
+    #[tokio::test]
+    async fn partition_batch_sink_ordered_waits_for_previous_request() {
+        let (acker, _) = Acker::new_for_testing();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+
+        let svc = tower::service_fn(|req: Vec<Partitions>| {
+            let calls = Arc::clone(&calls);
+            async move {
+                calls.lock().unwrap().push(format!("start {:?}", req[0]));
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                calls.lock().unwrap().push(format!("end {:?}", req[0]));
+                Ok::<_, std::io::Error>(())
+            }
+        });
+
+        let batch = BatchSettings::default().bytes(9999).events(1);
+        let mut sink = PartitionBatchSink::new(svc, VecBuffer::new(batch.size), TIMEOUT, acker);
+        sink.ordered();
+
+        let input = vec![Partitions::A, Partitions::A, Partitions::B];
+        sink.sink_map_err(drop)
+            .send_all(&mut stream::iter(input).map(|item| Ok(EncodedEvent::new(item))))
+            .await
+            .unwrap();
+
+        let calls = calls.lock().unwrap();
+        let calls_a = calls
+            .iter()
+            .filter(|call| call.ends_with('A'))
+            .collect::<Vec<_>>();
+        assert_eq!(calls_a, vec!["start A", "end A", "start A", "end A"]);
+        // The other partition doesn't wait for the requests of the first one.
+        assert_eq!(calls[1], "start B");
+    }
+
+    #[tokio::test]
+    async fn partition_batch_sink_limits_partitions() {
+        let (acker, _) = Acker::new_for_testing();
+        let sent_requests = Arc::new(Mutex::new(Vec::new()));
+
+        let svc = tower::service_fn(|req| {
+            let sent_requests = Arc::clone(&sent_requests);
+            sent_requests.lock().unwrap().push(req);
+            future::ok::<_, std::io::Error>(())
+        });
+
+        let batch = BatchSettings::default().bytes(9999).events(10);
+        let mut sink = PartitionBatchSink::new(svc, VecBuffer::new(batch.size), TIMEOUT, acker);
+        sink.max_partitions(1);
+
+        let input = vec![Partitions::A, Partitions::A, Partitions::B, Partitions::A];
+        sink.sink_map_err(drop)
+            .send_all(&mut stream::iter(input).map(|item| Ok(EncodedEvent::new(item))))
+            .await
+            .unwrap();
+
+        // The batch of a partition is sent as soon as another one comes in.
+        let output = sent_requests.lock().unwrap();
+        assert_eq!(
+            &*output,
+            &vec![
+                vec![Partitions::A, Partitions::A],
+                vec![Partitions::B],
+                vec![Partitions::A]
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn service_sink_doesnt_propagate_error() {
         // We need a mock executor here because we need to ensure
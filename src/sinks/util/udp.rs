@@ -77,6 +77,16 @@ impl UdpSinkConfig {
         &self,
         cx: SinkContext,
         encode_event: impl Fn(Event) -> Option<Bytes> + Send + Sync + 'static,
+    ) -> crate::Result<(VectorSink, Healthcheck)> {
+        self.build_datagrams(cx, move |event| encode_event(event).into_iter().collect())
+    }
+
+    /// Builds a sink sending each event as the datagrams it's encoded into,
+    /// for protocols splitting large events into chunks.
+    pub fn build_datagrams(
+        &self,
+        cx: SinkContext,
+        encode_event: impl Fn(Event) -> Vec<Bytes> + Send + Sync + 'static,
     ) -> crate::Result<(VectorSink, Healthcheck)> {
         let connector = self.build_connector(cx.clone())?;
         let sink = UdpSink::new(connector.clone(), cx.acker(), encode_event);
@@ -228,14 +238,14 @@ impl tower::Service<Bytes> for UdpService {
 struct UdpSink {
     connector: UdpConnector,
     acker: Acker,
-    encode_event: Box<dyn Fn(Event) -> Option<Bytes> + Send + Sync>,
+    encode_event: Box<dyn Fn(Event) -> Vec<Bytes> + Send + Sync>,
 }
 
 impl UdpSink {
     fn new(
         connector: UdpConnector,
         acker: Acker,
-        encode_event: impl Fn(Event) -> Option<Bytes> + Send + Sync + 'static,
+        encode_event: impl Fn(Event) -> Vec<Bytes> + Send + Sync + 'static,
     ) -> Self {
         Self {
             connector,
@@ -250,27 +260,29 @@ impl StreamSink for UdpSink {
     async fn run(&mut self, input: BoxStream<'_, Event>) -> Result<(), ()> {
         let mut input = input.peekable();
 
-        while Pin::new(&mut input).peek().await.is_some() {
+        'reconnect: while Pin::new(&mut input).peek().await.is_some() {
             let mut socket = self.connector.connect_backoff().await;
             while let Some(event) = input.next().await {
                 self.acker.ack(1);
 
-                let input = match (self.encode_event)(event) {
-                    Some(input) => input,
-                    None => continue,
-                };
-
-                match udp_send(&mut socket, &input).await {
-                    Ok(()) => emit!(SocketEventsSent {
-                        mode: SocketMode::Udp,
-                        count: 1,
-                        byte_size: input.len(),
-                    }),
-                    Err(error) => {
+                let datagrams = (self.encode_event)(event);
+                if datagrams.is_empty() {
+                    continue;
+                }
+
+                let mut byte_size = 0;
+                for datagram in &datagrams {
+                    if let Err(error) = udp_send(&mut socket, datagram).await {
                         emit!(UdpSocketError { error });
-                        break;
+                        continue 'reconnect;
                     }
-                };
+                    byte_size += datagram.len();
+                }
+                emit!(SocketEventsSent {
+                    mode: SocketMode::Udp,
+                    count: 1,
+                    byte_size,
+                });
             }
         }
 
@@ -0,0 +1,442 @@
+use super::Client;
+use crate::{
+    dns::Resolver,
+    event::Event,
+    internal_events::{VectorPeerEjected, VectorPeerResolveFailed},
+    proto::vector as proto,
+};
+use http::uri::{Authority, Uri};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    hash::Hasher,
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, Weak,
+    },
+    time::{Duration, Instant},
+};
+use tonic::transport::{ClientTlsConfig, Endpoint};
+use twox_hash::XxHash64;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Derivative, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadBalancingStrategy {
+    /// Batches are sent to the healthy peers in turn.
+    #[derivative(Default)]
+    RoundRobin,
+    /// Events sharing the value of `hash_field` are sent to the same peer.
+    ConsistentHash,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Derivative, PartialEq)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct LoadBalancingConfig {
+    pub strategy: LoadBalancingStrategy,
+    /// Field, or tag for metrics, whose value picks the peer of an event with
+    /// the `consistent_hash` strategy.
+    pub hash_field: Option<String>,
+    /// Interval at which host names are resolved again.
+    #[derivative(Default(value = "30"))]
+    pub dns_refresh_secs: u64,
+    /// Number of consecutive failed requests after which a peer is ejected.
+    #[derivative(Default(value = "3"))]
+    pub unhealthy_threshold: u32,
+    /// Duration for which an ejected peer doesn't receive requests.
+    #[derivative(Default(value = "30"))]
+    pub ejection_secs: u64,
+}
+
+/// One of the configured addresses, which may resolve to several peers.
+pub(super) struct Target {
+    pub(super) uri: Uri,
+    pub(super) tls: Option<ClientTlsConfig>,
+}
+
+struct Peer {
+    address: String,
+    client: Client,
+    consecutive_failures: u32,
+    ejected_until: Option<Instant>,
+}
+
+impl Peer {
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.ejected_until.map_or(true, |until| until <= now)
+    }
+}
+
+/// The set of downstream Vector instances, kept up to date with the DNS
+/// records of the configured addresses and the outcome of the requests.
+pub(super) struct Peers {
+    config: LoadBalancingConfig,
+    targets: Vec<Target>,
+    timeout: Duration,
+    peers: Mutex<Vec<Peer>>,
+    next: AtomicUsize,
+}
+
+impl Peers {
+    pub(super) async fn new(
+        config: LoadBalancingConfig,
+        targets: Vec<Target>,
+        timeout: Duration,
+    ) -> crate::Result<Arc<Self>> {
+        if config.strategy == LoadBalancingStrategy::ConsistentHash && config.hash_field.is_none() {
+            return Err("`hash_field` is required by the `consistent_hash` strategy.".into());
+        }
+
+        let peers = Arc::new(Self {
+            config,
+            targets,
+            timeout,
+            peers: Mutex::new(Vec::new()),
+            next: AtomicUsize::new(0),
+        });
+        peers.refresh().await?;
+
+        let refresh_interval = Duration::from_secs(peers.config.dns_refresh_secs.max(1));
+        tokio::spawn(refresh_peers(Arc::downgrade(&peers), refresh_interval));
+
+        Ok(peers)
+    }
+
+    /// Resolves the configured addresses, keeping the state of the peers
+    /// which are still around.
+    async fn refresh(&self) -> crate::Result<()> {
+        let mut resolved = Vec::new();
+        for (index, target) in self.targets.iter().enumerate() {
+            for uri in resolve(&target.uri, &self.current_addresses(index)).await {
+                resolved.push((index, uri));
+            }
+        }
+        let addresses = resolved
+            .iter()
+            .map(|(_, uri)| uri.to_string())
+            .collect::<HashSet<_>>();
+
+        let mut peers = self.peers.lock().unwrap();
+        let mut new_peers = Vec::with_capacity(resolved.len());
+        for (index, uri) in resolved {
+            let address = uri.to_string();
+            if peers.iter().any(|peer| peer.address == address)
+                || new_peers.iter().any(|peer: &Peer| peer.address == address)
+            {
+                continue;
+            }
+            new_peers.push(Peer {
+                client: self.client(&self.targets[index], uri)?,
+                address,
+                consecutive_failures: 0,
+                ejected_until: None,
+            });
+        }
+        peers.retain(|peer| addresses.contains(&peer.address));
+        peers.append(&mut new_peers);
+        peers.sort_by(|a, b| a.address.cmp(&b.address));
+
+        Ok(())
+    }
+
+    fn current_addresses(&self, index: usize) -> Vec<Uri> {
+        let target = &self.targets[index];
+        self.peers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|peer| peer.address.parse::<Uri>().ok())
+            .filter(|uri| {
+                uri.scheme() == target.uri.scheme()
+                    && uri.port_u16() == target.uri.port_u16()
+                    && uri.path() == target.uri.path()
+            })
+            .collect()
+    }
+
+    fn client(&self, target: &Target, uri: Uri) -> crate::Result<Client> {
+        let endpoint = Endpoint::from(uri).timeout(self.timeout);
+        let endpoint = match &target.tls {
+            Some(tls) => endpoint.tls_config(tls.clone())?,
+            None => endpoint,
+        };
+        Ok(proto::Client::new(endpoint.connect_lazy()?))
+    }
+
+    /// Returns the address of the peer which should receive the event, if it
+    /// depends on the event at all.
+    pub(super) fn route(&self, event: &Event) -> Option<String> {
+        let field = match self.config.strategy {
+            LoadBalancingStrategy::RoundRobin => return None,
+            LoadBalancingStrategy::ConsistentHash => self.config.hash_field.as_deref()?,
+        };
+        let value = match event {
+            Event::Log(log) => log.get(field)?.to_string_lossy(),
+            Event::Metric(metric) => metric.tag_value(field)?,
+        };
+
+        let now = Instant::now();
+        let peers = self.peers.lock().unwrap();
+        pick(value.as_bytes(), healthy_or_all(&peers, now)).map(|peer| peer.address.clone())
+    }
+
+    /// Picks the peer a request is sent to. Requests for a peer which has
+    /// been ejected or removed fail over to one of the healthy peers.
+    pub(super) fn select(&self, preferred: Option<&str>) -> Option<(String, Client)> {
+        let now = Instant::now();
+        let peers = self.peers.lock().unwrap();
+
+        if let Some(peer) = preferred.and_then(|address| {
+            peers
+                .iter()
+                .find(|peer| peer.address == address && peer.is_healthy(now))
+        }) {
+            return Some((peer.address.clone(), peer.client.clone()));
+        }
+
+        let candidates = healthy_or_all(&peers, now);
+        let peer = match preferred {
+            // Keep routing the same events together while failing over.
+            Some(address) => pick(address.as_bytes(), candidates)?,
+            None => {
+                let candidates = candidates.collect::<Vec<_>>();
+                if candidates.is_empty() {
+                    return None;
+                }
+                let next = self.next.fetch_add(1, Ordering::Relaxed);
+                candidates[next % candidates.len()]
+            }
+        };
+        Some((peer.address.clone(), peer.client.clone()))
+    }
+
+    /// Records the outcome of a request, ejecting the peer once it failed
+    /// `unhealthy_threshold` times in a row.
+    pub(super) fn report(&self, address: &str, success: bool) {
+        let mut peers = self.peers.lock().unwrap();
+        let peer = match peers.iter_mut().find(|peer| peer.address == address) {
+            Some(peer) => peer,
+            None => return,
+        };
+
+        if success {
+            peer.consecutive_failures = 0;
+            peer.ejected_until = None;
+        } else {
+            peer.consecutive_failures += 1;
+            let now = Instant::now();
+            if peer.consecutive_failures >= self.config.unhealthy_threshold && peer.is_healthy(now)
+            {
+                emit!(VectorPeerEjected {
+                    address,
+                    consecutive_failures: peer.consecutive_failures,
+                });
+                peer.ejected_until = Some(now + Duration::from_secs(self.config.ejection_secs));
+            }
+        }
+    }
+
+    pub(super) fn clients(&self) -> Vec<Client> {
+        self.peers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|peer| peer.client.clone())
+            .collect()
+    }
+}
+
+/// Healthy peers, or all of them when none is, as ejecting every peer would
+/// stall the sink.
+fn healthy_or_all(peers: &[Peer], now: Instant) -> impl Iterator<Item = &Peer> {
+    let any_healthy = peers.iter().any(|peer| peer.is_healthy(now));
+    peers
+        .iter()
+        .filter(move |peer| !any_healthy || peer.is_healthy(now))
+}
+
+/// Rendezvous hashing, so that only the keys of a peer which goes away are
+/// moved to other peers.
+fn pick<'a>(key: &[u8], peers: impl Iterator<Item = &'a Peer>) -> Option<&'a Peer> {
+    peers.max_by_key(|peer| {
+        let mut hasher = XxHash64::with_seed(0);
+        hasher.write(key);
+        hasher.write(peer.address.as_bytes());
+        hasher.finish()
+    })
+}
+
+/// Resolves the host of `uri` into one URI per address. Failing that, the
+/// previously resolved URIs are kept, or `uri` itself is used.
+async fn resolve(uri: &Uri, previous: &[Uri]) -> Vec<Uri> {
+    let host = match uri.host() {
+        Some(host) => host,
+        None => return vec![uri.clone()],
+    };
+    if host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .is_ok()
+    {
+        return vec![uri.clone()];
+    }
+
+    let port = uri.port_u16().unwrap_or_else(|| match uri.scheme_str() {
+        Some("https") => 443,
+        _ => 80,
+    });
+    match Resolver.lookup_ip(host.to_owned()).await {
+        Ok(ips) => ips
+            .filter_map(|ip| with_authority(uri, SocketAddr::new(ip, port)))
+            .collect(),
+        Err(error) => {
+            emit!(VectorPeerResolveFailed { host, error });
+            if previous.is_empty() {
+                vec![uri.clone()]
+            } else {
+                previous.to_vec()
+            }
+        }
+    }
+}
+
+fn with_authority(uri: &Uri, address: SocketAddr) -> Option<Uri> {
+    let mut parts = uri.clone().into_parts();
+    parts.authority = Some(address.to_string().parse::<Authority>().ok()?);
+    Uri::from_parts(parts).ok()
+}
+
+async fn refresh_peers(peers: Weak<Peers>, interval: Duration) {
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    loop {
+        interval.tick().await;
+        let peers = match peers.upgrade() {
+            Some(peers) => peers,
+            None => break,
+        };
+        if let Err(error) = peers.refresh().await {
+            error!(message = "Failed to refresh peers.", %error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn peers(config: LoadBalancingConfig, addresses: &[&str]) -> Arc<Peers> {
+        let targets = addresses
+            .iter()
+            .map(|address| Target {
+                uri: address.parse().unwrap(),
+                tls: None,
+            })
+            .collect();
+        Peers::new(config, targets, Duration::from_secs(5))
+            .await
+            .unwrap()
+    }
+
+    fn consistent_hash() -> LoadBalancingConfig {
+        LoadBalancingConfig {
+            strategy: LoadBalancingStrategy::ConsistentHash,
+            hash_field: Some("host".into()),
+            ..Default::default()
+        }
+    }
+
+    fn event(host: &str) -> Event {
+        let mut event = Event::from("hello");
+        event.as_mut_log().insert("host", host);
+        event
+    }
+
+    #[tokio::test]
+    async fn round_robin_skips_ejected_peers() {
+        let config = LoadBalancingConfig {
+            unhealthy_threshold: 2,
+            ..Default::default()
+        };
+        let peers = peers(
+            config,
+            &["http://127.0.0.1:6000/", "http://127.0.0.2:6000/"],
+        )
+        .await;
+
+        let selected = (0..4)
+            .map(|_| peers.select(None).unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            selected,
+            vec![
+                "http://127.0.0.1:6000/",
+                "http://127.0.0.2:6000/",
+                "http://127.0.0.1:6000/",
+                "http://127.0.0.2:6000/"
+            ]
+        );
+
+        peers.report("http://127.0.0.1:6000/", false);
+        assert_eq!(peers.select(None).unwrap().0, "http://127.0.0.1:6000/");
+        peers.report("http://127.0.0.1:6000/", false);
+        for _ in 0..4 {
+            assert_eq!(peers.select(None).unwrap().0, "http://127.0.0.2:6000/");
+        }
+
+        // With every peer ejected, requests are still spread over them.
+        peers.report("http://127.0.0.2:6000/", false);
+        peers.report("http://127.0.0.2:6000/", false);
+        assert_ne!(peers.select(None).unwrap().0, peers.select(None).unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn consistent_hash_is_stable() {
+        let addresses = [
+            "http://127.0.0.1:6000/",
+            "http://127.0.0.2:6000/",
+            "http://127.0.0.3:6000/",
+        ];
+        let peers = peers(consistent_hash(), &addresses).await;
+
+        let routes = (0..100)
+            .map(|i| peers.route(&event(&format!("host{}", i))).unwrap())
+            .collect::<Vec<_>>();
+        for address in &addresses {
+            assert!(routes.iter().any(|route| route == address));
+        }
+        assert_eq!(peers.route(&Event::from("no host")), None);
+
+        // Only the events of the ejected peer move elsewhere.
+        let ejected = addresses[0];
+        for _ in 0..3 {
+            peers.report(ejected, false);
+        }
+        for (i, route) in routes.iter().enumerate() {
+            let new_route = peers.route(&event(&format!("host{}", i))).unwrap();
+            if route == ejected {
+                assert_ne!(new_route, ejected);
+                assert_ne!(peers.select(Some(route)).unwrap().0, ejected);
+            } else {
+                assert_eq!(&new_route, route);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn requires_hash_field() {
+        let config = LoadBalancingConfig {
+            hash_field: None,
+            ..consistent_hash()
+        };
+        let targets = vec![Target {
+            uri: "http://127.0.0.1:6000/".parse().unwrap(),
+            tls: None,
+        }];
+        assert!(Peers::new(config, targets, Duration::from_secs(5))
+            .await
+            .is_err());
+    }
+}
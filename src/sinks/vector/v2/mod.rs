@@ -1,19 +1,24 @@
+mod load_balancing;
+
 use crate::{
     config::{DataType, GenerateConfig, Resource, SinkContext, SinkHealthcheckOptions},
     event::{proto::EventWrapper, Event},
     proto::vector as proto,
     sinks::util::{
-        retries::RetryLogic, sink, BatchConfig, BatchSettings, BatchSink, EncodedEvent,
-        EncodedLength, ServiceBuilderExt, TowerRequestConfig, VecBuffer,
+        retries::RetryLogic, sink, BatchConfig, BatchSettings, EncodedEvent, EncodedLength,
+        PartitionBatchSink, PartitionBuffer, PartitionInnerBuffer, ServiceBuilderExt,
+        TowerRequestConfig, VecBuffer,
     },
     sinks::{Healthcheck, VectorSink},
 };
-use futures::{future::BoxFuture, stream, SinkExt, StreamExt, TryFutureExt};
+use futures::{future::BoxFuture, stream, SinkExt, StreamExt};
 use http::uri::Uri;
+use load_balancing::{Peers, Target};
 use prost::Message;
 use serde::{Deserialize, Serialize};
 use snafu::Snafu;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use tonic::{
     transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity},
@@ -21,6 +26,8 @@ use tonic::{
 };
 use tower::ServiceBuilder;
 
+pub use load_balancing::{LoadBalancingConfig, LoadBalancingStrategy};
+
 type Client = proto::Client<Channel>;
 type Response = Result<tonic::Response<proto::PushEventsResponse>, tonic::Status>;
 
@@ -28,7 +35,12 @@ type Response = Result<tonic::Response<proto::PushEventsResponse>, tonic::Status
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct VectorConfig {
-    address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    addresses: Vec<String>,
+    #[serde(default)]
+    pub load_balancing: LoadBalancingConfig,
     #[serde(default)]
     pub batch: BatchConfig,
     #[serde(default)]
@@ -53,7 +65,9 @@ impl GenerateConfig for VectorConfig {
 
 fn default_config(address: &str) -> VectorConfig {
     VectorConfig {
-        address: address.to_owned(),
+        address: Some(address.to_owned()),
+        addresses: Vec::new(),
+        load_balancing: LoadBalancingConfig::default(),
         batch: BatchConfig::default(),
         request: TowerRequestConfig::default(),
         tls: None,
@@ -86,37 +100,54 @@ fn default_http(address: &str) -> crate::Result<Uri> {
 
 impl VectorConfig {
     pub(crate) async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
-        let endpoint = Endpoint::from(default_http(&self.address)?);
-        let endpoint = match &self.tls {
+        let addresses = self
+            .address
+            .iter()
+            .chain(self.addresses.iter())
+            .collect::<Vec<_>>();
+        if addresses.is_empty() {
+            return Err(Box::new(Error::NoAddresses));
+        }
+
+        let tls = match &self.tls {
             Some(tls) => {
-                let host = get_authority(&self.address)?;
                 let ca = Certificate::from_pem(tokio::fs::read(&tls.ca_file).await?);
                 let crt = tokio::fs::read(&tls.crt_file).await?;
                 let key = tokio::fs::read(&tls.key_file).await?;
                 let identity = Identity::from_pem(crt, key);
 
-                let tls_config = ClientTlsConfig::new()
-                    .identity(identity)
-                    .ca_certificate(ca)
-                    .domain_name(host);
-
-                endpoint.tls_config(tls_config)?
+                Some(ClientTlsConfig::new().identity(identity).ca_certificate(ca))
             }
-            None => endpoint,
+            None => None,
         };
+        let targets = addresses
+            .into_iter()
+            .map(|address| {
+                let tls = match &tls {
+                    // Peers are reached by IP address once resolved, so the
+                    // expected name of their certificate comes from the address.
+                    Some(tls) => Some(tls.clone().domain_name(get_authority(address)?)),
+                    None => None,
+                };
+                Ok(Target {
+                    uri: default_http(address)?,
+                    tls,
+                })
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
 
-        let client = proto::Client::new(endpoint.connect_lazy()?);
+        let request = self.request.unwrap_with(&TowerRequestConfig::default());
+        let peers = Peers::new(self.load_balancing.clone(), targets, request.timeout).await?;
 
-        let healthcheck_client = if let Some(uri) = cx.healthcheck.uri.clone() {
+        let healthcheck_clients = if let Some(uri) = cx.healthcheck.uri.clone() {
             let endpoint = Endpoint::from(uri.uri);
-            proto::Client::new(endpoint.connect_lazy()?)
+            vec![proto::Client::new(endpoint.connect_lazy()?)]
         } else {
-            client.clone()
+            peers.clients()
         };
 
-        let healthcheck = healthcheck(healthcheck_client, cx.healthcheck.clone());
+        let healthcheck = healthcheck(healthcheck_clients, cx.healthcheck.clone());
 
-        let request = self.request.unwrap_with(&TowerRequestConfig::default());
         let batch = BatchSettings::default()
             .events(1000)
             .timeout(1)
@@ -124,12 +155,14 @@ impl VectorConfig {
 
         let svc = ServiceBuilder::new()
             .settings(request, VectorGrpcRetryLogic)
-            .service(client);
+            .service(VectorService {
+                peers: Arc::clone(&peers),
+            });
 
-        let buffer = VecBuffer::new(batch.size);
-        let sink = BatchSink::new(svc, buffer, batch.timeout, cx.acker())
+        let buffer = PartitionBuffer::new(VecBuffer::new(batch.size));
+        let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
             .sink_map_err(|error| error!(message = "Fatal Vector GRPC sink error.", %error))
-            .with_flat_map(move |event| stream::iter(Some(encode_event(event))).map(Ok));
+            .with_flat_map(move |event| stream::iter(Some(encode_event(event, &peers))).map(Ok));
 
         Ok((VectorSink::Sink(Box::new(sink)), Box::pin(healthcheck)))
     }
@@ -147,19 +180,21 @@ impl VectorConfig {
     }
 }
 
-/// Check to see if the remote service accepts new events.
-async fn healthcheck(mut client: Client, options: SinkHealthcheckOptions) -> crate::Result<()> {
+/// Check to see if any of the remote services accepts new events.
+async fn healthcheck(clients: Vec<Client>, options: SinkHealthcheckOptions) -> crate::Result<()> {
     if !options.enabled {
         return Ok(());
     }
 
-    let request = client.health_check(proto::HealthCheckRequest {});
+    for mut client in clients {
+        let request = client.health_check(proto::HealthCheckRequest {});
 
-    if let Ok(response) = request.await {
-        let status = proto::ServingStatus::from_i32(response.into_inner().status);
+        if let Ok(response) = request.await {
+            let status = proto::ServingStatus::from_i32(response.into_inner().status);
 
-        if let Some(proto::ServingStatus::Serving) = status {
-            return Ok(());
+            if let Some(proto::ServingStatus::Serving) = status {
+                return Ok(());
+            }
         }
     }
 
@@ -173,7 +208,13 @@ fn get_authority(url: &str) -> Result<String, Error> {
         .ok_or(Error::NoHost)
 }
 
-impl tower::Service<Vec<EventWrapper>> for Client {
+/// Sends each batch to the peer picked by the load balancing strategy.
+#[derive(Clone)]
+struct VectorService {
+    peers: Arc<Peers>,
+}
+
+impl tower::Service<PartitionInnerBuffer<Vec<EventWrapper>, Option<String>>> for VectorService {
     type Response = ();
     type Error = Error;
     type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
@@ -187,25 +228,34 @@ impl tower::Service<Vec<EventWrapper>> for Client {
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, events: Vec<EventWrapper>) -> Self::Future {
-        let mut client = self.clone();
+    fn call(
+        &mut self,
+        batch: PartitionInnerBuffer<Vec<EventWrapper>, Option<String>>,
+    ) -> Self::Future {
+        let peers = Arc::clone(&self.peers);
+        let (events, peer) = batch.into_parts();
 
         let request = proto::PushEventsRequest { events };
         let future = async move {
-            client
-                .push_events(request.into_request())
-                .map_ok(|_| ())
+            let (address, mut client) = peers.select(peer.as_deref()).ok_or(Error::NoPeers)?;
+            let result = client.push_events(request.into_request()).await;
+            peers.report(&address, result.is_ok());
+            result
+                .map(|_| ())
                 .map_err(|source| Error::Request { source })
-                .await
         };
 
         Box::pin(future)
     }
 }
 
-fn encode_event(mut event: Event) -> EncodedEvent<EventWrapper> {
+fn encode_event(
+    mut event: Event,
+    peers: &Peers,
+) -> EncodedEvent<PartitionInnerBuffer<EventWrapper, Option<String>>> {
+    let peer = peers.route(&event);
     let finalizers = event.metadata_mut().take_finalizers();
-    let item = event.into();
+    let item = PartitionInnerBuffer::new(event.into(), peer);
 
     EncodedEvent { item, finalizers }
 }
@@ -232,6 +282,12 @@ pub enum Error {
 
     #[snafu(display("URL has no host."))]
     NoHost,
+
+    #[snafu(display("At least one of `address` or `addresses` must be set."))]
+    NoAddresses,
+
+    #[snafu(display("No peer to send events to."))]
+    NoPeers,
 }
 
 #[derive(Debug, Clone)]
@@ -305,6 +361,58 @@ mod tests {
         assert_eq!(input_lines, output_lines);
     }
 
+    #[tokio::test]
+    async fn load_balances_across_addresses() {
+        let num_lines = 10;
+
+        let addrs = [next_addr(), next_addr()];
+
+        let config = format!(
+            r#"
+            addresses = ["http://{}/", "http://{}/"]
+            batch.max_events = 1
+            "#,
+            addrs[0], addrs[1]
+        );
+        let config: VectorConfig = toml::from_str(&config).unwrap();
+
+        let cx = SinkContext::new_test();
+
+        let (sink, _) = config.build(cx).await.unwrap();
+        let mut receivers = Vec::new();
+        let mut triggers = Vec::new();
+        for addr in &addrs {
+            let (rx, trigger, server) = build_test_server_status(*addr, StatusCode::OK);
+            tokio::spawn(server);
+            receivers.push(rx);
+            triggers.push(trigger);
+        }
+
+        let (input_lines, events) = random_lines_with_stream(8, num_lines, None);
+
+        sink.run(events).await.unwrap();
+        drop(triggers);
+
+        let mut output_lines = Vec::new();
+        for rx in receivers {
+            let lines = get_received(rx, |_| {}).await;
+            assert!(!lines.is_empty());
+            output_lines.extend(lines);
+        }
+
+        output_lines.sort();
+        let mut input_lines = input_lines;
+        input_lines.sort();
+        assert_eq!(input_lines, output_lines);
+    }
+
+    #[tokio::test]
+    async fn requires_an_address() {
+        let config: VectorConfig = toml::from_str("").unwrap();
+
+        assert!(config.build(SinkContext::new_test()).await.is_err());
+    }
+
     #[tokio::test]
     #[ignore] // This test hangs, possibly an infinite retry loop
     async fn acknowledges_error() {
@@ -0,0 +1,382 @@
+use crate::{
+    buffers::Acker,
+    config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    dns,
+    event::Event,
+    internal_events::{
+        ConnectionOpen, OpenGauge, WebSocketConnectionError, WebSocketConnectionEstablished,
+        WebSocketConnectionFailed, WebSocketConnectionShutdown, WebSocketEventSent,
+        WebSocketPongTimeout,
+    },
+    sinks::util::{
+        encoding::{EncodingConfig, EncodingConfiguration},
+        retries::ExponentialBackoff,
+        Encoding, StreamSink,
+    },
+    tls::{MaybeTlsSettings, MaybeTlsStream, TlsError, TlsOptions, TlsSettings},
+};
+use async_trait::async_trait;
+use futures::{stream::BoxStream, FutureExt, SinkExt, StreamExt};
+use http::Uri;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{net::SocketAddr, time::Duration};
+use tokio::{
+    net::TcpStream,
+    time::{interval, sleep, sleep_until, Instant},
+};
+use tokio_tungstenite::{
+    client_async,
+    tungstenite::{error::Error as WsError, Message},
+    WebSocketStream,
+};
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("Invalid uri {:?}: {}", uri, source))]
+    InvalidUri {
+        uri: String,
+        source: http::uri::InvalidUri,
+    },
+    #[snafu(display("The uri must use the `ws` or `wss` scheme"))]
+    UnsupportedScheme,
+    #[snafu(display("The uri is missing a host"))]
+    MissingHost,
+    #[snafu(display("`ping_interval_secs` and `ping_timeout_secs` must be positive"))]
+    InvalidPing,
+}
+
+#[derive(Debug, Snafu)]
+enum WebSocketError {
+    #[snafu(display("DNS error: {}", source))]
+    Dns { source: dns::DnsError },
+    #[snafu(display("No addresses returned."))]
+    NoAddresses,
+    #[snafu(display("Connect error: {}", source))]
+    Connect { source: TlsError },
+    #[snafu(display("Handshake error: {}", source))]
+    Handshake { source: WsError },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WebSocketSinkConfig {
+    /// The `ws://` or `wss://` uri to connect to.
+    pub uri: String,
+    /// Used when connecting to a `wss://` uri.
+    pub tls: Option<TlsOptions>,
+    pub encoding: EncodingConfig<Encoding>,
+    #[serde(default)]
+    pub frame_type: FrameType,
+    /// How often the server is pinged to keep the connection alive.
+    #[serde(default = "default_ping_interval_secs")]
+    pub ping_interval_secs: u64,
+    /// The connection is reestablished when the server doesn't answer a ping
+    /// within this time.
+    #[serde(default = "default_ping_timeout_secs")]
+    pub ping_timeout_secs: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Derivative, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameType {
+    #[derivative(Default)]
+    Text,
+    Binary,
+}
+
+fn default_ping_interval_secs() -> u64 {
+    30
+}
+
+fn default_ping_timeout_secs() -> u64 {
+    10
+}
+
+inventory::submit! {
+    SinkDescription::new::<WebSocketSinkConfig>("websocket")
+}
+
+impl GenerateConfig for WebSocketSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"uri = "ws://127.0.0.1:8080"
+            encoding.codec = "json""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "websocket")]
+impl SinkConfig for WebSocketSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        if self.ping_interval_secs == 0 || self.ping_timeout_secs == 0 {
+            return Err(BuildError::InvalidPing.into());
+        }
+        let connector = WebSocketConnector::new(&self.uri, &self.tls)?;
+        let healthcheck = connector.clone().healthcheck().boxed();
+        let sink = WebSocketSink {
+            connector,
+            encoding: self.encoding.clone(),
+            frame_type: self.frame_type,
+            ping_interval: Duration::from_secs(self.ping_interval_secs),
+            ping_timeout: Duration::from_secs(self.ping_timeout_secs),
+            acker: cx.acker(),
+        };
+        Ok((super::VectorSink::Stream(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "websocket"
+    }
+}
+
+#[derive(Clone)]
+struct WebSocketConnector {
+    uri: String,
+    host: String,
+    port: u16,
+    tls: MaybeTlsSettings,
+}
+
+impl WebSocketConnector {
+    fn new(uri: &str, tls: &Option<TlsOptions>) -> crate::Result<Self> {
+        let parsed = uri.parse::<Uri>().with_context(|| InvalidUri {
+            uri: uri.to_owned(),
+        })?;
+        let (tls, default_port) = match parsed.scheme_str() {
+            Some("ws") => (MaybeTlsSettings::Raw(()), 80),
+            Some("wss") => (TlsSettings::from_options(tls)?.into(), 443),
+            _ => return Err(BuildError::UnsupportedScheme.into()),
+        };
+        let host = parsed.host().ok_or(BuildError::MissingHost)?;
+        Ok(Self {
+            uri: uri.to_owned(),
+            host: host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_owned(),
+            port: parsed.port_u16().unwrap_or(default_port),
+            tls,
+        })
+    }
+
+    fn fresh_backoff() -> ExponentialBackoff {
+        ExponentialBackoff::from_millis(2)
+            .factor(250)
+            .max_delay(Duration::from_secs(60))
+    }
+
+    async fn connect(&self) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, WebSocketError> {
+        let ip = dns::Resolver
+            .lookup_ip(self.host.clone())
+            .await
+            .context(Dns)?
+            .next()
+            .ok_or(WebSocketError::NoAddresses)?;
+
+        let addr = SocketAddr::new(ip, self.port);
+        let stream = self.tls.connect(&self.host, &addr).await.context(Connect)?;
+        let (ws, _response) = client_async(self.uri.as_str(), stream)
+            .await
+            .context(Handshake)?;
+        emit!(WebSocketConnectionEstablished { peer_addr: addr });
+        Ok(ws)
+    }
+
+    async fn connect_backoff(&self) -> WebSocketStream<MaybeTlsStream<TcpStream>> {
+        let mut backoff = Self::fresh_backoff();
+        loop {
+            match self.connect().await {
+                Ok(ws) => return ws,
+                Err(error) => {
+                    emit!(WebSocketConnectionFailed { error });
+                    sleep(backoff.next().unwrap()).await;
+                }
+            }
+        }
+    }
+
+    async fn healthcheck(self) -> crate::Result<()> {
+        let mut ws = self.connect().await?;
+        let _ = ws.close(None).await;
+        Ok(())
+    }
+}
+
+struct WebSocketSink {
+    connector: WebSocketConnector,
+    encoding: EncodingConfig<Encoding>,
+    frame_type: FrameType,
+    ping_interval: Duration,
+    ping_timeout: Duration,
+    acker: Acker,
+}
+
+impl WebSocketSink {
+    fn encode_event(&self, mut event: Event) -> Message {
+        self.encoding.apply_rules(&mut event);
+        let log = event.into_log();
+
+        let payload = match self.encoding.codec() {
+            Encoding::Json => serde_json::to_vec(&log).expect("Serializing JSON can't fail"),
+            Encoding::Text => log
+                .get(log_schema().message_key())
+                .map(|value| value.as_bytes().to_vec())
+                .unwrap_or_default(),
+        };
+
+        match self.frame_type {
+            FrameType::Text => Message::Text(String::from_utf8_lossy(&payload).into_owned()),
+            FrameType::Binary => Message::Binary(payload),
+        }
+    }
+}
+
+#[async_trait]
+impl StreamSink for WebSocketSink {
+    async fn run(&mut self, input: BoxStream<'_, Event>) -> Result<(), ()> {
+        let mut input = input.fuse();
+        // A message whose sending failed, resent once reconnected.
+        let mut pending = None;
+
+        loop {
+            let mut ws = self.connector.connect_backoff().await;
+            let _open_token = OpenGauge::new().open(|count| emit!(ConnectionOpen { count }));
+
+            if let Some(message) = pending.take() {
+                if let Err(error) = ws.send(message.clone()).await {
+                    emit!(WebSocketConnectionError { error });
+                    pending = Some(message);
+                    continue;
+                }
+                emit!(WebSocketEventSent {
+                    byte_size: message.len()
+                });
+                self.acker.ack(1);
+            }
+
+            let mut ping = interval(self.ping_interval);
+            // The first tick completes immediately.
+            ping.tick().await;
+            let mut pong_deadline = None;
+
+            loop {
+                tokio::select! {
+                    _ = ping.tick() => {
+                        if let Err(error) = ws.send(Message::Ping(Vec::new())).await {
+                            emit!(WebSocketConnectionError { error });
+                            break;
+                        }
+                        pong_deadline.get_or_insert_with(|| Instant::now() + self.ping_timeout);
+                    },
+                    _ = sleep_until(pong_deadline.unwrap_or_else(Instant::now)), if pong_deadline.is_some() => {
+                        emit!(WebSocketPongTimeout);
+                        break;
+                    },
+                    message = ws.next() => match message {
+                        Some(Ok(Message::Pong(_))) => pong_deadline = None,
+                        Some(Ok(Message::Close(_))) | None => {
+                            emit!(WebSocketConnectionShutdown);
+                            break;
+                        }
+                        // Pings are answered by the connection itself, and
+                        // the messages of the server are ignored.
+                        Some(Ok(_)) => (),
+                        Some(Err(error)) => {
+                            emit!(WebSocketConnectionError { error });
+                            break;
+                        }
+                    },
+                    event = input.next() => match event {
+                        Some(event) => {
+                            let message = self.encode_event(event);
+                            let byte_size = message.len();
+                            if let Err(error) = ws.send(message.clone()).await {
+                                emit!(WebSocketConnectionError { error });
+                                pending = Some(message);
+                                break;
+                            }
+                            emit!(WebSocketEventSent { byte_size });
+                            self.acker.ack(1);
+                        }
+                        None => {
+                            let _ = ws.close(None).await;
+                            return Ok(());
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{next_addr, random_lines_with_stream, trace_init};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_async;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<WebSocketSinkConfig>();
+    }
+
+    #[test]
+    fn rejects_invalid_uris() {
+        assert!(WebSocketConnector::new("http://localhost", &None).is_err());
+        assert!(WebSocketConnector::new("ws:///path", &None).is_err());
+
+        let connector = WebSocketConnector::new("wss://[::1]/events", &None).unwrap();
+        assert_eq!(connector.host, "::1");
+        assert_eq!(connector.port, 443);
+        assert!(connector.tls.is_tls());
+    }
+
+    #[tokio::test]
+    async fn sends_binary_frames_and_pings() {
+        trace_init();
+
+        let addr = next_addr();
+        let listener = TcpListener::bind(addr).await.unwrap();
+
+        let config: WebSocketSinkConfig = toml::from_str(&format!(
+            r#"
+            uri = "ws://{}/events"
+            encoding.codec = "text"
+            frame_type = "binary"
+            ping_interval_secs = 1
+            "#,
+            addr
+        ))
+        .unwrap();
+        let (sink, _healthcheck) = config.build(SinkContext::new_test()).await.unwrap();
+
+        let (lines, events) = random_lines_with_stream(10, 3, None);
+        tokio::spawn(sink.run(events.chain(futures::stream::pending::<Event>())));
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = accept_async(stream).await.unwrap();
+        let mut received = Vec::new();
+        let mut pinged = false;
+        while received.len() < lines.len() || !pinged {
+            match ws.next().await.unwrap().unwrap() {
+                Message::Binary(payload) => received.push(String::from_utf8(payload).unwrap()),
+                Message::Ping(_) => pinged = true,
+                message => panic!("unexpected message {:?}", message),
+            }
+        }
+
+        assert_eq!(received, lines);
+    }
+}
@@ -14,13 +14,16 @@ use bytes::Bytes;
 use chrono::Utc;
 use file_source::{
     paths_provider::glob::{Glob, MatchOptions},
-    Checkpointer, FileFingerprint, FileServer, FingerprintStrategy, Fingerprinter, Line, ReadFrom,
+    Checkpointer, DataDirLock, FileFingerprint, FileServer, FingerprintStrategy, Fingerprinter,
+    Line, ReadFrom,
 };
 use futures::{
     future::TryFutureExt,
     stream::{Stream, StreamExt},
     FutureExt, SinkExt,
 };
+use glob::Pattern;
+use indexmap::IndexMap;
 use regex::bytes::Regex;
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
@@ -47,6 +50,11 @@ enum BuildError {
     MissingDataDir { data_dir: PathBuf },
     #[snafu(display("data_dir {:?} is not writable", data_dir))]
     DataDirNotWritable { data_dir: PathBuf },
+    #[snafu(display("could not lock data_dir {:?}: {}", data_dir, source))]
+    DataDirLocked {
+        data_dir: PathBuf,
+        source: std::io::Error,
+    },
     #[snafu(display(
         "message_start_indicator {:?} is not a valid regex: {}",
         indicator,
@@ -56,6 +64,11 @@ enum BuildError {
         indicator: String,
         source: regex::Error,
     },
+    #[snafu(display("read_weights pattern {:?} is not a valid glob: {}", pattern, source))]
+    InvalidReadWeightPattern {
+        pattern: String,
+        source: glob::PatternError,
+    },
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
@@ -76,6 +89,7 @@ pub struct FileConfig {
     pub data_dir: Option<PathBuf>,
     #[serde(alias = "glob_minimum_cooldown")]
     pub glob_minimum_cooldown_ms: u64,
+    pub checkpoint_interval_ms: Option<u64>,
     // Deprecated name
     #[serde(alias = "fingerprinting")]
     pub fingerprint: FingerprintConfig,
@@ -85,6 +99,9 @@ pub struct FileConfig {
     pub multiline: Option<MultilineConfig>,
     pub max_read_bytes: usize,
     pub oldest_first: bool,
+    pub max_read_bytes_per_sec: Option<usize>,
+    pub max_read_bytes_per_sec_per_file: Option<usize>,
+    pub read_weights: IndexMap<String, usize>,
     #[serde(alias = "remove_after")]
     pub remove_after_secs: Option<u64>,
     pub line_delimiter: String,
@@ -182,11 +199,15 @@ impl Default for FileConfig {
             host_key: None,
             data_dir: None,
             glob_minimum_cooldown_ms: 1000, // millis
+            checkpoint_interval_ms: None,
             message_start_indicator: None,
             multi_line_timeout: 1000, // millis
             multiline: None,
             max_read_bytes: 2048,
             oldest_first: false,
+            max_read_bytes_per_sec: None,
+            max_read_bytes_per_sec_per_file: None,
+            read_weights: IndexMap::new(),
             remove_after_secs: None,
             line_delimiter: "\n".to_string(),
             encoding: None,
@@ -223,6 +244,10 @@ impl SourceConfig for FileConfig {
                 Regex::new(indicator)
                     .with_context(|| InvalidMessageStartIndicator { indicator })?;
             }
+
+            for pattern in self.read_weights.keys() {
+                Pattern::new(pattern).with_context(|| InvalidReadWeightPattern { pattern })?;
+            }
         }
 
         Ok(file_source(
@@ -254,6 +279,9 @@ pub fn file_source(
         .ignore_older_secs
         .map(|secs| Utc::now() - chrono::Duration::seconds(secs as i64));
     let glob_minimum_cooldown = Duration::from_millis(config.glob_minimum_cooldown_ms);
+    let checkpoint_interval = config
+        .checkpoint_interval_ms
+        .map_or(glob_minimum_cooldown, Duration::from_millis);
     let (ignore_checkpoints, read_from) = reconcile_position_options(
         config.start_at_beginning,
         config.ignore_checkpoints,
@@ -268,6 +296,15 @@ pub fn file_source(
     )
     .expect("invalid glob patterns");
 
+    let read_weights = config
+        .read_weights
+        .iter()
+        .map(|(pattern, weight)| {
+            let pattern = Pattern::new(pattern).expect("invalid read_weights patterns");
+            (pattern, *weight)
+        })
+        .collect();
+
     let encoding_charset = config.encoding.clone().map(|e| e.charset);
 
     // if file encoding is specified, need to convert the line delimiter (present as utf8)
@@ -278,6 +315,7 @@ pub fn file_source(
     };
 
     let checkpointer = Checkpointer::new(&data_dir);
+    let lock_dir = data_dir.clone();
     let file_server = FileServer {
         paths_provider,
         max_read_bytes: config.max_read_bytes,
@@ -288,6 +326,7 @@ pub fn file_source(
         line_delimiter: line_delimiter_as_bytes,
         data_dir,
         glob_minimum_cooldown,
+        checkpoint_interval,
         fingerprinter: Fingerprinter {
             strategy: config.fingerprint.clone().into(),
             max_line_length: config.max_line_bytes,
@@ -295,6 +334,9 @@ pub fn file_source(
         },
         oldest_first: config.oldest_first,
         remove_after: config.remove_after_secs.map(Duration::from_secs),
+        max_read_bytes_per_sec: config.max_read_bytes_per_sec,
+        max_read_bytes_per_sec_per_file: config.max_read_bytes_per_sec_per_file,
+        read_weights,
         emitter: FileSourceInternalEventsEmitter,
         handle: tokio::runtime::Handle::current(),
     };
@@ -315,12 +357,22 @@ pub fn file_source(
     let shutdown = shutdown.shared();
     let finalizer = acknowledgements.then(|| {
         let checkpoints = checkpointer.view();
-        OrderedFinalizer::new(shutdown.clone(), move |entry: FinalizerEntry| {
+        OrderedFinalizer::new(shutdown.clone(), move |_status, entry: FinalizerEntry| {
             checkpoints.update(entry.file_id, entry.offset)
         })
     });
 
     Box::pin(async move {
+        // Hold the lock for as long as the source runs, so that no other
+        // process reads the same files and overwrites our checkpoints. It's
+        // only taken once the source starts, as the source it replaces on a
+        // reload still holds it while this one is built.
+        let _lock = DataDirLock::acquire(&lock_dir)
+            .with_context(|| DataDirLocked {
+                data_dir: lock_dir.clone(),
+            })
+            .map_err(|error| error!(message = "Unable to start file server.", %error))?;
+
         info!(message = "Starting file server.", include = ?include, exclude = ?exclude);
 
         let mut encoding_decoder = encoding_charset.map(|e| Decoder::new(e));
@@ -477,6 +529,7 @@ mod tests {
         sources::file,
     };
     use encoding_rs::UTF_16LE;
+    use flate2::{write::GzEncoder, Compression};
     use pretty_assertions::assert_eq;
     use std::{
         collections::HashSet,
@@ -1372,6 +1425,92 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_data_dir_locked() {
+        let dir = tempdir().unwrap();
+        let config = file::FileConfig {
+            include: vec![dir.path().join("*")],
+            ..test_default_file_config(&dir)
+        };
+
+        let data_dir = config.data_dir.clone().unwrap();
+
+        let (tx, _rx) = Pipeline::new_test();
+        let (trigger_shutdown, shutdown, shutdown_done) = ShutdownSignal::new_wired();
+        let running = tokio::spawn(file::file_source(
+            &config,
+            data_dir.clone(),
+            shutdown,
+            tx,
+            false,
+        ));
+        sleep_500_millis().await;
+
+        // Building a second source succeeds, but it fails once it starts.
+        let (tx, _rx) = Pipeline::new_test();
+        let source =
+            file::file_source(&config, data_dir.clone(), ShutdownSignal::noop(), tx, false);
+        assert!(source.await.is_err());
+
+        drop(trigger_shutdown);
+        shutdown_done.await;
+        assert!(running.await.unwrap().is_ok());
+
+        let (tx, _rx) = Pipeline::new_test();
+        let (trigger_shutdown, shutdown, shutdown_done) = ShutdownSignal::new_wired();
+        let running = tokio::spawn(file::file_source(&config, data_dir, shutdown, tx, false));
+        sleep_500_millis().await;
+        drop(trigger_shutdown);
+        shutdown_done.await;
+        assert!(running.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_weighted_reads() {
+        let dir = tempdir().unwrap();
+        let config = file::FileConfig {
+            include: vec![dir.path().join("*")],
+            max_read_bytes: 30,
+            oldest_first: false,
+            read_weights: vec![("*/z_older_file".to_owned(), 3)].into_iter().collect(),
+            ..test_default_file_config(&dir)
+        };
+
+        let older_path = dir.path().join("z_older_file");
+        let mut older = File::create(&older_path).unwrap();
+
+        sleep_500_millis().await;
+
+        let newer_path = dir.path().join("a_newer_file");
+        let mut newer = File::create(&newer_path).unwrap();
+
+        writeln!(&mut older, "hello i am the old file").unwrap();
+        writeln!(&mut older, "i have been around a while").unwrap();
+        writeln!(&mut older, "and i get three times the reads").unwrap();
+
+        writeln!(&mut newer, "and i am the new file").unwrap();
+        writeln!(&mut newer, "i only get a third of them").unwrap();
+        writeln!(&mut newer, "so i have to wait my turn").unwrap();
+
+        sleep_500_millis().await;
+
+        let received = run_file_source(&config, false, NoAcks, sleep_500_millis()).await;
+
+        let received = extract_messages_value(received);
+
+        assert_eq!(
+            received,
+            vec![
+                "hello i am the old file".into(),
+                "i have been around a while".into(),
+                "and i get three times the reads".into(),
+                "and i am the new file".into(),
+                "i only get a third of them".into(),
+                "so i have to wait my turn".into(),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_oldest_first() {
         let dir = tempdir().unwrap();
@@ -1465,13 +1604,6 @@ mod tests {
         let dir = tempdir().unwrap();
         let config = file::FileConfig {
             include: vec![PathBuf::from("tests/data/gzipped.log")],
-            // TODO: remove this once files are fingerprinted after decompression
-            //
-            // Currently, this needs to be smaller than the total size of the compressed file
-            // because the fingerprinter tries to read until a newline, which it's not going to see
-            // in the compressed data, or this number of bytes. If it hits EOF before that, it
-            // can't return a fingerprint because the value would change once more data is written.
-            max_line_bytes: 100,
             ..test_default_file_config(&dir)
         };
 
@@ -1491,6 +1623,64 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_zstd_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("file.log.zst");
+        fs::write(
+            &path,
+            zstd::encode_all(&b"first line\nsecond line\n"[..], 0).unwrap(),
+        )
+        .unwrap();
+
+        let config = file::FileConfig {
+            include: vec![path],
+            ..test_default_file_config(&dir)
+        };
+
+        let received = run_file_source(&config, false, NoAcks, sleep_500_millis()).await;
+
+        let lines = extract_messages_string(received);
+        assert_eq!(lines, vec!["first line", "second line"]);
+    }
+
+    #[tokio::test]
+    async fn test_file_compressed_after_rotation() {
+        let dir = tempdir().unwrap();
+        let config = file::FileConfig {
+            include: vec![dir.path().join("app.log*")],
+            ..test_default_file_config(&dir)
+        };
+
+        let path = dir.path().join("app.log");
+        let mut file = File::create(&path).unwrap();
+        writeln!(&mut file, "first line").unwrap();
+        writeln!(&mut file, "second line").unwrap();
+        sleep_500_millis().await;
+
+        {
+            let received = run_file_source(&config, true, NoAcks, sleep_500_millis()).await;
+
+            let lines = extract_messages_string(received);
+            assert_eq!(lines, vec!["first line", "second line"]);
+        }
+
+        // Write a tail that is not read before the file gets rotated and compressed.
+        writeln!(&mut file, "third line").unwrap();
+        drop(file);
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&fs::read(&path).unwrap()).unwrap();
+        fs::write(dir.path().join("app.log.1.gz"), encoder.finish().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        {
+            let received = run_file_source(&config, true, NoAcks, sleep_500_millis()).await;
+
+            let lines = extract_messages_string(received);
+            assert_eq!(lines, vec!["third line"]);
+        }
+    }
+
     #[tokio::test]
     async fn test_non_utf8_encoded_file() {
         let dir = tempdir().unwrap();
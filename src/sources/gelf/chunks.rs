@@ -0,0 +1,175 @@
+use super::GelfError;
+use bytes::{Buf, Bytes, BytesMut};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// The two bytes starting the chunks of a message.
+pub(super) const CHUNK_MAGIC: [u8; 2] = [0x1e, 0x0f];
+/// Magic bytes, message id, sequence number and sequence count.
+const CHUNK_HEADER_LENGTH: usize = 12;
+const MAX_CHUNKS: u8 = 128;
+
+struct PartialMessage {
+    chunks: Vec<Option<Bytes>>,
+    received: usize,
+    first_received: Instant,
+}
+
+/// Reassembles the messages sent in chunks over UDP.
+pub(super) struct ChunkAssembler {
+    messages: HashMap<u64, PartialMessage>,
+    timeout: Duration,
+    max_messages: usize,
+}
+
+impl ChunkAssembler {
+    /// Creates an assembler reassembling at most `max_messages` at a time.
+    pub(super) fn new(timeout: Duration, max_messages: usize) -> Self {
+        Self {
+            messages: HashMap::new(),
+            timeout,
+            max_messages,
+        }
+    }
+
+    /// Adds a chunk, returning its message once all of its chunks have been
+    /// received.
+    pub(super) fn add(&mut self, mut chunk: Bytes) -> Result<Option<Bytes>, GelfError> {
+        if chunk.len() < CHUNK_HEADER_LENGTH {
+            return Err(GelfError::TruncatedChunk);
+        }
+        chunk.advance(CHUNK_MAGIC.len());
+        let message_id = chunk.get_u64();
+        let sequence_number = chunk.get_u8();
+        let sequence_count = chunk.get_u8();
+        if sequence_count == 0 || sequence_count > MAX_CHUNKS || sequence_number >= sequence_count {
+            return Err(GelfError::InvalidChunk {
+                sequence_number,
+                sequence_count,
+            });
+        }
+
+        if !self.messages.contains_key(&message_id) && self.messages.len() >= self.max_messages {
+            return Err(GelfError::TooManyChunkedMessages {
+                max: self.max_messages,
+            });
+        }
+        let message = self
+            .messages
+            .entry(message_id)
+            .or_insert_with(|| PartialMessage {
+                chunks: vec![None; sequence_count as usize],
+                received: 0,
+                first_received: Instant::now(),
+            });
+        if message.chunks.len() != sequence_count as usize {
+            return Err(GelfError::InvalidChunk {
+                sequence_number,
+                sequence_count,
+            });
+        }
+        let slot = &mut message.chunks[sequence_number as usize];
+        if slot.is_none() {
+            message.received += 1;
+        }
+        *slot = Some(chunk);
+
+        if message.received < message.chunks.len() {
+            return Ok(None);
+        }
+        let message = self.messages.remove(&message_id).unwrap();
+        let mut payload = BytesMut::new();
+        for chunk in message.chunks.into_iter().flatten() {
+            payload.extend_from_slice(&chunk);
+        }
+        Ok(Some(payload.freeze()))
+    }
+
+    /// Drops the messages whose chunks didn't all arrive in time, returning
+    /// how many were dropped.
+    pub(super) fn remove_expired(&mut self) -> usize {
+        let before = self.messages.len();
+        let timeout = self.timeout;
+        self.messages
+            .retain(|_, message| message.first_received.elapsed() < timeout);
+        before - self.messages.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BufMut;
+
+    fn chunk(message_id: u64, sequence_number: u8, sequence_count: u8, payload: &str) -> Bytes {
+        let mut chunk = BytesMut::new();
+        chunk.put_slice(&CHUNK_MAGIC);
+        chunk.put_u64(message_id);
+        chunk.put_u8(sequence_number);
+        chunk.put_u8(sequence_count);
+        chunk.put_slice(payload.as_bytes());
+        chunk.freeze()
+    }
+
+    #[test]
+    fn reassembles_chunks_in_any_order() {
+        let mut assembler = ChunkAssembler::new(Duration::from_secs(5), 10);
+        assert_eq!(assembler.add(chunk(1, 2, 3, "baz")).unwrap(), None);
+        assert_eq!(assembler.add(chunk(2, 0, 2, "other")).unwrap(), None);
+        assert_eq!(assembler.add(chunk(1, 0, 3, "foo")).unwrap(), None);
+        // A duplicate chunk replaces the previous one.
+        assert_eq!(assembler.add(chunk(1, 0, 3, "foo")).unwrap(), None);
+        assert_eq!(
+            assembler.add(chunk(1, 1, 3, "bar")).unwrap(),
+            Some(Bytes::from("foobarbaz"))
+        );
+        assert_eq!(assembler.messages.len(), 1);
+    }
+
+    #[test]
+    fn rejects_invalid_chunks() {
+        let mut assembler = ChunkAssembler::new(Duration::from_secs(5), 10);
+        assert!(matches!(
+            assembler.add(Bytes::from_static(&[0x1e, 0x0f, 0, 0])),
+            Err(GelfError::TruncatedChunk)
+        ));
+        assert!(matches!(
+            assembler.add(chunk(1, 3, 3, "foo")),
+            Err(GelfError::InvalidChunk { .. })
+        ));
+        assert!(matches!(
+            assembler.add(chunk(1, 0, 129, "foo")),
+            Err(GelfError::InvalidChunk { .. })
+        ));
+        assembler.add(chunk(1, 0, 3, "foo")).unwrap();
+        assert!(matches!(
+            assembler.add(chunk(1, 1, 2, "bar")),
+            Err(GelfError::InvalidChunk { .. })
+        ));
+    }
+
+    #[test]
+    fn limits_messages_being_reassembled() {
+        let mut assembler = ChunkAssembler::new(Duration::from_secs(5), 1);
+        assembler.add(chunk(1, 0, 2, "foo")).unwrap();
+        assert!(matches!(
+            assembler.add(chunk(2, 0, 2, "other")),
+            Err(GelfError::TooManyChunkedMessages { max: 1 })
+        ));
+        assert_eq!(
+            assembler.add(chunk(1, 1, 2, "bar")).unwrap(),
+            Some(Bytes::from("foobar"))
+        );
+        assert_eq!(assembler.add(chunk(2, 0, 2, "other")).unwrap(), None);
+    }
+
+    #[test]
+    fn removes_expired_messages() {
+        let mut assembler = ChunkAssembler::new(Duration::from_secs(0), 10);
+        assembler.add(chunk(1, 0, 2, "foo")).unwrap();
+        assert_eq!(assembler.remove_expired(), 1);
+        assert_eq!(assembler.add(chunk(1, 1, 2, "bar")).unwrap(), None);
+    }
+}
@@ -0,0 +1,522 @@
+use crate::{
+    config::{
+        log_schema, DataType, GenerateConfig, Resource, SourceConfig, SourceContext,
+        SourceDescription,
+    },
+    event::{Event, LogEvent},
+    internal_events::{
+        GelfChunksExpired, GelfInvalidMessage, GelfMessageReceived, GelfSocketError,
+    },
+    shutdown::ShutdownSignal,
+    sources::util::{SocketListenAddr, TcpSource},
+    tcp::TcpKeepaliveConfig,
+    tls::{MaybeTlsSettings, TlsConfig},
+    udp, Pipeline,
+};
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use codec::BytesDelimitedCodec;
+use flate2::read::{MultiGzDecoder, ZlibDecoder};
+use futures::{SinkExt, StreamExt, TryFutureExt};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    io::{self, Read},
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    time::Duration,
+};
+use tokio::net::UdpSocket;
+use tokio_util::{codec::BytesCodec, udp::UdpFramed};
+
+mod chunks;
+
+use chunks::{ChunkAssembler, CHUNK_MAGIC};
+
+#[derive(Debug, Snafu)]
+pub enum GelfError {
+    #[snafu(display("Chunk is shorter than its header"))]
+    TruncatedChunk,
+    #[snafu(display("Invalid chunk {} of {}", sequence_number, sequence_count))]
+    InvalidChunk {
+        sequence_number: u8,
+        sequence_count: u8,
+    },
+    #[snafu(display("Too many messages being reassembled, at most {}", max))]
+    TooManyChunkedMessages { max: usize },
+    #[snafu(display("Failed to decompress message: {}", source))]
+    Decompress { source: io::Error },
+    #[snafu(display("Decompressed message is longer than {} bytes", max_length))]
+    TooLong { max_length: usize },
+    #[snafu(display("Message is not a JSON object: {}", source))]
+    InvalidJson { source: serde_json::Error },
+    #[snafu(display("Invalid timestamp {}", timestamp))]
+    InvalidTimestamp { timestamp: serde_json::Value },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+enum GelfConfig {
+    Tcp(TcpConfig),
+    Udp(UdpConfig),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct UdpConfig {
+    address: SocketAddr,
+    receive_buffer_bytes: Option<usize>,
+    /// Messages whose chunks didn't all arrive within this time are dropped.
+    #[serde(default = "default_chunk_timeout_secs")]
+    chunk_timeout_secs: u64,
+    /// Chunks of new messages are dropped while this many are being reassembled.
+    #[serde(default = "default_max_chunked_messages")]
+    max_chunked_messages: usize,
+    /// Messages longer than this once decompressed are dropped.
+    #[serde(default = "default_max_length")]
+    max_length: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct TcpConfig {
+    address: SocketListenAddr,
+    keepalive: Option<TcpKeepaliveConfig>,
+    #[serde(default = "default_max_length")]
+    max_length: usize,
+    #[serde(default)]
+    tls: Option<TlsConfig>,
+    #[serde(default = "default_shutdown_timeout_secs")]
+    shutdown_timeout_secs: u64,
+    receive_buffer_bytes: Option<usize>,
+}
+
+fn default_chunk_timeout_secs() -> u64 {
+    5
+}
+
+fn default_max_chunked_messages() -> usize {
+    1000
+}
+
+fn default_max_length() -> usize {
+    bytesize::mib(1u64) as usize
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+inventory::submit! {
+    SourceDescription::new::<GelfConfig>("gelf")
+}
+
+impl GenerateConfig for GelfConfig {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(Self::Udp(UdpConfig {
+            address: SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), 12201)),
+            receive_buffer_bytes: None,
+            chunk_timeout_secs: default_chunk_timeout_secs(),
+            max_chunked_messages: default_max_chunked_messages(),
+            max_length: default_max_length(),
+        }))
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "gelf")]
+impl SourceConfig for GelfConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        match self {
+            GelfConfig::Udp(config) => Ok(Box::pin(gelf_udp(config.clone(), cx.shutdown, cx.out))),
+            GelfConfig::Tcp(config) => {
+                let tls = MaybeTlsSettings::from_config(&config.tls, true)?;
+                GelfTcpSource {
+                    max_length: config.max_length,
+                }
+                .run(
+                    config.address,
+                    config.keepalive,
+                    config.shutdown_timeout_secs,
+                    tls,
+                    config.receive_buffer_bytes,
+                    cx.shutdown,
+                    cx.out,
+                )
+            }
+        }
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "gelf"
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        match self {
+            Self::Tcp(tcp) => vec![tcp.address.into()],
+            Self::Udp(udp) => vec![Resource::udp(udp.address)],
+        }
+    }
+}
+
+/// Decompresses a message according to its magic bytes, as GELF clients
+/// may send them gzip or zlib compressed over UDP. Messages longer than
+/// `max_length` once decompressed are rejected.
+fn decompress(message: Bytes, max_length: usize) -> Result<Bytes, GelfError> {
+    // One more byte than allowed is read to tell the messages too long.
+    let limit = max_length as u64 + 1;
+    let mut decompressed = Vec::new();
+    match message.as_ref() {
+        [0x1f, 0x8b, ..] => MultiGzDecoder::new(message.as_ref())
+            .take(limit)
+            .read_to_end(&mut decompressed)
+            .context(Decompress)?,
+        [cmf, flg, ..] if cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0 => {
+            ZlibDecoder::new(message.as_ref())
+                .take(limit)
+                .read_to_end(&mut decompressed)
+                .context(Decompress)?
+        }
+        _ if message.len() > max_length => return Err(GelfError::TooLong { max_length }),
+        _ => return Ok(message),
+    };
+    if decompressed.len() > max_length {
+        return Err(GelfError::TooLong { max_length });
+    }
+    Ok(decompressed.into())
+}
+
+/// Maps a GELF message onto a log event: `short_message`, `host` and
+/// `timestamp` go to the fields of the log schema, additional fields lose
+/// their leading underscore unless that would replace another field, and
+/// the other fields are kept as they are.
+fn parse_message(message: &[u8]) -> Result<LogEvent, GelfError> {
+    let fields: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_slice(message).context(InvalidJson)?;
+
+    let mut log = LogEvent::default();
+    let mut additional = Vec::new();
+    for (name, value) in fields {
+        match name.as_str() {
+            "version" => (),
+            "short_message" => {
+                log.insert(log_schema().message_key(), value);
+            }
+            "host" => {
+                log.insert(log_schema().host_key(), value);
+            }
+            "timestamp" => {
+                let timestamp = match value.as_f64() {
+                    Some(timestamp) if timestamp.is_finite() && timestamp >= 0.0 => Utc
+                        .timestamp_opt(timestamp.trunc() as i64, (timestamp.fract() * 1e9) as u32)
+                        .single(),
+                    _ => None,
+                };
+                match timestamp {
+                    Some(timestamp) => {
+                        log.insert(log_schema().timestamp_key(), timestamp);
+                    }
+                    None => return InvalidTimestamp { timestamp: value }.fail(),
+                }
+            }
+            _ => match name.strip_prefix('_') {
+                Some(additional_name) if !additional_name.is_empty() => {
+                    additional.push((additional_name.to_owned(), value))
+                }
+                _ => log.insert_flat(name, value),
+            },
+        }
+    }
+    for (name, value) in additional {
+        if log.contains(&name) {
+            log.insert_flat(format!("_{}", name), value);
+        } else {
+            log.insert_flat(name, value);
+        }
+    }
+
+    if !log.contains(log_schema().timestamp_key()) {
+        log.insert(log_schema().timestamp_key(), Utc::now());
+    }
+    log.insert(log_schema().source_type_key(), Bytes::from("gelf"));
+    Ok(log)
+}
+
+fn build_event(message: &[u8]) -> Option<Event> {
+    match parse_message(message) {
+        Ok(log) => {
+            emit!(GelfMessageReceived {
+                byte_size: message.len()
+            });
+            Some(Event::Log(log))
+        }
+        Err(error) => {
+            emit!(GelfInvalidMessage {
+                error,
+                byte_size: message.len()
+            });
+            None
+        }
+    }
+}
+
+async fn gelf_udp(
+    config: UdpConfig,
+    shutdown: ShutdownSignal,
+    mut out: Pipeline,
+) -> Result<(), ()> {
+    let socket = UdpSocket::bind(&config.address)
+        .map_err(|error| emit!(GelfSocketError::bind(error)))
+        .await?;
+
+    if let Some(receive_buffer_bytes) = config.receive_buffer_bytes {
+        if let Err(error) = udp::set_receive_buffer_size(&socket, receive_buffer_bytes) {
+            warn!(message = "Failed configuring receive buffer size on UDP socket.", %error);
+        }
+    }
+
+    info!(
+        message = "Listening.",
+        addr = %config.address,
+        r#type = "udp"
+    );
+
+    let chunk_timeout = Duration::from_secs(config.chunk_timeout_secs);
+    let mut assembler = ChunkAssembler::new(chunk_timeout, config.max_chunked_messages);
+    let mut expiration =
+        tokio::time::interval(std::cmp::max(chunk_timeout / 2, Duration::from_millis(100)));
+    let mut stream = UdpFramed::new(socket, BytesCodec::new()).take_until(shutdown);
+    loop {
+        let datagram = tokio::select! {
+            frame = stream.next() => match frame {
+                Some(Ok((datagram, _sock))) => datagram.freeze(),
+                Some(Err(error)) => {
+                    emit!(GelfSocketError::read(error));
+                    continue;
+                }
+                None => break,
+            },
+            _ = expiration.tick() => {
+                let count = assembler.remove_expired();
+                if count > 0 {
+                    emit!(GelfChunksExpired { count });
+                }
+                continue;
+            }
+        };
+
+        let byte_size = datagram.len();
+        let message = if datagram.starts_with(&CHUNK_MAGIC) {
+            assembler.add(datagram)
+        } else {
+            Ok(Some(datagram))
+        };
+        let message = match message.and_then(|message| {
+            message
+                .map(|message| decompress(message, config.max_length))
+                .transpose()
+        }) {
+            Ok(Some(message)) => message,
+            Ok(None) => continue,
+            Err(error) => {
+                emit!(GelfInvalidMessage { error, byte_size });
+                continue;
+            }
+        };
+
+        if let Some(event) = build_event(&message) {
+            if let Err(error) = out.send(event).await {
+                error!(message = "Error sending event.", %error);
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Receives uncompressed messages delimited by null bytes.
+#[derive(Clone)]
+struct GelfTcpSource {
+    max_length: usize,
+}
+
+impl TcpSource for GelfTcpSource {
+    type Error = std::io::Error;
+    type Decoder = BytesDelimitedCodec;
+
+    fn decoder(&self) -> Self::Decoder {
+        BytesDelimitedCodec::new_with_max_length(b'\0', self.max_length)
+    }
+
+    fn build_event(&self, frame: Bytes, _host: Bytes) -> Option<Event> {
+        // Some clients also end messages with a newline.
+        let message = String::from_utf8_lossy(&frame);
+        let message = message.trim();
+        if message.is_empty() {
+            return None;
+        }
+        build_event(message.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{collect_n, next_addr, wait_for_tcp};
+    use flate2::{
+        write::{GzEncoder, ZlibEncoder},
+        Compression,
+    };
+    use std::io::Write;
+    use tokio::io::AsyncWriteExt;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<GelfConfig>();
+    }
+
+    const MESSAGE: &str = r#"{
+        "version": "1.1",
+        "host": "web-1",
+        "short_message": "Connection refused",
+        "full_message": "Connection refused\nat db.connect()",
+        "timestamp": 1628000000.25,
+        "level": 3,
+        "_user_id": 42,
+        "_message": "shadowed",
+        "_": "kept"
+    }"#;
+
+    #[test]
+    fn parses_message() {
+        let log = parse_message(MESSAGE.as_bytes()).unwrap();
+        assert_eq!(log[log_schema().message_key()], "Connection refused".into());
+        assert_eq!(log[log_schema().host_key()], "web-1".into());
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Utc.timestamp(1628000000, 250_000_000).into()
+        );
+        assert_eq!(
+            log["full_message"],
+            "Connection refused\nat db.connect()".into()
+        );
+        assert_eq!(log["level"], 3.into());
+        assert_eq!(log["user_id"], 42.into());
+        assert_eq!(log["_message"], "shadowed".into());
+        assert_eq!(log["_"], "kept".into());
+        assert_eq!(log[log_schema().source_type_key()], "gelf".into());
+        assert!(!log.contains("version"));
+    }
+
+    #[test]
+    fn rejects_invalid_messages() {
+        assert!(matches!(
+            parse_message(b"[]"),
+            Err(GelfError::InvalidJson { .. })
+        ));
+        assert!(matches!(
+            parse_message(br#"{"short_message":"foo","timestamp":"now"}"#),
+            Err(GelfError::InvalidTimestamp { .. })
+        ));
+    }
+
+    #[test]
+    fn decompresses_messages() {
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(MESSAGE.as_bytes()).unwrap();
+        let gzip = gzip.finish().unwrap();
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(MESSAGE.as_bytes()).unwrap();
+        let zlib = zlib.finish().unwrap();
+
+        for message in vec![gzip, zlib, MESSAGE.as_bytes().to_vec()] {
+            assert_eq!(
+                decompress(message.clone().into(), MESSAGE.len()).unwrap(),
+                MESSAGE.as_bytes()
+            );
+            assert!(matches!(
+                decompress(message.into(), MESSAGE.len() - 1),
+                Err(GelfError::TooLong { .. })
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn receives_chunked_messages_over_udp() {
+        let address = next_addr();
+        let (sender, recv) = Pipeline::new_test();
+        let config = GelfConfig::Udp(UdpConfig {
+            address,
+            receive_buffer_bytes: None,
+            chunk_timeout_secs: default_chunk_timeout_secs(),
+            max_chunked_messages: default_max_chunked_messages(),
+            max_length: default_max_length(),
+        });
+        let source = config.build(SourceContext::new_test(sender)).await.unwrap();
+        tokio::spawn(source);
+
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(MESSAGE.as_bytes()).unwrap();
+        let zlib = zlib.finish().unwrap();
+        let (first, second) = zlib.split_at(zlib.len() / 2);
+
+        let socket = UdpSocket::bind(next_addr()).await.unwrap();
+        // The source may not be listening yet, so keep sending until it is.
+        let send = async move {
+            for message_id in 0u64.. {
+                for (sequence_number, payload) in [first, second].iter().enumerate() {
+                    let mut chunk = CHUNK_MAGIC.to_vec();
+                    chunk.extend_from_slice(&message_id.to_be_bytes());
+                    chunk.extend_from_slice(&[sequence_number as u8, 2]);
+                    chunk.extend_from_slice(payload);
+                    socket.send_to(&chunk, address).await.unwrap();
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        };
+        let events = tokio::select! {
+            events = collect_n(recv, 1) => events,
+            _ = send => unreachable!(),
+        };
+
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "Connection refused".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn receives_messages_over_tcp() {
+        let address = next_addr();
+        let (sender, recv) = Pipeline::new_test();
+        let config = GelfConfig::Tcp(TcpConfig {
+            address: address.into(),
+            keepalive: None,
+            max_length: default_max_length(),
+            tls: None,
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
+            receive_buffer_bytes: None,
+        });
+        let source = config.build(SourceContext::new_test(sender)).await.unwrap();
+        tokio::spawn(source);
+        wait_for_tcp(address).await;
+
+        let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+        let messages = format!(
+            "{}\0{}\0",
+            MESSAGE.replace('\n', ""),
+            r#"{"version":"1.1","host":"web-2","short_message":"Second"}"#
+        );
+        stream.write_all(messages.as_bytes()).await.unwrap();
+
+        let events = collect_n(recv, 2).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "Connection refused".into()
+        );
+        assert_eq!(events[1].as_log()[log_schema().host_key()], "web-2".into());
+    }
+}
@@ -0,0 +1,415 @@
+use crate::{
+    config::{self, GenerateConfig, Resource, SourceConfig, SourceContext, SourceDescription},
+    event::Event,
+    internal_events::{GraphiteEventsReceived, GraphiteInvalidRecord, GraphiteSocketError},
+    shutdown::ShutdownSignal,
+    sources::util::{SocketListenAddr, TcpSource},
+    tcp::TcpKeepaliveConfig,
+    tls::{MaybeTlsSettings, TlsConfig},
+    udp, Pipeline,
+};
+use bytes::Bytes;
+use codec::BytesDelimitedCodec;
+use futures::{stream, SinkExt, StreamExt, TryFutureExt};
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use tokio::net::UdpSocket;
+use tokio_util::{codec::BytesCodec, udp::UdpFramed};
+
+pub mod parser;
+mod pickle;
+
+use parser::Parser;
+use pickle::{PickleDecoder, PickledMetric};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+// TODO: add back when serde-rs/serde#1358 is addressed
+// #[serde(deny_unknown_fields)]
+pub struct GraphiteConfig {
+    #[serde(flatten)]
+    mode: Mode,
+    /// Rules splitting the metric paths into names and tags, the first rule
+    /// matching a path being applied.
+    #[serde(default)]
+    templates: Vec<String>,
+    /// Joins the path segments which are part of the same name or tag.
+    #[serde(default = "default_separator")]
+    separator: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+enum Mode {
+    Tcp(TcpConfig),
+    Udp(UdpConfig),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct UdpConfig {
+    address: SocketAddr,
+    receive_buffer_bytes: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct TcpConfig {
+    address: SocketListenAddr,
+    #[serde(default)]
+    protocol: Protocol,
+    keepalive: Option<TcpKeepaliveConfig>,
+    #[serde(default)]
+    tls: Option<TlsConfig>,
+    #[serde(default = "default_shutdown_timeout_secs")]
+    shutdown_timeout_secs: u64,
+    receive_buffer_bytes: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Derivative, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+enum Protocol {
+    #[derivative(Default)]
+    Plaintext,
+    Pickle,
+}
+
+fn default_separator() -> String {
+    ".".into()
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+inventory::submit! {
+    SourceDescription::new::<GraphiteConfig>("graphite")
+}
+
+impl GenerateConfig for GraphiteConfig {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(Self {
+            mode: Mode::Tcp(TcpConfig {
+                address: SocketListenAddr::SocketAddr(SocketAddr::V4(SocketAddrV4::new(
+                    Ipv4Addr::new(0, 0, 0, 0),
+                    2003,
+                ))),
+                protocol: Protocol::Plaintext,
+                keepalive: None,
+                tls: None,
+                shutdown_timeout_secs: default_shutdown_timeout_secs(),
+                receive_buffer_bytes: None,
+            }),
+            templates: Vec::new(),
+            separator: default_separator(),
+        })
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "graphite")]
+impl SourceConfig for GraphiteConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let parser = Parser::new(&self.templates, self.separator.clone())?;
+        match &self.mode {
+            Mode::Udp(config) => Ok(Box::pin(graphite_udp(
+                config.clone(),
+                parser,
+                cx.shutdown,
+                cx.out,
+            ))),
+            Mode::Tcp(config) => {
+                let tls = MaybeTlsSettings::from_config(&config.tls, true)?;
+                match config.protocol {
+                    Protocol::Plaintext => GraphitePlaintextTcpSource { parser }.run(
+                        config.address,
+                        config.keepalive,
+                        config.shutdown_timeout_secs,
+                        tls,
+                        config.receive_buffer_bytes,
+                        cx.shutdown,
+                        cx.out,
+                    ),
+                    Protocol::Pickle => GraphitePickleTcpSource { parser }.run(
+                        config.address,
+                        config.keepalive,
+                        config.shutdown_timeout_secs,
+                        tls,
+                        config.receive_buffer_bytes,
+                        cx.shutdown,
+                        cx.out,
+                    ),
+                }
+            }
+        }
+    }
+
+    fn output_type(&self) -> config::DataType {
+        config::DataType::Metric
+    }
+
+    fn source_type(&self) -> &'static str {
+        "graphite"
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        match &self.mode {
+            Mode::Tcp(tcp) => vec![tcp.address.into()],
+            Mode::Udp(udp) => vec![Resource::udp(udp.address)],
+        }
+    }
+}
+
+fn parse_line(parser: &Parser, line: &str) -> Option<Event> {
+    if line.trim().is_empty() {
+        return None;
+    }
+    match parser.parse(line) {
+        Ok(metric) => {
+            emit!(GraphiteEventsReceived {
+                count: 1,
+                byte_size: line.len(),
+            });
+            Some(Event::Metric(metric))
+        }
+        Err(error) => {
+            emit!(GraphiteInvalidRecord { error, text: line });
+            None
+        }
+    }
+}
+
+async fn graphite_udp(
+    config: UdpConfig,
+    parser: Parser,
+    shutdown: ShutdownSignal,
+    mut out: Pipeline,
+) -> Result<(), ()> {
+    let socket = UdpSocket::bind(&config.address)
+        .map_err(|error| emit!(GraphiteSocketError::bind(error)))
+        .await?;
+
+    if let Some(receive_buffer_bytes) = config.receive_buffer_bytes {
+        if let Err(error) = udp::set_receive_buffer_size(&socket, receive_buffer_bytes) {
+            warn!(message = "Failed configuring receive buffer size on UDP socket.", %error);
+        }
+    }
+
+    info!(
+        message = "Listening.",
+        addr = %config.address,
+        r#type = "udp"
+    );
+
+    let mut stream = UdpFramed::new(socket, BytesCodec::new()).take_until(shutdown);
+    while let Some(frame) = stream.next().await {
+        match frame {
+            Ok((bytes, _sock)) => {
+                let packet = String::from_utf8_lossy(bytes.as_ref());
+                let metrics = packet
+                    .lines()
+                    .filter_map(|line| parse_line(&parser, line))
+                    .map(Ok);
+
+                // Need `boxed` to resolve a lifetime issue
+                // https://github.com/rust-lang/rust/issues/64552#issuecomment-669728225
+                let mut metrics = stream::iter(metrics).boxed();
+                if let Err(error) = out.send_all(&mut metrics).await {
+                    error!(message = "Error sending metric.", %error);
+                    break;
+                }
+            }
+            Err(error) => {
+                emit!(GraphiteSocketError::read(error));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone)]
+struct GraphitePlaintextTcpSource {
+    parser: Parser,
+}
+
+impl TcpSource for GraphitePlaintextTcpSource {
+    type Error = std::io::Error;
+    type Decoder = BytesDelimitedCodec;
+
+    fn decoder(&self) -> Self::Decoder {
+        BytesDelimitedCodec::new(b'\n')
+    }
+
+    fn build_event(&self, line: Bytes, _host: Bytes) -> Option<Event> {
+        let line = String::from_utf8_lossy(line.as_ref());
+        parse_line(&self.parser, &line)
+    }
+}
+
+#[derive(Clone)]
+struct GraphitePickleTcpSource {
+    parser: Parser,
+}
+
+impl TcpSource for GraphitePickleTcpSource {
+    type Error = std::io::Error;
+    type Decoder = PickleDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        PickleDecoder::new()
+    }
+
+    fn build_event(&self, metric: PickledMetric, _host: Bytes) -> Option<Event> {
+        let PickledMetric(series, (timestamp, value)) = metric;
+        match self.parser.build_metric(&series, value, Some(timestamp)) {
+            Ok(metric) => Some(Event::Metric(metric)),
+            Err(error) => {
+                emit!(GraphiteInvalidRecord {
+                    error,
+                    text: &series
+                });
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        event::metric::{Metric, MetricKind, MetricValue},
+        test_util::{collect_n, next_addr, wait_for_tcp},
+    };
+    use chrono::{TimeZone, Utc};
+    use futures::channel::mpsc;
+    use shared::assert_event_data_eq;
+    use tokio::io::AsyncWriteExt;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<GraphiteConfig>();
+    }
+
+    fn tcp_config(address: SocketAddr, protocol: Protocol) -> GraphiteConfig {
+        GraphiteConfig {
+            mode: Mode::Tcp(TcpConfig {
+                address: address.into(),
+                protocol,
+                keepalive: None,
+                tls: None,
+                shutdown_timeout_secs: default_shutdown_timeout_secs(),
+                receive_buffer_bytes: None,
+            }),
+            templates: vec!["servers.* .host.name*".into()],
+            separator: default_separator(),
+        }
+    }
+
+    async fn source(config: GraphiteConfig) -> mpsc::Receiver<Event> {
+        let (sender, recv) = Pipeline::new_test();
+        tokio::spawn(async move {
+            config
+                .build(SourceContext::new_test(sender))
+                .await
+                .unwrap()
+                .await
+                .unwrap()
+        });
+        recv
+    }
+
+    fn expected() -> Vec<Metric> {
+        vec![
+            Metric::new(
+                "cpu.load",
+                MetricKind::Absolute,
+                MetricValue::Gauge { value: 0.5 },
+            )
+            .with_tags(Some(
+                vec![("host".to_owned(), "web-1".to_owned())]
+                    .into_iter()
+                    .collect(),
+            ))
+            .with_timestamp(Some(Utc.timestamp(1628000000, 0))),
+            Metric::new(
+                "disk.used",
+                MetricKind::Absolute,
+                MetricValue::Gauge { value: 9.0 },
+            )
+            .with_timestamp(Some(Utc.timestamp(1628000001, 0))),
+        ]
+    }
+
+    fn assert_metrics(events: Vec<Event>) {
+        let metrics = events
+            .into_iter()
+            .map(|event| event.into_metric())
+            .collect::<Vec<_>>();
+        assert_event_data_eq!(metrics, expected());
+    }
+
+    #[tokio::test]
+    async fn receives_plaintext_over_tcp() {
+        let address = next_addr();
+        let out = source(tcp_config(address, Protocol::Plaintext)).await;
+        wait_for_tcp(address).await;
+
+        let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(
+                b"servers.web-1.cpu.load 0.5 1628000000\nnot a metric\ndisk.used 9 1628000001\n",
+            )
+            .await
+            .unwrap();
+
+        assert_metrics(collect_n(out, 2).await);
+    }
+
+    #[tokio::test]
+    async fn receives_pickle_over_tcp() {
+        let address = next_addr();
+        let out = source(tcp_config(address, Protocol::Pickle)).await;
+        wait_for_tcp(address).await;
+
+        // pickle.dumps([("servers.web-1.cpu.load", (1628000000, 0.5)), ("disk.used", (1628000001, 9))], 2)
+        let pickle = b"\x80\x02]q\x00(X\x16\x00\x00\x00servers.web-1.cpu.loadq\x01J\x00O\taG?\xe0\x00\x00\x00\x00\x00\x00\x86q\x02\x86q\x03X\t\x00\x00\x00disk.usedq\x04J\x01O\taK\t\x86q\x05\x86q\x06e.";
+        let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+        stream.write_u32(pickle.len() as u32).await.unwrap();
+        stream.write_all(pickle).await.unwrap();
+
+        assert_metrics(collect_n(out, 2).await);
+    }
+
+    #[tokio::test]
+    async fn receives_plaintext_over_udp() {
+        let address = next_addr();
+        let mut config = tcp_config(address, Protocol::Plaintext);
+        config.mode = Mode::Udp(UdpConfig {
+            address,
+            receive_buffer_bytes: None,
+        });
+        let out = source(config).await;
+
+        let socket = UdpSocket::bind(next_addr()).await.unwrap();
+        // The source may not be listening yet, so keep sending until it is.
+        let send = async move {
+            loop {
+                socket
+                    .send_to(
+                        b"servers.web-1.cpu.load 0.5 1628000000\ndisk.used 9 1628000001\n",
+                        address,
+                    )
+                    .await
+                    .unwrap();
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+        };
+        let events = tokio::select! {
+            events = collect_n(out, 2) => events,
+            _ = send => unreachable!(),
+        };
+
+        assert_metrics(events);
+    }
+}
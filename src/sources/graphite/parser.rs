@@ -0,0 +1,367 @@
+use crate::event::metric::{Metric, MetricKind, MetricTags, MetricValue};
+use chrono::{DateTime, TimeZone, Utc};
+use snafu::{ResultExt, Snafu};
+use std::{collections::BTreeMap, num::ParseFloatError};
+
+#[derive(Debug, PartialEq, Snafu)]
+pub enum ParseError {
+    #[snafu(display("Line should be a path, a value and an optional timestamp"))]
+    Malformed,
+    #[snafu(display("Metric path is empty"))]
+    EmptyPath,
+    #[snafu(display("Invalid tag {:?}, should be name=value", tag))]
+    InvalidTag { tag: String },
+    #[snafu(display("Invalid value: {}", source))]
+    InvalidValue { source: ParseFloatError },
+    #[snafu(display("Invalid timestamp: {}", source))]
+    InvalidTimestamp { source: ParseFloatError },
+    #[snafu(display("Timestamp {} is out of range", timestamp))]
+    TimestampOutOfRange { timestamp: f64 },
+}
+
+shared::impl_event_data_eq!(ParseError);
+
+#[derive(Debug, Snafu)]
+pub enum TemplateError {
+    #[snafu(display(
+        "Template rule {:?} should be an optional filter, a template and optional tags",
+        rule
+    ))]
+    InvalidRule { rule: String },
+    #[snafu(display("Template {:?} has parts after `name*`", template))]
+    WildcardNotLast { template: String },
+    #[snafu(display(
+        "Invalid tag {:?} in template rule {:?}, should be name=value",
+        tag,
+        rule
+    ))]
+    InvalidDefaultTag { tag: String, rule: String },
+}
+
+/// What a segment of a metric path becomes.
+#[derive(Clone, Debug, PartialEq)]
+enum TemplatePart {
+    Skip,
+    Namespace,
+    Name,
+    /// This segment and all the following ones are part of the name.
+    NameRest,
+    Tag(String),
+}
+
+/// Splits the metric paths matching `filter` into a namespace, a name and
+/// tags.
+#[derive(Clone, Debug)]
+struct TemplateRule {
+    filter: Option<Vec<String>>,
+    parts: Vec<TemplatePart>,
+    tags: MetricTags,
+}
+
+impl TemplateRule {
+    /// Parses `[filter] template [name=value,...]`.
+    fn parse(rule: &str) -> Result<Self, TemplateError> {
+        let fields = rule.split_whitespace().collect::<Vec<_>>();
+        let (filter, template, tags) = match fields.as_slice() {
+            [template] => (None, *template, None),
+            [template, tags] if tags.contains('=') => (None, *template, Some(*tags)),
+            [filter, template] => (Some(*filter), *template, None),
+            [filter, template, tags] => (Some(*filter), *template, Some(*tags)),
+            _ => return InvalidRule { rule }.fail(),
+        };
+
+        let parts = template
+            .split('.')
+            .map(|part| match part {
+                "" => TemplatePart::Skip,
+                "namespace" => TemplatePart::Namespace,
+                "name" => TemplatePart::Name,
+                "name*" => TemplatePart::NameRest,
+                tag => TemplatePart::Tag(tag.to_owned()),
+            })
+            .collect::<Vec<_>>();
+        let rest = parts
+            .iter()
+            .position(|part| *part == TemplatePart::NameRest);
+        if matches!(rest, Some(position) if position + 1 != parts.len()) {
+            return WildcardNotLast { template }.fail();
+        }
+
+        let tags = tags
+            .map(|tags| {
+                tags.split(',')
+                    .map(|tag| match tag.split_once('=') {
+                        Some((name, value)) if !name.is_empty() => {
+                            Ok((name.to_owned(), value.to_owned()))
+                        }
+                        _ => InvalidDefaultTag { tag, rule }.fail(),
+                    })
+                    .collect::<Result<MetricTags, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(Self {
+            filter: filter.map(|filter| filter.split('.').map(Into::into).collect()),
+            parts,
+            tags,
+        })
+    }
+
+    fn matches(&self, segments: &[&str]) -> bool {
+        match &self.filter {
+            None => true,
+            Some(filter) => {
+                filter.len() <= segments.len()
+                    && filter
+                        .iter()
+                        .zip(segments)
+                        .all(|(pattern, segment)| pattern == "*" || pattern == segment)
+            }
+        }
+    }
+
+    fn apply(
+        &self,
+        segments: &[&str],
+        separator: &str,
+    ) -> (Option<String>, Option<String>, MetricTags) {
+        let mut namespace = Vec::new();
+        let mut name = Vec::new();
+        let mut tags = BTreeMap::<_, Vec<_>>::new();
+        for (index, (part, segment)) in self.parts.iter().zip(segments).enumerate() {
+            match part {
+                TemplatePart::Skip => (),
+                TemplatePart::Namespace => namespace.push(*segment),
+                TemplatePart::Name => name.push(*segment),
+                TemplatePart::NameRest => name.extend(&segments[index..]),
+                TemplatePart::Tag(tag) => tags.entry(tag.clone()).or_default().push(*segment),
+            }
+        }
+
+        let join = |parts: Vec<&str>| Some(parts.join(separator)).filter(|s| !s.is_empty());
+        let mut all_tags = self.tags.clone();
+        all_tags.extend(
+            tags.into_iter()
+                .map(|(tag, values)| (tag, values.join(separator))),
+        );
+        (join(namespace), join(name), all_tags)
+    }
+}
+
+/// Parses the Carbon plaintext protocol, turning the paths into metric names
+/// and tags according to the template rules.
+#[derive(Clone, Debug)]
+pub struct Parser {
+    templates: Vec<TemplateRule>,
+    separator: String,
+}
+
+impl Parser {
+    pub fn new(templates: &[String], separator: impl Into<String>) -> Result<Self, TemplateError> {
+        Ok(Self {
+            templates: templates
+                .iter()
+                .map(|rule| TemplateRule::parse(rule))
+                .collect::<Result<_, _>>()?,
+            separator: separator.into(),
+        })
+    }
+
+    /// Parses a `path value [timestamp]` line.
+    pub fn parse(&self, line: &str) -> Result<Metric, ParseError> {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        let (series, value, timestamp) = match fields.as_slice() {
+            [series, value] => (*series, *value, None),
+            [series, value, timestamp] => (*series, *value, Some(*timestamp)),
+            _ => return Malformed.fail(),
+        };
+        let value = value.parse::<f64>().context(InvalidValue)?;
+        let timestamp = timestamp
+            .map(|timestamp| timestamp.parse::<f64>().context(InvalidTimestamp))
+            .transpose()?;
+        self.build_metric(series, value, timestamp)
+    }
+
+    /// Builds the metric of a series, which is either a plain path or a
+    /// tagged one like `path;name=value`. Negative timestamps stand for the
+    /// current time.
+    pub fn build_metric(
+        &self,
+        series: &str,
+        value: f64,
+        timestamp: Option<f64>,
+    ) -> Result<Metric, ParseError> {
+        let mut parts = series.split(';');
+        let path = parts.next().unwrap_or_default();
+        if path.is_empty() {
+            return EmptyPath.fail();
+        }
+        let series_tags = parts
+            .map(|tag| match tag.split_once('=') {
+                Some((name, value)) if !name.is_empty() && !value.is_empty() => {
+                    Ok((name.to_owned(), value.to_owned()))
+                }
+                _ => InvalidTag { tag }.fail(),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let segments = path.split('.').collect::<Vec<_>>();
+        let (namespace, name, mut tags) = self
+            .templates
+            .iter()
+            .find(|template| template.matches(&segments))
+            .map(|template| template.apply(&segments, &self.separator))
+            .unwrap_or_default();
+        tags.extend(series_tags);
+
+        let timestamp = timestamp
+            .filter(|timestamp| *timestamp >= 0.0)
+            .map(parse_timestamp)
+            .transpose()?;
+
+        Ok(Metric::new(
+            name.unwrap_or_else(|| path.to_owned()),
+            MetricKind::Absolute,
+            MetricValue::Gauge { value },
+        )
+        .with_namespace(namespace)
+        .with_tags(Some(tags).filter(|tags| !tags.is_empty()))
+        .with_timestamp(timestamp))
+    }
+}
+
+fn parse_timestamp(timestamp: f64) -> Result<DateTime<Utc>, ParseError> {
+    if !timestamp.is_finite() || timestamp > i64::MAX as f64 {
+        return TimestampOutOfRange { timestamp }.fail();
+    }
+    let nanos = (timestamp.fract() * 1e9) as u32;
+    Utc.timestamp_opt(timestamp.trunc() as i64, nanos)
+        .single()
+        .ok_or(ParseError::TimestampOutOfRange { timestamp })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::assert_event_data_eq;
+
+    fn parser(templates: &[&str]) -> Parser {
+        let templates = templates.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        Parser::new(&templates, ".").unwrap()
+    }
+
+    fn gauge(name: &str, value: f64) -> Metric {
+        Metric::new(name, MetricKind::Absolute, MetricValue::Gauge { value })
+    }
+
+    fn tags(tags: &[(&str, &str)]) -> Option<MetricTags> {
+        Some(
+            tags.iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn parses_plaintext() {
+        assert_event_data_eq!(
+            parser(&[]).parse("servers.web-1.cpu.load 0.5 1628000000"),
+            Ok(gauge("servers.web-1.cpu.load", 0.5)
+                .with_timestamp(Some(Utc.timestamp(1628000000, 0))))
+        );
+        assert_event_data_eq!(
+            parser(&[]).parse("servers.web-1.cpu.load 3 -1"),
+            Ok(gauge("servers.web-1.cpu.load", 3.0))
+        );
+        assert_event_data_eq!(
+            parser(&[]).parse("servers.web-1.cpu.load 3"),
+            Ok(gauge("servers.web-1.cpu.load", 3.0))
+        );
+    }
+
+    #[test]
+    fn parses_tagged_series() {
+        assert_event_data_eq!(
+            parser(&[]).parse("disk.used;datacenter=dc1;server=web-1 9 1628000000.5"),
+            Ok(gauge("disk.used", 9.0)
+                .with_tags(tags(&[("datacenter", "dc1"), ("server", "web-1")]))
+                .with_timestamp(Some(Utc.timestamp(1628000000, 500_000_000))))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        let parser = parser(&[]);
+        assert_eq!(parser.parse("cpu.load"), Err(ParseError::Malformed));
+        assert_eq!(parser.parse("cpu.load 1 2 3"), Err(ParseError::Malformed));
+        assert!(matches!(
+            parser.parse("cpu.load one"),
+            Err(ParseError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parser.parse("cpu.load 1 now"),
+            Err(ParseError::InvalidTimestamp { .. })
+        ));
+        assert_eq!(
+            parser.parse("cpu.load;dc 1"),
+            Err(ParseError::InvalidTag { tag: "dc".into() })
+        );
+        assert_eq!(parser.parse(";dc=1 1"), Err(ParseError::EmptyPath));
+    }
+
+    #[test]
+    fn applies_templates() {
+        let parser = parser(&[
+            "servers.* .host.name.name region=us-east",
+            "stats.* namespace..name*",
+            "host.name",
+        ]);
+        assert_event_data_eq!(
+            parser.parse("servers.web-1.cpu.load 1"),
+            Ok(gauge("cpu.load", 1.0).with_tags(tags(&[("host", "web-1"), ("region", "us-east")])))
+        );
+        assert_event_data_eq!(
+            parser.parse("stats.counters.http.requests.count 1"),
+            Ok(gauge("http.requests.count", 1.0).with_namespace(Some("stats")))
+        );
+        assert_event_data_eq!(
+            parser.parse("web-1.uptime.seconds;dc=dc1 1"),
+            Ok(gauge("uptime", 1.0).with_tags(tags(&[("dc", "dc1"), ("host", "web-1")])))
+        );
+    }
+
+    #[test]
+    fn joins_repeated_parts_with_separator() {
+        let templates = vec!["name.dc.dc.name".to_string()];
+        let parser = Parser::new(&templates, "_").unwrap();
+        assert_event_data_eq!(
+            parser.parse("cpu.us.east.load 1"),
+            Ok(gauge("cpu_load", 1.0).with_tags(tags(&[("dc", "us_east")])))
+        );
+    }
+
+    #[test]
+    fn falls_back_to_path() {
+        let parser = parser(&["servers.* .host.name"]);
+        assert_event_data_eq!(
+            parser.parse("databases.db-1.connections 1"),
+            Ok(gauge("databases.db-1.connections", 1.0))
+        );
+        // A template without name parts leaves the path as the name.
+        let parser = self::parser(&["host"]);
+        assert_event_data_eq!(
+            parser.parse("web-1.uptime 1"),
+            Ok(gauge("web-1.uptime", 1.0).with_tags(tags(&[("host", "web-1")])))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        let invalid = |rule: &str| Parser::new(&[rule.to_string()], ".").is_err();
+        assert!(invalid("name*.host"));
+        assert!(invalid("servers.* host.name region"));
+        assert!(invalid("a b c d"));
+        assert!(invalid(""));
+    }
+}
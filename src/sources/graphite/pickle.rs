@@ -0,0 +1,96 @@
+use crate::internal_events::{GraphiteEventsReceived, GraphitePickleDecodeError};
+use bytes::BytesMut;
+use serde::Deserialize;
+use std::{collections::VecDeque, io};
+use tokio_util::codec::{Decoder, LengthDelimitedCodec};
+
+/// A metric of the pickle protocol, sent by Carbon relays and aggregators as
+/// `(path, (timestamp, value))`.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct PickledMetric(pub String, pub (f64, f64));
+
+/// Decodes the frames of the pickle protocol, each being a pickled list of
+/// metrics prefixed with its length as a 4 bytes big endian integer, into the
+/// metrics they contain.
+pub struct PickleDecoder {
+    frames: LengthDelimitedCodec,
+    metrics: VecDeque<PickledMetric>,
+}
+
+impl PickleDecoder {
+    pub fn new() -> Self {
+        Self {
+            frames: LengthDelimitedCodec::new(),
+            metrics: VecDeque::new(),
+        }
+    }
+}
+
+impl Default for PickleDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder for PickleDecoder {
+    type Item = PickledMetric;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            if let Some(metric) = self.metrics.pop_front() {
+                return Ok(Some(metric));
+            }
+            let frame = match self.frames.decode(src)? {
+                Some(frame) => frame,
+                None => return Ok(None),
+            };
+            // A frame which can't be unpickled is skipped as the next one
+            // still starts at its length prefix.
+            match serde_pickle::from_slice::<Vec<PickledMetric>>(&frame) {
+                Ok(metrics) => {
+                    emit!(GraphiteEventsReceived {
+                        count: metrics.len(),
+                        byte_size: frame.len(),
+                    });
+                    self.metrics.extend(metrics);
+                }
+                Err(error) => emit!(GraphitePickleDecodeError { error }),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BufMut;
+
+    fn frame(pickle: &[u8]) -> BytesMut {
+        let mut frame = BytesMut::new();
+        frame.put_u32(pickle.len() as u32);
+        frame.put_slice(pickle);
+        frame
+    }
+
+    #[test]
+    fn decodes_pickled_metrics() {
+        // pickle.dumps([("cpu.load", (1628000000, 0.5)), ("disk.used", (1628000001, 9))], 2)
+        let pickle = b"\x80\x02]q\x00(X\x08\x00\x00\x00cpu.loadq\x01J\x00O\taG?\xe0\x00\x00\x00\x00\x00\x00\x86q\x02\x86q\x03X\t\x00\x00\x00disk.usedq\x04J\x01O\taK\t\x86q\x05\x86q\x06e.";
+        let mut src = frame(pickle);
+        src.extend_from_slice(&frame(b"not a pickle"));
+        src.extend_from_slice(&frame(pickle)[..10]);
+
+        let mut decoder = PickleDecoder::new();
+        assert_eq!(
+            decoder.decode(&mut src).unwrap(),
+            Some(PickledMetric("cpu.load".into(), (1628000000.0, 0.5)))
+        );
+        assert_eq!(
+            decoder.decode(&mut src).unwrap(),
+            Some(PickledMetric("disk.used".into(), (1628000001.0, 9.0)))
+        );
+        // The invalid frame is skipped, and the partial one waits for more.
+        assert_eq!(decoder.decode(&mut src).unwrap(), None);
+    }
+}
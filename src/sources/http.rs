@@ -5,7 +5,8 @@ use crate::{
     },
     event::{Event, Value},
     sources::util::{
-        add_query_parameters, decode_body, Encoding, ErrorMessage, HttpSource, HttpSourceAuthConfig,
+        add_query_parameters, decode_body, Encoding, ErrorMessage, HttpReply, HttpSource,
+        HttpSourceAuthConfig, HttpSourceRoute, PathPattern,
     },
     tls::TlsConfig,
 };
@@ -13,7 +14,7 @@ use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, net::SocketAddr};
 
-use warp::http::{HeaderMap, HeaderValue};
+use warp::http::{HeaderMap, HeaderValue, StatusCode};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct SimpleHttpConfig {
@@ -32,6 +33,42 @@ pub struct SimpleHttpConfig {
     path: String,
     #[serde(default = "default_path_key")]
     path_key: String,
+    #[serde(default)]
+    paths: Vec<PathConfig>,
+    #[serde(default = "default_output_key")]
+    output_key: String,
+    #[serde(default)]
+    health_checks: Vec<HealthCheckConfig>,
+}
+
+/// A path accepting events. When any are configured, they replace `path` and
+/// `strict_path`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PathConfig {
+    path: String,
+    #[serde(default = "crate::serde::default_true")]
+    strict: bool,
+    output: Option<String>,
+    #[serde(default = "default_response_code")]
+    response_code: u16,
+    #[serde(default)]
+    response_body: String,
+}
+
+/// A path answering `GET` and `HEAD` requests with a fixed response.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct HealthCheckConfig {
+    path: String,
+    #[serde(default = "default_response_code")]
+    response_code: u16,
+    #[serde(default)]
+    response_body: String,
+}
+
+fn build_reply(code: u16, body: &str) -> crate::Result<HttpReply> {
+    Ok(HttpReply::new(StatusCode::from_u16(code)?, body.to_owned()))
 }
 
 inventory::submit! {
@@ -50,6 +87,9 @@ impl GenerateConfig for SimpleHttpConfig {
             path_key: "path".to_string(),
             path: "/".to_string(),
             strict_path: true,
+            paths: Vec::new(),
+            output_key: "output".to_string(),
+            health_checks: Vec::new(),
         })
         .unwrap()
     }
@@ -63,12 +103,22 @@ fn default_path_key() -> String {
     "path".to_string()
 }
 
+fn default_output_key() -> String {
+    "output".to_string()
+}
+
+const fn default_response_code() -> u16 {
+    200
+}
+
 #[derive(Clone)]
 struct SimpleHttpSource {
     encoding: Encoding,
     headers: Vec<String>,
     query_parameters: Vec<String>,
     path_key: String,
+    paths: Vec<(PathPattern, Option<String>)>,
+    output_key: String,
 }
 
 impl HttpSource for SimpleHttpSource {
@@ -83,6 +133,7 @@ impl HttpSource for SimpleHttpSource {
             .map(|events| add_headers(events, &self.headers, header_map))
             .map(|events| add_query_parameters(events, &self.query_parameters, query_parameters))
             .map(|events| add_path(events, self.path_key.as_str(), request_path))
+            .map(|events| self.add_path_match(events, request_path))
             .map(|mut events| {
                 // Add source type
                 let key = log_schema().source_type_key();
@@ -94,20 +145,83 @@ impl HttpSource for SimpleHttpSource {
     }
 }
 
+impl SimpleHttpSource {
+    /// Adds the parameters captured by the first path matching `request_path`
+    /// and the name of its output.
+    fn add_path_match(&self, mut events: Vec<Event>, request_path: &str) -> Vec<Event> {
+        let matched = self.paths.iter().find_map(|(pattern, output)| {
+            pattern
+                .matches(request_path)
+                .map(|params| (params, output.as_ref()))
+        });
+
+        if let Some((params, output)) = matched {
+            for event in events.iter_mut() {
+                let log = event.as_mut_log();
+                for (name, value) in &params {
+                    log.insert(name.as_str(), Value::from(value.clone()));
+                }
+                if let Some(output) = output {
+                    log.insert(self.output_key.as_str(), Value::from(output.clone()));
+                }
+            }
+        }
+
+        events
+    }
+}
+
 #[async_trait::async_trait]
 #[typetag::serde(name = "http")]
 impl SourceConfig for SimpleHttpConfig {
     async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let routes = if self.paths.is_empty() {
+            vec![HttpSourceRoute::new(PathPattern::new(
+                &self.path,
+                self.strict_path,
+            ))]
+        } else {
+            self.paths
+                .iter()
+                .map(|path| {
+                    build_reply(path.response_code, &path.response_body).map(|reply| {
+                        HttpSourceRoute::new(PathPattern::new(&path.path, path.strict))
+                            .with_reply(reply)
+                    })
+                })
+                .collect::<crate::Result<_>>()?
+        };
+        let static_routes = self
+            .health_checks
+            .iter()
+            .map(|check| {
+                build_reply(check.response_code, &check.response_body).map(|reply| {
+                    HttpSourceRoute::new(PathPattern::new(&check.path, true)).with_reply(reply)
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+
         let source = SimpleHttpSource {
             encoding: self.encoding,
             headers: self.headers.clone(),
             query_parameters: self.query_parameters.clone(),
             path_key: self.path_key.clone(),
+            paths: self
+                .paths
+                .iter()
+                .map(|path| {
+                    (
+                        PathPattern::new(&path.path, path.strict),
+                        path.output.clone(),
+                    )
+                })
+                .collect(),
+            output_key: self.output_key.clone(),
         };
-        source.run(
+        source.run_routes(
             self.address,
-            self.path.as_str(),
-            self.strict_path,
+            routes,
+            static_routes,
             &self.tls,
             &self.auth,
             cx,
@@ -158,7 +272,7 @@ fn add_headers(
 
 #[cfg(test)]
 mod tests {
-    use super::{Encoding, SimpleHttpConfig};
+    use super::{Encoding, HealthCheckConfig, PathConfig, SimpleHttpConfig};
     use crate::{
         config::{log_schema, SourceConfig, SourceContext},
         event::{Event, EventStatus, Value},
@@ -208,6 +322,9 @@ mod tests {
                 strict_path,
                 path_key,
                 path,
+                paths: vec![],
+                output_key: "output".to_string(),
+                health_checks: vec![],
             }
             .build(context)
             .await
@@ -276,6 +393,48 @@ mod tests {
             .as_u16()
     }
 
+    async fn source_with_paths(
+        paths: Vec<PathConfig>,
+        health_checks: Vec<HealthCheckConfig>,
+    ) -> (impl Stream<Item = Event>, SocketAddr) {
+        let (sender, recv) = Pipeline::new_test_finalize(EventStatus::Delivered);
+        let address = next_addr();
+        let context = SourceContext::new_test(sender);
+        tokio::spawn(async move {
+            SimpleHttpConfig {
+                address,
+                encoding: Encoding::Ndjson,
+                headers: vec![],
+                query_parameters: vec![],
+                tls: None,
+                auth: None,
+                strict_path: true,
+                path_key: "http_path".to_string(),
+                path: "/".to_string(),
+                paths,
+                output_key: "output".to_string(),
+                health_checks,
+            }
+            .build(context)
+            .await
+            .unwrap()
+            .await
+            .unwrap();
+        });
+        wait_for_tcp(address).await;
+        (recv, address)
+    }
+
+    fn path_config(path: &str, output: Option<&str>) -> PathConfig {
+        PathConfig {
+            path: path.to_string(),
+            strict: true,
+            output: output.map(Into::into),
+            response_code: 200,
+            response_body: String::new(),
+        }
+    }
+
     async fn spawn_ok_collect_n(
         send: impl std::future::Future<Output = u16> + Send + 'static,
         rx: impl Stream<Item = Event> + Unpin,
@@ -776,6 +935,139 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn http_zstd() {
+        trace_init();
+
+        let body = zstd::stream::encode_all("test body".as_bytes(), 0).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Encoding", "zstd".parse().unwrap());
+
+        let (rx, addr) = source(
+            Encoding::default(),
+            vec![],
+            vec![],
+            "http_path",
+            "/",
+            true,
+            EventStatus::Delivered,
+            true,
+        )
+        .await;
+
+        let mut events = spawn_ok_collect_n(send_bytes(addr, body, headers), rx, 1).await;
+
+        let event = events.remove(0);
+        let log = event.as_log();
+        assert_eq!(log[log_schema().message_key()], "test body".into());
+    }
+
+    #[tokio::test]
+    async fn http_multiple_paths() {
+        trace_init();
+        let (rx, addr) = source_with_paths(
+            vec![
+                path_config("/hooks/{service}/events", Some("hooks")),
+                path_config("/logs", None),
+            ],
+            vec![],
+        )
+        .await;
+
+        let mut events = spawn_collect_n(
+            async move {
+                assert_eq!(
+                    200,
+                    send_with_path(addr, "{\"key1\":\"value1\"}", "/hooks/github/events").await
+                );
+                assert_eq!(
+                    200,
+                    send_with_path(addr, "{\"key2\":\"value2\"}", "/logs").await
+                );
+                assert_eq!(
+                    404,
+                    send_with_path(addr, "{\"key3\":\"value3\"}", "/hooks/github").await
+                );
+            },
+            rx,
+            2,
+        )
+        .await;
+
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key1"], "value1".into());
+            assert_eq!(log["service"], "github".into());
+            assert_eq!(log["output"], "hooks".into());
+            assert_eq!(log["http_path"], "/hooks/github/events".into());
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key2"], "value2".into());
+            assert!(log.get("output").is_none());
+            assert_eq!(log["http_path"], "/logs".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_path_response() {
+        trace_init();
+        let mut path = path_config("/events", None);
+        path.response_code = 202;
+        path.response_body = "accepted".to_string();
+        let (rx, addr) = source_with_paths(vec![path], vec![]).await;
+
+        spawn_collect_n(
+            async move {
+                let response = reqwest::Client::new()
+                    .post(&format!("http://{}/events", addr))
+                    .body("{\"key1\":\"value1\"}")
+                    .send()
+                    .await
+                    .unwrap();
+                assert_eq!(202, response.status().as_u16());
+                assert_eq!("accepted", response.text().await.unwrap());
+            },
+            rx,
+            1,
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn http_health_check() {
+        trace_init();
+        let (_rx, addr) = source_with_paths(
+            vec![path_config("/events", None)],
+            vec![HealthCheckConfig {
+                path: "/healthz".to_string(),
+                response_code: 204,
+                response_body: String::new(),
+            }],
+        )
+        .await;
+
+        let client = reqwest::Client::new();
+        let status = client
+            .get(&format!("http://{}/healthz", addr))
+            .send()
+            .await
+            .unwrap()
+            .status();
+        assert_eq!(204, status.as_u16());
+
+        let status = client
+            .get(&format!("http://{}/other", addr))
+            .send()
+            .await
+            .unwrap()
+            .status();
+        assert_eq!(404, status.as_u16());
+    }
+
     #[tokio::test]
     async fn http_wrong_path() {
         trace_init();
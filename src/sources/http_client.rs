@@ -0,0 +1,434 @@
+use crate::{
+    config::{self, log_schema, GenerateConfig, SourceConfig, SourceContext, SourceDescription},
+    event::{Event, LogEvent},
+    http::{Auth, HttpClient, HttpMethod},
+    internal_events::{
+        HttpClientErrorResponse, HttpClientEventsReceived, HttpClientHttpError,
+        HttpClientParseError, HttpClientRequestCompleted, TemplateRenderingFailed,
+    },
+    shutdown::ShutdownSignal,
+    sources::util::{decode_body, Encoding},
+    template::Template,
+    tls::{TlsOptions, TlsSettings},
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::{future, stream, SinkExt, StreamExt};
+use http::{
+    header::{HeaderName, HeaderValue},
+    HeaderMap, Method, Request, Uri,
+};
+use hyper::Body;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
+use tokio_stream::wrappers::IntervalStream;
+use url::Url;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("Invalid endpoint {:?}: {}", endpoint, source))]
+    InvalidEndpoint {
+        endpoint: String,
+        source: url::ParseError,
+    },
+    #[snafu(display("{}: {}", source, name))]
+    InvalidHeaderName {
+        name: String,
+        source: http::header::InvalidHeaderName,
+    },
+    #[snafu(display("{}: {}", source, value))]
+    InvalidHeaderValue {
+        value: String,
+        source: http::header::InvalidHeaderValue,
+    },
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct HttpClientConfig {
+    endpoints: Vec<String>,
+    #[serde(default = "default_scrape_interval_secs")]
+    scrape_interval_secs: u64,
+    #[serde(default)]
+    method: HttpMethod,
+    #[serde(default)]
+    headers: IndexMap<String, String>,
+    #[serde(default)]
+    query: IndexMap<String, Template>,
+    #[serde(default)]
+    encoding: Encoding,
+    tls: Option<TlsOptions>,
+    auth: Option<Auth>,
+}
+
+pub const fn default_scrape_interval_secs() -> u64 {
+    15
+}
+
+inventory::submit! {
+    SourceDescription::new::<HttpClientConfig>("http_client")
+}
+
+impl GenerateConfig for HttpClientConfig {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(Self {
+            endpoints: vec!["http://localhost:8080/logs".to_string()],
+            scrape_interval_secs: default_scrape_interval_secs(),
+            method: HttpMethod::Get,
+            headers: IndexMap::new(),
+            query: IndexMap::new(),
+            encoding: Encoding::Json,
+            tls: None,
+            auth: None,
+        })
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "http_client")]
+impl SourceConfig for HttpClientConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let urls = self
+            .endpoints
+            .iter()
+            .map(|endpoint| Url::parse(endpoint).context(InvalidEndpoint { endpoint }))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::try_from(name).context(InvalidHeaderName { name })?;
+            let value = HeaderValue::try_from(value).context(InvalidHeaderValue { value })?;
+            headers.insert(name, value);
+        }
+
+        let tls = TlsSettings::from_options(&self.tls)?;
+        let client = HttpClient::new(tls, &cx.proxy)?;
+
+        let scraper = Scraper {
+            urls,
+            method: self.method.clone().into(),
+            headers,
+            query: self.query.clone(),
+            encoding: self.encoding,
+            auth: self.auth.clone(),
+            client,
+        };
+
+        Ok(Box::pin(scraper.run(
+            Duration::from_secs(self.scrape_interval_secs),
+            cx.shutdown,
+            cx.out,
+        )))
+    }
+
+    fn output_type(&self) -> config::DataType {
+        config::DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "http_client"
+    }
+}
+
+struct Scraper {
+    urls: Vec<Url>,
+    method: Method,
+    headers: HeaderMap,
+    query: IndexMap<String, Template>,
+    encoding: Encoding,
+    auth: Option<Auth>,
+    client: HttpClient,
+}
+
+impl Scraper {
+    async fn run(
+        self,
+        interval: Duration,
+        shutdown: ShutdownSignal,
+        out: Pipeline,
+    ) -> Result<(), ()> {
+        let mut out = out.sink_map_err(|error| error!(message = "Error sending event.", %error));
+        let mut ticks = IntervalStream::new(tokio::time::interval(interval)).take_until(shutdown);
+
+        while ticks.next().await.is_some() {
+            let now = Utc::now();
+            let events = future::join_all(self.urls.iter().map(|url| self.scrape(url, now))).await;
+            let mut events = stream::iter(events.into_iter().flatten()).map(Ok);
+            out.send_all(&mut events).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Builds the URI of a request, rendering the query parameters against
+    /// the time of the scrape.
+    fn build_uri(&self, url: &Url, now: DateTime<Utc>) -> Option<Uri> {
+        let mut url = url.clone();
+        if !self.query.is_empty() {
+            let mut event = LogEvent::default();
+            event.insert(log_schema().timestamp_key(), now);
+
+            let mut pairs = url.query_pairs_mut();
+            for (name, template) in &self.query {
+                match template.render_string(&event) {
+                    Ok(value) => {
+                        pairs.append_pair(name, &value);
+                    }
+                    Err(error) => emit!(TemplateRenderingFailed {
+                        error,
+                        field: Some(name.as_str()),
+                        drop_event: false,
+                    }),
+                }
+            }
+        }
+
+        url.as_str().parse::<Uri>().ok()
+    }
+
+    fn build_request(&self, uri: Uri) -> Request<Body> {
+        let mut request = Request::new(Body::empty());
+        *request.method_mut() = self.method.clone();
+        *request.uri_mut() = uri;
+        request.headers_mut().extend(self.headers.clone());
+        if let Some(auth) = &self.auth {
+            auth.apply(&mut request);
+        }
+        request
+    }
+
+    async fn scrape(&self, url: &Url, now: DateTime<Utc>) -> Vec<Event> {
+        let uri = match self.build_uri(url, now) {
+            Some(uri) => uri,
+            None => {
+                emit!(HttpClientHttpError {
+                    error: "Rendered endpoint is not a valid URI.".into(),
+                    url: url.as_str(),
+                });
+                return Vec::new();
+            }
+        };
+
+        let start = Instant::now();
+        let response = match self.client.send(self.build_request(uri)).await {
+            Ok(response) => response,
+            Err(error) => {
+                emit!(HttpClientHttpError {
+                    error: error.into(),
+                    url: url.as_str(),
+                });
+                return Vec::new();
+            }
+        };
+
+        let (parts, body) = response.into_parts();
+        let body = match hyper::body::to_bytes(body).await {
+            Ok(body) => body,
+            Err(error) => {
+                emit!(HttpClientHttpError {
+                    error: error.into(),
+                    url: url.as_str(),
+                });
+                return Vec::new();
+            }
+        };
+
+        if !parts.status.is_success() {
+            emit!(HttpClientErrorResponse {
+                code: parts.status,
+                url: url.as_str(),
+            });
+            return Vec::new();
+        }
+
+        emit!(HttpClientRequestCompleted {
+            start,
+            end: Instant::now()
+        });
+
+        let byte_size = body.len();
+        match decode_body(body, self.encoding) {
+            Ok(mut events) => {
+                for event in &mut events {
+                    event
+                        .as_mut_log()
+                        .try_insert(log_schema().source_type_key(), Bytes::from("http_client"));
+                }
+                emit!(HttpClientEventsReceived {
+                    byte_size,
+                    count: events.len(),
+                    url: url.as_str(),
+                });
+                events
+            }
+            Err(error) => {
+                emit!(HttpClientParseError {
+                    error: &error.to_string(),
+                    url: url.as_str(),
+                });
+                Vec::new()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        test_util::{collect_ready, next_addr, wait_for_tcp},
+        Error,
+    };
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Response, Server,
+    };
+    use pretty_assertions::assert_eq;
+    use tokio::time::sleep;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<HttpClientConfig>();
+    }
+
+    async fn run_source(config: HttpClientConfig) -> Vec<Event> {
+        let (tx, rx) = Pipeline::new_test();
+        let source = config.build(SourceContext::new_test(tx)).await.unwrap();
+        tokio::spawn(source);
+
+        sleep(Duration::from_secs(1)).await;
+
+        collect_ready(rx).await
+    }
+
+    fn config(endpoint: String, encoding: Encoding) -> HttpClientConfig {
+        HttpClientConfig {
+            endpoints: vec![endpoint],
+            scrape_interval_secs: 1,
+            method: HttpMethod::Get,
+            headers: IndexMap::new(),
+            query: IndexMap::new(),
+            encoding,
+            tls: None,
+            auth: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn scrapes_json_with_query_and_auth() {
+        let in_addr = next_addr();
+
+        let make_svc = make_service_fn(|_| async {
+            Ok::<_, Error>(service_fn(|request: Request<Body>| async move {
+                let authorized = request
+                    .headers()
+                    .get("authorization")
+                    .map(|value| value == "Bearer secret")
+                    .unwrap_or(false);
+                let body = format!(
+                    r#"[{{"query":"{}","authorized":{},"custom":"{}"}}]"#,
+                    request.uri().query().unwrap_or_default(),
+                    authorized,
+                    request
+                        .headers()
+                        .get("x-custom")
+                        .and_then(|value| value.to_str().ok())
+                        .unwrap_or_default(),
+                );
+                Ok::<_, Error>(Response::new(Body::from(body)))
+            }))
+        });
+
+        tokio::spawn(async move {
+            if let Err(error) = Server::bind(&in_addr).serve(make_svc).await {
+                error!(message = "Server error.", %error);
+            }
+        });
+        wait_for_tcp(in_addr).await;
+
+        let mut config = config(format!("http://{}/logs?static=1", in_addr), Encoding::Json);
+        config.auth = Some(Auth::Bearer {
+            token: "secret".to_string(),
+        });
+        config
+            .headers
+            .insert("X-Custom".to_string(), "value".to_string());
+        config
+            .query
+            .insert("since".to_string(), Template::try_from("%Y").unwrap());
+
+        let events = run_source(config).await;
+        assert!(!events.is_empty());
+
+        let log = events[0].as_log();
+        assert_eq!(
+            log["query"],
+            format!("static=1&since={}", Utc::now().format("%Y")).into()
+        );
+        assert_eq!(log["authorized"], true.into());
+        assert_eq!(log["custom"], "value".into());
+        assert_eq!(log[log_schema().source_type_key()], "http_client".into());
+    }
+
+    #[tokio::test]
+    async fn scrapes_text_lines() {
+        let in_addr = next_addr();
+
+        let make_svc = make_service_fn(|_| async {
+            Ok::<_, Error>(service_fn(|_| async {
+                Ok::<_, Error>(Response::new(Body::from("line 1\nline 2\n")))
+            }))
+        });
+
+        tokio::spawn(async move {
+            if let Err(error) = Server::bind(&in_addr).serve(make_svc).await {
+                error!(message = "Server error.", %error);
+            }
+        });
+        wait_for_tcp(in_addr).await;
+
+        let events = run_source(config(format!("http://{}/", in_addr), Encoding::Text)).await;
+        assert!(events.len() >= 2);
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "line 1".into()
+        );
+        assert_eq!(
+            events[1].as_log()[log_schema().message_key()],
+            "line 2".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_error_responses() {
+        let in_addr = next_addr();
+
+        let make_svc = make_service_fn(|_| async {
+            Ok::<_, Error>(service_fn(|_| async {
+                Ok::<_, Error>(
+                    Response::builder()
+                        .status(500)
+                        .body(Body::from(r#"{"error":"oops"}"#))
+                        .unwrap(),
+                )
+            }))
+        });
+
+        tokio::spawn(async move {
+            if let Err(error) = Server::bind(&in_addr).serve(make_svc).await {
+                error!(message = "Server error.", %error);
+            }
+        });
+        wait_for_tcp(in_addr).await;
+
+        let events = run_source(config(format!("http://{}/", in_addr), Encoding::Json)).await;
+        assert!(events.is_empty());
+    }
+}
@@ -1,11 +1,18 @@
 use super::util::finalizer::OrderedFinalizer;
 use crate::{
     config::{log_schema, DataType, SourceConfig, SourceContext, SourceDescription},
-    internal_events::{KafkaEventFailed, KafkaEventReceived, KafkaOffsetUpdateFailed},
-    kafka::{KafkaAuthConfig, KafkaStatisticsContext},
+    internal_events::{
+        KafkaAvroDecodingFailed, KafkaEventFailed, KafkaEventReceived, KafkaOffsetUpdateFailed,
+        KafkaPartitionPauseFailed,
+    },
+    kafka::{
+        schema_registry::{decode_wire_format, SchemaRegistry, SchemaRegistryConfig},
+        KafkaAuthConfig, KafkaStatisticsContext,
+    },
     shutdown::ShutdownSignal,
     Pipeline,
 };
+use avro_rs::types::Value as AvroValue;
 use bytes::Bytes;
 use chrono::{TimeZone, Utc};
 use futures::{FutureExt, SinkExt, StreamExt};
@@ -19,7 +26,7 @@ use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
 use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
-use vector_core::event::{BatchNotifier, LogEvent, Value};
+use vector_core::event::{BatchNotifier, BatchStatus, LogEvent, Value};
 
 #[derive(Debug, Snafu)]
 enum BuildError {
@@ -27,13 +34,26 @@ enum BuildError {
     KafkaCreateError { source: rdkafka::error::KafkaError },
     #[snafu(display("Could not subscribe to Kafka topics: {}", source))]
     KafkaSubscribeError { source: rdkafka::error::KafkaError },
+    #[snafu(display("Could not assign Kafka partitions: {}", source))]
+    KafkaAssignError { source: rdkafka::error::KafkaError },
+    #[snafu(display("Exactly one of `topics` and `assignments` must be set"))]
+    TopicsOrAssignments,
+    #[snafu(display(
+        "The `end_offset` of partition {} of topic {:?} must be greater than its `start_offset`",
+        partition,
+        topic
+    ))]
+    InvalidOffsetRange { topic: String, partition: i32 },
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct KafkaSourceConfig {
     bootstrap_servers: String,
+    #[serde(default)]
     topics: Vec<String>,
+    #[serde(default)]
+    assignments: Vec<PartitionAssignment>,
     group_id: String,
     #[serde(default = "default_auto_offset_reset")]
     auto_offset_reset: String,
@@ -56,10 +76,22 @@ pub struct KafkaSourceConfig {
     #[serde(default = "default_headers_key")]
     headers_key: String,
     librdkafka_options: Option<HashMap<String, String>>,
+    schema_registry: Option<SchemaRegistryConfig>,
     #[serde(flatten)]
     auth: KafkaAuthConfig,
 }
 
+/// A partition consumed from `start_offset`, or from the committed offset of
+/// the group if unset, up to `end_offset` exclusive, or indefinitely if unset.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct PartitionAssignment {
+    topic: String,
+    partition: i32,
+    start_offset: Option<i64>,
+    end_offset: Option<i64>,
+}
+
 fn default_session_timeout_ms() -> u64 {
     10000 // default in librdkafka
 }
@@ -111,6 +143,11 @@ impl_generate_config_from_default!(KafkaSourceConfig);
 impl SourceConfig for KafkaSourceConfig {
     async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
         let consumer = create_consumer(self)?;
+        let schema_registry = self
+            .schema_registry
+            .as_ref()
+            .map(|config| SchemaRegistry::new(config, &cx.proxy).map(Arc::new))
+            .transpose()?;
 
         Ok(Box::pin(kafka_source(
             consumer,
@@ -119,6 +156,8 @@ impl SourceConfig for KafkaSourceConfig {
             self.partition_key.clone(),
             self.offset_key.clone(),
             self.headers_key.clone(),
+            schema_registry,
+            EndOffsets::new(&self.assignments),
             cx.shutdown,
             cx.out,
             cx.acknowledgements,
@@ -141,6 +180,8 @@ async fn kafka_source(
     partition_key: String,
     offset_key: String,
     headers_key: String,
+    schema_registry: Option<Arc<SchemaRegistry>>,
+    mut end_offsets: EndOffsets,
     shutdown: ShutdownSignal,
     mut out: Pipeline,
     acknowledgements: bool,
@@ -161,16 +202,45 @@ async fn kafka_source(
                     byte_size: msg.payload_len()
                 });
 
+                if end_offsets.is_past_end(&msg) {
+                    end_offsets.finish(&consumer, &msg);
+                    if end_offsets.is_done() {
+                        break;
+                    }
+                    continue;
+                }
+                if end_offsets.is_last(&msg) {
+                    end_offsets.finish(&consumer, &msg);
+                }
+
                 let payload = match msg.payload() {
-                    None => continue, // skip messages with empty payload
+                    // skip messages with empty payload
+                    None if end_offsets.is_done() => break,
+                    None => continue,
                     Some(payload) => payload,
                 };
                 let mut log = LogEvent::default();
 
-                log.insert(
-                    log_schema().message_key(),
-                    Value::from(Bytes::from(payload.to_owned())),
-                );
+                let decoded = match &schema_registry {
+                    Some(registry) => decode_avro(registry, payload)
+                        .await
+                        .map_err(|error| emit!(KafkaAvroDecodingFailed { error }))
+                        .ok(),
+                    None => None,
+                };
+                match decoded {
+                    Some(fields) => {
+                        for (key, value) in fields {
+                            log.insert_flat(key, value);
+                        }
+                    }
+                    None => {
+                        log.insert(
+                            log_schema().message_key(),
+                            Value::from(Bytes::from(payload.to_owned())),
+                        );
+                    }
+                }
 
                 // Extract timestamp from kafka message
                 let timestamp = msg
@@ -228,6 +298,10 @@ async fn kafka_source(
                         }
                     },
                 }
+
+                if end_offsets.is_done() {
+                    break;
+                }
             }
         }
     }
@@ -235,6 +309,128 @@ async fn kafka_source(
     Ok(())
 }
 
+/// Decodes a message in the Confluent wire format into the fields of its Avro
+/// record, fetching its schema from the registry.
+async fn decode_avro(
+    registry: &SchemaRegistry,
+    payload: &[u8],
+) -> crate::Result<Vec<(String, Value)>> {
+    let (id, mut datum) =
+        decode_wire_format(payload).ok_or("message is not in the Confluent wire format")?;
+    let schema = registry.schema(id).await?;
+    Ok(match avro_rs::from_avro_datum(&schema, &mut datum, None)? {
+        AvroValue::Record(fields) => fields
+            .into_iter()
+            .map(|(key, value)| (key, avro_to_value(value)))
+            .collect(),
+        value => vec![(log_schema().message_key().to_owned(), avro_to_value(value))],
+    })
+}
+
+fn avro_to_value(value: AvroValue) -> Value {
+    match value {
+        AvroValue::Null => Value::Null,
+        AvroValue::Boolean(value) => value.into(),
+        AvroValue::Int(value) | AvroValue::Date(value) | AvroValue::TimeMillis(value) => {
+            value.into()
+        }
+        AvroValue::Long(value) | AvroValue::TimeMicros(value) => value.into(),
+        AvroValue::Float(value) => value.into(),
+        AvroValue::Double(value) => value.into(),
+        AvroValue::Bytes(bytes) | AvroValue::Fixed(_, bytes) => Bytes::from(bytes).into(),
+        AvroValue::String(value) | AvroValue::Enum(_, value) => value.into(),
+        AvroValue::Union(value) => avro_to_value(*value),
+        AvroValue::Array(values) => values.into_iter().map(avro_to_value).collect(),
+        AvroValue::Map(values) => values
+            .into_iter()
+            .map(|(key, value)| (key, avro_to_value(value)))
+            .collect(),
+        AvroValue::Record(fields) => fields
+            .into_iter()
+            .map(|(key, value)| (key, avro_to_value(value)))
+            .collect(),
+        AvroValue::TimestampMillis(millis) => Utc
+            .timestamp_millis_opt(millis)
+            .single()
+            .map(Value::from)
+            .unwrap_or_else(|| millis.into()),
+        AvroValue::TimestampMicros(micros) => Utc
+            .timestamp_opt(
+                micros.div_euclid(1_000_000),
+                (micros.rem_euclid(1_000_000) * 1_000) as u32,
+            )
+            .single()
+            .map(Value::from)
+            .unwrap_or_else(|| micros.into()),
+        AvroValue::Uuid(uuid) => uuid.to_string().into(),
+        // Decimals and durations have no equivalent, so they're kept in their
+        // debug representation.
+        other => Value::from(format!("{:?}", other)),
+    }
+}
+
+/// The offsets at which consuming the assigned partitions stops. The source
+/// finishes once they're all reached, if every partition has one.
+#[derive(Debug, Default)]
+struct EndOffsets {
+    offsets: HashMap<(String, i32), i64>,
+    finite: bool,
+}
+
+impl EndOffsets {
+    fn new(assignments: &[PartitionAssignment]) -> Self {
+        Self {
+            offsets: assignments
+                .iter()
+                .filter_map(|assignment| {
+                    assignment.end_offset.map(|end_offset| {
+                        ((assignment.topic.clone(), assignment.partition), end_offset)
+                    })
+                })
+                .collect(),
+            finite: !assignments.is_empty()
+                && assignments
+                    .iter()
+                    .all(|assignment| assignment.end_offset.is_some()),
+        }
+    }
+
+    fn end_offset(&self, msg: &BorrowedMessage<'_>) -> Option<i64> {
+        self.offsets
+            .get(&(msg.topic().to_owned(), msg.partition()))
+            .copied()
+    }
+
+    fn is_past_end(&self, msg: &BorrowedMessage<'_>) -> bool {
+        self.end_offset(msg)
+            .map_or(false, |end_offset| msg.offset() >= end_offset)
+    }
+
+    fn is_last(&self, msg: &BorrowedMessage<'_>) -> bool {
+        self.end_offset(msg)
+            .map_or(false, |end_offset| msg.offset() + 1 >= end_offset)
+    }
+
+    /// Stops consuming the partition of `msg`.
+    fn finish(
+        &mut self,
+        consumer: &StreamConsumer<KafkaStatisticsContext>,
+        msg: &BorrowedMessage<'_>,
+    ) {
+        self.offsets
+            .remove(&(msg.topic().to_owned(), msg.partition()));
+        let mut tpl = TopicPartitionList::new();
+        tpl.add_partition(msg.topic(), msg.partition());
+        if let Err(error) = consumer.pause(&tpl) {
+            emit!(KafkaPartitionPauseFailed { error });
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.finite && self.offsets.is_empty()
+    }
+}
+
 #[derive(Debug)]
 struct FinalizerEntry {
     topic: String,
@@ -252,8 +448,10 @@ impl<'a> From<BorrowedMessage<'a>> for FinalizerEntry {
     }
 }
 
-fn mark_done(consumer: Arc<StreamConsumer<KafkaStatisticsContext>>) -> impl Fn(FinalizerEntry) {
-    move |entry| {
+fn mark_done(
+    consumer: Arc<StreamConsumer<KafkaStatisticsContext>>,
+) -> impl Fn(BatchStatus, FinalizerEntry) {
+    move |_status, entry| {
         // Would like to use `consumer.store_offset` here, but types don't allow it.
         let mut tpl = TopicPartitionList::new();
         tpl.add_partition(&entry.topic, entry.partition)
@@ -294,11 +492,41 @@ fn create_consumer(
         }
     }
 
+    if config.topics.is_empty() == config.assignments.is_empty() {
+        return Err(BuildError::TopicsOrAssignments.into());
+    }
+    for assignment in &config.assignments {
+        if let (Some(start_offset), Some(end_offset)) =
+            (assignment.start_offset, assignment.end_offset)
+        {
+            if end_offset <= start_offset {
+                return Err(BuildError::InvalidOffsetRange {
+                    topic: assignment.topic.clone(),
+                    partition: assignment.partition,
+                }
+                .into());
+            }
+        }
+    }
+
     let consumer = client_config
         .create_with_context::<_, StreamConsumer<_>>(KafkaStatisticsContext)
         .context(KafkaCreateError)?;
-    let topics: Vec<&str> = config.topics.iter().map(|s| s.as_str()).collect();
-    consumer.subscribe(&topics).context(KafkaSubscribeError)?;
+    if config.assignments.is_empty() {
+        let topics: Vec<&str> = config.topics.iter().map(|s| s.as_str()).collect();
+        consumer.subscribe(&topics).context(KafkaSubscribeError)?;
+    } else {
+        let mut tpl = TopicPartitionList::new();
+        for assignment in &config.assignments {
+            let offset = assignment
+                .start_offset
+                .map(Offset::Offset)
+                .unwrap_or(Offset::Stored);
+            tpl.add_partition_offset(&assignment.topic, assignment.partition, offset)
+                .context(KafkaAssignError)?;
+        }
+        consumer.assign(&tpl).context(KafkaAssignError)?;
+    }
 
     Ok(consumer)
 }
@@ -306,6 +534,13 @@ fn create_consumer(
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{
+        config::ProxyConfig,
+        kafka::schema_registry::{
+            encode_wire_format,
+            test::{mock_registry, SCHEMA},
+        },
+    };
 
     pub(super) const BOOTSTRAP_SERVER: &str = "localhost:9091";
 
@@ -347,6 +582,114 @@ mod test {
         };
         assert!(create_consumer(&config).is_err());
     }
+
+    #[tokio::test]
+    async fn consumer_create_with_assignments() {
+        let config = KafkaSourceConfig {
+            topics: Vec::new(),
+            assignments: vec![PartitionAssignment {
+                topic: "topic".into(),
+                partition: 0,
+                start_offset: Some(10),
+                end_offset: Some(20),
+            }],
+            ..make_config("topic", "group")
+        };
+        assert!(create_consumer(&config).is_ok());
+    }
+
+    #[tokio::test]
+    async fn consumer_create_requires_topics_or_assignments() {
+        let config = KafkaSourceConfig {
+            topics: Vec::new(),
+            ..make_config("topic", "group")
+        };
+        assert!(create_consumer(&config).is_err());
+
+        let config = KafkaSourceConfig {
+            assignments: vec![PartitionAssignment {
+                topic: "topic".into(),
+                partition: 0,
+                start_offset: None,
+                end_offset: None,
+            }],
+            ..make_config("topic", "group")
+        };
+        assert!(create_consumer(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn consumer_create_invalid_offset_range() {
+        let config = KafkaSourceConfig {
+            topics: Vec::new(),
+            assignments: vec![PartitionAssignment {
+                topic: "topic".into(),
+                partition: 0,
+                start_offset: Some(20),
+                end_offset: Some(20),
+            }],
+            ..make_config("topic", "group")
+        };
+        assert!(create_consumer(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn decodes_avro() {
+        let registry = SchemaRegistry::new(&mock_registry(), &ProxyConfig::default()).unwrap();
+        let schema = avro_rs::Schema::parse_str(SCHEMA).unwrap();
+        let record = AvroValue::Record(vec![
+            ("message".into(), AvroValue::String("hello".into())),
+            ("count".into(), AvroValue::Long(3)),
+        ]);
+        let payload = encode_wire_format(42, &avro_rs::to_avro_datum(&schema, record).unwrap());
+
+        let fields = decode_avro(&registry, &payload).await.unwrap();
+        assert_eq!(
+            fields,
+            vec![
+                ("message".to_owned(), Value::from("hello")),
+                ("count".to_owned(), Value::from(3)),
+            ]
+        );
+
+        assert!(decode_avro(&registry, b"hello").await.is_err());
+    }
+
+    #[test]
+    fn converts_avro_values() {
+        let mut map = std::collections::HashMap::new();
+        map.insert("key".to_owned(), AvroValue::Int(1));
+        let value = avro_to_value(AvroValue::Record(vec![
+            ("null".into(), AvroValue::Union(Box::new(AvroValue::Null))),
+            ("bytes".into(), AvroValue::Bytes(b"bytes".to_vec())),
+            ("enum".into(), AvroValue::Enum(1, "B".into())),
+            (
+                "array".into(),
+                AvroValue::Array(vec![AvroValue::Boolean(true)]),
+            ),
+            ("map".into(), AvroValue::Map(map)),
+            ("timestamp".into(), AvroValue::TimestampMillis(1_500)),
+        ]));
+
+        let mut expected = BTreeMap::new();
+        expected.insert("null".to_owned(), Value::Null);
+        expected.insert("bytes".to_owned(), Value::from("bytes"));
+        expected.insert("enum".to_owned(), Value::from("B"));
+        expected.insert("array".to_owned(), Value::Array(vec![Value::from(true)]));
+        expected.insert(
+            "map".to_owned(),
+            Value::from(
+                vec![("key".to_owned(), Value::from(1))]
+                    .into_iter()
+                    .collect::<BTreeMap<_, _>>(),
+            ),
+        );
+        expected.insert(
+            "timestamp".to_owned(),
+            Value::from(Utc.timestamp_millis(1_500)),
+        );
+        assert_eq!(value, Value::from(expected));
+    }
 }
 
 #[cfg(feature = "kafka-integration-tests")]
@@ -356,7 +699,7 @@ mod integration_test {
     use super::*;
     use crate::{
         shutdown::ShutdownSignal,
-        test_util::{collect_n, random_string},
+        test_util::{collect_n, collect_ready, random_string},
         Pipeline,
     };
     use chrono::{SubsecRound, Utc};
@@ -444,6 +787,8 @@ mod integration_test {
             config.partition_key,
             config.offset_key,
             config.headers_key,
+            None,
+            EndOffsets::default(),
             shutdown,
             tx,
             acknowledgements,
@@ -490,4 +835,61 @@ mod integration_test {
             assert_eq!(event.as_log()["headers"], Value::from(expected_headers));
         }
     }
+
+    #[tokio::test]
+    async fn consumes_assigned_offset_range() {
+        let topic = format!("test-topic-{}", random_string(10));
+        let group_id = format!("test-group-{}", random_string(10));
+        let now = Utc::now();
+
+        let config = KafkaSourceConfig {
+            topics: Vec::new(),
+            assignments: vec![PartitionAssignment {
+                topic: topic.clone(),
+                partition: 0,
+                start_offset: Some(3),
+                end_offset: Some(7),
+            }],
+            ..make_config(&topic, &group_id)
+        };
+
+        send_events(
+            topic.clone(),
+            10,
+            "my key",
+            "my message",
+            now.timestamp_millis(),
+            "my header",
+            "my header value",
+        )
+        .await;
+
+        let (tx, rx) = Pipeline::new_test();
+        // The source finishes on its own once the end offset is reached.
+        kafka_source(
+            create_consumer(&config).unwrap(),
+            config.key_field,
+            config.topic_key,
+            config.partition_key,
+            config.offset_key,
+            config.headers_key,
+            None,
+            EndOffsets::new(&config.assignments),
+            ShutdownSignal::noop(),
+            tx,
+            false,
+        )
+        .await
+        .unwrap();
+
+        let events = collect_ready(rx).await;
+        assert_eq!(events.len(), 4);
+        for (i, event) in events.into_iter().enumerate() {
+            assert_eq!(
+                event.as_log()[log_schema().message_key()],
+                format!("my message {}", i + 3).into()
+            );
+            assert_eq!(event.as_log()["offset"], Value::from(i as i64 + 3));
+        }
+    }
 }
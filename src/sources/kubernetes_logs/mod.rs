@@ -23,8 +23,8 @@ use crate::{
 };
 use bytes::Bytes;
 use file_source::{
-    Checkpointer, FileServer, FileServerShutdown, FingerprintStrategy, Fingerprinter, Line,
-    ReadFrom,
+    Checkpointer, DataDirLock, FileServer, FileServerShutdown, FingerprintStrategy, Fingerprinter,
+    Line, ReadFrom,
 };
 use k8s_openapi::api::core::v1::{Namespace, Pod};
 use serde::{Deserialize, Serialize};
@@ -94,6 +94,12 @@ pub struct Config {
     /// the files.
     max_read_bytes: usize,
 
+    /// Max amount of bytes per second to read from a single file.
+    max_read_bytes_per_sec_per_file: Option<usize>,
+
+    /// Max amount of bytes per second to read across all the files.
+    max_read_bytes_per_sec: Option<usize>,
+
     /// The maximum number of a bytes a line can contain before being discarded. This protects
     /// against malformed lines or tailing incorrect files.
     max_line_bytes: usize,
@@ -150,6 +156,8 @@ impl Default for Config {
             namespace_annotation_fields: namespace_metadata_annotator::FieldsSpec::default(),
             exclude_paths_glob_patterns: default_path_exclusion(),
             max_read_bytes: default_max_read_bytes(),
+            max_read_bytes_per_sec_per_file: None,
+            max_read_bytes_per_sec: None,
             max_line_bytes: default_max_line_bytes(),
             fingerprint_lines: default_fingerprint_lines(),
             glob_minimum_cooldown_ms: default_glob_minimum_cooldown_ms(),
@@ -194,6 +202,8 @@ struct Source {
     label_selector: String,
     exclude_paths: Vec<glob::Pattern>,
     max_read_bytes: usize,
+    max_read_bytes_per_sec_per_file: Option<usize>,
+    max_read_bytes_per_sec: Option<usize>,
     max_line_bytes: usize,
     fingerprint_lines: usize,
     glob_minimum_cooldown: Duration,
@@ -237,6 +247,8 @@ impl Source {
             label_selector,
             exclude_paths,
             max_read_bytes: config.max_read_bytes,
+            max_read_bytes_per_sec_per_file: config.max_read_bytes_per_sec_per_file,
+            max_read_bytes_per_sec: config.max_read_bytes_per_sec,
             max_line_bytes: config.max_line_bytes,
             fingerprint_lines: config.fingerprint_lines,
             glob_minimum_cooldown,
@@ -260,6 +272,8 @@ impl Source {
             label_selector,
             exclude_paths,
             max_read_bytes,
+            max_read_bytes_per_sec_per_file,
+            max_read_bytes_per_sec,
             max_line_bytes,
             fingerprint_lines,
             glob_minimum_cooldown,
@@ -267,6 +281,12 @@ impl Source {
             timezone,
         } = self;
 
+        // Hold the lock for as long as the source runs, so that no other
+        // process reads the same files and overwrites our checkpoints. It's
+        // only taken once the source starts, as the source it replaces on a
+        // reload still holds it while this one is built.
+        let _lock = DataDirLock::acquire(&data_dir)?;
+
         let watcher =
             k8s::api_watcher::ApiWatcher::new(client.clone(), Pod::watch_pod_for_all_namespaces);
         let watcher = k8s::instrumenting_watcher::InstrumentingWatcher::new(watcher);
@@ -350,6 +370,8 @@ impl Source {
             // This value specifies not exactly the globbing, but interval
             // between the polling the files to watch from the `paths_provider`.
             glob_minimum_cooldown,
+            // Checkpoints are flushed as often as the files are polled.
+            checkpoint_interval: glob_minimum_cooldown,
             // The shape of the log files is well-known in the Kubernetes
             // environment, so we pick the a specially crafted fingerprinter
             // for the log files.
@@ -372,6 +394,12 @@ impl Source {
             oldest_first: false,
             // We do not remove the log files, `kubelet` is responsible for it.
             remove_after: None,
+            // Optional caps on the read rate, to keep a chatty container from
+            // hogging the node's IO and starving the other containers.
+            max_read_bytes_per_sec,
+            max_read_bytes_per_sec_per_file,
+            // All the containers are weighted equally.
+            read_weights: Vec::new(),
             // The standard emitter.
             emitter: FileSourceInternalEventsEmitter,
             // A handle to the current tokio runtime
@@ -0,0 +1,287 @@
+use super::util::finalizer::OrderedFinalizer;
+use crate::{
+    config::{
+        log_schema, DataType, GenerateConfig, SourceConfig, SourceContext, SourceDescription,
+    },
+    event::{Event, Value},
+    internal_events::{MqttAckFailed, MqttConnectionFailed, MqttEventReceived},
+    mqtt::{MqttConnectionConfig, MqttQos, RECONNECT_DELAY},
+    shutdown::ShutdownSignal,
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::Utc;
+use futures::{FutureExt, SinkExt};
+use rumqttc::{AsyncClient, Event as MqttEvent, EventLoop, Packet, Publish};
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use vector_core::event::{BatchNotifier, BatchStatus};
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("At least one topic filter must be set in `topics`"))]
+    NoTopics,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MqttSourceConfig {
+    #[serde(flatten)]
+    connection: MqttConnectionConfig,
+    topics: Vec<String>,
+    #[serde(default)]
+    qos: MqttQos,
+    #[serde(default = "default_topic_key")]
+    topic_key: String,
+}
+
+fn default_topic_key() -> String {
+    "topic".to_owned()
+}
+
+inventory::submit! {
+    SourceDescription::new::<MqttSourceConfig>("mqtt")
+}
+
+impl GenerateConfig for MqttSourceConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"
+            host = "127.0.0.1"
+            topics = ["vector/#"]"#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "mqtt")]
+impl SourceConfig for MqttSourceConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        if self.topics.is_empty() {
+            return Err(BuildError::NoTopics.into());
+        }
+
+        let mut options = self.connection.options()?;
+        // Messages are acknowledged by hand, so QoS 1 messages are only
+        // acknowledged to the broker once they have been handled.
+        options.set_manual_acks(true);
+        let (client, eventloop) = AsyncClient::new(options, 1024);
+
+        Ok(Box::pin(mqtt_source(
+            client,
+            eventloop,
+            self.topics.clone(),
+            self.qos,
+            self.topic_key.clone(),
+            cx.shutdown,
+            cx.out,
+            cx.acknowledgements,
+        )))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "mqtt"
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn mqtt_source(
+    client: AsyncClient,
+    mut eventloop: EventLoop,
+    topics: Vec<String>,
+    qos: MqttQos,
+    topic_key: String,
+    shutdown: ShutdownSignal,
+    mut out: Pipeline,
+    acknowledgements: bool,
+) -> Result<(), ()> {
+    let shutdown = shutdown.shared();
+    let finalizer = acknowledgements.then(|| {
+        let client = client.clone();
+        // Messages that weren't delivered are left unacknowledged, so that
+        // the broker sends them again.
+        OrderedFinalizer::new(shutdown.clone(), move |status, publish: Publish| {
+            if status == BatchStatus::Delivered {
+                ack(&client, &publish)
+            }
+        })
+    });
+    let mut shutdown = shutdown.fuse();
+
+    loop {
+        let notification = tokio::select! {
+            _ = &mut shutdown => break,
+            notification = eventloop.poll() => notification,
+        };
+
+        let publish = match notification {
+            Ok(MqttEvent::Incoming(Packet::ConnAck(_))) => {
+                // Subscriptions don't survive a reconnect with a clean
+                // session, so (re)subscribe on every connection.
+                for topic in &topics {
+                    if let Err(error) = client.try_subscribe(topic, qos.into()) {
+                        error!(message = "Failed to subscribe to topic.", %topic, %error);
+                    }
+                }
+                continue;
+            }
+            Ok(MqttEvent::Incoming(Packet::Publish(publish))) => publish,
+            Ok(_) => continue,
+            Err(error) => {
+                emit!(MqttConnectionFailed { error });
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+
+        emit!(MqttEventReceived {
+            byte_size: publish.payload.len(),
+            topic: &publish.topic,
+        });
+
+        let mut event = Event::new_empty_log();
+        let log = event.as_mut_log();
+        log.insert(log_schema().message_key(), publish.payload.clone());
+        log.insert(log_schema().timestamp_key(), Utc::now());
+        log.insert(log_schema().source_type_key(), Bytes::from("mqtt"));
+        log.insert(&topic_key, Value::from(publish.topic.clone()));
+
+        match &finalizer {
+            Some(finalizer) => {
+                let (batch, receiver) = BatchNotifier::new_with_receiver();
+                let event = event.with_batch_notifier(&batch);
+                match out.send(event).await {
+                    Err(error) => {
+                        error!(message = "Error sending to sink.", %error);
+                        break;
+                    }
+                    Ok(_) => finalizer.add(publish, receiver),
+                }
+            }
+            None => match out.send(event).await {
+                Err(error) => {
+                    error!(message = "Error sending to sink.", %error);
+                    break;
+                }
+                Ok(_) => ack(&client, &publish),
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Acknowledges a message to the broker. This is a no-op for QoS 0 messages.
+fn ack(client: &AsyncClient, publish: &Publish) {
+    if let Err(error) = client.try_ack(publish) {
+        emit!(MqttAckFailed { error });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<MqttSourceConfig>();
+    }
+
+    #[tokio::test]
+    async fn requires_topics() {
+        let config: MqttSourceConfig = toml::from_str(
+            r#"
+            host = "127.0.0.1"
+            topics = []"#,
+        )
+        .unwrap();
+        assert!(config
+            .build(SourceContext::new_test(Pipeline::new_test().0))
+            .await
+            .is_err());
+    }
+}
+
+#[cfg(feature = "mqtt-integration-tests")]
+#[cfg(test)]
+mod integration_tests {
+    use super::*;
+    use crate::test_util::{collect_n, random_string};
+    use rumqttc::{MqttOptions, QoS};
+    use std::{convert::TryFrom, time::Duration};
+    use vector_core::event::EventStatus;
+
+    fn make_config(topic: &str, qos: u8) -> MqttSourceConfig {
+        toml::from_str(&format!(
+            r#"
+            host = "127.0.0.1"
+            client_id = "vector-source-{}"
+            topics = ["{}/#"]
+            qos = {}"#,
+            random_string(10),
+            topic,
+            qos
+        ))
+        .unwrap()
+    }
+
+    async fn publish(topic: &str, messages: &[&str], qos: QoS) {
+        let options = MqttOptions::new(
+            format!("publisher-{}", random_string(10)),
+            "127.0.0.1",
+            1883,
+        );
+        let (client, mut eventloop) = AsyncClient::new(options, 10);
+        tokio::spawn(async move { while eventloop.poll().await.is_ok() {} });
+        for message in messages {
+            client
+                .publish(
+                    format!("{}/sub", topic),
+                    qos,
+                    false,
+                    message.as_bytes().to_vec(),
+                )
+                .await
+                .unwrap();
+        }
+    }
+
+    async fn consume(qos: u8, acknowledgements: bool) {
+        let topic = format!("test-{}", random_string(10));
+        let config = make_config(&topic, qos);
+
+        let (tx, rx) = Pipeline::new_test_finalize(EventStatus::Delivered);
+        let mut cx = SourceContext::new_test(tx);
+        cx.acknowledgements = acknowledgements;
+        tokio::spawn(config.build(cx).await.unwrap());
+        // Give the source time to connect and subscribe.
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let qos = MqttQos::try_from(qos).unwrap().into();
+        publish(&topic, &["message 1", "message 2"], qos).await;
+
+        let events = collect_n(rx, 2).await;
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "message 1".into());
+        assert_eq!(log["topic"], format!("{}/sub", topic).into());
+        assert_eq!(log[log_schema().source_type_key()], "mqtt".into());
+        assert_eq!(
+            events[1].as_log()[log_schema().message_key()],
+            "message 2".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn consumes_qos0() {
+        consume(0, false).await;
+    }
+
+    #[tokio::test]
+    async fn consumes_qos1_with_acknowledgements() {
+        consume(1, true).await;
+    }
+}
@@ -0,0 +1,261 @@
+use super::relabel::Labels;
+use crate::internal_events::PrometheusFileSdError;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+#[derive(Debug, Snafu)]
+pub(super) enum FileSdError {
+    #[snafu(display("Invalid file pattern {:?}: {}", pattern, source))]
+    InvalidPattern {
+        pattern: String,
+        source: glob::PatternError,
+    },
+    #[snafu(display("Failed to read targets: {}", source))]
+    Read { source: std::io::Error },
+    #[snafu(display("Invalid JSON targets: {}", source))]
+    ParseJson { source: serde_json::Error },
+    #[snafu(display("Invalid YAML targets: {}", source))]
+    ParseYaml { source: serde_yaml::Error },
+    #[snafu(display("Targets must be in a `.json`, `.yml` or `.yaml` file"))]
+    UnknownExtension,
+}
+
+/// Discovers the targets listed in files, in the format of Prometheus'
+/// `file_sd_configs`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub(super) struct FileSdConfig {
+    /// Paths of the files, whose last segment may be a glob pattern.
+    pub(super) files: Vec<String>,
+    /// The scheme of the discovered targets, unless their `__scheme__` label
+    /// sets it.
+    #[serde(default = "default_scheme")]
+    pub(super) scheme: String,
+    /// The path the metrics of the discovered targets are exposed on, unless
+    /// their `__metrics_path__` label sets it.
+    #[serde(default = "default_metrics_path")]
+    pub(super) metrics_path: String,
+}
+
+fn default_scheme() -> String {
+    "http".into()
+}
+
+fn default_metrics_path() -> String {
+    "/metrics".into()
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct TargetGroup {
+    targets: Vec<String>,
+    #[serde(default)]
+    labels: Labels,
+}
+
+struct TargetFile {
+    modified: Option<SystemTime>,
+    groups: Vec<TargetGroup>,
+}
+
+/// Keeps track of the targets listed in files, rereading the files when
+/// they are modified.
+pub(super) struct FileDiscovery {
+    config: FileSdConfig,
+    files: HashMap<PathBuf, TargetFile>,
+}
+
+impl FileDiscovery {
+    pub(super) fn new(config: FileSdConfig) -> Result<Self, FileSdError> {
+        for pattern in &config.files {
+            glob::Pattern::new(pattern).with_context(|| InvalidPattern {
+                pattern: pattern.clone(),
+            })?;
+        }
+        Ok(Self {
+            config,
+            files: HashMap::new(),
+        })
+    }
+
+    /// Rereads the files which were modified since the previous refresh.
+    /// The files which can't be read or parsed keep their previous targets.
+    pub(super) fn refresh(&mut self) {
+        let paths = self
+            .config
+            .files
+            .iter()
+            .filter_map(|pattern| glob::glob(pattern).ok())
+            .flatten()
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+        self.files.retain(|path, _| paths.contains(path));
+
+        for path in paths {
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            let file = self.files.entry(path.clone()).or_insert(TargetFile {
+                modified: None,
+                groups: Vec::new(),
+            });
+            if modified.is_some() && modified == file.modified {
+                continue;
+            }
+            // The modification time is only recorded once the file is
+            // parsed, so a file read while partially written is read again
+            // even if it's completed within the same mtime granularity.
+            match read_target_groups(&path) {
+                Ok(groups) => {
+                    file.modified = modified;
+                    file.groups = groups;
+                }
+                Err(error) => emit!(PrometheusFileSdError {
+                    error: error.into(),
+                    path: &path,
+                }),
+            }
+        }
+    }
+
+    /// The labels of the discovered targets, before relabeling.
+    pub(super) fn targets(&self) -> Vec<Labels> {
+        let mut targets = Vec::new();
+        for (path, file) in &self.files {
+            for group in &file.groups {
+                for target in &group.targets {
+                    let mut labels = group.labels.clone();
+                    labels.insert("__address__".into(), target.clone());
+                    labels
+                        .entry("__scheme__".into())
+                        .or_insert_with(|| self.config.scheme.clone());
+                    labels
+                        .entry("__metrics_path__".into())
+                        .or_insert_with(|| self.config.metrics_path.clone());
+                    labels.insert(
+                        "__meta_filepath".into(),
+                        path.to_string_lossy().into_owned(),
+                    );
+                    targets.push(labels);
+                }
+            }
+        }
+        targets
+    }
+}
+
+fn read_target_groups(path: &Path) -> Result<Vec<TargetGroup>, FileSdError> {
+    let contents = fs::read(path).context(Read)?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => serde_json::from_slice(&contents).context(ParseJson),
+        Some("yml") | Some("yaml") => serde_yaml::from_slice(&contents).context(ParseYaml),
+        _ => Err(FileSdError::UnknownExtension),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn discovery(dir: &Path) -> FileDiscovery {
+        FileDiscovery::new(FileSdConfig {
+            files: vec![
+                dir.join("*.json").to_string_lossy().into_owned(),
+                dir.join("targets.yml").to_string_lossy().into_owned(),
+            ],
+            scheme: default_scheme(),
+            metrics_path: default_metrics_path(),
+        })
+        .unwrap()
+    }
+
+    fn addresses(discovery: &FileDiscovery) -> Vec<String> {
+        let mut addresses = discovery
+            .targets()
+            .into_iter()
+            .map(|labels| labels["__address__"].clone())
+            .collect::<Vec<_>>();
+        addresses.sort();
+        addresses
+    }
+
+    #[test]
+    fn reads_json_and_yaml_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("web.json"),
+            r#"[{"targets": ["web-1:9100", "web-2:9100"], "labels": {"env": "prod", "__metrics_path__": "/probe"}}]"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("targets.yml"), "- targets: ['db-1:9187']\n").unwrap();
+        fs::write(dir.path().join("ignored.yml"), "- targets: ['x:1']\n").unwrap();
+
+        let mut discovery = discovery(dir.path());
+        discovery.refresh();
+
+        assert_eq!(
+            addresses(&discovery),
+            vec!["db-1:9187", "web-1:9100", "web-2:9100"]
+        );
+        let web = discovery
+            .targets()
+            .into_iter()
+            .find(|labels| labels["__address__"] == "web-1:9100")
+            .unwrap();
+        assert_eq!(web["env"], "prod");
+        assert_eq!(web["__scheme__"], "http");
+        assert_eq!(web["__metrics_path__"], "/probe");
+        assert_eq!(
+            web["__meta_filepath"],
+            dir.path().join("web.json").to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn rereads_modified_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("web.json");
+        fs::write(&path, r#"[{"targets": ["web-1:9100"]}]"#).unwrap();
+
+        let mut discovery = discovery(dir.path());
+        discovery.refresh();
+        assert_eq!(addresses(&discovery), vec!["web-1:9100"]);
+
+        // Invalid files keep their previous targets.
+        std::thread::sleep(Duration::from_millis(10));
+        fs::write(&path, r#"[{"targets": "#).unwrap();
+        discovery.refresh();
+        assert_eq!(addresses(&discovery), vec!["web-1:9100"]);
+        // They are read again on the next refresh, even if unmodified.
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        assert_ne!(discovery.files[&path].modified, Some(modified));
+
+        std::thread::sleep(Duration::from_millis(10));
+        fs::write(&path, r#"[{"targets": ["web-2:9100"]}]"#).unwrap();
+        discovery.refresh();
+        assert_eq!(addresses(&discovery), vec!["web-2:9100"]);
+
+        fs::remove_file(&path).unwrap();
+        discovery.refresh();
+        assert!(discovery.targets().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(matches!(
+            FileDiscovery::new(FileSdConfig {
+                files: vec!["targets/[.json".into()],
+                scheme: default_scheme(),
+                metrics_path: default_metrics_path(),
+            }),
+            Err(FileSdError::InvalidPattern { .. })
+        ));
+    }
+}
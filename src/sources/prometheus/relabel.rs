@@ -0,0 +1,249 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::collections::BTreeMap;
+
+pub(super) type Labels = BTreeMap<String, String>;
+
+#[derive(Debug, Snafu)]
+pub(super) enum RelabelError {
+    #[snafu(display("Invalid relabeling regex {:?}: {}", regex, source))]
+    InvalidRegex { regex: String, source: regex::Error },
+    #[snafu(display("Relabeling with the `replace` action needs a `target_label`"))]
+    MissingTargetLabel,
+}
+
+/// A relabeling rule, applied to the labels of the targets before they are
+/// scraped, as with Prometheus' `relabel_configs`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub(super) struct RelabelConfig {
+    #[serde(default)]
+    source_labels: Vec<String>,
+    #[serde(default = "default_separator")]
+    separator: String,
+    #[serde(default = "default_regex")]
+    regex: String,
+    target_label: Option<String>,
+    #[serde(default = "default_replacement")]
+    replacement: String,
+    #[serde(default)]
+    action: RelabelAction,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Derivative, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum RelabelAction {
+    /// Sets the target label to the replacement, when the regex matches.
+    #[derivative(Default)]
+    Replace,
+    /// Drops the targets for which the regex doesn't match.
+    Keep,
+    /// Drops the targets for which the regex matches.
+    Drop,
+}
+
+fn default_separator() -> String {
+    ";".into()
+}
+
+fn default_regex() -> String {
+    "(.*)".into()
+}
+
+fn default_replacement() -> String {
+    "$1".into()
+}
+
+struct Rule {
+    source_labels: Vec<String>,
+    separator: String,
+    regex: Regex,
+    target_label: String,
+    replacement: String,
+    action: RelabelAction,
+}
+
+pub(super) struct Relabeler {
+    rules: Vec<Rule>,
+}
+
+impl Relabeler {
+    pub(super) fn new(configs: &[RelabelConfig]) -> Result<Self, RelabelError> {
+        let rules = configs
+            .iter()
+            .map(|config| {
+                // Like Prometheus, the regexes must match the whole value.
+                let regex = Regex::new(&format!("^(?:{})$", config.regex)).with_context(|| {
+                    InvalidRegex {
+                        regex: config.regex.clone(),
+                    }
+                })?;
+                let target_label = match (config.action, &config.target_label) {
+                    (RelabelAction::Replace, None) => return Err(RelabelError::MissingTargetLabel),
+                    (_, target_label) => target_label.clone().unwrap_or_default(),
+                };
+                Ok(Rule {
+                    source_labels: config.source_labels.clone(),
+                    separator: config.separator.clone(),
+                    regex,
+                    target_label,
+                    replacement: config.replacement.clone(),
+                    action: config.action,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Applies the rules in order, returning `None` if the target is dropped.
+    pub(super) fn relabel(&self, mut labels: Labels) -> Option<Labels> {
+        for rule in &self.rules {
+            let value = rule
+                .source_labels
+                .iter()
+                .map(|name| labels.get(name).map(String::as_str).unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join(&rule.separator);
+            let captures = rule.regex.captures(&value);
+
+            match rule.action {
+                RelabelAction::Keep if captures.is_none() => return None,
+                RelabelAction::Drop if captures.is_some() => return None,
+                RelabelAction::Keep | RelabelAction::Drop => (),
+                RelabelAction::Replace => {
+                    if let Some(captures) = captures {
+                        let mut target_label = String::new();
+                        captures.expand(&rule.target_label, &mut target_label);
+                        let mut replacement = String::new();
+                        captures.expand(&rule.replacement, &mut replacement);
+                        if target_label.is_empty() {
+                            continue;
+                        }
+                        if replacement.is_empty() {
+                            labels.remove(&target_label);
+                        } else {
+                            labels.insert(target_label, replacement);
+                        }
+                    }
+                }
+            }
+        }
+        Some(labels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configs(rules: &str) -> Vec<RelabelConfig> {
+        #[derive(Deserialize)]
+        struct Rules {
+            rules: Vec<RelabelConfig>,
+        }
+        toml::from_str::<Rules>(rules).unwrap().rules
+    }
+
+    fn relabeler(rules: &str) -> Relabeler {
+        Relabeler::new(&configs(rules)).unwrap()
+    }
+
+    fn labels(labels: &[(&str, &str)]) -> Labels {
+        labels
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn keeps_and_drops_targets() {
+        let relabeler = relabeler(
+            r#"
+            [[rules]]
+            source_labels = ["env"]
+            regex = "prod|staging"
+            action = "keep"
+
+            [[rules]]
+            source_labels = ["__address__", "team"]
+            regex = "db-.*;.*"
+            action = "drop"
+            "#,
+        );
+
+        let target = labels(&[("__address__", "web-1:9100"), ("env", "prod")]);
+        assert_eq!(relabeler.relabel(target.clone()), Some(target));
+        // The regex must match the whole value.
+        assert_eq!(
+            relabeler.relabel(labels(&[
+                ("__address__", "web-1:9100"),
+                ("env", "production")
+            ])),
+            None
+        );
+        assert_eq!(
+            relabeler.relabel(labels(&[("__address__", "web-1:9100")])),
+            None
+        );
+        assert_eq!(
+            relabeler.relabel(labels(&[("__address__", "db-1:9100"), ("env", "staging")])),
+            None
+        );
+    }
+
+    #[test]
+    fn replaces_labels() {
+        let relabeler = relabeler(
+            r#"
+            [[rules]]
+            source_labels = ["__address__"]
+            regex = "([^:]+):\\d+"
+            target_label = "instance"
+
+            [[rules]]
+            source_labels = ["__address__"]
+            regex = "(.+):\\d+"
+            target_label = "__address__"
+            replacement = "${1}:9090"
+
+            [[rules]]
+            source_labels = ["__meta_role"]
+            regex = "(.*)"
+            target_label = "role_${1}"
+            replacement = "true"
+
+            [[rules]]
+            target_label = "env"
+            replacement = ""
+            "#,
+        );
+
+        assert_eq!(
+            relabeler.relabel(labels(&[
+                ("__address__", "web-1:9100"),
+                ("__meta_role", "api"),
+                ("env", "prod"),
+            ])),
+            Some(labels(&[
+                ("__address__", "web-1:9090"),
+                ("__meta_role", "api"),
+                ("instance", "web-1"),
+                ("role_api", "true"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        assert!(matches!(
+            Relabeler::new(&configs("[[rules]]\nregex = \"(\"\naction = \"keep\"")),
+            Err(RelabelError::InvalidRegex { .. })
+        ));
+        assert!(matches!(
+            Relabeler::new(&configs("[[rules]]\nsource_labels = [\"a\"]")),
+            Err(RelabelError::MissingTargetLabel)
+        ));
+    }
+}
@@ -0,0 +1,242 @@
+use super::ApiError;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant},
+};
+use vector_core::event::{BatchStatus, BatchStatusReceiver};
+
+/// Limits on the state kept for indexer acknowledgements.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Derivative, PartialEq)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct HecAcknowledgementsConfig {
+    /// Maximum number of ack ids waiting to be queried on a single channel
+    #[derivative(Default(value = "1_000_000"))]
+    pub max_pending_acks_per_channel: u64,
+    /// Maximum number of channels tracked at once
+    #[derivative(Default(value = "1_000_000"))]
+    pub max_number_of_ack_channels: u64,
+    /// Channels that haven't been used for this long are forgotten
+    #[derivative(Default(value = "300"))]
+    pub max_idle_time_secs: u64,
+}
+
+#[derive(Deserialize, Debug)]
+pub(super) struct HecAckStatusRequest {
+    pub acks: Vec<u64>,
+}
+
+#[derive(Serialize, Debug)]
+pub(super) struct HecAckStatusResponse {
+    pub acks: HashMap<u64, bool>,
+}
+
+type Channels = Mutex<HashMap<String, Arc<Mutex<Channel>>>>;
+
+/// Issues ack ids per channel and tracks which of them have been delivered
+/// by the sinks.
+pub(super) struct IndexerAcknowledgement {
+    config: HecAcknowledgementsConfig,
+    channels: Arc<Channels>,
+}
+
+struct Channel {
+    next_ack_id: u64,
+    /// Number of ack ids whose events haven't been finalized yet.
+    pending: u64,
+    /// Ack ids whose events were delivered but haven't been queried yet.
+    delivered: HashSet<u64>,
+    last_used: Instant,
+}
+
+impl Channel {
+    fn new() -> Self {
+        Self {
+            next_ack_id: 0,
+            pending: 0,
+            delivered: HashSet::new(),
+            last_used: Instant::now(),
+        }
+    }
+}
+
+impl IndexerAcknowledgement {
+    pub(super) fn new(config: HecAcknowledgementsConfig) -> Self {
+        let channels = Arc::new(Channels::default());
+        let max_idle_time = Duration::from_secs(config.max_idle_time_secs);
+        tokio::spawn(remove_idle_channels(
+            Arc::downgrade(&channels),
+            max_idle_time,
+        ));
+        Self { config, channels }
+    }
+
+    /// Reserves the next ack id of the channel, failing if the limits on
+    /// channels or pending acks are reached. Reservations are made before
+    /// the events of a request are sent, so that no events are accepted
+    /// without an ack id.
+    pub(super) fn reserve_ack_id(&self, channel_id: &str) -> Result<AckIdReservation, ApiError> {
+        let channel = self.channel(channel_id)?;
+        let ack_id = {
+            let mut state = channel.lock().unwrap();
+            let pending = state.pending + state.delivered.len() as u64;
+            if pending >= self.config.max_pending_acks_per_channel {
+                return Err(ApiError::ServiceUnavailable);
+            }
+            let ack_id = state.next_ack_id;
+            state.next_ack_id += 1;
+            state.pending += 1;
+            state.last_used = Instant::now();
+            ack_id
+        };
+
+        Ok(AckIdReservation {
+            channel: Some(channel),
+            ack_id,
+        })
+    }
+
+    /// Reports which of the ack ids have been delivered. Delivered ack ids are
+    /// only reported once.
+    pub(super) fn get_acks_status(
+        &self,
+        channel_id: &str,
+        ack_ids: &[u64],
+    ) -> Result<HashMap<u64, bool>, ApiError> {
+        let channel = self.channel(channel_id)?;
+        let mut state = channel.lock().unwrap();
+        state.last_used = Instant::now();
+        Ok(ack_ids
+            .iter()
+            .map(|ack_id| (*ack_id, state.delivered.remove(ack_id)))
+            .collect())
+    }
+
+    fn channel(&self, channel_id: &str) -> Result<Arc<Mutex<Channel>>, ApiError> {
+        let mut channels = self.channels.lock().unwrap();
+        if let Some(channel) = channels.get(channel_id) {
+            return Ok(Arc::clone(channel));
+        }
+        if channels.len() as u64 >= self.config.max_number_of_ack_channels {
+            return Err(ApiError::ServiceUnavailable);
+        }
+        let channel = Arc::new(Mutex::new(Channel::new()));
+        channels.insert(channel_id.to_owned(), Arc::clone(&channel));
+        Ok(channel)
+    }
+}
+
+/// An ack id counted as pending on its channel, released if the events of
+/// its request couldn't be sent.
+pub(super) struct AckIdReservation {
+    channel: Option<Arc<Mutex<Channel>>>,
+    ack_id: u64,
+}
+
+impl AckIdReservation {
+    /// Issues the ack id, which is reported as delivered once `receiver`
+    /// resolves to `BatchStatus::Delivered`.
+    pub(super) fn track(mut self, receiver: BatchStatusReceiver) -> u64 {
+        let channel = self.channel.take().expect("reservation already tracked");
+        let ack_id = self.ack_id;
+        tokio::spawn(async move {
+            let status = receiver.await;
+            let mut state = channel.lock().unwrap();
+            state.pending -= 1;
+            if status == BatchStatus::Delivered {
+                state.delivered.insert(ack_id);
+            }
+        });
+        ack_id
+    }
+}
+
+impl Drop for AckIdReservation {
+    fn drop(&mut self) {
+        if let Some(channel) = self.channel.take() {
+            channel.lock().unwrap().pending -= 1;
+        }
+    }
+}
+
+/// Forgets channels without events in flight that haven't been used for
+/// `max_idle_time`, until the source shuts down.
+async fn remove_idle_channels(channels: Weak<Channels>, max_idle_time: Duration) {
+    let mut interval =
+        tokio::time::interval(std::cmp::max(max_idle_time / 2, Duration::from_secs(1)));
+    loop {
+        interval.tick().await;
+        let channels = match channels.upgrade() {
+            Some(channels) => channels,
+            None => break,
+        };
+        let now = Instant::now();
+        channels.lock().unwrap().retain(|_, channel| {
+            let channel = channel.lock().unwrap();
+            channel.pending > 0 || now.duration_since(channel.last_used) < max_idle_time
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vector_core::event::BatchNotifier;
+
+    fn config() -> HecAcknowledgementsConfig {
+        HecAcknowledgementsConfig {
+            max_pending_acks_per_channel: 2,
+            max_number_of_ack_channels: 1,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_delivered_acks_once() {
+        let idx_ack = IndexerAcknowledgement::new(config());
+
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        let delivered = idx_ack.reserve_ack_id("channel").unwrap().track(receiver);
+        drop(batch);
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        let in_flight = idx_ack.reserve_ack_id("channel").unwrap().track(receiver);
+        assert_eq!((delivered, in_flight), (0, 1));
+
+        // Let the finalization task run.
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let status = idx_ack
+            .get_acks_status("channel", &[delivered, in_flight])
+            .unwrap();
+        assert!(status[&delivered]);
+        assert!(!status[&in_flight]);
+
+        let status = idx_ack.get_acks_status("channel", &[delivered]).unwrap();
+        assert!(!status[&delivered]);
+        drop(batch);
+    }
+
+    #[tokio::test]
+    async fn enforces_limits() {
+        let idx_ack = IndexerAcknowledgement::new(config());
+
+        let (_batch, receiver) = BatchNotifier::new_with_receiver();
+        idx_ack.reserve_ack_id("channel").unwrap().track(receiver);
+        let reservation = idx_ack.reserve_ack_id("channel").unwrap();
+
+        assert!(matches!(
+            idx_ack.reserve_ack_id("channel"),
+            Err(ApiError::ServiceUnavailable)
+        ));
+        assert!(matches!(
+            idx_ack.get_acks_status("other", &[0]),
+            Err(ApiError::ServiceUnavailable)
+        ));
+
+        // Reservations whose events weren't sent are released.
+        drop(reservation);
+        assert!(idx_ack.reserve_ack_id("channel").is_ok());
+    }
+}
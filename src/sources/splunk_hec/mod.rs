@@ -1,6 +1,6 @@
 use crate::{
     config::{log_schema, DataType, Resource, SourceConfig, SourceContext, SourceDescription},
-    event::{Event, LogEvent, Value},
+    event::{Event, LogEvent, Metric, MetricKind, MetricValue, Value},
     internal_events::{
         SplunkHecEventReceived, SplunkHecRequestBodyInvalid, SplunkHecRequestError,
         SplunkHecRequestReceived,
@@ -11,20 +11,29 @@ use crate::{
 use bytes::{Buf, Bytes};
 use chrono::{DateTime, TimeZone, Utc};
 use flate2::read::MultiGzDecoder;
-use futures::{stream, FutureExt, SinkExt, StreamExt, TryFutureExt};
+use futures::{stream, FutureExt, SinkExt, StreamExt};
 use http::StatusCode;
 use serde::{de, Deserialize, Serialize};
-use serde_json::{de::IoRead, json, Deserializer, Value as JsonValue};
+use serde_json::{de::IoRead, json, Deserializer, Map as JsonMap, Value as JsonValue};
 use snafu::Snafu;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, VecDeque},
     future,
     io::Read,
     net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
 };
+use vector_core::event::{BatchNotifier, BatchStatusReceiver};
 
 use warp::{filters::BoxedFilter, path, reject::Rejection, reply::Response, Filter, Reply};
 
+mod acknowledgements;
+
+use self::acknowledgements::{
+    AckIdReservation, HecAckStatusRequest, HecAckStatusResponse, HecAcknowledgementsConfig,
+    IndexerAcknowledgement,
+};
+
 // Event fields unique to splunk_hec source
 pub const CHANNEL: &str = "splunk_channel";
 pub const INDEX: &str = "splunk_index";
@@ -43,6 +52,9 @@ pub struct SplunkConfig {
     /// A list of tokens to accept. Omit this to accept any token
     valid_tokens: Option<Vec<String>>,
     tls: Option<TlsConfig>,
+    /// Limits for indexer acknowledgements, which are enabled along with
+    /// end-to-end acknowledgements
+    indexer_acknowledgements: HecAcknowledgementsConfig,
 }
 
 inventory::submit! {
@@ -68,6 +80,7 @@ impl Default for SplunkConfig {
             token: None,
             valid_tokens: None,
             tls: None,
+            indexer_acknowledgements: HecAcknowledgementsConfig::default(),
         }
     }
 }
@@ -80,10 +93,11 @@ fn default_socket_address() -> SocketAddr {
 #[typetag::serde(name = "splunk_hec")]
 impl SourceConfig for SplunkConfig {
     async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
-        let source = SplunkSource::new(self);
+        let source = SplunkSource::new(self, cx.acknowledgements);
 
         let event_service = source.event_service(cx.out.clone());
         let raw_service = source.raw_service(cx.out);
+        let ack_service = source.ack_service();
         let health_service = source.health_service();
         let options = SplunkSource::options();
 
@@ -101,6 +115,8 @@ impl SourceConfig for SplunkConfig {
                 event_service
                     .or(raw_service)
                     .unify()
+                    .or(ack_service)
+                    .unify()
                     .or(health_service)
                     .unify()
                     .or(options)
@@ -126,7 +142,7 @@ impl SourceConfig for SplunkConfig {
     }
 
     fn output_type(&self) -> DataType {
-        DataType::Log
+        DataType::Any
     }
 
     fn source_type(&self) -> &'static str {
@@ -141,10 +157,11 @@ impl SourceConfig for SplunkConfig {
 /// Shared data for responding to requests.
 struct SplunkSource {
     valid_credentials: Vec<String>,
+    idx_ack: Option<Arc<IndexerAcknowledgement>>,
 }
 
 impl SplunkSource {
-    fn new(config: &SplunkConfig) -> Self {
+    fn new(config: &SplunkConfig, acknowledgements: bool) -> Self {
         let valid_tokens = config
             .valid_tokens
             .iter()
@@ -154,22 +171,17 @@ impl SplunkSource {
             valid_credentials: valid_tokens
                 .map(|token| format!("Splunk {}", token))
                 .collect(),
+            idx_ack: acknowledgements
+                .then(|| Arc::new(IndexerAcknowledgement::new(config.indexer_acknowledgements))),
         }
     }
 
     fn event_service(&self, out: Pipeline) -> BoxedFilter<(Response,)> {
-        let splunk_channel_query_param = warp::query::<HashMap<String, String>>()
-            .map(|qs: HashMap<String, String>| qs.get("channel").map(|v| v.to_owned()));
-        let splunk_channel_header = warp::header::optional::<String>("x-splunk-request-channel");
-
-        let splunk_channel = splunk_channel_header
-            .and(splunk_channel_query_param)
-            .map(|header: Option<String>, query_param| header.or(query_param));
-
+        let idx_ack = self.idx_ack.clone();
         warp::post()
             .and(path!("event").or(path!("event" / "1.0")))
             .and(self.authorization())
-            .and(splunk_channel)
+            .and(self.channel())
             .and(warp::addr::remote())
             .and(warp::header::optional::<String>("X-Forwarded-For"))
             .and(self.gzip())
@@ -185,14 +197,37 @@ impl SplunkSource {
                     let mut out = out
                         .clone()
                         .sink_map_err(|_| Rejection::from(ApiError::ServerShutdown));
+                    let idx_ack = idx_ack.clone();
                     async move {
+                        // Acknowledgements are tracked per channel.
+                        let ack_channel = match (&idx_ack, &channel) {
+                            (Some(_), None) => return Err(ApiError::MissingChannel.into()),
+                            (Some(_), Some(channel)) => Some(channel.clone()),
+                            (None, _) => None,
+                        };
+                        let (batch, receiver) = match ack_channel {
+                            Some(_) => {
+                                let (batch, receiver) = BatchNotifier::new_with_receiver();
+                                (Some(batch), Some(receiver))
+                            }
+                            None => (None, None),
+                        };
+                        let reservation =
+                            reserve_ack_id(idx_ack.as_deref(), ack_channel.as_deref())?;
+
                         let reader: Box<dyn Read + Send> = if gzip {
                             Box::new(MultiGzDecoder::new(body.reader()))
                         } else {
                             Box::new(body.reader())
                         };
 
-                        let events = stream::iter(EventIterator::new(reader, channel, remote, xff));
+                        let events = stream::iter(EventIterator::new(reader, channel, remote, xff))
+                            .map(move |event| {
+                                event.map(|event| match &batch {
+                                    Some(batch) => event.with_batch_notifier(batch),
+                                    None => event,
+                                })
+                            });
 
                         // `fn send_all` can be used once https://github.com/rust-lang/futures-rs/issues/2402
                         // is resolved.
@@ -200,7 +235,8 @@ impl SplunkSource {
 
                         out.flush().await?;
 
-                        res
+                        res?;
+                        Ok(track_ack_id(reservation, receiver))
                     }
                 },
             )
@@ -209,22 +245,11 @@ impl SplunkSource {
     }
 
     fn raw_service(&self, out: Pipeline) -> BoxedFilter<(Response,)> {
-        let splunk_channel_query_param = warp::query::<HashMap<String, String>>()
-            .map(|qs: HashMap<String, String>| qs.get("channel").map(|v| v.to_owned()));
-        let splunk_channel_header = warp::header::optional::<String>("x-splunk-request-channel");
-
-        let splunk_channel = splunk_channel_header
-            .and(splunk_channel_query_param)
-            .and_then(|header: Option<String>, query_param| async move {
-                header
-                    .or(query_param)
-                    .ok_or_else(|| Rejection::from(ApiError::MissingChannel))
-            });
-
+        let idx_ack = self.idx_ack.clone();
         warp::post()
             .and(path!("raw" / "1.0").or(path!("raw")))
             .and(self.authorization())
-            .and(splunk_channel)
+            .and(self.required_channel())
             .and(warp::addr::remote())
             .and(warp::header::optional::<String>("X-Forwarded-For"))
             .and(self.gzip())
@@ -238,14 +263,25 @@ impl SplunkSource {
                       gzip: bool,
                       body: Bytes| {
                     let out = out.clone();
+                    let idx_ack = idx_ack.clone();
                     async move {
-                        let event = future::ready(raw_event(body, gzip, channel, remote, xff));
-                        futures::stream::once(event)
+                        let event = raw_event(body, gzip, channel.clone(), remote, xff)?;
+                        let reservation = reserve_ack_id(idx_ack.as_deref(), Some(&channel))?;
+                        let (event, receiver) = match idx_ack {
+                            Some(_) => {
+                                let (batch, receiver) = BatchNotifier::new_with_receiver();
+                                (event.with_batch_notifier(&batch), Some(receiver))
+                            }
+                            None => (event, None),
+                        };
+
+                        futures::stream::once(future::ready(Ok(event)))
                             .forward(
                                 out.sink_map_err(|_| Rejection::from(ApiError::ServerShutdown)),
                             )
-                            .map_ok(|_| ())
-                            .await
+                            .await?;
+
+                        Ok(track_ack_id(reservation, receiver))
                     }
                 },
             )
@@ -253,6 +289,28 @@ impl SplunkSource {
             .boxed()
     }
 
+    fn ack_service(&self) -> BoxedFilter<(Response,)> {
+        let idx_ack = self.idx_ack.clone();
+        warp::post()
+            .and(path!("ack"))
+            .and(self.authorization())
+            .and(self.required_channel())
+            .and(warp::body::json())
+            .and_then(move |_, channel: String, body: HecAckStatusRequest| {
+                let idx_ack = idx_ack.clone();
+                async move {
+                    match idx_ack {
+                        Some(idx_ack) => {
+                            let acks = idx_ack.get_acks_status(&channel, &body.acks)?;
+                            Ok(warp::reply::json(&HecAckStatusResponse { acks }).into_response())
+                        }
+                        None => Err(Rejection::from(ApiError::AckIsDisabled)),
+                    }
+                }
+            })
+            .boxed()
+    }
+
     fn health_service(&self) -> BoxedFilter<(Response,)> {
         let valid_credentials = self.valid_credentials.clone();
         let authorize =
@@ -282,7 +340,8 @@ impl SplunkSource {
                 path!("event")
                     .or(path!("event" / "1.0"))
                     .or(path!("raw" / "1.0"))
-                    .or(path!("raw")),
+                    .or(path!("raw"))
+                    .or(path!("ack")),
             )
             .map(|_| warp::reply::with_header(warp::reply(), "Allow", "POST").into_response());
 
@@ -311,6 +370,27 @@ impl SplunkSource {
             .boxed()
     }
 
+    /// Channel from either the header or the query parameter
+    fn channel(&self) -> BoxedFilter<(Option<String>,)> {
+        let splunk_channel_query_param = warp::query::<HashMap<String, String>>()
+            .map(|qs: HashMap<String, String>| qs.get("channel").map(|v| v.to_owned()));
+        let splunk_channel_header = warp::header::optional::<String>("x-splunk-request-channel");
+
+        splunk_channel_header
+            .and(splunk_channel_query_param)
+            .map(|header: Option<String>, query_param| header.or(query_param))
+            .boxed()
+    }
+
+    /// As `channel`, but rejects requests without one
+    fn required_channel(&self) -> BoxedFilter<(String,)> {
+        self.channel()
+            .and_then(|channel: Option<String>| async move {
+                channel.ok_or_else(|| Rejection::from(ApiError::MissingChannel))
+            })
+            .boxed()
+    }
+
     /// Is body encoded with gzip
     fn gzip(&self) -> BoxedFilter<(bool,)> {
         warp::header::optional::<String>("Content-Encoding")
@@ -324,6 +404,28 @@ impl SplunkSource {
             .boxed()
     }
 }
+/// Reserves an ack id for the request's events if indexer acknowledgements
+/// are enabled, before the events are sent.
+fn reserve_ack_id(
+    idx_ack: Option<&IndexerAcknowledgement>,
+    channel: Option<&str>,
+) -> Result<Option<AckIdReservation>, Rejection> {
+    match (idx_ack, channel) {
+        (Some(idx_ack), Some(channel)) => Ok(Some(idx_ack.reserve_ack_id(channel)?)),
+        _ => Ok(None),
+    }
+}
+
+/// Issues the reserved ack id once the request's events were sent.
+fn track_ack_id(
+    reservation: Option<AckIdReservation>,
+    receiver: Option<BatchStatusReceiver>,
+) -> Option<u64> {
+    reservation
+        .zip(receiver)
+        .map(|(reservation, receiver)| reservation.track(receiver))
+}
+
 /// Constructs one or more events from json-s coming from reader.
 /// If errors, it's done with input.
 struct EventIterator<R: Read> {
@@ -337,6 +439,8 @@ struct EventIterator<R: Read> {
     time: Time,
     /// Remaining extracted default values
     extractors: [DefaultExtractor; 4],
+    /// Metrics of the last multi-metric event that weren't returned yet
+    pending: VecDeque<Event>,
 }
 
 impl<R: Read> EventIterator<R> {
@@ -367,6 +471,7 @@ impl<R: Read> EventIterator<R> {
                 DefaultExtractor::new("source", SOURCE),
                 DefaultExtractor::new("sourcetype", SOURCETYPE),
             ],
+            pending: VecDeque::new(),
         }
     }
 
@@ -385,6 +490,17 @@ impl<R: Read> EventIterator<R> {
     }
 
     fn build_event(&mut self, mut json: JsonValue) -> Result<Event, Rejection> {
+        // Metric events carry their measurements in the `fields` field
+        let metric_fields = match json.get("event") {
+            Some(JsonValue::String(event)) if event == "metric" => {
+                match json.get_mut("fields").map(JsonValue::take) {
+                    Some(JsonValue::Object(fields)) => Some(fields),
+                    _ => return Err(ApiError::InvalidDataFormat { event: self.events }.into()),
+                }
+            }
+            _ => None,
+        };
+
         // Construct Event from parsed json event
         let mut event = Event::new_empty_log();
         let log = event.as_mut_log();
@@ -479,17 +595,95 @@ impl<R: Read> EventIterator<R> {
             de.extract(log, &mut json);
         }
 
+        if let Some(fields) = metric_fields {
+            let mut metrics = self.build_metrics(fields, event.into_log())?.into_iter();
+            let first = metrics
+                .next()
+                .ok_or(ApiError::InvalidDataFormat { event: self.events })?;
+            self.pending.extend(metrics);
+            self.events += 1;
+            return Ok(first);
+        }
+
         emit!(SplunkHecEventReceived);
         self.events += 1;
 
         Ok(event)
     }
+
+    /// Builds a gauge for each measurement of a metric event, in either the
+    /// single (`metric_name` and `_value`) or multi-metric (`metric_name:<name>`)
+    /// format. The remaining fields and the default fields become tags.
+    fn build_metrics(
+        &self,
+        fields: JsonMap<String, JsonValue>,
+        log: LogEvent,
+    ) -> Result<Vec<Event>, Rejection> {
+        let timestamp = match log.get(log_schema().timestamp_key()) {
+            Some(Value::Timestamp(timestamp)) => Some(*timestamp),
+            _ => None,
+        };
+
+        let mut tags = BTreeMap::new();
+        for key in &[log_schema().host_key(), CHANNEL, INDEX, SOURCE, SOURCETYPE] {
+            if let Some(value) = log.get(*key) {
+                tags.insert((*key).to_owned(), value.to_string_lossy());
+            }
+        }
+
+        let mut measurements = Vec::new();
+        let mut name = None;
+        let mut value = None;
+        for (key, field) in fields {
+            if let Some(metric_name) = key.strip_prefix("metric_name:") {
+                measurements.push((metric_name.to_owned(), field));
+            } else if key == "metric_name" {
+                name = Some(field);
+            } else if key == "_value" {
+                value = Some(field);
+            } else {
+                let tag = match field {
+                    JsonValue::String(string) => string,
+                    other => other.to_string(),
+                };
+                tags.insert(key, tag);
+            }
+        }
+        match (name, value) {
+            (Some(JsonValue::String(name)), Some(value)) => measurements.push((name, value)),
+            (None, None) => (),
+            _ => return Err(ApiError::InvalidDataFormat { event: self.events }.into()),
+        }
+
+        measurements
+            .into_iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    JsonValue::Number(number) => number.as_f64(),
+                    JsonValue::String(string) => string.parse().ok(),
+                    _ => None,
+                }
+                .ok_or(ApiError::InvalidDataFormat { event: self.events })?;
+
+                emit!(SplunkHecEventReceived);
+                Ok(Event::Metric(
+                    Metric::new(name, MetricKind::Absolute, MetricValue::Gauge { value })
+                        .with_tags(Some(tags.clone()))
+                        .with_timestamp(timestamp),
+                ))
+            })
+            .collect()
+    }
 }
 
 impl<R: Read> Iterator for EventIterator<R> {
     type Item = Result<Event, Rejection>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.pending.pop_front() {
+            return Some(Ok(event));
+        }
+
         match self.from_reader_take::<JsonValue>() {
             Ok(Some(json)) => Some(self.build_event(json)),
             Ok(None) => {
@@ -660,6 +854,8 @@ pub(crate) enum ApiError {
     EmptyEventField { event: usize },
     MissingEventField { event: usize },
     BadRequest,
+    AckIsDisabled,
+    ServiceUnavailable,
 }
 
 impl warp::reject::Reject for ApiError {}
@@ -689,11 +885,21 @@ mod splunk_response {
             json_to_bytes(json!({"text":"unsupported content encoding"}));
         pub static ref NO_CHANNEL: Bytes =
             json_to_bytes(json!({"text":"Data channel is missing","code":10}));
+        pub static ref ACK_IS_DISABLED: Bytes =
+            json_to_bytes(json!({"text":"ACK is disabled","code":14}));
+        pub static ref SERVICE_UNAVAILABLE: Bytes =
+            json_to_bytes(json!({"text":"Server is busy","code":9}));
     }
 }
 
-fn finish_ok(_: ()) -> Response {
-    response_json(StatusCode::OK, splunk_response::SUCCESS.as_ref())
+fn finish_ok(ack_id: Option<u64>) -> Response {
+    match ack_id {
+        Some(ack_id) => response_json(
+            StatusCode::OK,
+            json!({"text":"Success","code":0,"ackId":ack_id}),
+        ),
+        None => response_json(StatusCode::OK, splunk_response::SUCCESS.as_ref()),
+    }
 }
 
 async fn finish_err(rejection: Rejection) -> Result<(Response,), Rejection> {
@@ -731,6 +937,14 @@ async fn finish_err(rejection: Rejection) -> Result<(Response,), Rejection> {
                 event_error("Event field is required", 12, event)
             }
             ApiError::BadRequest => empty_response(StatusCode::BAD_REQUEST),
+            ApiError::AckIsDisabled => response_json(
+                StatusCode::BAD_REQUEST,
+                splunk_response::ACK_IS_DISABLED.as_ref(),
+            ),
+            ApiError::ServiceUnavailable => response_json(
+                StatusCode::SERVICE_UNAVAILABLE,
+                splunk_response::SERVICE_UNAVAILABLE.as_ref(),
+            ),
         },))
     } else {
         Err(rejection)
@@ -772,10 +986,10 @@ fn event_error(text: &str, code: u16, event: usize) -> Response {
 #[cfg(feature = "sinks-splunk_hec")]
 #[cfg(test)]
 mod tests {
-    use super::{parse_timestamp, SplunkConfig};
+    use super::{parse_timestamp, SplunkConfig, CHANNEL};
     use crate::{
         config::{log_schema, SinkConfig, SinkContext, SourceConfig, SourceContext},
-        event::Event,
+        event::{Event, Metric, MetricKind, MetricValue},
         sinks::{
             splunk_hec::{Encoding, HecSinkConfig},
             util::{encoding::EncodingConfig, BatchConfig, Compression, TowerRequestConfig},
@@ -785,8 +999,10 @@ mod tests {
         Pipeline,
     };
     use chrono::{TimeZone, Utc};
-    use futures::{channel::mpsc, stream, StreamExt};
+    use futures::{channel::mpsc, stream, Stream, StreamExt};
+    use serde_json::Value as JsonValue;
     use std::{future::ready, net::SocketAddr};
+    use vector_core::event::EventStatus;
 
     #[test]
     fn generate_config() {
@@ -815,6 +1031,7 @@ mod tests {
                 token,
                 valid_tokens,
                 tls: None,
+                indexer_acknowledgements: Default::default(),
             }
             .build(SourceContext::new_test(sender))
             .await
@@ -826,6 +1043,23 @@ mod tests {
         (recv, address)
     }
 
+    async fn ack_source() -> (impl Stream<Item = Event> + Unpin, SocketAddr) {
+        let (sender, recv) = Pipeline::new_test_finalize(EventStatus::Delivered);
+        let address = next_addr();
+        let mut cx = SourceContext::new_test(sender);
+        cx.acknowledgements = true;
+        tokio::spawn(async move {
+            SplunkConfig::on(address)
+                .build(cx)
+                .await
+                .unwrap()
+                .await
+                .unwrap()
+        });
+        wait_for_tcp(address).await;
+        (recv, address)
+    }
+
     async fn sink(
         address: SocketAddr,
         encoding: impl Into<EncodingConfig<Encoding>>,
@@ -844,6 +1078,7 @@ mod tests {
             batch: BatchConfig::default(),
             request: TowerRequestConfig::default(),
             tls: None,
+            indexer_acknowledgements: Default::default(),
         }
         .build(SinkContext::new_test())
         .await
@@ -1188,6 +1423,103 @@ mod tests {
         assert_eq!(event.as_log()[&super::CHANNEL], "guid".into());
     }
 
+    #[tokio::test]
+    async fn multi_metric_event() {
+        trace_init();
+
+        let message = r#"{"time":1627819200,"event":"metric","host":"example.com","fields":{"region":"us-west-1","metric_name:cpu.usr":11.12,"metric_name:cpu.sys":"12.23"}}"#;
+        let (source, address) = source().await;
+
+        assert_eq!(
+            200,
+            post(address, "services/collector/event", message).await
+        );
+
+        let mut metrics = collect_n(source, 2)
+            .await
+            .into_iter()
+            .map(Event::into_metric)
+            .collect::<Vec<_>>();
+        metrics.sort_by(|a, b| a.name().cmp(b.name()));
+
+        assert_eq!(metrics[0].name(), "cpu.sys");
+        assert_eq!(metrics[0].value(), &MetricValue::Gauge { value: 12.23 });
+        assert_eq!(metrics[1].name(), "cpu.usr");
+        assert_eq!(metrics[1].value(), &MetricValue::Gauge { value: 11.12 });
+        for metric in &metrics {
+            assert_eq!(metric.kind(), MetricKind::Absolute);
+            assert_eq!(
+                metric.timestamp(),
+                Some(Utc.ymd(2021, 8, 1).and_hms(12, 0, 0))
+            );
+            assert_eq!(metric.tag_value("region"), Some("us-west-1".to_owned()));
+            assert_eq!(metric.tag_value("host"), Some("example.com".to_owned()));
+            assert_eq!(metric.tag_value(CHANNEL), Some("channel".to_owned()));
+        }
+    }
+
+    #[tokio::test]
+    async fn metric_round_trip() {
+        trace_init();
+
+        let metric = Metric::new(
+            "requests",
+            MetricKind::Incremental,
+            MetricValue::Counter { value: 42.0 },
+        )
+        .with_namespace(Some("http"))
+        .with_tags(Some(
+            vec![("status".to_owned(), "200".to_owned())]
+                .into_iter()
+                .collect(),
+        ));
+        let (sink, source) = start(Encoding::Json, Compression::gzip_default()).await;
+
+        let event = channel_n(vec![Event::Metric(metric)], sink, source)
+            .await
+            .remove(0);
+
+        let metric = event.as_metric();
+        assert_eq!(metric.name(), "http.requests");
+        assert_eq!(metric.value(), &MetricValue::Gauge { value: 42.0 });
+        assert_eq!(metric.tag_value("status"), Some("200".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn single_metric_event() {
+        trace_init();
+
+        let message = r#"{"event":"metric","fields":{"metric_name":"memory.free","_value":512}}{"event":"first"}"#;
+        let (source, address) = source().await;
+
+        assert_eq!(
+            200,
+            post(address, "services/collector/event", message).await
+        );
+
+        let events = collect_n(source, 2).await;
+        let metric = events[0].as_metric();
+        assert_eq!(metric.name(), "memory.free");
+        assert_eq!(metric.value(), &MetricValue::Gauge { value: 512.0 });
+        assert_eq!(
+            events[1].as_log()[log_schema().message_key()],
+            "first".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_metric_value() {
+        trace_init();
+
+        let message = r#"{"event":"metric","fields":{"metric_name:cpu.usr":"high"}}"#;
+        let (_source, address) = source().await;
+
+        assert_eq!(
+            400,
+            post(address, "services/collector/event", message).await
+        );
+    }
+
     #[tokio::test]
     async fn no_data() {
         trace_init();
@@ -1197,6 +1529,77 @@ mod tests {
         assert_eq!(400, post(address, "services/collector/event", "").await);
     }
 
+    async fn post_json(address: SocketAddr, api: &str, body: &str) -> (u16, JsonValue) {
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/{}", address, api))
+            .header("Authorization", format!("Splunk {}", TOKEN))
+            .header("x-splunk-request-channel", "channel")
+            .body(body.to_owned())
+            .send()
+            .await
+            .unwrap();
+        let status = response.status().as_u16();
+        (status, response.json().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn ack_is_disabled() {
+        trace_init();
+
+        let (_source, address) = source().await;
+
+        let (status, body) = post_json(address, "services/collector/ack", r#"{"acks":[0]}"#).await;
+        assert_eq!(400, status);
+        assert_eq!(body["code"], 14);
+    }
+
+    #[tokio::test]
+    async fn event_indexer_acknowledgement() {
+        trace_init();
+
+        let (source, address) = ack_source().await;
+
+        let (status, body) =
+            post_json(address, "services/collector/event", r#"{"event":"first"}"#).await;
+        assert_eq!(200, status);
+        let ack_id = body["ackId"].as_u64().unwrap();
+
+        let event = collect_n(source, 1).await.remove(0);
+        assert_eq!(event.as_log()[log_schema().message_key()], "first".into());
+        // The event is finalized once dropped.
+        drop(event);
+
+        let ack = format!(r#"{{"acks":[{}]}}"#, ack_id);
+        let mut acked = false;
+        for _ in 0..10 {
+            let (status, body) = post_json(address, "services/collector/ack", &ack).await;
+            assert_eq!(200, status);
+            acked = body["acks"][ack_id.to_string()].as_bool().unwrap();
+            if acked {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        assert!(acked);
+
+        // Ack ids are only reported once.
+        let (_, body) = post_json(address, "services/collector/ack", &ack).await;
+        assert_eq!(body["acks"][ack_id.to_string()], false);
+    }
+
+    #[tokio::test]
+    async fn raw_indexer_acknowledgement() {
+        trace_init();
+
+        let (_source, address) = ack_source().await;
+
+        let (status, body) = post_json(address, "services/collector/raw", "raw").await;
+        assert_eq!(200, status);
+        assert_eq!(body["ackId"], 0);
+        let (_, body) = post_json(address, "services/collector/raw", "raw").await;
+        assert_eq!(body["ackId"], 1);
+    }
+
     #[tokio::test]
     async fn invalid_token() {
         trace_init();
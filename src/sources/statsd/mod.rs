@@ -21,7 +21,7 @@ pub mod parser;
 #[cfg(unix)]
 mod unix;
 
-use parser::parse;
+use parser::parse_packet;
 #[cfg(unix)]
 use unix::{statsd_unix, UnixConfig};
 
@@ -116,7 +116,7 @@ impl SourceConfig for StatsdConfig {
     }
 
     fn output_type(&self) -> config::DataType {
-        config::DataType::Metric
+        config::DataType::Any
     }
 
     fn source_type(&self) -> &'static str {
@@ -134,12 +134,12 @@ impl SourceConfig for StatsdConfig {
 }
 
 pub(self) fn parse_event(line: &str) -> Option<Event> {
-    match parse(line) {
-        Ok(metric) => {
+    match parse_packet(line) {
+        Ok(event) => {
             emit!(StatsdEventReceived {
                 byte_size: line.len()
             });
-            Some(Event::Metric(metric))
+            Some(event)
         }
         Err(error) => {
             emit!(StatsdInvalidRecord { error, text: line });
@@ -314,6 +314,8 @@ mod test {
                 buckets: vec![1.0, 2.0, 4.0],
                 quantiles: vec![],
                 flush_period_secs: 1,
+                ttl_secs: None,
+                distributions_as_summaries: false,
             },
         );
 
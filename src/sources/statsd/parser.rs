@@ -1,4 +1,11 @@
-use crate::event::metric::{Metric, MetricKind, MetricValue, StatisticKind};
+use crate::{
+    config::log_schema,
+    event::{
+        metric::{Metric, MetricKind, MetricValue, StatisticKind},
+        Event, LogEvent, Value,
+    },
+};
+use chrono::{DateTime, TimeZone, Utc};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::{
@@ -12,6 +19,18 @@ lazy_static! {
     static ref NONALPHANUM: Regex = Regex::new(r"[^a-zA-Z_\-0-9\.]").unwrap();
 }
 
+/// Parses a line of the DogStatsD protocol, where events and service checks
+/// become log events.
+pub fn parse_packet(packet: &str) -> Result<Event, ParseError> {
+    if packet.starts_with("_e{") {
+        parse_event(packet).map(Event::Log)
+    } else if packet.starts_with("_sc|") {
+        parse_service_check(packet).map(Event::Log)
+    } else {
+        parse(packet).map(Event::Metric)
+    }
+}
+
 pub fn parse(packet: &str) -> Result<Metric, ParseError> {
     // https://docs.datadoghq.com/developers/dogstatsd/datagram_shell/#datagram-format
     let key_and_body = packet.splitn(2, ':').collect::<Vec<_>>();
@@ -32,26 +51,24 @@ pub fn parse(packet: &str) -> Result<Metric, ParseError> {
     let name = sanitize_key(key);
     let metric_type = parts[1];
 
-    // sampling part is optional and comes after metric type part
-    let sampling = parts.get(2).filter(|s| s.starts_with('@'));
-    let sample_rate = if let Some(s) = sampling {
-        1.0 / sanitize_sampling(parse_sampling(s)?)
-    } else {
-        1.0
-    };
-
-    // tags are optional and could be found either after sampling of after metric type part
-    let tags = if sampling.is_none() {
-        parts.get(2)
-    } else {
-        parts.get(3)
-    };
-    let tags = tags.filter(|s| s.starts_with('#'));
-    let tags = if let Some(t) = tags {
-        Some(parse_tags(t)?)
-    } else {
-        None
-    };
+    // sampling, tags, container id and timestamp parts are optional and
+    // come after the metric type part
+    let mut sample_rate = 1.0;
+    let mut tags = None;
+    let mut timestamp = None;
+    for part in &parts[2..] {
+        if part.starts_with('@') {
+            sample_rate = 1.0 / sanitize_sampling(parse_sampling(part)?);
+        } else if part.starts_with('#') {
+            let parsed = parse_tags(part)?;
+            tags.get_or_insert_with(BTreeMap::new).extend(parsed);
+        } else if let Some(container_id) = part.strip_prefix("c:") {
+            tags.get_or_insert_with(BTreeMap::new)
+                .insert("container_id".to_owned(), container_id.to_owned());
+        } else if let Some(ts) = part.strip_prefix('T') {
+            timestamp = Some(parse_timestamp(ts)?);
+        }
+    }
 
     let metric = match metric_type {
         "c" => {
@@ -109,7 +126,117 @@ pub fn parse(packet: &str) -> Result<Metric, ParseError> {
         .with_tags(tags),
         other => return Err(ParseError::UnknownMetricType(other.into())),
     };
-    Ok(metric)
+    Ok(metric.with_timestamp(timestamp))
+}
+
+fn parse_event(packet: &str) -> Result<LogEvent, ParseError> {
+    // _e{<TITLE_LENGTH>,<TEXT_LENGTH>}:<TITLE>|<TEXT>|d:<TIMESTAMP>|h:<HOSTNAME>|...
+    // https://docs.datadoghq.com/developers/dogstatsd/datagram_shell/#events
+    let (lengths, body) = packet["_e{".len()..]
+        .split_once("}:")
+        .ok_or(ParseError::Malformed("event should have lengths and body"))?;
+    let (title_length, text_length) = lengths.split_once(',').ok_or(ParseError::Malformed(
+        "event lengths should be comma separated",
+    ))?;
+    let (title_length, text_length): (usize, usize) = (title_length.parse()?, text_length.parse()?);
+
+    let title = body.get(..title_length).ok_or(ParseError::Malformed(
+        "event title is shorter than its length",
+    ))?;
+    let text = body
+        .get(title_length..)
+        .and_then(|rest| rest.strip_prefix('|'))
+        .and_then(|rest| rest.get(..text_length))
+        .ok_or(ParseError::Malformed(
+            "event text is shorter than its length",
+        ))?;
+    let rest = &body[title_length + 1 + text_length..];
+    if !rest.is_empty() && !rest.starts_with('|') {
+        return Err(ParseError::Malformed(
+            "event text is longer than its length",
+        ));
+    }
+
+    let mut log = LogEvent::default();
+    log.insert("title", title);
+    log.insert(log_schema().message_key(), text.replace("\\n", "\n"));
+
+    for part in rest.split('|').skip(1) {
+        if let Some(timestamp) = part.strip_prefix("d:") {
+            log.insert(log_schema().timestamp_key(), parse_timestamp(timestamp)?);
+        } else if let Some(host) = part.strip_prefix("h:") {
+            log.insert(log_schema().host_key(), host);
+        } else if let Some(aggregation_key) = part.strip_prefix("k:") {
+            log.insert("aggregation_key", aggregation_key);
+        } else if let Some(priority) = part.strip_prefix("p:") {
+            log.insert("priority", priority);
+        } else if let Some(source_type_name) = part.strip_prefix("s:") {
+            log.insert("source_type_name", source_type_name);
+        } else if let Some(alert_type) = part.strip_prefix("t:") {
+            log.insert("alert_type", alert_type);
+        } else if let Some(container_id) = part.strip_prefix("c:") {
+            log.insert("container_id", container_id);
+        } else if part.starts_with('#') {
+            log.insert("tags", parse_tag_list(part)?);
+        }
+    }
+    if !log.contains(log_schema().timestamp_key()) {
+        log.insert(log_schema().timestamp_key(), Utc::now());
+    }
+
+    Ok(log)
+}
+
+fn parse_service_check(packet: &str) -> Result<LogEvent, ParseError> {
+    // _sc|<NAME>|<STATUS>|d:<TIMESTAMP>|h:<HOSTNAME>|#<TAGS>|m:<MESSAGE>
+    // https://docs.datadoghq.com/developers/dogstatsd/datagram_shell/#service-checks
+    let parts = packet.split('|').collect::<Vec<_>>();
+    if parts.len() < 3 {
+        return Err(ParseError::Malformed(
+            "service check should have a name and a status",
+        ));
+    }
+    let status: i64 = parts[2].parse()?;
+    if !(0..=3).contains(&status) {
+        return Err(ParseError::Malformed(
+            "service check status should be between 0 and 3",
+        ));
+    }
+
+    let mut log = LogEvent::default();
+    log.insert("check", parts[1]);
+    log.insert("status", status);
+
+    for (index, part) in parts.iter().enumerate().skip(3) {
+        if let Some(timestamp) = part.strip_prefix("d:") {
+            log.insert(log_schema().timestamp_key(), parse_timestamp(timestamp)?);
+        } else if let Some(host) = part.strip_prefix("h:") {
+            log.insert(log_schema().host_key(), host);
+        } else if let Some(container_id) = part.strip_prefix("c:") {
+            log.insert("container_id", container_id);
+        } else if part.starts_with('#') {
+            log.insert("tags", parse_tag_list(part)?);
+        } else if part.starts_with("m:") {
+            // The message comes last, and may contain pipes.
+            let message = parts[index..].join("|");
+            log.insert(
+                log_schema().message_key(),
+                message["m:".len()..].replace("\\n", "\n"),
+            );
+            break;
+        }
+    }
+    if !log.contains(log_schema().timestamp_key()) {
+        log.insert(log_schema().timestamp_key(), Utc::now());
+    }
+
+    Ok(log)
+}
+
+fn parse_timestamp(input: &str) -> Result<DateTime<Utc>, ParseError> {
+    Utc.timestamp_opt(input.parse()?, 0)
+        .single()
+        .ok_or(ParseError::Malformed("timestamp out of range"))
 }
 
 fn parse_sampling(input: &str) -> Result<f64, ParseError> {
@@ -150,6 +277,22 @@ fn parse_tags(input: &str) -> Result<BTreeMap<String, String>, ParseError> {
     Ok(result)
 }
 
+/// Tags of events and service checks are kept as a list, the way the
+/// Datadog API expects them.
+fn parse_tag_list(input: &str) -> Result<Value, ParseError> {
+    if input.len() < 2 {
+        return Err(ParseError::Malformed(
+            "expected non empty '#'-prefixed tags component",
+        ));
+    }
+
+    Ok(input[1..]
+        .split(',')
+        .map(|tag| Value::from(tag.to_owned()))
+        .collect::<Vec<_>>()
+        .into())
+}
+
 fn parse_direction(input: &str) -> Result<Option<f64>, ParseError> {
     match input
         .chars()
@@ -224,8 +367,15 @@ impl From<ParseFloatError> for ParseError {
 
 #[cfg(test)]
 mod test {
-    use super::{parse, sanitize_key, sanitize_sampling};
-    use crate::event::metric::{Metric, MetricKind, MetricValue, StatisticKind};
+    use super::{parse, parse_packet, sanitize_key, sanitize_sampling, ParseError};
+    use crate::{
+        config::log_schema,
+        event::{
+            metric::{Metric, MetricKind, MetricValue, StatisticKind},
+            Value,
+        },
+    };
+    use chrono::{TimeZone, Utc};
     use shared::assert_event_data_eq;
 
     #[test]
@@ -393,6 +543,128 @@ mod test {
         );
     }
 
+    #[test]
+    fn container_id_and_timestamp() {
+        assert_event_data_eq!(
+            parse("page.views:1|c|#env:prod|T1656581400|c:83c0a99c0a54c0c187f461c7980e9b57f3f6a8b0c918c8d93df19a9de6f3fe1d"),
+            Ok(Metric::new(
+                "page.views",
+                MetricKind::Incremental,
+                MetricValue::Counter { value: 1.0 },
+            )
+            .with_tags(Some(
+                vec![
+                    ("env".to_owned(), "prod".to_owned()),
+                    (
+                        "container_id".to_owned(),
+                        "83c0a99c0a54c0c187f461c7980e9b57f3f6a8b0c918c8d93df19a9de6f3fe1d"
+                            .to_owned()
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+            ))
+            .with_timestamp(Some(Utc.timestamp(1656581400, 0)))),
+        );
+    }
+
+    #[test]
+    fn tags_before_sampling() {
+        assert_event_data_eq!(
+            parse("glork:320|d|#region:us-west1|@0.5"),
+            Ok(Metric::new(
+                "glork",
+                MetricKind::Incremental,
+                MetricValue::Distribution {
+                    samples: vector_core::samples![320.0 => 2],
+                    statistic: StatisticKind::Summary
+                },
+            )
+            .with_tags(Some(
+                vec![("region".to_owned(), "us-west1".to_owned())]
+                    .into_iter()
+                    .collect(),
+            ))),
+        );
+    }
+
+    #[test]
+    fn event() {
+        let event = parse_packet(
+            "_e{21,36}:An exception occurred|Cannot parse CSV file from 10.0.0.17|d:1656581400|h:web-1|k:csv|p:low|s:vector|t:warning|#env:prod,critical",
+        )
+        .unwrap();
+        let log = event.as_log();
+
+        assert_eq!(log["title"], "An exception occurred".into());
+        assert_eq!(
+            log[log_schema().message_key()],
+            "Cannot parse CSV file from 10.0.0.17".into()
+        );
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Utc.timestamp(1656581400, 0).into()
+        );
+        assert_eq!(log[log_schema().host_key()], "web-1".into());
+        assert_eq!(log["aggregation_key"], "csv".into());
+        assert_eq!(log["priority"], "low".into());
+        assert_eq!(log["source_type_name"], "vector".into());
+        assert_eq!(log["alert_type"], "warning".into());
+        assert_eq!(
+            log["tags"],
+            Value::from(vec!["env:prod".to_owned(), "critical".to_owned()])
+        );
+    }
+
+    #[test]
+    fn event_text_with_pipes_and_newlines() {
+        let event = parse_packet("_e{5,8}:title|a|b\\nc|d|#tag").unwrap();
+        let log = event.as_log();
+
+        assert_eq!(log["title"], "title".into());
+        assert_eq!(log[log_schema().message_key()], "a|b\nc|d".into());
+        assert_eq!(log["tags"], Value::from(vec!["tag".to_owned()]));
+    }
+
+    #[test]
+    fn malformed_event() {
+        assert_eq!(
+            parse_packet("_e{20,4}:title|text").unwrap_err(),
+            ParseError::Malformed("event title is shorter than its length")
+        );
+        assert_eq!(
+            parse_packet("_e{5,2}:title|text").unwrap_err(),
+            ParseError::Malformed("event text is longer than its length")
+        );
+    }
+
+    #[test]
+    fn service_check() {
+        let event = parse_packet(
+            "_sc|Redis connection|2|d:1656581400|h:web-1|#env:dev|m:Redis connection timed out | after 10s",
+        )
+        .unwrap();
+        let log = event.as_log();
+
+        assert_eq!(log["check"], "Redis connection".into());
+        assert_eq!(log["status"], 2.into());
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Utc.timestamp(1656581400, 0).into()
+        );
+        assert_eq!(log[log_schema().host_key()], "web-1".into());
+        assert_eq!(log["tags"], Value::from(vec!["env:dev".to_owned()]));
+        assert_eq!(
+            log[log_schema().message_key()],
+            "Redis connection timed out | after 10s".into()
+        );
+
+        assert_eq!(
+            parse_packet("_sc|Redis connection|4").unwrap_err(),
+            ParseError::Malformed("service check status should be between 0 and 3")
+        );
+    }
+
     #[test]
     fn sanitizing_keys() {
         assert_eq!("foo-bar-baz", sanitize_key("foo/bar/baz"));
@@ -1,4 +1,4 @@
-use crate::event::BatchStatusReceiver;
+use crate::event::{BatchStatus, BatchStatusReceiver};
 use crate::shutdown::ShutdownSignal;
 use futures::{future::Shared, stream::FuturesOrdered, FutureExt, StreamExt};
 use std::future::Future;
@@ -10,7 +10,7 @@ use tokio::sync::mpsc;
 /// events from a source as done in a single background task *in the
 /// order they are received from the source*. The type `T` is the
 /// source-specific data associated with each entry to be used to
-/// complete the finalization.
+/// complete the finalization, along with the status of its batch.
 pub(crate) struct OrderedFinalizer<T> {
     sender: Option<mpsc::UnboundedSender<(BatchStatusReceiver, T)>>,
 }
@@ -18,7 +18,7 @@ pub(crate) struct OrderedFinalizer<T> {
 impl<T: Send + 'static> OrderedFinalizer<T> {
     pub(crate) fn new(
         shutdown: Shared<ShutdownSignal>,
-        apply_done: impl Fn(T) + Send + 'static,
+        apply_done: impl Fn(BatchStatus, T) + Send + 'static,
     ) -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
         tokio::spawn(run_finalizer(shutdown, receiver, apply_done));
@@ -39,7 +39,7 @@ impl<T: Send + 'static> OrderedFinalizer<T> {
 async fn run_finalizer<T>(
     shutdown: Shared<ShutdownSignal>,
     mut new_entries: mpsc::UnboundedReceiver<(BatchStatusReceiver, T)>,
-    apply_done: impl Fn(T),
+    apply_done: impl Fn(BatchStatus, T),
 ) {
     let mut status_receivers = FuturesOrdered::default();
 
@@ -56,7 +56,7 @@ async fn run_finalizer<T>(
                 None => break,
             },
             finished = status_receivers.next(), if !status_receivers.is_empty() => match finished {
-                Some((_status, entry)) => apply_done(entry),
+                Some((status, entry)) => apply_done(status, entry),
                 // The is_empty guard above prevents this from being reachable.
                 None => unreachable!(),
             },
@@ -65,8 +65,8 @@ async fn run_finalizer<T>(
     // We've either seen a shutdown signal or the new entry sender was
     // closed. Wait for the last statuses to come in before indicating
     // we are done.
-    while let Some((_status, entry)) = status_receivers.next().await {
-        apply_done(entry);
+    while let Some((status, entry)) = status_receivers.next().await {
+        apply_done(status, entry);
     }
     drop(shutdown);
 }
@@ -12,15 +12,23 @@ use headers::{Authorization, HeaderMapExt};
 use serde::{Deserialize, Serialize};
 use snap::raw::Decoder as SnappyDecoder;
 use std::{
-    collections::HashMap, convert::TryFrom, error::Error, fmt, io::Read, net::SocketAddr, sync::Arc,
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    error::Error,
+    fmt,
+    io::Read,
+    net::SocketAddr,
+    sync::Arc,
 };
 use vector_core::event::{BatchNotifier, BatchStatus, BatchStatusReceiver, Event};
 use warp::{
-    filters::{path::FullPath, path::Tail, BoxedFilter},
+    filters::path::FullPath,
     http::{HeaderMap, StatusCode},
     reject::Rejection,
+    reply::Response,
     Filter,
 };
+use zstd::stream::read::Decoder as ZstdDecoder;
 
 #[cfg(any(feature = "sources-http", feature = "sources-heroku_logs"))]
 pub(crate) fn add_query_parameters(
@@ -70,6 +78,120 @@ impl fmt::Debug for RejectShuttingDown {
 }
 impl warp::reject::Reject for RejectShuttingDown {}
 
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Literal(String),
+    Param(String),
+}
+
+/// A URL path pattern accepted by an `HttpSource`.
+///
+/// Segments written as `{name}` match any single path segment and are
+/// captured under `name`. A non-strict pattern also accepts any path
+/// that merely starts with it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathPattern {
+    segments: Vec<PathSegment>,
+    strict: bool,
+}
+
+impl PathPattern {
+    pub fn new(path: &str, strict: bool) -> Self {
+        let segments = split_path(path)
+            .map(|segment| {
+                match segment
+                    .strip_prefix('{')
+                    .and_then(|segment| segment.strip_suffix('}'))
+                {
+                    Some(name) if !name.is_empty() => PathSegment::Param(name.to_owned()),
+                    _ => PathSegment::Literal(segment.to_owned()),
+                }
+            })
+            .collect();
+        Self { segments, strict }
+    }
+
+    /// Matches `path` against this pattern, returning the captured
+    /// parameters on success.
+    pub fn matches(&self, path: &str) -> Option<BTreeMap<String, String>> {
+        let mut params = BTreeMap::new();
+        let mut parts = split_path(path);
+        for segment in &self.segments {
+            let part = parts.next()?;
+            match segment {
+                PathSegment::Literal(literal) if literal != part => return None,
+                PathSegment::Literal(_) => (),
+                PathSegment::Param(name) => {
+                    params.insert(name.clone(), part.to_owned());
+                }
+            }
+        }
+        if self.strict && parts.next().is_some() {
+            return None;
+        }
+        Some(params)
+    }
+}
+
+fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+/// The response sent back for a request matched by an `HttpSourceRoute`.
+#[derive(Debug, Clone)]
+pub struct HttpReply {
+    status: StatusCode,
+    body: Bytes,
+}
+
+impl HttpReply {
+    pub fn new(status: StatusCode, body: impl Into<Bytes>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+        }
+    }
+
+    fn to_response(&self) -> Response {
+        let mut response = Response::new(self.body.clone().into());
+        *response.status_mut() = self.status;
+        response
+    }
+}
+
+impl Default for HttpReply {
+    fn default() -> Self {
+        Self::new(StatusCode::OK, Bytes::new())
+    }
+}
+
+/// A path served by an `HttpSource` together with the reply sent on success.
+#[derive(Debug, Clone)]
+pub struct HttpSourceRoute {
+    pattern: PathPattern,
+    reply: HttpReply,
+}
+
+impl HttpSourceRoute {
+    pub fn new(pattern: PathPattern) -> Self {
+        Self {
+            pattern,
+            reply: HttpReply::default(),
+        }
+    }
+
+    pub fn with_reply(mut self, reply: HttpReply) -> Self {
+        self.reply = reply;
+        self
+    }
+}
+
+fn find_route<'a>(routes: &'a [HttpSourceRoute], path: &str) -> Option<&'a HttpSourceRoute> {
+    routes
+        .iter()
+        .find(|route| route.pattern.matches(path).is_some())
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct HttpSourceAuthConfig {
     pub username: String,
@@ -150,6 +272,13 @@ pub fn decode(header: &Option<String>, mut body: Bytes) -> Result<Bytes, ErrorMe
                     .decompress_vec(&body)
                     .map_err(|error| handle_decode_error(encoding, error))?
                     .into(),
+                "zstd" => {
+                    let mut decoded = Vec::new();
+                    ZstdDecoder::new(body.reader())
+                        .and_then(|mut decoder| decoder.read_to_end(&mut decoded))
+                        .map_err(|error| handle_decode_error(encoding, error))?;
+                    decoded.into()
+                }
                 encoding => {
                     return Err(ErrorMessage::new(
                         StatusCode::UNSUPPORTED_MEDIA_TYPE,
@@ -192,34 +321,50 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
         tls: &Option<TlsConfig>,
         auth: &Option<HttpSourceAuthConfig>,
         cx: SourceContext,
+    ) -> crate::Result<crate::sources::Source> {
+        let route = HttpSourceRoute::new(PathPattern::new(path, strict_path));
+        self.run_routes(address, vec![route], Vec::new(), tls, auth, cx)
+    }
+
+    /// Serves `POST` requests on every path of `routes`, and answers `GET`
+    /// and `HEAD` requests on `static_routes` with their configured reply
+    /// without producing any events.
+    fn run_routes(
+        self,
+        address: SocketAddr,
+        routes: Vec<HttpSourceRoute>,
+        static_routes: Vec<HttpSourceRoute>,
+        tls: &Option<TlsConfig>,
+        auth: &Option<HttpSourceAuthConfig>,
+        cx: SourceContext,
     ) -> crate::Result<crate::sources::Source> {
         let tls = MaybeTlsSettings::from_config(tls, true)?;
         let auth = HttpSourceAuth::try_from(auth.as_ref())?;
-        let path = path.to_owned();
+        let routes = Arc::new(routes);
+        let static_routes = Arc::new(static_routes);
         let out = cx.out;
         let shutdown = cx.shutdown;
         let acknowledgements = cx.acknowledgements;
         Ok(Box::pin(async move {
             let span = crate::trace::current_span();
-            let mut filter: BoxedFilter<()> = warp::post().boxed();
-            for s in path.split('/').filter(|&x| !x.is_empty()) {
-                filter = filter.and(warp::path(s.to_string())).boxed()
-            }
-            let svc = filter
-                .and(warp::path::tail())
-                .and_then(move |tail: Tail| async move {
-                    if !strict_path || tail.as_str().is_empty() {
-                        Ok(())
-                    } else {
-                        debug!(message = "Path rejected.");
-                        Err(warp::reject::custom(ErrorMessage::new(
-                            StatusCode::NOT_FOUND,
-                            "Not found".to_string(),
-                        )))
+            let svc = warp::post()
+                .and(warp::path::full())
+                .and_then(move |path: FullPath| {
+                    let routes = Arc::clone(&routes);
+                    async move {
+                        match find_route(&routes, path.as_str()) {
+                            Some(route) => Ok((path, route.reply.clone())),
+                            None => {
+                                debug!(message = "Path rejected.");
+                                Err(warp::reject::custom(ErrorMessage::new(
+                                    StatusCode::NOT_FOUND,
+                                    "Not found".to_string(),
+                                )))
+                            }
+                        }
                     }
                 })
                 .untuple_one()
-                .and(warp::path::full())
                 .and(warp::header::optional::<String>("authorization"))
                 .and(warp::header::optional::<String>("content-encoding"))
                 .and(warp::header::headers_cloned())
@@ -227,6 +372,7 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
                 .and(warp::query::<HashMap<String, String>>())
                 .and_then(
                     move |path: FullPath,
+                          reply: HttpReply,
                           auth_header,
                           encoding_header,
                           headers: HeaderMap,
@@ -243,25 +389,41 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
                                     .map(|events| (events, body_len))
                             });
 
-                        handle_request(events, acknowledgements, out.clone())
+                        handle_request(events, reply, acknowledgements, out.clone())
                     },
                 )
                 .with(warp::trace(move |_info| span.clone()));
 
+            let static_svc = warp::get()
+                .or(warp::head())
+                .unify()
+                .and(warp::path::full())
+                .and_then(move |path: FullPath| {
+                    let static_routes = Arc::clone(&static_routes);
+                    async move {
+                        find_route(&static_routes, path.as_str())
+                            .map(|route| route.reply.to_response())
+                            .ok_or_else(warp::reject::not_found)
+                    }
+                });
+
             let ping = warp::get().and(warp::path("ping")).map(|| "pong");
-            let routes = svc.or(ping).recover(|r: Rejection| async move {
-                if let Some(e_msg) = r.find::<ErrorMessage>() {
-                    let json = warp::reply::json(e_msg);
-                    Ok(warp::reply::with_status(
-                        json,
-                        StatusCode::from_u16(e_msg.code)
-                            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-                    ))
-                } else {
-                    //other internal error - will return 500 internal server error
-                    Err(r)
-                }
-            });
+            let routes = svc
+                .or(static_svc)
+                .or(ping)
+                .recover(|r: Rejection| async move {
+                    if let Some(e_msg) = r.find::<ErrorMessage>() {
+                        let json = warp::reply::json(e_msg);
+                        Ok(warp::reply::with_status(
+                            json,
+                            StatusCode::from_u16(e_msg.code)
+                                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                        ))
+                    } else {
+                        //other internal error - will return 500 internal server error
+                        Err(r)
+                    }
+                });
 
             info!(message = "Building HTTP server.", address = %address);
 
@@ -279,6 +441,7 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
 
 async fn handle_request(
     events: Result<(Vec<Event>, usize), ErrorMessage>,
+    reply: HttpReply,
     acknowledgements: bool,
     mut out: Pipeline,
 ) -> Result<impl warp::Reply, Rejection> {
@@ -305,7 +468,7 @@ async fn handle_request(
                     error!(message = "Tried to send the following event.", %error);
                     warp::reject::custom(RejectShuttingDown)
                 })
-                .and_then(|_| handle_batch_status(receiver))
+                .and_then(|_| handle_batch_status(receiver, reply))
                 .await
         }
         Err(error) => {
@@ -320,11 +483,12 @@ async fn handle_request(
 
 async fn handle_batch_status(
     receiver: Option<BatchStatusReceiver>,
+    reply: HttpReply,
 ) -> Result<impl warp::Reply, Rejection> {
     match receiver {
-        None => Ok(warp::reply()),
+        None => Ok(reply.to_response()),
         Some(receiver) => match receiver.await {
-            BatchStatus::Delivered => Ok(warp::reply()),
+            BatchStatus::Delivered => Ok(reply.to_response()),
             BatchStatus::Errored => Err(warp::reject::custom(ErrorMessage::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Error delivering contents to sink".into(),
@@ -1,7 +1,11 @@
-#[cfg(any(feature = "sources-http"))]
+#[cfg(any(feature = "sources-http", feature = "sources-http_client"))]
 mod body_decoding;
 mod encoding_config;
-#[cfg(any(feature = "sources-file", feature = "sources-kafka"))]
+#[cfg(any(
+    feature = "sources-file",
+    feature = "sources-kafka",
+    feature = "sources-mqtt"
+))]
 pub(crate) mod finalizer;
 #[cfg(all(unix, feature = "sources-dnstap"))]
 pub mod framestream;
@@ -15,7 +19,7 @@ mod unix_datagram;
 #[cfg(all(unix, feature = "sources-utils-unix"))]
 mod unix_stream;
 
-#[cfg(any(feature = "sources-http"))]
+#[cfg(any(feature = "sources-http", feature = "sources-http_client"))]
 pub(crate) use self::body_decoding::{decode_body, Encoding};
 #[cfg(any(feature = "sources-http", feature = "sources-heroku_logs"))]
 pub(crate) use self::http::add_query_parameters;
@@ -23,6 +27,8 @@ pub(crate) use self::http::add_query_parameters;
 pub(crate) use self::http::decode;
 #[cfg(feature = "sources-utils-http")]
 pub(crate) use self::http::{ErrorMessage, HttpSource, HttpSourceAuthConfig};
+#[cfg(feature = "sources-http")]
+pub(crate) use self::http::{HttpReply, HttpSourceRoute, PathPattern};
 pub use encoding_config::EncodingConfig;
 pub use multiline_config::MultilineConfig;
 #[cfg(all(feature = "sources-utils-tls", feature = "listenfd"))]
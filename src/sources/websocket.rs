@@ -0,0 +1,390 @@
+use crate::{
+    config::{
+        log_schema, DataType, GenerateConfig, Resource, SourceConfig, SourceContext,
+        SourceDescription,
+    },
+    event::{Event, LogEvent},
+    internal_events::{
+        ConnectionOpen, OpenGauge, WebSocketConnectionError, WebSocketConnectionEstablished,
+        WebSocketInvalidMessage, WebSocketMessageReceived,
+    },
+    shutdown::ShutdownSignal,
+    tls::{MaybeTlsIncomingStream, MaybeTlsSettings, TlsConfig},
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::Utc;
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    accept_async_with_config,
+    tungstenite::{protocol::WebSocketConfig as ProtocolConfig, Message},
+};
+
+#[derive(Debug, Snafu)]
+pub enum WebSocketDecodeError {
+    #[snafu(display("Invalid JSON: {}", source))]
+    InvalidJson { source: serde_json::Error },
+    #[snafu(display("Expected a JSON object or an array of objects"))]
+    NotAnObject,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WebSocketConfig {
+    address: SocketAddr,
+    tls: Option<TlsConfig>,
+    #[serde(default)]
+    encoding: Encoding,
+    /// Messages larger than this are rejected, closing their connection.
+    #[serde(default = "default_max_length")]
+    max_length: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Derivative, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    /// Each message becomes the message of an event.
+    #[derivative(Default)]
+    Text,
+    /// Each message is a JSON object, or an array of JSON objects, giving
+    /// the fields of the events.
+    Json,
+}
+
+fn default_max_length() -> usize {
+    bytesize::mib(1u64) as usize
+}
+
+inventory::submit! {
+    SourceDescription::new::<WebSocketConfig>("websocket")
+}
+
+impl GenerateConfig for WebSocketConfig {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(Self {
+            address: SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), 8080)),
+            tls: None,
+            encoding: Encoding::default(),
+            max_length: default_max_length(),
+        })
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "websocket")]
+impl SourceConfig for WebSocketConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let tls = MaybeTlsSettings::from_config(&self.tls, true)?;
+        Ok(Box::pin(websocket_source(
+            self.clone(),
+            tls,
+            cx.shutdown,
+            cx.out,
+        )))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "websocket"
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        vec![Resource::tcp(self.address)]
+    }
+}
+
+async fn websocket_source(
+    config: WebSocketConfig,
+    tls: MaybeTlsSettings,
+    shutdown: ShutdownSignal,
+    out: Pipeline,
+) -> Result<(), ()> {
+    let listener = tls.bind(&config.address).await.map_err(|error| {
+        error!(message = "Failed to bind to listener socket.", %error);
+    })?;
+
+    info!(message = "Listening.", addr = %config.address);
+
+    let connection_gauge = OpenGauge::new();
+    let mut connections = listener.accept_stream().take_until(shutdown.clone());
+    while let Some(connection) = connections.next().await {
+        match connection {
+            Ok(socket) => {
+                let open_token = connection_gauge.open(|count| emit!(ConnectionOpen { count }));
+                let connection = handle_connection(
+                    socket,
+                    config.encoding,
+                    config.max_length,
+                    shutdown.clone(),
+                    out.clone(),
+                );
+                tokio::spawn(async move {
+                    connection.await;
+                    drop(open_token);
+                });
+            }
+            Err(error) => error!(message = "Failed to accept socket.", %error),
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_connection(
+    mut socket: MaybeTlsIncomingStream<TcpStream>,
+    encoding: Encoding,
+    max_length: usize,
+    mut shutdown: ShutdownSignal,
+    mut out: Pipeline,
+) {
+    let peer_addr = socket.peer_addr();
+    let host = Bytes::from(peer_addr.ip().to_string());
+
+    let config = ProtocolConfig {
+        max_message_size: Some(max_length),
+        max_frame_size: Some(max_length),
+        ..Default::default()
+    };
+    let handshake = async move {
+        socket.handshake().await.map_err(crate::Error::from)?;
+        accept_async_with_config(socket, Some(config))
+            .await
+            .map_err(crate::Error::from)
+    };
+    let mut ws = tokio::select! {
+        result = handshake => match result {
+            Ok(ws) => ws,
+            Err(error) => {
+                emit!(WebSocketConnectionError { error });
+                return;
+            }
+        },
+        _ = &mut shutdown => return,
+    };
+    emit!(WebSocketConnectionEstablished { peer_addr });
+
+    loop {
+        let message = tokio::select! {
+            _ = &mut shutdown => {
+                let _ = ws.close(None).await;
+                break;
+            },
+            message = ws.next() => match message {
+                Some(Ok(Message::Text(text))) => Bytes::from(text),
+                Some(Ok(Message::Binary(data))) => Bytes::from(data),
+                // Pings are answered, and closes acknowledged, by the
+                // connection itself.
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => continue,
+                Some(Err(error)) => {
+                    emit!(WebSocketConnectionError { error });
+                    break;
+                }
+            },
+        };
+
+        let byte_size = message.len();
+        let events = match decode_message(message, encoding, &host) {
+            Ok(events) => events,
+            Err(error) => {
+                emit!(WebSocketInvalidMessage { error, byte_size });
+                continue;
+            }
+        };
+        emit!(WebSocketMessageReceived {
+            count: events.len(),
+            byte_size
+        });
+
+        for event in events {
+            if let Err(error) = out.send(event).await {
+                error!(message = "Error sending event.", %error);
+                return;
+            }
+        }
+    }
+}
+
+/// Decodes a message into log events, adding the address of the client as
+/// their host unless they already have one.
+fn decode_message(
+    message: Bytes,
+    encoding: Encoding,
+    host: &Bytes,
+) -> Result<Vec<Event>, WebSocketDecodeError> {
+    let logs = match encoding {
+        Encoding::Text => vec![LogEvent::from(message)],
+        Encoding::Json => {
+            match serde_json::from_slice::<serde_json::Value>(&message).context(InvalidJson)? {
+                serde_json::Value::Array(values) => values
+                    .into_iter()
+                    .map(json_to_log)
+                    .collect::<Result<_, _>>()?,
+                value => vec![json_to_log(value)?],
+            }
+        }
+    };
+
+    Ok(logs
+        .into_iter()
+        .map(|mut log| {
+            if !log.contains(log_schema().host_key()) {
+                log.insert(log_schema().host_key(), host.clone());
+            }
+            if !log.contains(log_schema().timestamp_key()) {
+                log.insert(log_schema().timestamp_key(), Utc::now());
+            }
+            log.insert(log_schema().source_type_key(), Bytes::from("websocket"));
+            Event::Log(log)
+        })
+        .collect())
+}
+
+fn json_to_log(value: serde_json::Value) -> Result<LogEvent, WebSocketDecodeError> {
+    match value {
+        serde_json::Value::Object(fields) => {
+            let mut log = LogEvent::default();
+            for (name, value) in fields {
+                log.insert_flat(name, value);
+            }
+            Ok(log)
+        }
+        _ => Err(WebSocketDecodeError::NotAnObject),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{collect_n, next_addr, wait_for_tcp};
+    use tokio_tungstenite::client_async;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<WebSocketConfig>();
+    }
+
+    #[test]
+    fn decodes_json_messages() {
+        let host = Bytes::from("127.0.0.1");
+        let events = decode_message(
+            Bytes::from(r#"[{"message": "one", "host": "web-1"}, {"message": "two"}]"#),
+            Encoding::Json,
+            &host,
+        )
+        .unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].as_log()[log_schema().host_key()], "web-1".into());
+        assert_eq!(events[1].as_log()[log_schema().message_key()], "two".into());
+        assert_eq!(
+            events[1].as_log()[log_schema().host_key()],
+            "127.0.0.1".into()
+        );
+        assert_eq!(
+            events[1].as_log()[log_schema().source_type_key()],
+            "websocket".into()
+        );
+
+        assert!(matches!(
+            decode_message(Bytes::from("[1]"), Encoding::Json, &host),
+            Err(WebSocketDecodeError::NotAnObject)
+        ));
+        assert!(matches!(
+            decode_message(Bytes::from("{"), Encoding::Json, &host),
+            Err(WebSocketDecodeError::InvalidJson { .. })
+        ));
+    }
+
+    async fn start_source(
+        encoding: Encoding,
+    ) -> (SocketAddr, impl futures::Stream<Item = Event> + Unpin) {
+        let address = next_addr();
+        let (sender, recv) = Pipeline::new_test();
+        let config = WebSocketConfig {
+            address,
+            tls: None,
+            encoding,
+            max_length: default_max_length(),
+        };
+        let source = config.build(SourceContext::new_test(sender)).await.unwrap();
+        tokio::spawn(source);
+        wait_for_tcp(address).await;
+        (address, recv)
+    }
+
+    #[tokio::test]
+    async fn receives_text_and_binary_messages() {
+        let (address, recv) = start_source(Encoding::Text).await;
+
+        let stream = TcpStream::connect(address).await.unwrap();
+        let (mut ws, _) = client_async(format!("ws://{}", address), stream)
+            .await
+            .unwrap();
+        ws.send(Message::Text("first".into())).await.unwrap();
+        ws.send(Message::Ping(b"ping".to_vec())).await.unwrap();
+        ws.send(Message::Binary(b"second".to_vec())).await.unwrap();
+        assert_eq!(
+            ws.next().await.unwrap().unwrap(),
+            Message::Pong(b"ping".to_vec())
+        );
+
+        let events = collect_n(recv, 2).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "first".into()
+        );
+        assert_eq!(
+            events[1].as_log()[log_schema().message_key()],
+            "second".into()
+        );
+        assert_eq!(
+            events[0].as_log()[log_schema().host_key()],
+            address.ip().to_string().into()
+        );
+    }
+
+    #[cfg(feature = "sinks-websocket")]
+    #[tokio::test]
+    async fn receives_events_from_websocket_sink() {
+        use crate::{
+            config::{SinkConfig, SinkContext},
+            sinks::websocket::WebSocketSinkConfig,
+            test_util::{random_lines_with_stream, trace_init},
+        };
+
+        trace_init();
+
+        let (address, recv) = start_source(Encoding::Json).await;
+
+        let config: WebSocketSinkConfig = toml::from_str(&format!(
+            r#"
+            uri = "ws://{}"
+            encoding.codec = "json"
+            "#,
+            address
+        ))
+        .unwrap();
+        let (sink, healthcheck) = config.build(SinkContext::new_test()).await.unwrap();
+        healthcheck.await.unwrap();
+
+        let (lines, events) = random_lines_with_stream(10, 5, None);
+        sink.run(events).await.unwrap();
+
+        let events = collect_n(recv, lines.len()).await;
+        let messages = events
+            .iter()
+            .map(|event| event.as_log()[log_schema().message_key()].to_string_lossy())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, lines);
+    }
+}
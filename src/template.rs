@@ -147,6 +147,17 @@ impl Template {
     pub fn get_ref(&self) -> &str {
         &self.src
     }
+
+    /// Creates a template only rendering the `{{ field }}` placeholders, for
+    /// values where `%` is a literal character, like percent-encoded URIs.
+    pub fn fields_only(src: impl Into<String>) -> Self {
+        let src = src.into();
+        Template {
+            has_fields: RE.is_match(&src),
+            src,
+            has_ts: false,
+        }
+    }
 }
 
 fn render_fields<'a>(src: &str, event: EventRef<'a>) -> Result<String, TemplateRenderingError> {
@@ -275,6 +286,17 @@ mod tests {
             .is_dynamic());
     }
 
+    #[test]
+    fn fields_only_keeps_percent_signs() {
+        let mut event = Event::from("hello world");
+        event.as_mut_log().insert("id", "one");
+
+        let template = Template::fields_only("/path%2F{{ id }}/%F");
+        assert!(template.is_dynamic());
+        assert_eq!(Ok(Bytes::from("/path%2Fone/%F")), template.render(&event));
+        assert!(!Template::fields_only("/path%20").is_dynamic());
+    }
+
     #[test]
     fn render_log_static() {
         let event = Event::from("hello world");